@@ -130,6 +130,51 @@ impl Step for Linkcheck {
     }
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct RustdocJS {
+    target: Interned<String>,
+}
+
+impl Step for RustdocJS {
+    type Output = ();
+    const DEFAULT: bool = true;
+    const ONLY_HOSTS: bool = true;
+
+    /// Runs the search queries in `src/test/rustdoc-js` against the standard
+    /// library's documentation, using the `main.js` it was generated with.
+    ///
+    /// This requires node.js and is skipped without it.
+    fn run(self, builder: &Builder) {
+        let build = builder.build;
+        let nodejs = match build.config.nodejs {
+            Some(ref nodejs) => nodejs,
+            None => {
+                println!("No nodejs found, skipping \"src/test/rustdoc-js\" tests");
+                return
+            }
+        };
+
+        builder.ensure(::doc::Std { stage: builder.top_stage, target: self.target });
+
+        let _folder = build.fold_output(|| "rustdoc-js");
+        println!("Rustdoc search tests ({})", self.target);
+        let mut cmd = Command::new(nodejs);
+        cmd.arg(build.src.join("src/tools/rustdoc-js/tester.js"))
+           .arg(build.doc_out(self.target))
+           .arg(build.src.join("src/test/rustdoc-js"));
+        try_run(build, &mut cmd);
+    }
+
+    fn should_run(run: ShouldRun) -> ShouldRun {
+        let builder = run.builder;
+        run.path("src/test/rustdoc-js").default_condition(builder.build.config.docs)
+    }
+
+    fn make_run(run: RunConfig) {
+        run.builder.ensure(RustdocJS { target: run.target });
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct Cargotest {
     stage: u32,
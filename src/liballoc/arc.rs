@@ -25,7 +25,7 @@ use core::intrinsics::abort;
 use core::mem::{self, align_of_val, size_of_val, uninitialized};
 use core::ops::Deref;
 use core::ops::CoerceUnsized;
-use core::ptr::{self, Shared};
+use core::ptr::{self, Shared, Unique};
 use core::marker::Unsize;
 use core::hash::{Hash, Hasher};
 use core::{isize, usize};
@@ -578,7 +578,7 @@ impl<T: ?Sized> Arc<T> {
                 value_size);
 
             // Free the allocation without dropping its contents
-            box_free(bptr);
+            box_free(Unique::new_unchecked(bptr), Heap);
 
             Arc { ptr: Shared::new_unchecked(ptr) }
         }
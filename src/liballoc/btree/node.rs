@@ -47,8 +47,7 @@ use core::nonzero::NonZero;
 use core::ptr::{self, Unique};
 use core::slice;
 
-use boxed::Box;
-use heap::{Heap, Alloc, Layout};
+use heap::{Alloc, Layout};
 
 const B: usize = 6;
 pub const MIN_LEN: usize = B - 1;
@@ -130,22 +129,33 @@ impl<K, V> InternalNode<K, V> {
     }
 }
 
-/// An owned pointer to a node. This basically is either `Box<LeafNode<K, V>>` or
-/// `Box<InternalNode<K, V>>`. However, it contains no information as to which of the two types
-/// of nodes is actually behind the box, and, partially due to this lack of information, has no
-/// destructor.
+/// Moves `node` into memory allocated from `alloc`, calling `Alloc::oom` if that fails.
+fn alloc_node<T, A: Alloc>(alloc: &mut A, node: T) -> Unique<T> {
+    match alloc.alloc_one::<T>() {
+        Ok(ptr) => {
+            unsafe { ptr::write(ptr.as_ptr(), node); }
+            ptr
+        }
+        Err(err) => alloc.oom(err),
+    }
+}
+
+/// An owned pointer to a node. This basically is either a `LeafNode<K, V>` or an
+/// `InternalNode<K, V>` allocated by the tree's allocator. However, it contains no information
+/// as to which of the two types of nodes is actually behind the pointer, and, partially due to
+/// this lack of information, has no destructor.
 struct BoxedNode<K, V> {
     ptr: Unique<LeafNode<K, V>>
 }
 
 impl<K, V> BoxedNode<K, V> {
-    fn from_leaf(node: Box<LeafNode<K, V>>) -> Self {
-        BoxedNode { ptr: Box::into_unique(node) }
+    fn from_leaf(node: Unique<LeafNode<K, V>>) -> Self {
+        BoxedNode { ptr: node }
     }
 
-    fn from_internal(node: Box<InternalNode<K, V>>) -> Self {
+    fn from_internal(node: Unique<InternalNode<K, V>>) -> Self {
         unsafe {
-            BoxedNode { ptr: Unique::new_unchecked(Box::into_raw(node) as *mut LeafNode<K, V>) }
+            BoxedNode { ptr: Unique::new_unchecked(node.as_ptr() as *mut LeafNode<K, V>) }
         }
     }
 
@@ -171,9 +181,9 @@ unsafe impl<K: Sync, V: Sync> Sync for Root<K, V> { }
 unsafe impl<K: Send, V: Send> Send for Root<K, V> { }
 
 impl<K, V> Root<K, V> {
-    pub fn new_leaf() -> Self {
+    pub fn new_leaf<A: Alloc>(alloc: &mut A) -> Self {
         Root {
-            node: BoxedNode::from_leaf(Box::new(unsafe { LeafNode::new() })),
+            node: BoxedNode::from_leaf(alloc_node(alloc, unsafe { LeafNode::new() })),
             height: 0
         }
     }
@@ -210,10 +220,12 @@ impl<K, V> Root<K, V> {
 
     /// Adds a new internal node with a single edge, pointing to the previous root, and make that
     /// new node the root. This increases the height by 1 and is the opposite of `pop_level`.
-    pub fn push_level(&mut self)
+    pub fn push_level<A: Alloc>(&mut self, alloc: &mut A)
             -> NodeRef<marker::Mut, K, V, marker::Internal> {
-        let mut new_node = Box::new(unsafe { InternalNode::new() });
-        new_node.edges[0] = unsafe { BoxedNode::from_ptr(self.node.as_ptr()) };
+        let mut new_node = alloc_node(alloc, unsafe { InternalNode::new() });
+        unsafe {
+            new_node.as_mut().edges[0] = BoxedNode::from_ptr(self.node.as_ptr());
+        }
 
         self.node = BoxedNode::from_internal(new_node);
         self.height += 1;
@@ -236,7 +248,7 @@ impl<K, V> Root<K, V> {
     /// the tree consists only of a leaf node. As it is intended only to be called when the root
     /// has only one edge, no cleanup is done on any of the other children are elements of the root.
     /// This decreases the height by 1 and is the opposite of `push_level`.
-    pub fn pop_level(&mut self) {
+    pub fn pop_level<A: Alloc>(&mut self, alloc: &mut A) {
         debug_assert!(self.height > 0);
 
         let top = self.node.ptr.as_ptr() as *mut u8;
@@ -252,7 +264,7 @@ impl<K, V> Root<K, V> {
         self.as_mut().as_leaf_mut().parent = ptr::null();
 
         unsafe {
-            Heap.dealloc(top, Layout::new::<InternalNode<K, V>>());
+            alloc.dealloc(top, Layout::new::<InternalNode<K, V>>());
         }
     }
 }
@@ -425,7 +437,7 @@ impl<K, V> NodeRef<marker::Owned, K, V, marker::Leaf> {
     /// Similar to `ascend`, gets a reference to a node's parent node, but also
     /// deallocate the current node in the process. This is unsafe because the
     /// current node will still be accessible despite being deallocated.
-    pub unsafe fn deallocate_and_ascend(self) -> Option<
+    pub unsafe fn deallocate_and_ascend<A: Alloc>(self, alloc: &mut A) -> Option<
         Handle<
             NodeRef<
                 marker::Owned,
@@ -437,7 +449,7 @@ impl<K, V> NodeRef<marker::Owned, K, V, marker::Leaf> {
     > {
         let ptr = self.as_leaf() as *const LeafNode<K, V> as *const u8 as *mut u8;
         let ret = self.ascend().ok();
-        Heap.dealloc(ptr, Layout::new::<LeafNode<K, V>>());
+        alloc.dealloc(ptr, Layout::new::<LeafNode<K, V>>());
         ret
     }
 }
@@ -446,7 +458,7 @@ impl<K, V> NodeRef<marker::Owned, K, V, marker::Internal> {
     /// Similar to `ascend`, gets a reference to a node's parent node, but also
     /// deallocate the current node in the process. This is unsafe because the
     /// current node will still be accessible despite being deallocated.
-    pub unsafe fn deallocate_and_ascend(self) -> Option<
+    pub unsafe fn deallocate_and_ascend<A: Alloc>(self, alloc: &mut A) -> Option<
         Handle<
             NodeRef<
                 marker::Owned,
@@ -458,7 +470,7 @@ impl<K, V> NodeRef<marker::Owned, K, V, marker::Internal> {
     > {
         let ptr = self.as_internal() as *const InternalNode<K, V> as *const u8 as *mut u8;
         let ret = self.ascend().ok();
-        Heap.dealloc(ptr, Layout::new::<InternalNode<K, V>>());
+        alloc.dealloc(ptr, Layout::new::<InternalNode<K, V>>());
         ret
     }
 }
@@ -901,7 +913,7 @@ impl<'a, K, V> Handle<NodeRef<marker::Mut<'a>, K, V, marker::Leaf>, marker::Edge
     /// this edge. This method splits the node if there isn't enough room.
     ///
     /// The returned pointer points to the inserted value.
-    pub fn insert(mut self, key: K, val: V)
+    pub fn insert<A: Alloc>(mut self, key: K, val: V, alloc: &mut A)
             -> (InsertResult<'a, K, V, marker::Leaf>, *mut V) {
 
         if self.node.len() < CAPACITY {
@@ -909,7 +921,7 @@ impl<'a, K, V> Handle<NodeRef<marker::Mut<'a>, K, V, marker::Leaf>, marker::Edge
             (InsertResult::Fit(Handle::new_kv(self.node, self.idx)), ptr)
         } else {
             let middle = Handle::new_kv(self.node, B);
-            let (mut left, k, v, mut right) = middle.split();
+            let (mut left, k, v, mut right) = middle.split(alloc);
             let ptr = if self.idx <= B {
                 unsafe {
                     Handle::new_edge(left.reborrow_mut(), self.idx).insert_fit(key, val)
@@ -976,7 +988,7 @@ impl<'a, K, V> Handle<NodeRef<marker::Mut<'a>, K, V, marker::Internal>, marker::
     /// Inserts a new key/value pair and an edge that will go to the right of that new pair
     /// between this edge and the key/value pair to the right of this edge. This method splits
     /// the node if there isn't enough room.
-    pub fn insert(mut self, key: K, val: V, edge: Root<K, V>)
+    pub fn insert<A: Alloc>(mut self, key: K, val: V, edge: Root<K, V>, alloc: &mut A)
             -> InsertResult<'a, K, V, marker::Internal> {
 
         // Necessary for correctness, but this is an internal module
@@ -987,7 +999,7 @@ impl<'a, K, V> Handle<NodeRef<marker::Mut<'a>, K, V, marker::Internal>, marker::
             InsertResult::Fit(Handle::new_kv(self.node, self.idx))
         } else {
             let middle = Handle::new_kv(self.node, B);
-            let (mut left, k, v, mut right) = middle.split();
+            let (mut left, k, v, mut right) = middle.split(alloc);
             if self.idx <= B {
                 unsafe {
                     Handle::new_edge(left.reborrow_mut(), self.idx).insert_fit(key, val, edge);
@@ -1061,10 +1073,11 @@ impl<'a, K, V> Handle<NodeRef<marker::Mut<'a>, K, V, marker::Leaf>, marker::KV>
     /// - The key and value pointed to by this handle and extracted.
     /// - All the key/value pairs to the right of this handle are put into a newly
     ///   allocated node.
-    pub fn split(mut self)
+    pub fn split<A: Alloc>(mut self, alloc: &mut A)
             -> (NodeRef<marker::Mut<'a>, K, V, marker::Leaf>, K, V, Root<K, V>) {
         unsafe {
-            let mut new_node = Box::new(LeafNode::new());
+            let new_ptr = alloc_node(alloc, LeafNode::new());
+            let new_node = &mut *new_ptr.as_ptr();
 
             let k = ptr::read(self.node.keys().get_unchecked(self.idx));
             let v = ptr::read(self.node.vals().get_unchecked(self.idx));
@@ -1089,7 +1102,7 @@ impl<'a, K, V> Handle<NodeRef<marker::Mut<'a>, K, V, marker::Leaf>, marker::KV>
                 self.node,
                 k, v,
                 Root {
-                    node: BoxedNode::from_leaf(new_node),
+                    node: BoxedNode::from_leaf(new_ptr),
                     height: 0
                 }
             )
@@ -1117,10 +1130,11 @@ impl<'a, K, V> Handle<NodeRef<marker::Mut<'a>, K, V, marker::Internal>, marker::
     /// - The key and value pointed to by this handle and extracted.
     /// - All the edges and key/value pairs to the right of this handle are put into
     ///   a newly allocated node.
-    pub fn split(mut self)
+    pub fn split<A: Alloc>(mut self, alloc: &mut A)
             -> (NodeRef<marker::Mut<'a>, K, V, marker::Internal>, K, V, Root<K, V>) {
         unsafe {
-            let mut new_node = Box::new(InternalNode::new());
+            let new_ptr = alloc_node(alloc, InternalNode::new());
+            let new_node = &mut *new_ptr.as_ptr();
 
             let k = ptr::read(self.node.keys().get_unchecked(self.idx));
             let v = ptr::read(self.node.vals().get_unchecked(self.idx));
@@ -1148,7 +1162,7 @@ impl<'a, K, V> Handle<NodeRef<marker::Mut<'a>, K, V, marker::Internal>, marker::
             new_node.data.len = new_len as u16;
 
             let mut new_root = Root {
-                node: BoxedNode::from_internal(new_node),
+                node: BoxedNode::from_internal(new_ptr),
                 height,
             };
 
@@ -1186,7 +1200,7 @@ impl<'a, K, V> Handle<NodeRef<marker::Mut<'a>, K, V, marker::Internal>, marker::
     /// child of the underlying node, returning an edge referencing that new child.
     ///
     /// Assumes that this edge `.can_merge()`.
-    pub fn merge(mut self)
+    pub fn merge<A: Alloc>(mut self, alloc: &mut A)
             -> Handle<NodeRef<marker::Mut<'a>, K, V, marker::Internal>, marker::Edge> {
         let self1 = unsafe { ptr::read(&self) };
         let self2 = unsafe { ptr::read(&self) };
@@ -1240,12 +1254,12 @@ impl<'a, K, V> Handle<NodeRef<marker::Mut<'a>, K, V, marker::Internal>, marker::
                     ).correct_parent_link();
                 }
 
-                Heap.dealloc(
+                alloc.dealloc(
                     right_node.node.get() as *mut u8,
                     Layout::new::<InternalNode<K, V>>(),
                 );
             } else {
-                Heap.dealloc(
+                alloc.dealloc(
                     right_node.node.get() as *mut u8,
                     Layout::new::<LeafNode<K, V>>(),
                 );
@@ -17,6 +17,7 @@
 
 use core::intrinsics::{min_align_of_val, size_of_val};
 use core::mem::{self, ManuallyDrop};
+use core::ptr::Unique;
 use core::usize;
 
 pub use allocator::*;
@@ -252,18 +253,28 @@ unsafe fn exchange_malloc(size: usize, align: usize) -> *mut u8 {
     }
 }
 
-#[cfg_attr(not(test), lang = "box_free")]
+#[cfg_attr(all(not(test), not(stage0)), lang = "box_free")]
 #[inline]
-pub(crate) unsafe fn box_free<T: ?Sized>(ptr: *mut T) {
+pub(crate) unsafe fn box_free<T: ?Sized, A: Alloc>(ptr: Unique<T>, mut a: A) {
+    let ptr = ptr.as_ptr();
     let size = size_of_val(&*ptr);
     let align = min_align_of_val(&*ptr);
     // We do not allocate for Box<T> when T is ZST, so deallocation is also not necessary.
     if size != 0 {
         let layout = Layout::from_size_align_unchecked(size, align);
-        Heap.dealloc(ptr as *mut u8, layout);
+        a.dealloc(ptr as *mut u8, layout);
     }
 }
 
+// The stage0 compiler's drop elaboration still passes just the box's pointer.
+// Remove this after the next beta bump.
+#[cfg(all(stage0, not(test)))]
+#[lang = "box_free"]
+#[inline]
+unsafe fn box_free_stage0<T: ?Sized>(ptr: *mut T) {
+    box_free(Unique::new_unchecked(ptr), Heap)
+}
+
 #[cfg(test)]
 mod tests {
     extern crate test;
@@ -12,9 +12,11 @@ use std::collections::BTreeMap;
 use std::collections::Bound::{self, Excluded, Included, Unbounded};
 use std::collections::btree_map::Entry::{Occupied, Vacant};
 use std::rc::Rc;
+use std::cell::Cell;
 
 use std::iter::FromIterator;
 use super::DeterministicRng;
+use heap::CountingAlloc;
 
 #[test]
 fn test_basic_large() {
@@ -685,3 +687,37 @@ fn test_split_off_large_random_sorted() {
     assert!(map.into_iter().eq(data.clone().into_iter().filter(|x| x.0 < key)));
     assert!(right.into_iter().eq(data.into_iter().filter(|x| x.0 >= key)));
 }
+
+#[test]
+fn test_alloc_in() {
+    let live = Cell::new(0);
+
+    let mut map = BTreeMap::new_in(CountingAlloc(&live));
+    assert_eq!(live.get(), 1);
+    for i in 0..100 {
+        map.insert(i, i * 10);
+    }
+    assert!(live.get() > 1);
+    assert_eq!(map[&42], 420);
+
+    let copy = map.clone();
+    assert!(map.iter().eq(copy.iter()));
+    drop(copy);
+
+    let mut right = map.split_off(&50);
+    map.append(&mut right);
+    for i in 0..90 {
+        assert_eq!(map.remove(&i), Some(i * 10));
+    }
+    map.clear();
+    assert_eq!(live.get(), 2);
+    drop(right);
+    assert_eq!(live.get(), 1);
+
+    map.extend((0..100).map(|i| (i, i)));
+    let mut it = map.into_iter();
+    assert_eq!(it.next(), Some((0, 0)));
+    assert_eq!(it.next_back(), Some((99, 99)));
+    drop(it);
+    assert_eq!(live.get(), 0);
+}
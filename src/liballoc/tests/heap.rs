@@ -9,7 +9,8 @@
 // except according to those terms.
 
 use alloc_system::System;
-use std::heap::{Heap, Alloc, Layout};
+use std::cell::Cell;
+use std::heap::{Heap, Alloc, AllocErr, Layout};
 
 /// https://github.com/rust-lang/rust/issues/45955
 ///
@@ -43,3 +44,39 @@ fn check_overalign_requests<T: Alloc>(mut allocator: T) {
         }
     }
 }
+
+/// Allocator that keeps track of how many of its blocks are live.
+#[derive(Clone)]
+pub struct CountingAlloc<'a>(pub &'a Cell<usize>);
+
+unsafe impl<'a> Alloc for CountingAlloc<'a> {
+    unsafe fn alloc(&mut self, layout: Layout) -> Result<*mut u8, AllocErr> {
+        self.0.set(self.0.get() + 1);
+        Heap.alloc(layout)
+    }
+
+    unsafe fn dealloc(&mut self, ptr: *mut u8, layout: Layout) {
+        self.0.set(self.0.get() - 1);
+        Heap.dealloc(ptr, layout)
+    }
+}
+
+#[test]
+fn box_new_in() {
+    let live = Cell::new(0);
+    {
+        let a = Box::new_in(5, CountingAlloc(&live));
+        let b = a.clone();
+        assert_eq!(live.get(), 2);
+        assert_eq!(*a + *b, 10);
+
+        let c: Box<[i32], CountingAlloc> = Box::new_in([1, 2, 3], CountingAlloc(&live));
+        assert_eq!(c.len(), 3);
+        assert_eq!(live.get(), 3);
+
+        let d = Box::new_in((), CountingAlloc(&live));
+        assert_eq!(*d, ());
+        assert_eq!(live.get(), 3);
+    }
+    assert_eq!(live.get(), 0);
+}
@@ -9,9 +9,13 @@
 // except according to those terms.
 
 use std::borrow::Cow;
+use std::cell::Cell;
 use std::mem::size_of;
 use std::panic;
 use std::vec::{Drain, IntoIter};
+use std::collections::CollectionAllocErr::CapacityOverflow;
+
+use heap::CountingAlloc;
 
 struct DropCounter<'a> {
     count: &'a mut u32,
@@ -965,3 +969,80 @@ fn drain_filter_complex() {
         assert_eq!(vec, vec![1, 3, 5, 7, 9, 11, 13, 15, 17, 19]);
     }
 }
+
+#[test]
+fn test_try_reserve() {
+    let mut v: Vec<u8> = Vec::new();
+    assert_eq!(v.try_reserve(10), Ok(()));
+    assert!(v.capacity() >= 10);
+
+    v.push(1);
+    // `len + additional` overflows `usize`.
+    assert_eq!(v.try_reserve(usize::max_value()), Err(CapacityOverflow));
+    assert_eq!(v.try_reserve_exact(usize::max_value()), Err(CapacityOverflow));
+
+    // The byte size of the buffer overflows `usize`.
+    let mut w: Vec<u32> = Vec::new();
+    assert_eq!(w.try_reserve_exact(usize::max_value() / 2), Err(CapacityOverflow));
+
+    // Failed reservations leave the vector untouched.
+    assert_eq!(v, [1]);
+    assert!(v.capacity() >= 10);
+    assert_eq!(w.capacity(), 0);
+}
+
+#[test]
+fn test_new_in() {
+    let live = Cell::new(0);
+
+    let mut v = Vec::new_in(CountingAlloc(&live));
+    assert_eq!(live.get(), 0);
+    v.push(1);
+    v.push(2);
+    v.insert(0, 0);
+    assert_eq!(live.get(), 1);
+    assert_eq!(v, [0, 1, 2]);
+    v.truncate(1);
+    v.shrink_to_fit();
+    assert_eq!(live.get(), 1);
+    drop(v);
+    assert_eq!(live.get(), 0);
+
+    let mut v = Vec::with_capacity_in(4, CountingAlloc(&live));
+    assert_eq!(live.get(), 1);
+    for i in 1..4 {
+        v.push(i);
+    }
+    assert_eq!(v.capacity(), 4);
+    let mut it = v.into_iter();
+    assert_eq!(it.next(), Some(1));
+    assert_eq!(live.get(), 1);
+    assert_eq!(it.collect::<Vec<_>>(), [2, 3]);
+    assert_eq!(live.get(), 0);
+}
+
+#[test]
+fn test_alloc_generic_impls() {
+    let live = Cell::new(0);
+
+    let mut v = Vec::new_in(CountingAlloc(&live));
+    v.extend_from_slice(&[1, 2, 3]);
+    v.extend(&[4, 5]);
+    v.extend(vec![6, 7]);
+    assert_eq!(v, [1, 2, 3, 4, 5, 6, 7]);
+    assert_eq!(live.get(), 1);
+
+    let w = v.clone();
+    assert_eq!(live.get(), 2);
+    assert_eq!(v, w);
+    assert!(v <= w);
+
+    assert_eq!(v.drain(5..).collect::<Vec<_>>(), [6, 7]);
+    assert_eq!(v.splice(..2, vec![0]).collect::<Vec<_>>(), [1, 2]);
+    assert_eq!(v.drain_filter(|x| *x % 2 == 0).collect::<Vec<_>>(), [0, 4]);
+    assert_eq!(v, [3, 5]);
+    assert!(v > w);
+
+    drop((v, w));
+    assert_eq!(live.get(), 0);
+}
@@ -8,10 +8,14 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use std::cell::Cell;
 use std::collections::VecDeque;
+use std::collections::CollectionAllocErr::CapacityOverflow;
 use std::fmt::Debug;
 use std::collections::vec_deque::{Drain};
 
+use heap::CountingAlloc;
+
 use self::Taggy::*;
 use self::Taggypar::*;
 
@@ -1022,3 +1026,43 @@ fn test_placement_in() {
     }
     assert_eq!(buf, [5,4,3,1,2,6]);
 }
+
+#[test]
+fn test_try_reserve() {
+    let mut buf: VecDeque<u8> = VecDeque::new();
+    assert_eq!(buf.try_reserve(10), Ok(()));
+    assert!(buf.capacity() >= 10);
+
+    buf.push_back(1);
+    // The capacity rounded up to a power of two overflows `usize`.
+    assert_eq!(buf.try_reserve(usize::max_value() / 2 + 1), Err(CapacityOverflow));
+    assert_eq!(buf.try_reserve_exact(usize::max_value()), Err(CapacityOverflow));
+    assert_eq!(buf, [1]);
+}
+
+#[test]
+fn test_new_in() {
+    let live = Cell::new(0);
+
+    let mut d = VecDeque::new_in(CountingAlloc(&live));
+    assert_eq!(live.get(), 1);
+    for i in 0..10 {
+        d.push_back(i);
+        d.push_front(-i);
+    }
+    assert_eq!(live.get(), 1);
+    assert_eq!(d.drain(..5).count(), 5);
+    assert_eq!(d.len(), 15);
+    d.clear();
+    assert!(d.is_empty());
+    drop(d);
+    assert_eq!(live.get(), 0);
+
+    let mut d = VecDeque::with_capacity_in(3, CountingAlloc(&live));
+    d.push_back(1);
+    d.push_back(2);
+    d.push_front(0);
+    assert_eq!(live.get(), 1);
+    assert_eq!(d.into_iter().collect::<Vec<_>>(), [0, 1, 2]);
+    assert_eq!(live.get(), 0);
+}
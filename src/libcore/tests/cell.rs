@@ -304,3 +304,63 @@ fn refcell_replace_borrows() {
     let _b = x.borrow();
     x.replace(1);
 }
+
+#[test]
+fn once_cell() {
+    let c = OnceCell::new();
+    assert!(c.get().is_none());
+    c.get_or_init(|| 92);
+    assert_eq!(c.get(), Some(&92));
+
+    c.get_or_init(|| panic!("Kabom!"));
+    assert_eq!(c.get(), Some(&92));
+}
+
+#[test]
+fn once_cell_set_and_take() {
+    let mut c = OnceCell::new();
+    assert_eq!(c.set(1), Ok(()));
+    assert_eq!(c.set(2), Err(2));
+    assert_eq!(c.take(), Some(1));
+    assert!(c.get().is_none());
+    assert_eq!(c.into_inner(), None);
+}
+
+#[test]
+fn once_cell_get_or_try_init() {
+    let c = OnceCell::new();
+    assert_eq!(c.get_or_try_init(|| Err(())), Err(()));
+    assert!(c.get().is_none());
+    assert_eq!(c.get_or_try_init(|| Ok::<i32, ()>(1)), Ok(&1));
+}
+
+#[test]
+#[should_panic(expected = "reentrant init")]
+fn once_cell_reentrant_init() {
+    let x: OnceCell<Box<i32>> = OnceCell::new();
+    x.get_or_init(|| {
+        let _ = x.get_or_init(|| Box::new(92));
+        Box::new(62)
+    });
+}
+
+#[test]
+fn lazy_new() {
+    let called = Cell::new(0);
+    let x = Lazy::new(|| {
+        called.set(called.get() + 1);
+        92
+    });
+
+    assert_eq!(called.get(), 0);
+    assert_eq!(*x, 92);
+    assert_eq!(called.get(), 1);
+    assert_eq!(*x, 92);
+    assert_eq!(called.get(), 1);
+}
+
+#[test]
+fn lazy_default() {
+    let x: Lazy<Vec<i32>> = Lazy::default();
+    assert!(x.is_empty());
+}
@@ -31,7 +31,7 @@ use middle::lang_items;
 use middle::resolve_lifetime::{self, ObjectLifetimeDefault};
 use middle::stability;
 use mir::{Mir, interpret};
-use ty::subst::{Kind, Substs};
+use ty::subst::{Kind, Subst, Substs};
 use ty::ReprOptions;
 use ty::Instance;
 use traits;
@@ -49,6 +49,7 @@ use ty::BindingMode;
 use util::nodemap::{NodeMap, NodeSet, DefIdSet, ItemLocalMap};
 use util::nodemap::{FxHashMap, FxHashSet};
 use rustc_data_structures::accumulate_vec::AccumulateVec;
+use rustc_data_structures::fx::FxHasher;
 use rustc_data_structures::stable_hasher::{HashStable, hash_stable_hashmap,
                                            StableHasher, StableHasherResult,
                                            StableVec};
@@ -59,7 +60,7 @@ use std::any::Any;
 use std::borrow::Borrow;
 use std::cell::{Cell, RefCell};
 use std::cmp::Ordering;
-use std::collections::hash_map::{self, Entry};
+use std::collections::hash_map::{self, Entry, RawEntryMut};
 use std::hash::{Hash, Hasher};
 use std::mem;
 use std::ops::Deref;
@@ -107,13 +108,24 @@ impl<'tcx> GlobalArenas<'tcx> {
     }
 }
 
+/// Inserts `key`, which is known not to be in `map` yet, using a hash that
+/// was already computed while looking it up.
+fn insert_hashed<K: Hash + Eq>(map: &mut FxHashMap<K, ()>, hash: u64, key: K) {
+    match map.raw_entry_mut().from_hash(hash, |_| false) {
+        RawEntryMut::Vacant(entry) => {
+            entry.insert_hashed_nocheck(hash, key, ());
+        }
+        RawEntryMut::Occupied(_) => bug!("insert_hashed: entry already present"),
+    }
+}
+
 pub struct CtxtInterners<'tcx> {
     /// The arena that types, regions, etc are allocated from
     arena: &'tcx DroplessArena,
 
     /// Specifically use a speedy hash algorithm for these hash sets,
     /// they're accessed quite often.
-    type_: RefCell<FxHashSet<Interned<'tcx, TyS<'tcx>>>>,
+    type_: RefCell<FxHashMap<Interned<'tcx, TyS<'tcx>>, ()>>,
     type_list: RefCell<FxHashSet<Interned<'tcx, Slice<Ty<'tcx>>>>>,
     substs: RefCell<FxHashSet<Interned<'tcx, Substs<'tcx>>>>,
     region: RefCell<FxHashSet<Interned<'tcx, RegionKind>>>,
@@ -126,7 +138,7 @@ impl<'gcx: 'tcx, 'tcx> CtxtInterners<'tcx> {
     fn new(arena: &'tcx DroplessArena) -> CtxtInterners<'tcx> {
         CtxtInterners {
             arena,
-            type_: RefCell::new(FxHashSet()),
+            type_: RefCell::new(FxHashMap()),
             type_list: RefCell::new(FxHashSet()),
             substs: RefCell::new(FxHashSet()),
             region: RefCell::new(FxHashSet()),
@@ -146,11 +158,21 @@ impl<'gcx: 'tcx, 'tcx> CtxtInterners<'tcx> {
             let global_interner = global_interners.map(|interners| {
                 interners.type_.borrow_mut()
             });
-            if let Some(&Interned(ty)) = interner.get(&st) {
+
+            // Hash the type only once; the same hash is used to probe both
+            // interners and to insert into whichever one ends up owning it.
+            let hash = {
+                let mut hasher = FxHasher::default();
+                st.hash(&mut hasher);
+                hasher.finish()
+            };
+            if let Some((&Interned(ty), _)) =
+                    interner.raw_entry().from_key_hashed_nocheck(hash, &st) {
                 return ty;
             }
             if let Some(ref interner) = global_interner {
-                if let Some(&Interned(ty)) = interner.get(&st) {
+                if let Some((&Interned(ty), _)) =
+                        interner.raw_entry().from_key_hashed_nocheck(hash, &st) {
                     return ty;
                 }
             }
@@ -171,7 +193,7 @@ impl<'gcx: 'tcx, 'tcx> CtxtInterners<'tcx> {
                         mem::transmute(ty_struct)
                     };
                     let ty: Ty<'gcx> = interner.arena.alloc(ty_struct);
-                    global_interner.unwrap().insert(Interned(ty));
+                    insert_hashed(&mut global_interner.unwrap(), hash, Interned(ty));
                     return ty;
                 }
             } else {
@@ -187,7 +209,7 @@ impl<'gcx: 'tcx, 'tcx> CtxtInterners<'tcx> {
 
             // Don't be &mut TyS.
             let ty: Ty<'tcx> = self.arena.alloc(ty_struct);
-            interner.insert(Interned(ty));
+            insert_hashed(&mut interner, hash, Interned(ty));
             ty
         };
 
@@ -1616,7 +1638,7 @@ macro_rules! sty_debug_print {
                 $(let mut $variant = total;)*
 
 
-                for &Interned(t) in tcx.interners.type_.borrow().iter() {
+                for &Interned(t) in tcx.interners.type_.borrow().keys() {
                     let variant = match t.sty {
                         ty::TyBool | ty::TyChar | ty::TyInt(..) | ty::TyUint(..) |
                             ty::TyFloat(..) | ty::TyStr | ty::TyNever => continue,
@@ -1944,7 +1966,16 @@ impl<'a, 'gcx, 'tcx> TyCtxt<'a, 'gcx, 'tcx> {
     pub fn mk_box(self, ty: Ty<'tcx>) -> Ty<'tcx> {
         let def_id = self.require_lang_item(lang_items::OwnedBoxLangItem);
         let adt_def = self.adt_def(def_id);
-        let substs = self.mk_substs(iter::once(Kind::from(ty)));
+        // Any parameter after the boxed type (the allocator) takes its default.
+        let substs = Substs::for_item(self, def_id, |_, _| {
+            bug!("mk_box: unexpected lifetime parameter on Box")
+        }, |def, substs| {
+            if def.index == 0 {
+                ty
+            } else {
+                self.type_of(def.def_id).subst(self, substs)
+            }
+        });
         self.mk_ty(TyAdt(adt_def, substs))
     }
 
@@ -1566,6 +1566,38 @@ impl<'a> Resolver<'a> {
         }
     }
 
+    /// Resolves a path written as a string, such as the target of an intra-doc
+    /// link in rustdoc, in namespace `ns` as if it appeared in the module
+    /// `module_id`. No errors are reported if the path doesn't resolve.
+    pub fn resolve_str_path_error(&mut self,
+                                  span: Span,
+                                  path_str: &str,
+                                  ns: Namespace,
+                                  module_id: NodeId)
+                                  -> Result<Def, ()> {
+        let path: Vec<SpannedIdent> = path_str.split("::").enumerate().map(|(i, segment)| {
+            // `::a::b` and `crate::a::b` are both relative to the crate root.
+            let name = if i == 0 && (segment.is_empty() || segment == "crate") {
+                keywords::CrateRoot.name()
+            } else {
+                Symbol::intern(segment)
+            };
+            respan(span, Ident::with_empty_ctxt(name))
+        }).collect();
+
+        let mut result = Err(());
+        self.with_scope(module_id, |this| {
+            result = match this.resolve_path(&path, Some(ns), false, span) {
+                PathResult::Module(module) => module.def().ok_or(()),
+                PathResult::NonModule(path_res) if path_res.unresolved_segments() == 0 => {
+                    Ok(path_res.base_def())
+                }
+                _ => Err(()),
+            };
+        });
+        result
+    }
+
     fn per_ns<T, F: FnMut(&mut Self, Namespace) -> T>(&mut self, mut f: F) -> PerNS<T> {
         PerNS {
             type_ns: f(self, TypeNS),
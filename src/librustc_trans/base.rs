@@ -299,7 +299,9 @@ pub fn coerce_unsized_into<'a, 'tcx>(bcx: &Builder<'a, 'tcx>,
         (&ty::TyRawPtr(..), &ty::TyRawPtr(..)) => {
             coerce_ptr()
         }
-        (&ty::TyAdt(def_a, _), &ty::TyAdt(def_b, _)) if def_a.is_box() && def_b.is_box() => {
+        (&ty::TyAdt(def_a, _), &ty::TyAdt(def_b, _))
+                if def_a.is_box() && def_b.is_box() &&
+                   common::type_is_ptr_box(bcx.ccx, src_ty) => {
             coerce_ptr()
         }
 
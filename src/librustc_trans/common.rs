@@ -53,6 +53,15 @@ pub fn type_is_freeze<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, ty: Ty<'tcx>) -> bo
     ty.is_freeze(tcx, ty::ParamEnv::empty(traits::Reveal::All), DUMMY_SP)
 }
 
+/// Returns true if `ty` is a `Box` whose allocator is zero-sized, i.e. one
+/// that is represented by nothing more than its (possibly fat) pointer.
+pub fn type_is_ptr_box<'a, 'tcx>(ccx: &CrateContext<'a, 'tcx>, ty: Ty<'tcx>) -> bool {
+    match ty.sty {
+        ty::TyAdt(def, _) if def.is_box() => ccx.layout_of(ty).field(ccx, 1).is_zst(),
+        _ => false
+    }
+}
+
 /*
 * A note on nomenclature of linking: "extern", "foreign", and "upcall".
 *
@@ -19,6 +19,7 @@ use rustc::mir::visit::{Visitor, PlaceContext};
 use rustc::mir::traversal;
 use rustc::ty;
 use rustc::ty::layout::LayoutOf;
+use common;
 use type_of::LayoutLlvmExt;
 use super::MirContext;
 
@@ -118,11 +119,13 @@ impl<'mir, 'a, 'tcx> Visitor<'tcx> for LocalAnalyzer<'mir, 'a, 'tcx> {
                 }),
                 ref args, ..
             } if Some(def_id) == self.cx.ccx.tcx().lang_items().box_free_fn() => {
-                // box_free(x) shares with `drop x` the property that it
+                // box_free(x.0, x.1) shares with `drop x` the property that it
                 // is not guaranteed to be statically dominated by the
                 // definition of x, so x must always be in an alloca.
-                if let mir::Operand::Move(ref place) = args[0] {
-                    self.visit_place(place, PlaceContext::Drop, location);
+                for arg in args {
+                    if let mir::Operand::Move(ref place) = *arg {
+                        self.visit_place(place, PlaceContext::Drop, location);
+                    }
                 }
             }
             _ => {}
@@ -167,9 +170,15 @@ impl<'mir, 'a, 'tcx> Visitor<'tcx> for LocalAnalyzer<'mir, 'a, 'tcx> {
                 }
             }
 
-            // A deref projection only reads the pointer, never needs the place.
+            // A deref projection only reads the pointer, never needs the place,
+            // unless it's a `Box` with a non-zero-sized allocator, which
+            // `trans_place` has to read the pointer field out of.
             if let mir::ProjectionElem::Deref = proj.elem {
-                return self.visit_place(&proj.base, PlaceContext::Copy, location);
+                let base_ty = proj.base.ty(self.cx.mir, ccx.tcx());
+                let base_ty = self.cx.monomorphize(&base_ty.to_ty(ccx.tcx()));
+                if !base_ty.is_box() || common::type_is_ptr_box(ccx, base_ty) {
+                    return self.visit_place(&proj.base, PlaceContext::Copy, location);
+                }
             }
         }
 
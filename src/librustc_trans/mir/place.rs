@@ -16,7 +16,7 @@ use rustc::mir::tcx::PlaceTy;
 use rustc_data_structures::indexed_vec::Idx;
 use base;
 use builder::Builder;
-use common::{CrateContext, C_usize, C_u8, C_u32, C_uint, C_int, C_null, C_uint_big};
+use common::{self, CrateContext, C_usize, C_u8, C_u32, C_uint, C_int, C_null, C_uint_big};
 use consts;
 use type_of::LayoutLlvmExt;
 use type_::Type;
@@ -430,8 +430,20 @@ impl<'a, 'tcx> MirContext<'a, 'tcx> {
                 ref base,
                 elem: mir::ProjectionElem::Deref
             }) => {
-                // Load the pointer from its location.
-                self.trans_consume(bcx, base).deref(bcx.ccx)
+                let base_ty = self.monomorphized_place_ty(base);
+                if base_ty.is_box() && !common::type_is_ptr_box(bcx.ccx, base_ty) {
+                    // A `Box` with a non-zero-sized allocator is an aggregate,
+                    // so load the pointer out of `Box.0: Unique<T>`, through
+                    // `Unique.pointer: NonZero<*const T>` and `NonZero.0`.
+                    let ptr = self.trans_place(bcx, base)
+                        .project_field(bcx, 0)
+                        .project_field(bcx, 0)
+                        .project_field(bcx, 0);
+                    ptr.load(bcx).deref(bcx.ccx)
+                } else {
+                    // Load the pointer from its location.
+                    self.trans_consume(bcx, base).deref(bcx.ccx)
+                }
             }
             mir::Place::Projection(ref projection) => {
                 let tr_base = self.trans_place(bcx, &projection.base);
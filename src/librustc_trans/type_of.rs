@@ -341,7 +341,7 @@ impl<'tcx> LayoutLlvmExt<'tcx> for TyLayout<'tcx> {
             ty::TyRawPtr(_) => {
                 return self.field(ccx, index).llvm_type(ccx);
             }
-            ty::TyAdt(def, _) if def.is_box() => {
+            ty::TyAdt(def, _) if def.is_box() && type_is_ptr_box(ccx, self.ty) => {
                 let ptr_ty = ccx.tcx().mk_mut_ptr(self.ty.boxed_ty());
                 return ccx.layout_of(ptr_ty).scalar_pair_element_llvm_type(ccx, index);
             }
@@ -487,7 +487,7 @@ impl<'tcx> LayoutLlvmExt<'tcx> for TyLayout<'tcx> {
                 // FIXME(eddyb) This should be for `ptr::Unique<T>`, not `Box<T>`.
                 if let Some(ref mut pointee) = result {
                     if let ty::TyAdt(def, _) = self.ty.sty {
-                        if def.is_box() && offset.bytes() == 0 {
+                        if def.is_box() && offset == self.fields.offset(0) {
                             pointee.safe = Some(PointerKind::UniqueOwned);
                         }
                     }
@@ -116,8 +116,10 @@ pub fn try_inline(cx: &DocContext, def: Def, name: ast::Name)
     Some(ret)
 }
 
+/// Loads the attributes of an item from another crate. Intra-doc links in them aren't
+/// resolved, since that can only be done in the scope of a local module.
 pub fn load_attrs(cx: &DocContext, did: DefId) -> clean::Attributes {
-    cx.tcx.get_attrs(did).clean(cx)
+    clean::Attributes::from_ast(cx.sess().diagnostic(), &cx.tcx.get_attrs(did))
 }
 
 /// Record an external fully qualified name in the external_paths cache.
@@ -38,6 +38,7 @@ use rustc::ty::subst::Substs;
 use rustc::ty::{self, Ty, AdtKind};
 use rustc::middle::stability;
 use rustc::util::nodemap::{FxHashMap, FxHashSet};
+use rustc_resolve::Namespace::{self, TypeNS, ValueNS};
 use rustc_typeck::hir_ty_to_ty;
 
 use rustc::hir;
@@ -53,6 +54,7 @@ use core::DocContext;
 use doctree;
 use visit_ast;
 use html::item_type::ItemType;
+use html::markdown;
 
 pub mod inline;
 pub mod cfg;
@@ -124,7 +126,7 @@ pub struct Crate {
     pub masked_crates: FxHashSet<CrateNum>,
 }
 
-impl<'a, 'tcx> Clean<Crate> for visit_ast::RustdocVisitor<'a, 'tcx> {
+impl<'a, 'tcx, 'rcx> Clean<Crate> for visit_ast::RustdocVisitor<'a, 'tcx, 'rcx> {
     fn clean(&self, cx: &DocContext) -> Crate {
         use ::visit_lib::LibEmbargoVisitor;
 
@@ -466,6 +468,9 @@ impl Clean<Item> for doctree::Module {
             "".to_string()
         };
 
+        // Intra-doc links in the module and its items are resolved in its scope.
+        cx.mod_ids.borrow_mut().push(self.id);
+
         let mut items: Vec<Item> = vec![];
         items.extend(self.extern_crates.iter().map(|x| x.clean(cx)));
         items.extend(self.imports.iter().flat_map(|x| x.clean(cx)));
@@ -498,9 +503,12 @@ impl Clean<Item> for doctree::Module {
             }
         };
 
+        let attrs = self.attrs.clean(cx);
+        cx.mod_ids.borrow_mut().pop();
+
         Item {
             name: Some(name),
-            attrs: self.attrs.clean(cx),
+            attrs,
             source: whence.clean(cx),
             visibility: self.vis.clean(cx),
             stability: self.stab.clean(cx),
@@ -635,6 +643,9 @@ pub struct Attributes {
     pub other_attrs: Vec<ast::Attribute>,
     pub cfg: Option<Rc<Cfg>>,
     pub span: Option<syntax_pos::Span>,
+    /// The intra-doc links in the documentation: the link as written, the item it resolved
+    /// to, and the fragment to append to that item's URL, if any.
+    pub links: Vec<(String, DefId, Option<String>)>,
 }
 
 impl Attributes {
@@ -769,6 +780,7 @@ impl Attributes {
             other_attrs,
             cfg: if cfg == Cfg::True { None } else { Some(Rc::new(cfg)) },
             span: sp,
+            links: vec![],
         }
     }
 
@@ -787,6 +799,23 @@ impl Attributes {
             None
         }
     }
+
+    /// Returns the intra-doc links along with the URLs they point to. Links to items which
+    /// aren't documented are left out.
+    ///
+    /// The cache must be populated before this is called.
+    pub fn links(&self) -> Vec<(String, String)> {
+        use html::format::href;
+        self.links.iter().filter_map(|&(ref link, did, ref fragment)| {
+            href(did).map(|(mut url, ..)| {
+                if let Some(ref fragment) = *fragment {
+                    url.push('#');
+                    url.push_str(fragment);
+                }
+                (link.clone(), url)
+            })
+        }).collect()
+    }
 }
 
 impl AttributesExt for Attributes {
@@ -797,8 +826,134 @@ impl AttributesExt for Attributes {
 
 impl Clean<Attributes> for [ast::Attribute] {
     fn clean(&self, cx: &DocContext) -> Attributes {
-        Attributes::from_ast(cx.sess().diagnostic(), self)
+        let mut attrs = Attributes::from_ast(cx.sess().diagnostic(), self);
+
+        // Links are resolved in the scope of the module being cleaned; the attributes of
+        // inlined items from other crates are loaded separately.
+        let module_id = cx.mod_ids.borrow().last().cloned();
+        if let (Some(module_id), Some(dox)) = (module_id, attrs.collapsed_doc_value()) {
+            for link in markdown::markdown_links(&dox) {
+                let (path_str, ns) = match intra_link_path(&link) {
+                    Some(path) => path,
+                    None => continue,
+                };
+                match resolve_intra_link(cx, path_str, ns, module_id) {
+                    Some((did, fragment)) => attrs.links.push((link.clone(), did, fragment)),
+                    // A lone word is as likely to be a relative URL or some text that happens
+                    // to be in brackets as a path, so only warn about the links that were
+                    // clearly meant to name an item.
+                    None if ns.is_some() || path_str.contains("::") ||
+                            link.trim().starts_with('`') => {
+                        let sp = attrs.span.unwrap_or(DUMMY_SP);
+                        cx.sess().span_warn(sp, &format!("unresolved link to `{}`", path_str));
+                    }
+                    None => {}
+                }
+            }
+        }
+
+        attrs
+    }
+}
+
+/// Extracts the Rust path from the target of an intra-doc link, along with the namespace
+/// it is restricted to, if any. The path may be in backticks, have a prefix such as
+/// `struct@` or `fn@`, or end in `()` to pick a namespace. Returns `None` for anything that
+/// isn't a path, such as a URL.
+fn intra_link_path(link: &str) -> Option<(&str, Option<Namespace>)> {
+    let mut path = link.trim().trim_matches('`');
+    let mut ns = None;
+
+    if let Some(idx) = path.find('@') {
+        ns = Some(match &path[..idx] {
+            "struct" | "enum" | "union" | "trait" | "type" | "mod" | "module" => TypeNS,
+            "fn" | "function" | "method" | "const" | "constant" | "static" | "value" => ValueNS,
+            _ => return None,
+        });
+        path = &path[idx + 1..];
+    } else if path.ends_with("()") {
+        ns = Some(ValueNS);
+        path = &path[..path.len() - 2];
+    }
+
+    let is_path = !path.is_empty() && path.split("::").enumerate().all(|(i, segment)| {
+        (i == 0 && segment.is_empty()) ||
+        (!segment.is_empty() && segment.chars().all(|c| c == '_' || c.is_alphanumeric()))
+    });
+    if is_path && path != "::" {
+        Some((path, ns))
+    } else {
+        None
+    }
+}
+
+/// Resolves the path of an intra-doc link in the scope of the module `module_id`, returning
+/// the item to link to and the fragment of its page to link to, if any.
+fn resolve_intra_link(cx: &DocContext, path_str: &str, ns: Option<Namespace>,
+                      module_id: ast::NodeId) -> Option<(DefId, Option<String>)> {
+    let resolve = |path: &str, ns| {
+        cx.resolver.borrow_mut().resolve_str_path_error(DUMMY_SP, path, ns, module_id).ok()
+    };
+
+    let namespaces = match ns {
+        Some(ns) => vec![ns],
+        None => vec![TypeNS, ValueNS],
+    };
+    for &ns in &namespaces {
+        if let Some(def) = resolve(path_str, ns) {
+            return match def {
+                Def::Variant(did) | Def::VariantCtor(did, _) => {
+                    let fragment = format!("variant.{}", cx.tcx.item_name(did));
+                    cx.tcx.parent_def_id(did).map(|parent| (parent, Some(fragment)))
+                }
+                Def::StructCtor(did, _) => cx.tcx.parent_def_id(did).map(|parent| (parent, None)),
+                Def::Mod(did) | Def::Struct(did) | Def::Union(did) | Def::Enum(did) |
+                Def::Trait(did) | Def::TyAlias(did) | Def::TyForeign(did) | Def::Fn(did) |
+                Def::Const(did) | Def::Static(did, _) | Def::Macro(did, _) => Some((did, None)),
+                _ => None,
+            };
+        }
+    }
+
+    // The resolver doesn't know about associated items and fields, so for `Type::item`
+    // look `item` up in the type instead.
+    let idx = path_str.rfind("::")?;
+    let (parent, item) = (&path_str[..idx], &path_str[idx + 2..]);
+    let parent_def = resolve(parent, TypeNS)?;
+    let (did, is_trait) = match parent_def {
+        Def::Trait(did) => (did, true),
+        Def::Struct(did) | Def::Union(did) | Def::Enum(did) | Def::TyForeign(did) => (did, false),
+        _ => return None,
+    };
+
+    let containers = if is_trait {
+        vec![did]
+    } else {
+        cx.tcx.inherent_impls(did).iter().cloned().collect()
+    };
+    for container in containers {
+        let assoc = cx.tcx.associated_items(container).find(|assoc| assoc.name == item);
+        if let Some(assoc) = assoc {
+            let kind = match assoc.kind {
+                ty::AssociatedKind::Method if is_trait && !assoc.defaultness.has_value() => {
+                    "tymethod"
+                }
+                ty::AssociatedKind::Method => "method",
+                ty::AssociatedKind::Const => "associatedconstant",
+                ty::AssociatedKind::Type => "associatedtype",
+            };
+            return Some((did, Some(format!("{}.{}", kind, item))));
+        }
     }
+
+    if let Def::Struct(_) = parent_def {
+        let adt = cx.tcx.adt_def(did);
+        if adt.struct_variant().fields.iter().any(|field| field.name == item) {
+            return Some((did, Some(format!("structfield.{}", item))));
+        }
+    }
+
+    None
 }
 
 #[derive(Clone, RustcEncodable, RustcDecodable, PartialEq, Debug)]
@@ -2302,7 +2457,7 @@ impl<'tcx> Clean<Item> for ty::FieldDef {
     fn clean(&self, cx: &DocContext) -> Item {
         Item {
             name: Some(self.name).clean(cx),
-            attrs: cx.tcx.get_attrs(self.did).clean(cx),
+            attrs: inline::load_attrs(cx, self.did),
             source: cx.tcx.def_span(self.did).clean(cx),
             visibility: self.vis.clean(cx),
             stability: get_stability(cx, self.did),
@@ -2472,7 +2627,7 @@ impl<'tcx> Clean<Item> for ty::VariantDef {
                         Item {
                             source: cx.tcx.def_span(field.did).clean(cx),
                             name: Some(field.name.clean(cx)),
-                            attrs: cx.tcx.get_attrs(field.did).clean(cx),
+                            attrs: inline::load_attrs(cx, field.did),
                             visibility: field.vis.clean(cx),
                             def_id: field.did,
                             stability: get_stability(cx, field.did),
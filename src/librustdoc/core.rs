@@ -21,8 +21,10 @@ use rustc::util::nodemap::FxHashMap;
 use rustc_trans;
 use rustc_trans::back::link;
 use rustc_resolve as resolve;
+use rustc_metadata::creader::CrateLoader;
 use rustc_metadata::cstore::CStore;
 
+use syntax::ast::NodeId;
 use syntax::codemap;
 use syntax::feature_gate::UnstableFeatures;
 use errors;
@@ -36,6 +38,9 @@ use std::path::PathBuf;
 use visit_ast::RustdocVisitor;
 use clean;
 use clean::Clean;
+use json;
+use scrape_examples;
+use span_map;
 use html::render::RenderInfo;
 use arena::DroplessArena;
 
@@ -44,8 +49,12 @@ pub use rustc::session::search_paths::SearchPaths;
 
 pub type ExternalPaths = FxHashMap<DefId, (Vec<String>, clean::TypeKind)>;
 
-pub struct DocContext<'a, 'tcx: 'a> {
+pub struct DocContext<'a, 'tcx: 'a, 'rcx: 'a> {
     pub tcx: TyCtxt<'a, 'tcx, 'tcx>,
+    /// Used to resolve the paths in intra-doc links
+    pub resolver: &'a RefCell<resolve::Resolver<'rcx>>,
+    /// The stack of modules being cleaned, whose scope intra-doc links are resolved in
+    pub mod_ids: RefCell<Vec<NodeId>>,
     pub populated_all_crate_impls: Cell<bool>,
     // Note that external items for which `doc(hidden)` applies to are shown as
     // non-reachable while local items aren't. This is because we're reusing
@@ -66,7 +75,7 @@ pub struct DocContext<'a, 'tcx: 'a> {
     pub lt_substs: RefCell<FxHashMap<DefId, clean::Lifetime>>,
 }
 
-impl<'a, 'tcx> DocContext<'a, 'tcx> {
+impl<'a, 'tcx, 'rcx> DocContext<'a, 'tcx, 'rcx> {
     pub fn sess(&self) -> &session::Session {
         &self.tcx.sess
     }
@@ -98,6 +107,13 @@ impl DocAccessLevels for AccessLevels<DefId> {
     }
 }
 
+/// Returns the crate name and definition path of `did`, such as `std::vec[0]::Vec[0]`. Unlike
+/// the `DefId` itself, this is the same whether the crate is being documented or is a
+/// dependency of the one that is.
+pub fn def_key(tcx: TyCtxt, did: DefId) -> String {
+    format!("{}{}", tcx.crate_name(did.krate), tcx.def_path(did).to_string_no_crate())
+}
+
 
 pub fn run_core(search_paths: SearchPaths,
                 cfgs: Vec<String>,
@@ -106,7 +122,9 @@ pub fn run_core(search_paths: SearchPaths,
                 triple: Option<String>,
                 maybe_sysroot: Option<PathBuf>,
                 allow_warnings: bool,
-                force_unstable_if_unmarked: bool) -> (clean::Crate, RenderInfo)
+                force_unstable_if_unmarked: bool,
+                scrape_examples: Option<scrape_examples::ScrapeOptions>,
+                with_examples: Vec<PathBuf>) -> (clean::Crate, RenderInfo)
 {
     // Parse, resolve, and typecheck the given crate.
 
@@ -158,18 +176,43 @@ pub fn run_core(search_paths: SearchPaths,
 
     let name = link::find_crate_name(Some(&sess), &krate.attrs, &input);
 
-    let driver::ExpansionResult { defs, analysis, resolutions, mut hir_forest, .. } = {
-        let result = driver::phase_2_configure_and_expand(&sess,
-                                                          &cstore,
-                                                          krate,
-                                                          None,
-                                                          &name,
-                                                          None,
-                                                          resolve::MakeGlobMap::No,
-                                                          |_| Ok(()));
-        abort_on_err(result, &sess)
+    let mut crate_loader = CrateLoader::new(&sess, &cstore, &name);
+
+    let resolver_arenas = resolve::Resolver::arenas();
+    let result = driver::phase_2_configure_and_expand_inner(&sess,
+                                                            &cstore,
+                                                            krate,
+                                                            None,
+                                                            &name,
+                                                            None,
+                                                            resolve::MakeGlobMap::No,
+                                                            &resolver_arenas,
+                                                            &mut crate_loader,
+                                                            |_| Ok(()));
+    let driver::InnerExpansionResult {
+        mut hir_forest,
+        resolver,
+        ..
+    } = abort_on_err(result, &sess);
+
+    // The resolver is kept around to resolve intra-doc links while the crate is cleaned, so
+    // the analysis passes get copies of its results.
+    let defs = resolver.definitions.clone();
+    let resolutions = ty::Resolutions {
+        freevars: resolver.freevars.clone(),
+        export_map: resolver.export_map.clone(),
+        trait_map: resolver.trait_map.clone(),
+        maybe_unused_trait_imports: resolver.maybe_unused_trait_imports.clone(),
+        maybe_unused_extern_crates: resolver.maybe_unused_extern_crates.clone(),
+    };
+    let analysis = ty::CrateAnalysis {
+        access_levels: Rc::new(AccessLevels::default()),
+        name: name.to_string(),
+        glob_map: None,
     };
 
+    let resolver = RefCell::new(resolver);
+
     let arena = DroplessArena::new();
     let arenas = GlobalArenas::new();
     let hir_map = hir_map::map_crate(&sess, &*cstore, &mut hir_forest, &defs);
@@ -206,6 +249,8 @@ pub fn run_core(search_paths: SearchPaths,
 
         let ctxt = DocContext {
             tcx,
+            resolver: &resolver,
+            mod_ids: Default::default(),
             populated_all_crate_impls: Cell::new(false),
             access_levels: RefCell::new(access_levels),
             external_traits: Default::default(),
@@ -220,7 +265,17 @@ pub fn run_core(search_paths: SearchPaths,
             v.visit(tcx.hir.krate());
             v.clean(&ctxt)
         };
-
-        (krate, ctxt.renderinfo.into_inner())
+        let span_map = span_map::collect(&ctxt);
+        if let Some(ref options) = scrape_examples {
+            scrape_examples::scrape(&ctxt, options);
+        }
+        let call_locations = scrape_examples::load(&ctxt, &with_examples);
+        let def_keys = json::def_keys(&ctxt, &krate);
+
+        let mut renderinfo = ctxt.renderinfo.into_inner();
+        renderinfo.span_map = span_map;
+        renderinfo.call_locations = call_locations;
+        renderinfo.def_keys = def_keys;
+        (krate, renderinfo)
     }), &sess)
 }
@@ -0,0 +1,220 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Documentation coverage reports, for `--show-coverage`.
+//!
+//! This walks the crate as it looks after the passes have run, so with the default passes
+//! only public items which aren't `#[doc(hidden)]` are counted. Items in trait impls aren't
+//! counted either, since rustdoc shows the trait's documentation for them.
+
+use std::collections::BTreeMap;
+use std::ops;
+
+use serialize::json::{Json, ToJson};
+
+use clean;
+use html::item_type::ItemType;
+use html::markdown;
+
+#[derive(Default, Copy, Clone)]
+pub struct ItemCount {
+    pub total: u64,
+    pub with_docs: u64,
+    pub with_examples: u64,
+}
+
+impl ItemCount {
+    fn count_item(&mut self, has_docs: bool, has_examples: bool) {
+        self.total += 1;
+        if has_docs {
+            self.with_docs += 1;
+        }
+        if has_examples {
+            self.with_examples += 1;
+        }
+    }
+
+    fn percentage(&self) -> Option<f64> {
+        if self.total > 0 {
+            Some(self.with_docs as f64 * 100.0 / self.total as f64)
+        } else {
+            None
+        }
+    }
+}
+
+impl ops::AddAssign for ItemCount {
+    fn add_assign(&mut self, rhs: ItemCount) {
+        self.total += rhs.total;
+        self.with_docs += rhs.with_docs;
+        self.with_examples += rhs.with_examples;
+    }
+}
+
+impl ToJson for ItemCount {
+    fn to_json(&self) -> Json {
+        let mut obj = BTreeMap::new();
+        obj.insert("total".to_string(), self.total.to_json());
+        obj.insert("with_docs".to_string(), self.with_docs.to_json());
+        obj.insert("with_examples".to_string(), self.with_examples.to_json());
+        Json::Object(obj)
+    }
+}
+
+/// The number of documented items in each module of a crate, by item kind.
+pub struct CoverageReport {
+    /// Counts keyed by module path, then by item kind.
+    modules: BTreeMap<String, BTreeMap<&'static str, ItemCount>>,
+}
+
+impl CoverageReport {
+    pub fn new(krate: &clean::Crate) -> CoverageReport {
+        let mut report = CoverageReport { modules: BTreeMap::new() };
+        if let Some(ref module) = krate.module {
+            report.visit_item(module, &krate.name);
+        }
+        report
+    }
+
+    fn visit_item(&mut self, item: &clean::Item, module: &str) {
+        match item.inner {
+            clean::ModuleItem(ref m) => {
+                let path = match item.name {
+                    Some(ref name) if !m.is_crate => format!("{}::{}", module, name),
+                    _ => module.to_string(),
+                };
+                self.count(item, &path);
+                for item in &m.items {
+                    self.visit_item(item, &path);
+                }
+            }
+            // The contents of a stripped module aren't part of the crate's API, but the
+            // inherent impls in it are.
+            clean::StrippedItem(box clean::ModuleItem(ref m)) => {
+                for item in &m.items {
+                    match item.inner {
+                        clean::ImplItem(..) |
+                        clean::StrippedItem(box clean::ModuleItem(..)) => {
+                            self.visit_item(item, module);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            clean::ImplItem(ref i) => {
+                if i.trait_.is_none() {
+                    for item in &i.items {
+                        self.visit_item(item, module);
+                    }
+                }
+            }
+            clean::StructItem(clean::Struct { ref fields, .. }) |
+            clean::UnionItem(clean::Union { ref fields, .. }) |
+            clean::VariantItem(clean::Variant {
+                kind: clean::VariantKind::Struct(clean::VariantStruct { ref fields, .. })
+            }) => {
+                self.count(item, module);
+                for field in fields {
+                    self.visit_item(field, module);
+                }
+            }
+            clean::EnumItem(ref e) => {
+                self.count(item, module);
+                for variant in &e.variants {
+                    self.visit_item(variant, module);
+                }
+            }
+            clean::TraitItem(ref t) => {
+                self.count(item, module);
+                for item in &t.items {
+                    self.visit_item(item, module);
+                }
+            }
+            clean::StrippedItem(..) |
+            clean::AutoImplItem(..) |
+            clean::ExternCrateItem(..) |
+            clean::ImportItem(..) => {}
+            _ => self.count(item, module),
+        }
+    }
+
+    fn count(&mut self, item: &clean::Item, module: &str) {
+        let docs = item.attrs.collapsed_doc_value().unwrap_or_default();
+        let has_docs = !docs.trim().is_empty();
+        let has_examples = has_docs && markdown::has_code_examples(&docs);
+        let kind = ItemType::from(item).css_class();
+        self.modules.entry(module.to_string()).or_insert_with(BTreeMap::new)
+            .entry(kind).or_insert_with(ItemCount::default)
+            .count_item(has_docs, has_examples);
+    }
+
+    fn totals(&self) -> (ItemCount, BTreeMap<&'static str, ItemCount>) {
+        let mut total = ItemCount::default();
+        let mut kinds = BTreeMap::new();
+        for counts in self.modules.values() {
+            for (&kind, &count) in counts {
+                total += count;
+                *kinds.entry(kind).or_insert_with(ItemCount::default) += count;
+            }
+        }
+        (total, kinds)
+    }
+
+    pub fn print_table(&self) {
+        fn print_row(name: &str, count: &ItemCount) {
+            let percentage = match count.percentage() {
+                Some(p) => format!("{:.1}%", p),
+                None => "-".to_string(),
+            };
+            println!("| {:<35} | {:>10} | {:>10} | {:>10} | {:>10} |",
+                     name, count.total, count.with_docs, percentage, count.with_examples);
+        }
+        let separator = format!("+{}+{}+", "-".repeat(37), vec!["-".repeat(12); 4].join("+"));
+
+        println!("{}", separator);
+        println!("| {:<35} | {:>10} | {:>10} | {:>10} | {:>10} |",
+                 "Module / item kind", "Public", "Documented", "Percentage", "Examples");
+        println!("{}", separator);
+        for (module, counts) in &self.modules {
+            let mut module_total = ItemCount::default();
+            for &count in counts.values() {
+                module_total += count;
+            }
+            print_row(module, &module_total);
+            for (kind, count) in counts {
+                print_row(&format!("  {}", kind), count);
+            }
+        }
+        println!("{}", separator);
+        let (total, kinds) = self.totals();
+        for (kind, count) in &kinds {
+            print_row(&format!("  {}", kind), count);
+        }
+        print_row("Total", &total);
+        println!("{}", separator);
+    }
+}
+
+impl ToJson for CoverageReport {
+    fn to_json(&self) -> Json {
+        let modules = self.modules.iter().map(|(module, counts)| {
+            let counts = counts.iter().map(|(kind, count)| (kind.to_string(), count.to_json()));
+            (module.clone(), Json::Object(counts.collect()))
+        }).collect();
+        let (total, kinds) = self.totals();
+        let kinds = kinds.iter().map(|(kind, count)| (kind.to_string(), count.to_json()));
+
+        let mut obj = BTreeMap::new();
+        obj.insert("modules".to_string(), Json::Object(modules));
+        obj.insert("kinds".to_string(), Json::Object(kinds.collect()));
+        obj.insert("total".to_string(), total.to_json());
+        Json::Object(obj)
+    }
+}
@@ -26,6 +26,7 @@ use std::fmt::Display;
 use std::io;
 use std::io::prelude::*;
 
+use rustc::util::nodemap::FxHashMap;
 use syntax::codemap::{CodeMap, FilePathMapping};
 use syntax::parse::lexer::{self, TokenAndSpan};
 use syntax::parse::token;
@@ -60,6 +61,25 @@ pub fn render_with_highlighting(src: &str, class: Option<&str>, id: Option<&str>
     String::from_utf8_lossy(&out[..]).into_owned()
 }
 
+/// Highlights the source of a file like `render_with_highlighting`, turning the
+/// identifiers whose byte ranges within `src` are in `links` into links to the
+/// corresponding URLs.
+pub fn render_with_links(src: &str, links: &FxHashMap<(u32, u32), String>) -> String {
+    let sess = parse::ParseSess::new(FilePathMapping::empty());
+    let fm = sess.codemap().new_filemap(FileName::Custom("stdin".to_string()), src.to_string());
+
+    let mut out = LinkedWriter { out: Vec::new(), links };
+    write_header(None, None, &mut out.out).unwrap();
+
+    let mut classifier = Classifier::new(lexer::StringReader::new(&sess, fm), sess.codemap());
+    if let Err(_) = classifier.write_source(&mut out) {
+        return format!("<pre>{}</pre>", src);
+    }
+
+    write_footer(&mut out.out).unwrap();
+    String::from_utf8_lossy(&out.out[..]).into_owned()
+}
+
 /// Highlights `src`, returning the HTML output. Returns only the inner html to
 /// be inserted into an element. C.f., `render_with_highlighting` which includes
 /// an enclosing `<pre>` block.
@@ -167,6 +187,40 @@ impl<U: Write> Writer for U {
     }
 }
 
+/// Writes the default rustdoc output, wrapping the tokens whose spans are in
+/// `links` in links to the corresponding URLs. The source is the only file in
+/// its codemap, so the spans of the tokens are their byte ranges in the source.
+struct LinkedWriter<'a> {
+    out: Vec<u8>,
+    links: &'a FxHashMap<(u32, u32), String>,
+}
+
+impl<'a> Writer for LinkedWriter<'a> {
+    fn string<T: Display>(&mut self,
+                          text: T,
+                          klass: Class,
+                          tas: Option<&TokenAndSpan>)
+                          -> io::Result<()> {
+        let links = self.links;
+        match tas.and_then(|tas| links.get(&(tas.sp.lo().0, tas.sp.hi().0))) {
+            Some(url) => {
+                write!(self.out, "<a href=\"{}\">", Escape(url))?;
+                self.out.string(text, klass, tas)?;
+                write!(self.out, "</a>")
+            }
+            None => self.out.string(text, klass, tas),
+        }
+    }
+
+    fn enter_span(&mut self, klass: Class) -> io::Result<()> {
+        self.out.enter_span(klass)
+    }
+
+    fn exit_span(&mut self) -> io::Result<()> {
+        self.out.exit_span()
+    }
+}
+
 impl<'a> Classifier<'a> {
     pub fn new(lexer: lexer::StringReader<'a>, codemap: &'a CodeMap) -> Classifier<'a> {
         Classifier {
@@ -128,7 +128,9 @@ r##"<!DOCTYPE html>
 
                 <p>
                     Search functions by type signature (e.g.
-                    <code>vec -> usize</code> or <code>* -> vec</code>)
+                    <code>Vec&lt;T&gt; -> usize</code>, <code>&amp;str -> Option&lt;_&gt;</code>
+                    or <code>* -> vec</code>). <code>_</code> and single letters like
+                    <code>T</code> stand for any type.
                 </p>
             </div>
         </div>
@@ -1140,9 +1140,110 @@ pub fn plain_summary_line(md: &str) -> String {
     s
 }
 
+/// Returns whether `md` contains at least one Rust code block, i.e. an example that would be
+/// run as a doctest.
+pub fn has_code_examples(md: &str) -> bool {
+    Parser::new(md).any(|event| match event {
+        Event::Start(Tag::CodeBlock(lang)) => lang.is_empty() || LangString::parse(&lang).rust,
+        _ => false,
+    })
+}
+
+/// Returns the links in `md` which may be intra-doc links: the destinations of inline and
+/// reference links, and the text of shortcut links that have no destination, such as
+/// ``[`Vec::push`]`` or `[std::mem::swap]`. Whether they actually are paths to items is up
+/// to the caller.
+pub fn markdown_links(md: &str) -> Vec<String> {
+    if md.is_empty() {
+        return vec![];
+    }
+
+    let mut opts = Options::empty();
+    opts.insert(OPTION_ENABLE_TABLES);
+    opts.insert(OPTION_ENABLE_FOOTNOTES);
+
+    let mut links = vec![];
+    // The text of the current block, with inline code in backticks, which is searched for
+    // shortcut links when the block ends. The text of code blocks is left out.
+    let mut text = String::new();
+    let mut in_code_block = false;
+
+    for event in Parser::new_ext(md, opts) {
+        match event {
+            Event::Start(Tag::Link(dest, _)) => {
+                if !dest.is_empty() {
+                    links.push(dest.into_owned());
+                }
+                // The brackets around the text of a link aren't part of the text, but keep
+                // it from being joined up with whatever is around it.
+                text.push('\n');
+            }
+            Event::End(Tag::Link(..)) => text.push('\n'),
+            Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+            Event::End(Tag::CodeBlock(_)) => in_code_block = false,
+            Event::Start(Tag::Code) | Event::End(Tag::Code) => text.push('`'),
+            Event::Text(ref s) if !in_code_block => text.push_str(s),
+            Event::SoftBreak | Event::HardBreak => text.push(' '),
+            Event::End(_) => {
+                shortcut_links(&text, &mut links);
+                text.clear();
+            }
+            _ => {}
+        }
+    }
+    shortcut_links(&text, &mut links);
+
+    links
+}
+
+// Finds the shortcut links such as `[`Vec::push`]` in some text. Brackets around anything
+// other than inline code or a path with `::` are too likely to be plain text to count.
+fn shortcut_links(text: &str, links: &mut Vec<String>) {
+    let mut rest = text;
+    while let Some(start) = rest.find('[') {
+        rest = &rest[start + 1..];
+        let end = match rest.find(|c: char| c == '[' || c == ']' || c == '\n') {
+            Some(end) if rest[end..].starts_with(']') => end,
+            _ => continue,
+        };
+        let label = &rest[..end];
+        let is_code = label.len() > 2 && label.starts_with('`') && label.ends_with('`');
+        if is_code || label.contains("::") {
+            links.push(label.to_owned());
+        }
+        rest = &rest[end + 1..];
+    }
+}
+
+/// Points the intra-doc links in `md` at the URLs they were resolved to. `links` holds the
+/// links as returned by `markdown_links` along with their URLs. Shortcut links get a
+/// reference definition appended to the text, which turns them into actual links.
+pub fn replace_links(md: &str, links: &[(String, String)]) -> String {
+    let mut md = md.to_owned();
+    let mut definitions = String::new();
+
+    for &(ref link, ref url) in links {
+        let inline = format!("]({})", link);
+        let reference = format!("]: {}", link);
+        if md.contains(&inline) || md.contains(&reference) {
+            md = md.replace(&inline, &format!("]({})", url))
+                   .replace(&reference, &format!("]: {}", url));
+        } else {
+            definitions.push_str(&format!("\n[{}]: {}", link, url));
+        }
+    }
+
+    if !definitions.is_empty() {
+        md.push('\n');
+        md.push_str(&definitions);
+    }
+    md
+}
+
 #[cfg(test)]
 mod tests {
     use super::{LangString, Markdown, MarkdownHtml};
+    use super::{has_code_examples, markdown_links, replace_links};
     use super::plain_summary_line;
     use super::RenderType;
     use html::render::reset_ids;
@@ -1269,4 +1370,43 @@ mod tests {
         t("Struct<'a, T>", "<p>Struct&lt;'a, T&gt;</p>\n");
         t("Struct<br>", "<p>Struct&lt;br&gt;</p>\n");
     }
+
+    #[test]
+    fn test_has_code_examples() {
+        assert!(has_code_examples("```\nfoo();\n```"));
+        assert!(has_code_examples("text\n\n```rust,no_run\nfoo();\n```"));
+        assert!(has_code_examples("    indented();"));
+        assert!(!has_code_examples("```text\nnot rust\n```"));
+        assert!(!has_code_examples("just `inline` code"));
+    }
+
+    #[test]
+    fn test_markdown_links() {
+        fn t(input: &str, expect: &[&str]) {
+            assert_eq!(markdown_links(input), expect, "original: {}", input);
+        }
+
+        t("[Rust](https://www.rust-lang.org)", &["https://www.rust-lang.org"]);
+        t("see [the vector](Vec) or [`Vec::push`]", &["Vec", "`Vec::push`"]);
+        t("[std::mem::swap] and [`Option`]", &["std::mem::swap", "`Option`"]);
+        t("[foo]: crate::foo\n\nuse [foo]", &["crate::foo"]);
+        t("an [aside] and an array `[u8]`", &[]);
+        t("```\n[`Vec::push`]\n```", &[]);
+    }
+
+    #[test]
+    fn test_replace_links() {
+        fn t(input: &str, links: &[(&str, &str)], expect: &str) {
+            let links = links.iter()
+                             .map(|&(link, url)| (link.to_owned(), url.to_owned()))
+                             .collect::<Vec<_>>();
+            assert_eq!(replace_links(input, &links), expect, "original: {}", input);
+        }
+
+        t("see [the vector](Vec)", &[("Vec", "struct.Vec.html")],
+          "see [the vector](struct.Vec.html)");
+        t("[foo]: crate::foo", &[("crate::foo", "fn.foo.html")], "[foo]: fn.foo.html");
+        t("see [`Vec::push`]", &[("`Vec::push`", "struct.Vec.html#method.push")],
+          "see [`Vec::push`]\n\n[`Vec::push`]: struct.Vec.html#method.push");
+    }
 }
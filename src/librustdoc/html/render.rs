@@ -73,6 +73,8 @@ use html::format::fmt_impl_for_trait_page;
 use html::item_type::ItemType;
 use html::markdown::{self, Markdown, MarkdownHtml, MarkdownSummaryLine, RenderType};
 use html::{highlight, layout};
+use scrape_examples::FnCallLocations;
+use span_map::{LinkTarget, SpanMap};
 
 use html_diff;
 
@@ -129,6 +131,9 @@ pub struct SharedContext {
     /// The directories that have already been created in this doc run. Used to reduce the number
     /// of spurious `create_dir_all` calls.
     pub created_dirs: RefCell<FxHashSet<PathBuf>>,
+    /// The calls to the crate's functions and methods found in examples, given with
+    /// `--with-examples`.
+    pub call_locations: FxHashMap<DefId, FnCallLocations>,
 }
 
 impl SharedContext {
@@ -322,6 +327,10 @@ pub struct RenderInfo {
     pub deref_trait_did: Option<DefId>,
     pub deref_mut_trait_did: Option<DefId>,
     pub owned_box_did: Option<DefId>,
+    pub span_map: SpanMap,
+    pub call_locations: FxHashMap<DefId, FnCallLocations>,
+    /// The ids given to items by the JSON backend; see `core::def_key`.
+    pub def_keys: FxHashMap<DefId, String>,
 }
 
 /// Helper struct to render all source code to HTML pages
@@ -330,11 +339,15 @@ struct SourceCollector<'a> {
 
     /// Root destination to place all HTML output into
     dst: PathBuf,
+
+    /// Where the identifiers in each source file link to
+    span_map: SpanMap,
 }
 
 /// Wrapper struct to render the source code of a file. This will do things like
-/// adding line numbers to the left-hand side.
-struct Source<'a>(&'a str);
+/// adding line numbers to the left-hand side, and linking the identifiers whose
+/// byte ranges are in the map to the given URLs.
+struct Source<'a>(&'a str, &'a FxHashMap<(u32, u32), String>);
 
 // Helper structs for rendering items/sidebars and carrying along contextual
 // information
@@ -377,21 +390,29 @@ impl ToJson for IndexItem {
 
 /// A type used for the search index.
 struct Type {
+    /// The lowercased name of the type, or `None` if it can't be described in the index.
     name: Option<String>,
-    generics: Option<Vec<String>>,
+    generics: Vec<Type>,
+    /// For generic parameters and `impl Trait`, the traits the type is bounded by.
+    bounds: Option<Vec<Type>>,
 }
 
 impl ToJson for Type {
     fn to_json(&self) -> Json {
+        // Types are written out as compactly as possible, as there are a lot of them: just
+        // the name for a plain type, `[name, generics]` for one with generic arguments, and
+        // `[name, generics, bounds]` for a generic parameter.
         match self.name {
             Some(ref name) => {
-                let mut data = BTreeMap::new();
-                data.insert("name".to_owned(), name.to_json());
-                if let Some(ref generics) = self.generics {
-                    data.insert("generics".to_owned(), generics.to_json());
+                if self.generics.is_empty() && self.bounds.is_none() {
+                    return name.to_json();
                 }
-                Json::Object(data)
-            },
+                let mut data = vec![name.to_json(), self.generics.to_json()];
+                if let Some(ref bounds) = self.bounds {
+                    data.push(bounds.to_json());
+                }
+                Json::Array(data)
+            }
             None => Json::Null
         }
     }
@@ -405,15 +426,7 @@ struct IndexItemFunctionType {
 
 impl ToJson for IndexItemFunctionType {
     fn to_json(&self) -> Json {
-        // If we couldn't figure out a type, just write `null`.
-        if self.inputs.iter().chain(self.output.iter()).any(|ref i| i.name.is_none()) {
-            Json::Null
-        } else {
-            let mut data = BTreeMap::new();
-            data.insert("inputs".to_owned(), self.inputs.to_json());
-            data.insert("output".to_owned(), self.output.to_json());
-            Json::Object(data)
-        }
+        Json::Array(vec![self.inputs.to_json(), self.output.to_json()])
     }
 }
 
@@ -514,6 +527,7 @@ pub fn run(mut krate: clean::Crate,
         css_file_extension: css_file_extension.clone(),
         markdown_warnings: RefCell::new(vec![]),
         created_dirs: RefCell::new(FxHashSet()),
+        call_locations: FxHashMap(),
     };
 
     // If user passed in `--playground-url` arg, we fill in crate name here
@@ -552,14 +566,6 @@ pub fn run(mut krate: clean::Crate,
         }
     }
     try_err!(fs::create_dir_all(&dst), &dst);
-    krate = render_sources(&dst, &mut scx, krate)?;
-    let cx = Context {
-        current: Vec::new(),
-        dst,
-        render_redirect_pages: false,
-        shared: Arc::new(scx),
-        render_type,
-    };
 
     // Crawl the crate to build various caches used for the output
     let RenderInfo {
@@ -569,7 +575,11 @@ pub fn run(mut krate: clean::Crate,
         deref_trait_did,
         deref_mut_trait_did,
         owned_box_did,
+        span_map,
+        call_locations,
+        def_keys: _,
     } = renderinfo;
+    scx.call_locations = call_locations;
 
     let external_paths = external_paths.into_iter()
         .map(|(k, (v, t))| (k, (v, ItemType::from(t))))
@@ -608,7 +618,7 @@ pub fn run(mut krate: clean::Crate,
             _ => PathBuf::new(),
         };
         cache.extern_locations.insert(n, (e.name.clone(), src_root,
-                                          extern_location(e, &cx.dst)));
+                                          extern_location(e, &dst)));
 
         let did = DefId { krate: n, index: CRATE_DEF_INDEX };
         cache.external_paths.insert(did, (vec![e.name.to_string()], ItemType::Module));
@@ -639,6 +649,17 @@ pub fn run(mut krate: clean::Crate,
     CACHE_KEY.with(|v| *v.borrow_mut() = cache.clone());
     CURRENT_LOCATION_KEY.with(|s| s.borrow_mut().clear());
 
+    // The sources are rendered once the cache is in place, as their identifiers
+    // link to the documentation of the items they refer to.
+    krate = render_sources(&dst, &mut scx, krate, span_map)?;
+    let cx = Context {
+        current: Vec::new(),
+        dst,
+        render_redirect_pages: false,
+        shared: Arc::new(scx),
+        render_type,
+    };
+
     write_shared(&cx, &krate, &*cache, index)?;
 
     let scx = cx.shared.clone();
@@ -838,7 +859,7 @@ fn build_index(krate: &clean::Crate, cache: &mut Cache) -> String {
     crate_data.insert("paths".to_owned(), Json::Array(crate_paths));
 
     // Collect the index into a string
-    format!("searchIndex[{}] = {};",
+    format!("registerSearchIndex({}, {});",
             as_json(&krate.name),
             Json::Object(crate_data))
 }
@@ -921,19 +942,31 @@ fn write_shared(cx: &Context,
         Ok(ret)
     }
 
-    // Update the search index
+    // Write out this crate's part of the search index. Each crate gets a file of its own,
+    // which the search page only loads once a search is started, so that pages stay quick
+    // to load however many crates are documented alongside each other.
+    let dst = cx.dst.join("search-index");
+    try_err!(fs::create_dir_all(&dst), &dst);
+    let dst = dst.join(&format!("{}.js", krate.name));
+    let mut w = try_err!(File::create(&dst), &dst);
+    try_err!(writeln!(&mut w, "{}", search_index), &dst);
+
+    // Update the list of crates that have a search index
+    let crate_doc = krate.module.as_ref().map(|module| {
+        plain_summary_line(module.doc_value())
+    }).unwrap_or(String::new());
     let dst = cx.dst.join("search-index.js");
-    let mut all_indexes = try_err!(collect(&dst, &krate.name, "searchIndex"), &dst);
-    all_indexes.push(search_index);
-    // Sort the indexes by crate so the file will be generated identically even
+    let mut all_crates = try_err!(collect(&dst, &krate.name, "searchCrates"), &dst);
+    all_crates.push(format!("searchCrates[{}] = {};", as_json(&krate.name), as_json(&crate_doc)));
+    // Sort the crates so the file will be generated identically even
     // with rustdoc running in parallel.
-    all_indexes.sort();
+    all_crates.sort();
     let mut w = try_err!(File::create(&dst), &dst);
-    try_err!(writeln!(&mut w, "var searchIndex = {{}};"), &dst);
-    for index in &all_indexes {
-        try_err!(writeln!(&mut w, "{}", *index), &dst);
+    try_err!(writeln!(&mut w, "var searchCrates = {{}};"), &dst);
+    for line in &all_crates {
+        try_err!(writeln!(&mut w, "{}", *line), &dst);
     }
-    try_err!(writeln!(&mut w, "initSearch(searchIndex);"), &dst);
+    try_err!(writeln!(&mut w, "initSearch(searchCrates);"), &dst);
 
     // Update the list of all implementors for traits
     let dst = cx.dst.join("implementors");
@@ -1008,14 +1041,15 @@ fn write_shared(cx: &Context,
     Ok(())
 }
 
-fn render_sources(dst: &Path, scx: &mut SharedContext,
-                  krate: clean::Crate) -> Result<clean::Crate, Error> {
+fn render_sources(dst: &Path, scx: &mut SharedContext, krate: clean::Crate,
+                  span_map: SpanMap) -> Result<clean::Crate, Error> {
     info!("emitting source files");
     let dst = dst.join("src").join(&krate.name);
     try_err!(fs::create_dir_all(&dst), &dst);
     let mut folder = SourceCollector {
         dst,
         scx,
+        span_map,
     };
     Ok(folder.fold_crate(krate))
 }
@@ -1135,11 +1169,13 @@ impl<'a> SourceCollector<'a> {
         // Create the intermediate directories
         let mut cur = self.dst.clone();
         let mut root_path = String::from("../../");
+        let mut depth = 2;
         let mut href = String::new();
         clean_srcpath(&self.scx.src_root, &p, false, |component| {
             cur.push(component);
             fs::create_dir_all(&cur).unwrap();
             root_path.push_str("../");
+            depth += 1;
             href.push_str(component);
             href.push('/');
         });
@@ -1160,13 +1196,49 @@ impl<'a> SourceCollector<'a> {
             description: &desc,
             keywords: BASIC_KEYWORDS,
         };
+        let links = match self.span_map.get(&**p) {
+            Some(links) => self.link_urls(links, &root_path, depth),
+            None => FxHashMap(),
+        };
         layout::render(&mut w, &self.scx.layout,
-                       &page, &(""), &Source(contents),
+                       &page, &(""), &Source(contents, &links),
                        self.scx.css_file_extension.is_some())?;
         w.flush()?;
         self.scx.local_sources.insert(p.clone(), href);
         Ok(())
     }
+
+    /// Turns the link targets of the identifiers in a source page into URLs,
+    /// relative to the page which is `depth` directories below the root.
+    fn link_urls(&self, links: &FxHashMap<(u32, u32), LinkTarget>, root_path: &str,
+                 depth: usize) -> FxHashMap<(u32, u32), String> {
+        let location = CURRENT_LOCATION_KEY.with(|l| {
+            mem::replace(&mut *l.borrow_mut(), vec![String::new(); depth])
+        });
+        let urls = links.iter().filter_map(|(&span, target)| {
+            let url = match *target {
+                LinkTarget::Docs(did, ref fragment) => {
+                    let (mut url, ..) = href(did)?;
+                    if let Some(ref fragment) = *fragment {
+                        url.push('#');
+                        url.push_str(fragment);
+                    }
+                    url
+                }
+                LinkTarget::Source(ref file, line) => {
+                    let mut url = format!("{}src/{}", root_path, self.scx.layout.krate);
+                    clean_srcpath(&self.scx.src_root, file, true, |component| {
+                        url.push('/');
+                        url.push_str(component);
+                    });
+                    format!("{}.html#{}", url, line)
+                }
+            };
+            Some((span, url))
+        }).collect();
+        CURRENT_LOCATION_KEY.with(|l| *l.borrow_mut() = location);
+        urls
+    }
 }
 
 impl DocFolder for Cache {
@@ -1928,7 +2000,8 @@ fn document_full(w: &mut fmt::Formatter, item: &clean::Item,
                  cx: &Context, prefix: &str) -> fmt::Result {
     if let Some(s) = cx.shared.maybe_collapsed_doc_value(item) {
         debug!("Doc block: =====\n{}\n=====", s);
-        render_markdown(w, &*s, item.source.clone(), cx.render_type, prefix, &cx.shared)?;
+        let s = markdown::replace_links(&s, &item.attrs.links());
+        render_markdown(w, &s, item.source.clone(), cx.render_type, prefix, &cx.shared)?;
     } else if !prefix.is_empty() {
         write!(w, "<div class='docblock'>{}</div>", prefix)?;
     }
@@ -1947,6 +2020,49 @@ fn document_stability(w: &mut fmt::Formatter, cx: &Context, item: &clean::Item)
     Ok(())
 }
 
+/// Renders the calls to a function or method found in the examples given with
+/// `--with-examples`, each shown with the item it appears in. Only the first
+/// example is expanded, the others are folded under a toggle.
+fn render_call_locations(w: &mut fmt::Formatter, cx: &Context,
+                         item: &clean::Item) -> fmt::Result {
+    let files = match cx.shared.call_locations.get(&item.def_id) {
+        Some(files) => files,
+        None => return Ok(()),
+    };
+    write!(w, "<div class='docblock scraped-example-list'>\
+               <h5 class='scraped-examples-title'>Examples found in repository</h5>")?;
+    let locations = files.iter().flat_map(|(file, locations)| {
+        locations.iter().map(move |location| (file, location))
+    });
+    for (i, (file, location)) in locations.enumerate() {
+        if i == 1 {
+            write!(w, "<details class='more-scraped-examples'><summary>More examples</summary>")?;
+        }
+        let is_call = |line: usize| {
+            location.call_lines.iter().any(|&(lo, hi)| lo <= line && line <= hi)
+        };
+        let (first, _) = location.call_lines[0];
+        write!(w, "<div class='scraped-example'>\
+                   <div class='scraped-example-title'>{} (line {})</div>\
+                   <pre class='line-numbers'>",
+               Escape(file), first)?;
+        let lines = location.code.lines().count();
+        for line in location.start_line..location.start_line + lines {
+            if is_call(line) {
+                write!(w, "<span class='line-highlighted'>{}</span>\n", line)?;
+            } else {
+                write!(w, "<span>{}</span>\n", line)?;
+            }
+        }
+        write!(w, "</pre>{}</div>",
+               highlight::render_with_highlighting(&location.code, None, None, None, None))?;
+    }
+    if files.values().map(|locations| locations.len()).sum::<usize>() > 1 {
+        write!(w, "</details>")?;
+    }
+    write!(w, "</div>")
+}
+
 fn name_key(name: &str) -> (&str, u64, usize) {
     // find number at end
     let split = name.bytes().rposition(|b| b < b'0' || b'9' < b).map_or(0, |s| s + 1);
@@ -2139,7 +2255,9 @@ fn item_module(w: &mut fmt::Formatter, cx: &Context,
                     _ => "",
                 };
 
-                let doc_value = myitem.doc_value().unwrap_or("");
+                let doc_value = markdown::replace_links(myitem.doc_value().unwrap_or(""),
+                                                        &myitem.attrs.links());
+                let doc_value = &*doc_value;
                 write!(w, "
                        <tr class='{stab} module-item'>
                            <td><a class=\"{class}\" href=\"{href}\"
@@ -2318,7 +2436,8 @@ fn item_function(w: &mut fmt::Formatter, cx: &Context, it: &clean::Item,
               name_len,
               indent: 0,
            })?;
-    document(w, cx, it)
+    document(w, cx, it)?;
+    render_call_locations(w, cx, it)
 }
 
 fn implementor2item<'a>(cache: &'a Cache, imp : &Implementor) -> Option<&'a clean::Item> {
@@ -2446,6 +2565,7 @@ fn item_trait(w: &mut fmt::Formatter, cx: &Context, it: &clean::Item,
         render_stability_since(w, m, t)?;
         write!(w, "</span></h3>")?;
         document(w, cx, m)?;
+        render_call_locations(w, cx, m)?;
         Ok(())
     }
 
@@ -3362,7 +3482,8 @@ fn render_impl(w: &mut fmt::Formatter, cx: &Context, i: &Impl, link: AssocItemLi
         write!(w, "</span>")?;
         write!(w, "</h3>\n")?;
         if let Some(ref dox) = cx.shared.maybe_collapsed_doc_value(&i.impl_item) {
-            write!(w, "<div class='docblock'>{}</div>", Markdown(&*dox, cx.render_type))?;
+            let dox = markdown::replace_links(dox, &i.impl_item.attrs.links());
+            write!(w, "<div class='docblock'>{}</div>", Markdown(&dox, cx.render_type))?;
         }
     }
 
@@ -3454,6 +3575,7 @@ fn render_impl(w: &mut fmt::Formatter, cx: &Context, i: &Impl, link: AssocItemLi
                     document_stability(w, cx, item)?;
                     if show_def_docs {
                         document_full(w, item, cx, &prefix)?;
+                        render_call_locations(w, cx, item)?;
                     }
                 }
             } else {
@@ -4047,7 +4169,7 @@ fn sidebar_foreign_type(fmt: &mut fmt::Formatter, it: &clean::Item) -> fmt::Resu
 
 impl<'a> fmt::Display for Source<'a> {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        let Source(s) = *self;
+        let Source(s, links) = *self;
         let lines = s.lines().count();
         let mut cols = 0;
         let mut tmp = lines;
@@ -4060,8 +4182,7 @@ impl<'a> fmt::Display for Source<'a> {
             write!(fmt, "<span id=\"{0}\">{0:1$}</span>\n", i, cols)?;
         }
         write!(fmt, "</pre>")?;
-        write!(fmt, "{}",
-               highlight::render_with_highlighting(s, None, None, None, None))?;
+        write!(fmt, "{}", highlight::render_with_links(s, links))?;
         Ok(())
     }
 }
@@ -4090,57 +4211,96 @@ fn make_item_keywords(it: &clean::Item) -> String {
 }
 
 fn get_index_search_type(item: &clean::Item) -> Option<IndexItemFunctionType> {
-    let decl = match item.inner {
-        clean::FunctionItem(ref f) => &f.decl,
-        clean::MethodItem(ref m) => &m.decl,
-        clean::TyMethodItem(ref m) => &m.decl,
+    let (decl, generics) = match item.inner {
+        clean::FunctionItem(ref f) => (&f.decl, &f.generics),
+        clean::MethodItem(ref m) => (&m.decl, &m.generics),
+        clean::TyMethodItem(ref m) => (&m.decl, &m.generics),
         _ => return None
     };
 
-    let inputs = decl.inputs.values.iter().map(|arg| get_index_type(&arg.type_)).collect();
+    let inputs = decl.inputs.values.iter().map(|arg| {
+        get_index_type(&arg.type_, generics)
+    }).collect();
     let output = match decl.output {
-        clean::FunctionRetTy::Return(ref return_type) => Some(get_index_type(return_type)),
+        clean::FunctionRetTy::Return(ref return_type) => {
+            Some(get_index_type(return_type, generics))
+        }
         _ => None
     };
 
     Some(IndexItemFunctionType { inputs: inputs, output: output })
 }
 
-fn get_index_type(clean_type: &clean::Type) -> Type {
-    let t = Type {
-        name: get_index_type_name(clean_type, true).map(|s| s.to_ascii_lowercase()),
-        generics: get_generics(clean_type),
+/// Describes `clean_type` for the search index. References are left out, so that searching
+/// for `str` finds functions taking `&str`. `Self` is left as the generic name `self`, which
+/// the search page replaces with the type of the item's parent.
+fn get_index_type(clean_type: &clean::Type, generics: &clean::Generics) -> Type {
+    let types = |tys: &[clean::Type]| {
+        tys.iter().map(|ty| get_index_type(ty, generics)).collect::<Vec<_>>()
+    };
+    let named = |name: &str, args: Vec<Type>| {
+        Type { name: Some(name.to_ascii_lowercase()), generics: args, bounds: None }
     };
-    t
-}
 
-fn get_index_type_name(clean_type: &clean::Type, accept_generic: bool) -> Option<String> {
     match *clean_type {
-        clean::ResolvedPath { ref path, .. } => {
-            let segments = &path.segments;
-            Some(segments[segments.len() - 1].name.clone())
-        }
-        clean::Generic(ref s) if accept_generic => Some(s.clone()),
-        clean::Primitive(ref p) => Some(format!("{:?}", p)),
-        clean::BorrowedRef { ref type_, .. } => get_index_type_name(type_, accept_generic),
-        // FIXME: add all from clean::Type.
-        _ => None
+        clean::ResolvedPath { ref path, is_generic: false, .. } => {
+            let name = &path.segments[path.segments.len() - 1].name;
+            named(name, clean_type.generics().map(types).unwrap_or_default())
+        }
+        clean::Generic(ref name) if name == "Self" => named(name, vec![]),
+        clean::Generic(ref name) => {
+            Type {
+                name: Some(name.to_ascii_lowercase()),
+                generics: vec![],
+                bounds: Some(get_index_bounds(&param_bounds(name, generics), generics)),
+            }
+        }
+        clean::ImplTrait(ref bounds) => {
+            Type {
+                name: Some("impl".to_string()),
+                generics: vec![],
+                bounds: Some(get_index_bounds(&bounds.iter().collect::<Vec<_>>(), generics)),
+            }
+        }
+        clean::Primitive(p) => named(p.as_str(), vec![]),
+        clean::BorrowedRef { ref type_, .. } => get_index_type(type_, generics),
+        clean::Unique(ref type_) => named("box", vec![get_index_type(type_, generics)]),
+        clean::RawPointer(_, ref type_) => named("pointer", vec![get_index_type(type_, generics)]),
+        clean::Slice(ref type_) => named("slice", vec![get_index_type(type_, generics)]),
+        clean::Array(ref type_, _) => named("array", vec![get_index_type(type_, generics)]),
+        clean::Tuple(ref tys) => named("tuple", types(tys)),
+        clean::BareFunction(..) => named("fn", vec![]),
+        clean::Never => named("never", vec![]),
+        clean::ResolvedPath { .. } | clean::QPath { .. } | clean::Infer => {
+            Type { name: None, generics: vec![], bounds: None }
+        }
+    }
+}
+
+/// All the bounds on the generic parameter `name`, from both the parameter list and the
+/// where clause.
+fn param_bounds<'a>(name: &str, generics: &'a clean::Generics) -> Vec<&'a clean::TyParamBound> {
+    let mut bounds = vec![];
+    for param in generics.type_params.iter().filter(|p| p.name == name) {
+        bounds.extend(param.bounds.iter());
+    }
+    for pred in &generics.where_predicates {
+        if let clean::WherePredicate::BoundPredicate { ref ty, bounds: ref b } = *pred {
+            if let clean::Generic(ref ty) = *ty {
+                if ty == name {
+                    bounds.extend(b.iter());
+                }
+            }
+        }
     }
+    bounds
 }
 
-fn get_generics(clean_type: &clean::Type) -> Option<Vec<String>> {
-    clean_type.generics()
-              .and_then(|types| {
-                  let r = types.iter()
-                               .filter_map(|t| get_index_type_name(t, false))
-                               .map(|s| s.to_ascii_lowercase())
-                               .collect::<Vec<_>>();
-                  if r.is_empty() {
-                      None
-                  } else {
-                      Some(r)
-                  }
-              })
+fn get_index_bounds(bounds: &[&clean::TyParamBound], generics: &clean::Generics) -> Vec<Type> {
+    bounds.iter().filter_map(|bound| match **bound {
+        clean::TraitBound(ref poly, _) => Some(get_index_type(&poly.trait_, generics)),
+        clean::RegionBound(..) => None,
+    }).collect()
 }
 
 pub fn cache() -> Arc<Cache> {
@@ -374,8 +374,11 @@
         };
     })();
 
-    function initSearch(rawSearchIndex) {
-        var currentResults, index, searchIndex;
+    function initSearch(crates) {
+        var currentResults, index = [], searchIndex = [];
+        // The parts of the search index loaded so far, and those requested, keyed by crate.
+        var rawSearchIndex = {}, shardsRequested = {};
+        var shardTimeout;
         var MAX_LEV_DISTANCE = 3;
         var MAX_RESULTS = 200;
         var params = getQueryStringParams();
@@ -530,21 +533,146 @@
                 return transformResults(results);
             }
 
-            function extractGenerics(val) {
-                val = val.toLowerCase();
-                if (val.indexOf('<') !== -1) {
-                    var values = val.substring(val.indexOf('<') + 1, val.lastIndexOf('>'));
+            // Splits `val` at each `sep` which isn't nested inside brackets.
+            function splitTopLevel(val, sep) {
+                var parts = [];
+                var depth = 0;
+                var start = 0;
+                for (var i = 0; i < val.length; ++i) {
+                    var c = val.charAt(i);
+                    if (c === '<' || c === '(' || c === '[') {
+                        depth += 1;
+                    } else if (c === '>' || c === ')' || c === ']') {
+                        depth -= 1;
+                    } else if (c === sep && depth === 0) {
+                        parts.push(val.substring(start, i));
+                        start = i + 1;
+                    }
+                }
+                parts.push(val.substring(start));
+                return parts;
+            }
+
+            // Parses a type in a query, such as `&mut Vec<Option<T>>`, into the form that
+            // types take in the search index. As in the index, references and paths are
+            // dropped, and slices, arrays, tuples and pointers get names of their own.
+            function parseType(val) {
+                val = val.toLowerCase().trim()
+                         .replace(/^(&\s*(mut\s+)?|\*\s*(const|mut)\s+|impl\s+|dyn\s+)+/, "");
+                var generics = function(inner) {
+                    return splitTopLevel(inner, ',').map(function(s) { return s.trim(); })
+                                                    .filter(function(s) { return s !== ""; })
+                                                    .map(parseType);
+                };
+                if (val.charAt(0) === '[') {
+                    var inner = splitTopLevel(val.substring(1, val.lastIndexOf(']')), ';');
+                    return {
+                        name: inner.length > 1 ? "array" : "slice",
+                        generics: [parseType(inner[0])],
+                    };
+                }
+                if (val.charAt(0) === '(') {
                     return {
-                        name: val.substring(0, val.indexOf('<')),
-                        generics: values.split(/\s*,\s*/),
+                        name: "tuple",
+                        generics: generics(val.substring(1, val.lastIndexOf(')'))),
                     };
                 }
+                var name = val;
+                var args = [];
+                if (val.indexOf('<') !== -1) {
+                    name = val.substring(0, val.indexOf('<'));
+                    args = generics(val.substring(val.indexOf('<') + 1, val.lastIndexOf('>')));
+                }
+                name = name.trim().split("::");
                 return {
-                    name: val,
-                    generics: [],
+                    name: name[name.length - 1],
+                    generics: args,
                 };
             }
 
+            // `_`, `*` and single letters like `T` stand for any type.
+            function isWildcard(val) {
+                return val.name === "_" || val.name === "*" || /^[a-z]$/.test(val.name);
+            }
+
+            // Check whether the type `obj` from the index is the type `val` from the query.
+            function typeMatches(val, obj) {
+                if (isWildcard(val)) {
+                    return true;
+                }
+                if (!obj) {
+                    return false;
+                }
+                if (obj.bounds !== null) {
+                    // A generic parameter matches the traits it is bounded by.
+                    for (var i = 0; i < obj.bounds.length; ++i) {
+                        if (obj.bounds[i].name === val.name &&
+                            genericsMatch(val.generics, obj.bounds[i].generics)) {
+                            return true;
+                        }
+                    }
+                    return false;
+                }
+                return obj.name === val.name && genericsMatch(val.generics, obj.generics);
+            }
+
+            // Each generic argument in the query must match a different one of the type's,
+            // in any order. Wildcards match even if the index doesn't know the argument, as
+            // happens for `Self` in methods.
+            function genericsMatch(vals, objs) {
+                var remaining = objs.slice(0);
+                for (var i = 0; i < vals.length; ++i) {
+                    if (isWildcard(vals[i])) {
+                        continue;
+                    }
+                    var found = false;
+                    for (var j = 0; j < remaining.length; ++j) {
+                        if (typeMatches(vals[i], remaining[j])) {
+                            remaining.splice(j, 1);
+                            found = true;
+                            break;
+                        }
+                    }
+                    if (!found) {
+                        return false;
+                    }
+                }
+                return true;
+            }
+
+            // Check whether the inputs and output of a function's type match those of a
+            // `inputs -> output` query. Returns the number of the function's inputs that
+            // weren't mentioned in the query, or -1 if it doesn't match.
+            function checkSignature(inputs, output, type) {
+                if (output === null || output.name === "*") {
+                    // any output will do
+                } else if (output.name === "tuple" && output.generics.length === 0) {
+                    if (type.output) {
+                        return -1;
+                    }
+                } else if (!typeMatches(output, type.output)) {
+                    return -1;
+                }
+                var remaining = type.inputs.slice(0);
+                for (var i = 0; i < inputs.length; ++i) {
+                    if (inputs[i].name === "*") {
+                        return 0;
+                    }
+                    var found = false;
+                    for (var j = 0; j < remaining.length; ++j) {
+                        if (typeMatches(inputs[i], remaining[j])) {
+                            remaining.splice(j, 1);
+                            found = true;
+                            break;
+                        }
+                    }
+                    if (!found) {
+                        return -1;
+                    }
+                }
+                return remaining.length;
+            }
+
             function checkGenerics(obj, val) {
                 // The names match, but we need to be sure that all generics kinda
                 // match as well.
@@ -559,7 +687,7 @@
                         for (var y = 0; y < val.generics.length; ++y) {
                             var lev = { pos: -1, lev: MAX_LEV_DISTANCE + 1};
                             for (var x = 0; x < elems.length; ++x) {
-                                var tmp_lev = levenshtein(elems[x], val.generics[y]);
+                                var tmp_lev = levenshtein(elems[x].name, val.generics[y].name);
                                 if (tmp_lev < lev.lev) {
                                     lev.lev = tmp_lev;
                                     lev.pos = x;
@@ -582,36 +710,26 @@
 
             // Check for type name and type generics (if any).
             function checkType(obj, val, literalSearch) {
-                var lev_distance = MAX_LEV_DISTANCE + 1;
-                if (obj.name === val.name) {
-                    if (literalSearch === true) {
-                        if (val.generics.length !== 0) {
-                            if (obj.generics && obj.length >= val.generics.length) {
-                                var elems = obj.generics.slice(0);
-                                var allFound = true;
-                                var x;
-
-                                for (var y = 0; allFound === true && y < val.generics.length; ++y) {
-                                    allFound = false;
-                                    for (x = 0; allFound === false && x < elems.length; ++x) {
-                                        allFound = elems[x] === val.generics[y];
-                                    }
-                                    if (allFound === true) {
-                                        elems.splice(x - 1, 1);
-                                    }
-                                }
-                                if (allFound === true) {
-                                    return true;
-                                }
-                            } else {
-                                return false;
-                            }
-                        }
+                if (!obj) {
+                    return literalSearch === true ? false : MAX_LEV_DISTANCE + 1;
+                }
+                if (literalSearch === true) {
+                    // The type matches, or one of its generic arguments does.
+                    if (typeMatches(val, obj)) {
                         return true;
                     }
+                    for (var x = 0; x < obj.generics.length; ++x) {
+                        if (typeMatches(val, obj.generics[x])) {
+                            return true;
+                        }
+                    }
+                    return false;
+                }
+                var lev_distance = MAX_LEV_DISTANCE + 1;
+                if (obj.name === val.name) {
                     // If the type has generics but don't match, then it won't return at this point.
                     // Otherwise, `checkGenerics` will return 0 and it'll return.
-                    if (obj.generics && obj.generics.length !== 0) {
+                    if (obj.generics.length !== 0) {
                         var tmp_lev = checkGenerics(obj, val);
                         if (tmp_lev <= MAX_LEV_DISTANCE) {
                             return tmp_lev;
@@ -620,24 +738,13 @@
                         return 0;
                     }
                 }
-                // Names didn't match so let's check if one of the generic types could.
-                if (literalSearch === true) {
-                     if (obj.generics.length > 0) {
-                        for (var x = 0; x < obj.generics.length; ++x) {
-                            if (obj.generics[x] === val.name) {
-                                return true;
-                            }
-                        }
-                    }
-                    return false;
-                }
                 var lev_distance = Math.min(levenshtein(obj.name, val.name), lev_distance);
                 if (lev_distance <= MAX_LEV_DISTANCE) {
                     lev_distance = Math.min(checkGenerics(obj, val), lev_distance);
-                } else if (obj.generics && obj.generics.length > 0) {
+                } else if (obj.generics.length > 0) {
                     // We can check if the type we're looking for is inside the generics!
                     for (var x = 0; x < obj.generics.length; ++x) {
-                        lev_distance = Math.min(levenshtein(obj.generics[x], val.name),
+                        lev_distance = Math.min(levenshtein(obj.generics[x].name, val.name),
                                                 lev_distance);
                     }
                 }
@@ -757,7 +864,7 @@
             if ((val.charAt(0) === "\"" || val.charAt(0) === "'") &&
                 val.charAt(val.length - 1) === val.charAt(0))
             {
-                val = extractGenerics(val.substr(1, val.length - 2));
+                val = parseType(val.substr(1, val.length - 2));
                 for (var i = 0; i < nSearchWords; ++i) {
                     var in_args = findArg(searchIndex[i], val, true);
                     var returned = checkReturned(searchIndex[i], val, true);
@@ -802,67 +909,54 @@
                 query.search = val;
             // searching by type
             } else if (val.search("->") > -1) {
-                var trimmer = function (s) { return s.trim(); };
-                var parts = val.split("->").map(trimmer);
-                var input = parts[0];
-                // sort inputs so that order does not matter
-                var inputs = input.split(",").map(trimmer).sort();
-                for (var i = 0; i < inputs.length; ++i) {
-                    inputs[i] = extractGenerics(inputs[i]);
-                }
-                var output = extractGenerics(parts[1]);
+                var parts = val.split("->");
+                var input = parts[0].trim();
+                var inputs = splitTopLevel(input, ',').map(function(s) { return s.trim(); })
+                                                     .filter(function(s) { return s !== ""; })
+                                                     .map(parseType);
+                var output = parts.slice(1).join("->").trim();
+                output = output === "" ? null : parseType(output);
 
                 for (var i = 0; i < nSearchWords; ++i) {
                     var type = searchIndex[i].type;
                     var ty = searchIndex[i];
-                    if (!type) {
+                    if (!type || !typePassesFilter(typeFilter, ty.ty)) {
                         continue;
                     }
                     var fullId = generateId(ty);
 
-                    // allow searching for void (no output) functions as well
-                    var typeOutput = type.output ? type.output.name : "";
-                    var returned = checkReturned(ty, output, true);
-                    if (output.name === "*" || returned === true) {
-                        var in_args = false;
-                        var module = false;
-
-                        if (input === "*") {
-                            module = true;
-                        } else {
-                            var allFound = true;
-                            for (var it = 0; allFound === true && it < inputs.length; it++) {
-                                allFound = checkType(type, inputs[it], true);
-                            }
-                            in_args = allFound;
-                        }
-                        if (in_args === true) {
-                            results_in_args[fullId] = {
-                                id: i,
-                                index: -1,
-                                dontValidate: true,
-                            };
-                        }
-                        if (returned === true) {
-                            results_returned[fullId] = {
-                                id: i,
-                                index: -1,
-                                dontValidate: true,
-                            };
-                        }
-                        if (module === true) {
-                            results[fullId] = {
-                                id: i,
-                                index: -1,
-                                dontValidate: true,
-                            };
-                        }
+                    // Functions matching the whole signature are ranked by how many inputs
+                    // they take beyond those in the query.
+                    var extra = checkSignature(inputs, output, type);
+                    if (extra !== -1) {
+                        results[fullId] = {
+                            id: i,
+                            index: -1,
+                            lev: extra,
+                            dontValidate: true,
+                        };
+                    }
+                    if (inputs.length > 0 && checkSignature(inputs, null, type) !== -1) {
+                        results_in_args[fullId] = {
+                            id: i,
+                            index: -1,
+                            lev: 0,
+                            dontValidate: true,
+                        };
+                    }
+                    if (output !== null && checkSignature([], output, type) !== -1) {
+                        results_returned[fullId] = {
+                            id: i,
+                            index: -1,
+                            lev: 0,
+                            dontValidate: true,
+                        };
                     }
                 }
                 query.inputs = inputs.map(function(input) {
                     return input.name;
                 });
-                query.output = output.name;
+                query.output = output === null ? "" : output.name;
             } else {
                 query.inputs = [val];
                 query.output = val;
@@ -870,7 +964,7 @@
                 // gather matching search results up to a certain maximum
                 val = val.replace(/\_/g, "");
 
-                var valGenerics = extractGenerics(val);
+                var valGenerics = parseType(val);
 
                 var paths = valLower.split("::");
                 var j;
@@ -1014,9 +1108,12 @@
         }
 
         function getQuery() {
-            var matches, type, query, raw =
-                document.getElementsByClassName('search-input')[0].value;
-            query = raw;
+            return parseQuery(document.getElementsByClassName('search-input')[0].value);
+        }
+
+        // Splits an item type filter such as `fn:` off the start of the query `raw`.
+        function parseQuery(raw) {
+            var matches, type, query = raw;
 
             matches = query.match(/^(fn|mod|struct|enum|trait|type|const|macro)\s*:\s*/i);
             if (matches) {
@@ -1221,7 +1318,8 @@
             output = '<h1>Results for ' + escape(query.query) +
                 (query.type ? ' (type: ' + escape(query.type) + ')' : '') + '</h1>' +
                 '<div id="titles">' +
-                makeTabHeader(0, "In Names", results['others'].length) +
+                makeTabHeader(0, query.query.indexOf("->") !== -1 ? "In Signatures" : "In Names",
+                              results['others'].length) +
                 makeTabHeader(1, "In Parameters", results['in_args'].length) +
                 makeTabHeader(2, "In Return Types", results['returned'].length) +
                 '</div><div id="results">';
@@ -1267,6 +1365,7 @@
             if (!query.query || query.id === currentResults) {
                 return;
             }
+            loadSearchIndex(cratesForQuery(query));
 
             // Update document title to maintain a meaningful browser history
             document.title = "Results for " + query.query + " - Rust";
@@ -1294,66 +1393,141 @@
             return -1;
         }
 
-        function buildIndex(rawSearchIndex) {
-            searchIndex = [];
-            var searchWords = [];
-            for (var crate in rawSearchIndex) {
-                if (!rawSearchIndex.hasOwnProperty(crate)) { continue; }
-
-                searchWords.push(crate);
-                searchIndex.push({
-                    crate: crate,
-                    ty: 1, // == ExternCrate
-                    name: crate,
-                    path: "",
-                    desc: rawSearchIndex[crate].doc,
-                    type: null,
-                });
+        // Types in the index are `null` where rustdoc couldn't describe them, a name for
+        // plain types, `[name, generics]` for types with generic arguments, and
+        // `[name, generics, bounds]` for generic parameters. `self` stands for the type of
+        // the item's parent, if it has one.
+        function decodeType(raw, selfName) {
+            if (raw === null || raw === undefined) {
+                return null;
+            }
+            if (typeof raw === "string") {
+                raw = [raw, []];
+            }
+            var decode = function(types) {
+                return types.map(function(t) { return decodeType(t, selfName); })
+                            .filter(function(t) { return t !== null; });
+            };
+            return {
+                name: raw[0] === "self" && selfName ? selfName : raw[0],
+                generics: decode(raw[1]),
+                bounds: raw.length > 2 ? decode(raw[2]) : null,
+            };
+        }
+
+        function addCrateIndex(crate, crateIndex) {
+            index.push(crate);
+            searchIndex.push({
+                crate: crate,
+                ty: 1, // == ExternCrate
+                name: crate,
+                path: "",
+                desc: crateIndex.doc,
+                type: null,
+            });
 
-                // an array of [(Number) item type,
-                //              (String) name,
-                //              (String) full path or empty string for previous path,
-                //              (String) description,
-                //              (Number | null) the parent path index to `paths`]
-                //              (Object | null) the type of the function (if any)
-                var items = rawSearchIndex[crate].items;
-                // an array of [(Number) item type,
-                //              (String) name]
-                var paths = rawSearchIndex[crate].paths;
-
-                // convert `paths` into an object form
-                var len = paths.length;
-                for (var i = 0; i < len; ++i) {
-                    paths[i] = {ty: paths[i][0], name: paths[i][1]};
+            // an array of [(Number) item type,
+            //              (String) name,
+            //              (String) full path or empty string for previous path,
+            //              (String) description,
+            //              (Number | null) the parent path index to `paths`]
+            //              (Array | null) the inputs and output of the function (if any)
+            var items = crateIndex.items;
+            // an array of [(Number) item type,
+            //              (String) name]
+            var paths = crateIndex.paths;
+
+            // convert `paths` into an object form
+            var len = paths.length;
+            for (var i = 0; i < len; ++i) {
+                paths[i] = {ty: paths[i][0], name: paths[i][1]};
+            }
+
+            // convert `items` into an object form, and construct word indices.
+            //
+            // before any analysis is performed lets gather the search terms to
+            // search against apart from the rest of the data.  This is a quick
+            // operation that is cached for the life of the page state so that
+            // all other search operations have access to this cached data for
+            // faster analysis operations
+            var len = items.length;
+            var lastPath = "";
+            for (var i = 0; i < len; ++i) {
+                var rawRow = items[i];
+                var parent = paths[rawRow[4]];
+                var type = null;
+                if (rawRow[5]) {
+                    var selfName = parent ? parent.name.toLowerCase() : null;
+                    type = {
+                        inputs: rawRow[5][0].map(function(t) { return decodeType(t, selfName); }),
+                        output: decodeType(rawRow[5][1], selfName),
+                    };
+                }
+                var row = {crate: crate, ty: rawRow[0], name: rawRow[1],
+                           path: rawRow[2] || lastPath, desc: rawRow[3],
+                           parent: parent, type: type};
+                searchIndex.push(row);
+                if (typeof row.name === "string") {
+                    var word = row.name.toLowerCase();
+                    index.push(word);
+                } else {
+                    index.push("");
                 }
+                lastPath = row.path;
+            }
+        }
 
-                // convert `items` into an object form, and construct word indices.
-                //
-                // before any analysis is performed lets gather the search terms to
-                // search against apart from the rest of the data.  This is a quick
-                // operation that is cached for the life of the page state so that
-                // all other search operations have access to this cached data for
-                // faster analysis operations
-                var len = items.length;
-                var lastPath = "";
-                for (var i = 0; i < len; ++i) {
-                    var rawRow = items[i];
-                    var row = {crate: crate, ty: rawRow[0], name: rawRow[1],
-                               path: rawRow[2] || lastPath, desc: rawRow[3],
-                               parent: paths[rawRow[4]], type: rawRow[5]};
-                    searchIndex.push(row);
-                    if (typeof row.name === "string") {
-                        var word = row.name.toLowerCase();
-                        searchWords.push(word);
-                    } else {
-                        searchWords.push("");
-                    }
-                    lastPath = row.path;
+        // Each crate's part of the search index is in a file of its own, which is only
+        // loaded once it's needed: the current crate's when the search bar is focused, and
+        // the others' when a search that could find their items is run. Results are updated
+        // as the files arrive.
+        function loadSearchIndex(names) {
+            for (var i = 0; i < names.length; ++i) {
+                if (!crates.hasOwnProperty(names[i]) || shardsRequested[names[i]] === true) {
+                    continue;
+                }
+                shardsRequested[names[i]] = true;
+                var script = document.createElement('script');
+                script.src = rootPath + "search-index/" + names[i] + ".js";
+                document.head.appendChild(script);
+            }
+        }
+
+        // The crates whose items `query` could find: only the named crate for a path that
+        // starts with a crate name, such as `std::vec::Vec`, and every crate otherwise.
+        function cratesForQuery(query) {
+            var names = Object.keys(crates).sort();
+            var val = query.query.toLowerCase();
+            if (val.indexOf("::") !== -1 && val.indexOf("->") === -1) {
+                var first = val.split("::")[0].trim();
+                var named = names.filter(function(name) {
+                    return name.toLowerCase() === first;
+                });
+                if (named.length > 0) {
+                    return named;
                 }
             }
-            return searchWords;
+            // Load the current crate first, as its results are the most relevant.
+            names.sort(function(a, b) {
+                return (b === window.currentCrate) - (a === window.currentCrate);
+            });
+            return names;
         }
 
+        window.registerSearchIndex = function(crate, crateIndex) {
+            if (rawSearchIndex.hasOwnProperty(crate)) {
+                return;
+            }
+            rawSearchIndex[crate] = crateIndex;
+            addCrateIndex(crate, crateIndex);
+            // Several files usually arrive at once, so only search again once they're in.
+            clearTimeout(shardTimeout);
+            shardTimeout = setTimeout(function() {
+                currentResults = null;
+                search();
+            }, 50);
+        };
+
         function startSearch() {
             var searchTimeout;
             var callback = function() {
@@ -1378,6 +1552,9 @@
                 }
             };
             var search_input = document.getElementsByClassName("search-input")[0];
+            search_input.onfocus = function() {
+                loadSearchIndex([window.currentCrate]);
+            };
             search_input.onkeyup = callback;
             search_input.oninput = callback;
             document.getElementsByClassName("search-form")[0].onsubmit = function(e) {
@@ -1442,7 +1619,6 @@
             search();
         }
 
-        index = buildIndex(rawSearchIndex);
         startSearch();
 
         // Draw a convenient sidebar of known crates if we have a listing
@@ -1454,24 +1630,17 @@
             var ul = document.createElement('ul');
             div.appendChild(ul);
 
-            var crates = [];
-            for (var crate in rawSearchIndex) {
-                if (!rawSearchIndex.hasOwnProperty(crate)) {
-                    continue;
-                }
-                crates.push(crate);
-            }
-            crates.sort();
-            for (var i = 0; i < crates.length; ++i) {
+            var names = Object.keys(crates).sort();
+            for (var i = 0; i < names.length; ++i) {
                 var klass = 'crate';
-                if (crates[i] === window.currentCrate) {
+                if (names[i] === window.currentCrate) {
                     klass += ' current';
                 }
                 var link = document.createElement('a');
-                link.href = '../' + crates[i] + '/index.html';
-                link.title = rawSearchIndex[crates[i]].doc;
+                link.href = '../' + names[i] + '/index.html';
+                link.title = crates[names[i]];
                 link.className = klass;
-                link.textContent = crates[i];
+                link.textContent = names[i];
 
                 var li = document.createElement('li');
                 li.appendChild(link);
@@ -0,0 +1,845 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Rustdoc's JSON backend.
+//!
+//! Instead of a tree of HTML pages, this writes the cleaned crate out as a single
+//! `<crate>.json` document for tools to consume. The layout of that document is
+//! described in the rustdoc book; any change to it must bump `FORMAT_VERSION`.
+//!
+//! Items are identified by their crate name and definition path (see `core::def_key`),
+//! which is the same in the document of the crate that defines an item as in the documents
+//! of the crates that use it. Paths aren't known once the type context is gone, so
+//! `def_keys` looks them up in `run_core` for every item the document can refer to.
+
+use std::cell::RefCell;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::mem;
+use std::path::PathBuf;
+
+use rustc::hir;
+use rustc::hir::def_id::DefId;
+use rustc::middle::stability;
+use rustc::util::nodemap::FxHashMap;
+use serialize::json::{self, Json, ToJson};
+use syntax::abi::Abi;
+use syntax::codemap::FileName;
+use syntax::print::pprust;
+
+use clean::{self, AttributesExt, GetDefId, PrimitiveType};
+use core::{self, DocContext, ExternalPaths};
+use doctree;
+use html::item_type::ItemType;
+use html::render::{Error, RenderInfo};
+
+/// The version of the JSON document produced by this backend.
+pub const FORMAT_VERSION: u32 = 2;
+
+thread_local!(static DEF_KEYS: RefCell<FxHashMap<DefId, String>> = Default::default());
+
+/// Renders `krate` to `<dst>/<crate name>.json`.
+pub fn run(mut krate: clean::Crate, dst: PathBuf, mut renderinfo: RenderInfo)
+           -> Result<(), Error> {
+    let def_keys = mem::replace(&mut renderinfo.def_keys, FxHashMap());
+    DEF_KEYS.with(|keys| *keys.borrow_mut() = def_keys);
+
+    let mut renderer = JsonRenderer {
+        index: json::Object::new(),
+        paths: json::Object::new(),
+        impls: FxHashMap(),
+        implementors: FxHashMap(),
+        blanket_impls: FxHashMap(),
+        primitive_locations: FxHashMap(),
+    };
+
+    for &(did, prim, _) in &krate.primitives {
+        renderer.primitive_locations.insert(prim, did);
+    }
+    for &(_, ref e) in &krate.externs {
+        for &(did, prim, _) in &e.primitives {
+            renderer.primitive_locations.entry(prim).or_insert(did);
+        }
+    }
+
+    if let Some(ref mut module) = krate.module {
+        module.name = Some(krate.name.clone());
+    }
+
+    // Impls are listed on the items they implement, so find all of them before
+    // anything is rendered.
+    if let Some(ref module) = krate.module {
+        renderer.collect_impls(module);
+    }
+    let root = match krate.module {
+        Some(ref module) => renderer.item(module, Some(&[])),
+        None => None,
+    };
+    renderer.external_paths(&renderinfo.external_paths);
+
+    let mut external_crates = json::Object::new();
+    for &(cnum, ref e) in &krate.externs {
+        let html_root_url = e.attrs.lists("doc")
+            .filter(|a| a.check_name("html_root_url"))
+            .filter_map(|a| a.value_str())
+            .map(|url| url.to_string())
+            .next();
+        external_crates.insert(cnum.as_u32().to_string(), object(vec![
+            ("name", e.name.to_json()),
+            ("html_root_url", html_root_url.to_json()),
+        ]));
+    }
+
+    let output = object(vec![
+        ("format_version", FORMAT_VERSION.to_json()),
+        ("crate_name", krate.name.to_json()),
+        ("crate_version", krate.version.to_json()),
+        ("root", root.unwrap_or(Json::Null)),
+        ("index", Json::Object(renderer.index)),
+        ("paths", Json::Object(renderer.paths)),
+        ("external_crates", Json::Object(external_crates)),
+    ]);
+
+    fs::create_dir_all(&dst).map_err(|e| Error::new(e, &dst))?;
+    let file = dst.join(format!("{}.json", krate.name));
+    let mut w = BufWriter::new(File::create(&file).map_err(|e| Error::new(e, &file))?);
+    write!(w, "{}", json::as_json(&output)).map_err(|e| Error::new(e, &file))?;
+    Ok(())
+}
+
+struct JsonRenderer {
+    /// Every rendered item, keyed by id.
+    index: json::Object,
+    /// The crate and full path of every item referred to by the document.
+    paths: json::Object,
+    /// Impls for each type, keyed by the implementing type.
+    impls: FxHashMap<DefId, Vec<DefId>>,
+    /// Impls of each trait for concrete types, keyed by the trait.
+    implementors: FxHashMap<DefId, Vec<DefId>>,
+    /// Impls of each trait for any `T` meeting some bounds, keyed by the trait. These
+    /// aren't repeated on the types they happen to apply to.
+    blanket_impls: FxHashMap<DefId, Vec<DefId>>,
+    primitive_locations: FxHashMap<PrimitiveType, DefId>,
+}
+
+impl JsonRenderer {
+    fn collect_impls(&mut self, item: &clean::Item) {
+        match item.inner {
+            clean::ModuleItem(ref m) |
+            clean::StrippedItem(box clean::ModuleItem(ref m)) => {
+                for item in &m.items {
+                    self.collect_impls(item);
+                }
+            }
+            clean::ImplItem(ref i) => {
+                let trait_did = i.trait_.def_id();
+                if let clean::Generic(..) = i.for_ {
+                    if let Some(did) = trait_did {
+                        self.blanket_impls.entry(did).or_insert(vec![]).push(item.def_id);
+                    }
+                    return
+                }
+                if let Some(did) = trait_did {
+                    self.implementors.entry(did).or_insert(vec![]).push(item.def_id);
+                }
+                if let Some(did) = self.type_did(&i.for_) {
+                    self.impls.entry(did).or_insert(vec![]).push(item.def_id);
+                }
+            }
+            // `impl Trait for .. {}` applies to every type, like a blanket impl.
+            clean::AutoImplItem(ref i) => {
+                if let Some(did) = i.trait_.def_id() {
+                    self.blanket_impls.entry(did).or_insert(vec![]).push(item.def_id);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The item that impls for `ty` should be listed on: either the type itself or,
+    /// for built-in types, the crate's documentation of that primitive.
+    fn type_did(&self, ty: &clean::Type) -> Option<DefId> {
+        let prim = match *ty {
+            clean::ResolvedPath { did, .. } => return Some(did),
+            clean::Primitive(p) => p,
+            clean::BorrowedRef { type_: box clean::Generic(..), .. } => PrimitiveType::Reference,
+            clean::BorrowedRef { ref type_, .. } => return self.type_did(type_),
+            clean::Tuple(ref tys) if tys.is_empty() => PrimitiveType::Unit,
+            clean::Tuple(..) => PrimitiveType::Tuple,
+            clean::BareFunction(..) => PrimitiveType::Fn,
+            clean::Never => PrimitiveType::Never,
+            clean::Slice(..) => PrimitiveType::Slice,
+            clean::Array(..) => PrimitiveType::Array,
+            clean::RawPointer(..) => PrimitiveType::RawPointer,
+            _ => return None,
+        };
+        self.primitive_locations.get(&prim).cloned()
+    }
+
+    fn external_paths(&mut self, paths: &ExternalPaths) {
+        for (&did, &(ref fqp, kind)) in paths {
+            let entry = object(vec![
+                ("crate_id", did.krate.as_u32().to_json()),
+                ("path", fqp.to_json()),
+                ("kind", ItemType::from(kind).css_class().to_json()),
+            ]);
+            self.paths.entry(id(did)).or_insert(entry);
+        }
+    }
+
+    fn ids(&mut self, items: &[clean::Item], path: Option<&[String]>) -> Json {
+        Json::Array(items.iter().filter_map(|item| self.item(item, path)).collect())
+    }
+
+    /// Adds `item` and everything it contains to the index, returning its id, or
+    /// `None` if it was stripped. `path` is the path of the enclosing item, if the
+    /// item can be named by a path at all.
+    fn item(&mut self, item: &clean::Item, path: Option<&[String]>) -> Option<Json> {
+        let inner = match item.inner {
+            // Stripped modules may still contain impls of public traits for public types.
+            clean::StrippedItem(box clean::ModuleItem(ref m)) => {
+                self.ids(&m.items, None);
+                return None
+            }
+            clean::StrippedItem(..) => return None,
+            ref inner => inner,
+        };
+
+        let kind = ItemType::from(item);
+        let fqp = match (path, &item.name) {
+            (Some(path), &Some(ref name)) => {
+                let mut fqp = path.to_vec();
+                fqp.push(name.clone());
+                Some(fqp)
+            }
+            _ => None,
+        };
+        if let Some(ref fqp) = fqp {
+            self.paths.insert(id(item.def_id), object(vec![
+                ("crate_id", item.def_id.krate.as_u32().to_json()),
+                ("path", fqp.to_json()),
+                ("kind", kind.css_class().to_json()),
+            ]));
+        }
+        let child_path = match *inner {
+            clean::ImplItem(..) | clean::AutoImplItem(..) => None,
+            _ => fqp.as_ref().map(|p| &p[..]),
+        };
+
+        let inner = match *inner {
+            clean::ExternCrateItem(ref name, ref original) => object(vec![
+                ("name", name.to_json()),
+                ("original", original.to_json()),
+            ]),
+            clean::ImportItem(ref import) => {
+                let (name, source, glob) = match *import {
+                    clean::Import::Simple(ref name, ref source) => (Some(name), source, false),
+                    clean::Import::Glob(ref source) => (None, source, true),
+                };
+                object(vec![
+                    ("source", path_name(&source.path).to_json()),
+                    ("name", name.cloned().to_json()),
+                    ("id", source.did.map(id).to_json()),
+                    ("glob", glob.to_json()),
+                ])
+            }
+            clean::StructItem(clean::Struct {
+                struct_type, ref generics, ref fields, fields_stripped
+            }) |
+            clean::UnionItem(clean::Union {
+                struct_type, ref generics, ref fields, fields_stripped
+            }) => object(vec![
+                ("struct_type", struct_type_name(struct_type).to_json()),
+                ("generics", generics_json(generics)),
+                ("fields", self.ids(fields, child_path)),
+                ("fields_stripped", fields_stripped.to_json()),
+                ("impls", ids_json(self.impls.get(&item.def_id))),
+            ]),
+            clean::EnumItem(ref e) => object(vec![
+                ("generics", generics_json(&e.generics)),
+                ("variants", self.ids(&e.variants, child_path)),
+                ("variants_stripped", e.variants_stripped.to_json()),
+                ("impls", ids_json(self.impls.get(&item.def_id))),
+            ]),
+            clean::FunctionItem(ref f) | clean::ForeignFunctionItem(ref f) => {
+                function_json(&f.decl, &f.generics, f.unsafety, Some(f.constness), f.abi, true)
+            }
+            clean::MethodItem(ref m) => {
+                function_json(&m.decl, &m.generics, m.unsafety, Some(m.constness), m.abi, true)
+            }
+            clean::TyMethodItem(ref m) => {
+                function_json(&m.decl, &m.generics, m.unsafety, None, m.abi, false)
+            }
+            clean::ModuleItem(ref m) => object(vec![
+                ("is_crate", m.is_crate.to_json()),
+                ("items", self.ids(&m.items, child_path)),
+            ]),
+            clean::TypedefItem(ref t, _) => object(vec![
+                ("type", type_json(&t.type_)),
+                ("generics", generics_json(&t.generics)),
+            ]),
+            clean::StaticItem(ref s) | clean::ForeignStaticItem(ref s) => object(vec![
+                ("type", type_json(&s.type_)),
+                ("mutable", (s.mutability == clean::Mutable).to_json()),
+                ("expr", s.expr.to_json()),
+            ]),
+            clean::ConstantItem(ref c) => object(vec![
+                ("type", type_json(&c.type_)),
+                ("expr", c.expr.to_json()),
+            ]),
+            clean::TraitItem(ref t) => object(vec![
+                ("is_unsafe", (t.unsafety == hir::Unsafety::Unsafe).to_json()),
+                ("items", self.ids(&t.items, child_path)),
+                ("generics", generics_json(&t.generics)),
+                ("bounds", bounds_json(&t.bounds)),
+                ("implementors", ids_json(self.implementors.get(&item.def_id))),
+                ("blanket_impls", ids_json(self.blanket_impls.get(&item.def_id))),
+            ]),
+            clean::ImplItem(ref i) => {
+                let mut provided = i.provided_trait_methods.iter().cloned().collect::<Vec<_>>();
+                provided.sort();
+                let is_blanket = match i.for_ {
+                    clean::Generic(..) => true,
+                    _ => false,
+                };
+                object(vec![
+                    ("is_unsafe", (i.unsafety == hir::Unsafety::Unsafe).to_json()),
+                    ("generics", generics_json(&i.generics)),
+                    ("provided_trait_methods", provided.to_json()),
+                    ("trait", i.trait_.as_ref().map(type_json).to_json()),
+                    ("for", type_json(&i.for_)),
+                    ("items", self.ids(&i.items, None)),
+                    ("negative", (i.polarity == Some(clean::ImplPolarity::Negative)).to_json()),
+                    ("blanket", is_blanket.to_json()),
+                    ("auto", false.to_json()),
+                ])
+            }
+            clean::AutoImplItem(ref i) => {
+                let generics = clean::Generics {
+                    lifetimes: vec![],
+                    type_params: vec![],
+                    where_predicates: vec![],
+                };
+                object(vec![
+                    ("is_unsafe", (i.unsafety == hir::Unsafety::Unsafe).to_json()),
+                    ("generics", generics_json(&generics)),
+                    ("provided_trait_methods", Json::Array(vec![])),
+                    ("trait", type_json(&i.trait_)),
+                    ("for", Json::Null),
+                    ("items", Json::Array(vec![])),
+                    ("negative", false.to_json()),
+                    ("blanket", true.to_json()),
+                    ("auto", true.to_json()),
+                ])
+            }
+            clean::StructFieldItem(ref ty) => type_json(ty),
+            clean::VariantItem(ref v) => match v.kind {
+                clean::VariantKind::CLike => tagged("plain", Json::Null),
+                clean::VariantKind::Tuple(ref tys) => {
+                    tagged("tuple", Json::Array(tys.iter().map(type_json).collect()))
+                }
+                clean::VariantKind::Struct(ref s) => tagged("struct", object(vec![
+                    ("fields", self.ids(&s.fields, child_path)),
+                    ("fields_stripped", s.fields_stripped.to_json()),
+                ])),
+            },
+            clean::ForeignTypeItem => Json::Null,
+            clean::MacroItem(ref m) => object(vec![
+                ("source", m.source.to_json()),
+            ]),
+            clean::PrimitiveItem(prim) => object(vec![
+                ("name", prim.as_str().to_json()),
+                ("impls", ids_json(self.impls.get(&item.def_id))),
+            ]),
+            clean::AssociatedConstItem(ref ty, ref default) => object(vec![
+                ("type", type_json(ty)),
+                ("default", default.to_json()),
+            ]),
+            clean::AssociatedTypeItem(ref bounds, ref default) => object(vec![
+                ("bounds", bounds_json(bounds)),
+                ("default", default.as_ref().map(type_json).to_json()),
+            ]),
+            clean::StrippedItem(..) => unreachable!(),
+        };
+
+        let links = item.attrs.links.iter().map(|&(ref link, did, _)| {
+            (link.clone(), Json::String(id(did)))
+        }).collect::<json::Object>();
+        let attrs = item.attrs.other_attrs.iter()
+            .map(pprust::attribute_to_string)
+            .collect::<Vec<_>>();
+        let visibility = match item.visibility {
+            Some(clean::Public) => "public".to_json(),
+            Some(clean::Inherited) => "default".to_json(),
+            None => Json::Null,
+        };
+
+        let item_id = id(item.def_id);
+        self.index.insert(item_id.clone(), object(vec![
+            ("id", item_id.to_json()),
+            ("crate_id", item.def_id.krate.as_u32().to_json()),
+            ("name", item.name.to_json()),
+            ("span", span_json(&item.source)),
+            ("visibility", visibility),
+            ("docs", item.attrs.collapsed_doc_value().to_json()),
+            ("links", Json::Object(links)),
+            ("attrs", attrs.to_json()),
+            ("stability", item.stability.as_ref().map(stability_json).to_json()),
+            ("deprecation", item.deprecation.as_ref().map(deprecation_json).to_json()),
+            ("kind", kind.css_class().to_json()),
+            ("inner", inner),
+        ]));
+        Some(Json::String(item_id))
+    }
+}
+
+/// Looks up the id of every item the document of `krate` can refer to: the items themselves,
+/// the targets of their links and imports, and the items named by the types they mention.
+pub fn def_keys(cx: &DocContext, krate: &clean::Crate) -> FxHashMap<DefId, String> {
+    let mut collector = KeyCollector { cx, keys: FxHashMap() };
+    if let Some(ref module) = krate.module {
+        collector.item(module);
+    }
+    for &(did, _, _) in &krate.primitives {
+        collector.did(did);
+    }
+    for &(_, ref e) in &krate.externs {
+        for &(did, _, _) in &e.primitives {
+            collector.did(did);
+        }
+    }
+    let external = cx.renderinfo.borrow().external_paths.keys().cloned().collect::<Vec<_>>();
+    for did in external {
+        collector.did(did);
+    }
+    collector.keys
+}
+
+struct KeyCollector<'a, 'tcx: 'a, 'rcx: 'a> {
+    cx: &'a DocContext<'a, 'tcx, 'rcx>,
+    keys: FxHashMap<DefId, String>,
+}
+
+impl<'a, 'tcx, 'rcx> KeyCollector<'a, 'tcx, 'rcx> {
+    fn did(&mut self, did: DefId) {
+        if !self.keys.contains_key(&did) {
+            self.keys.insert(did, core::def_key(self.cx.tcx, did));
+        }
+    }
+
+    /// Visits stripped items too, since they may still contain impls that are rendered.
+    fn item(&mut self, item: &clean::Item) {
+        self.did(item.def_id);
+        for &(_, did, _) in &item.attrs.links {
+            self.did(did);
+        }
+        let inner = match item.inner {
+            clean::StrippedItem(box ref inner) => inner,
+            ref inner => inner,
+        };
+        match *inner {
+            clean::ImportItem(clean::Import::Simple(_, ref source)) |
+            clean::ImportItem(clean::Import::Glob(ref source)) => {
+                if let Some(did) = source.did {
+                    self.did(did);
+                }
+            }
+            clean::ModuleItem(ref m) => self.items(&m.items),
+            clean::StructItem(clean::Struct { ref generics, ref fields, .. }) |
+            clean::UnionItem(clean::Union { ref generics, ref fields, .. }) => {
+                self.generics(generics);
+                self.items(fields);
+            }
+            clean::EnumItem(ref e) => {
+                self.generics(&e.generics);
+                self.items(&e.variants);
+            }
+            clean::FunctionItem(ref f) | clean::ForeignFunctionItem(ref f) => {
+                self.decl(&f.decl);
+                self.generics(&f.generics);
+            }
+            clean::MethodItem(ref m) => {
+                self.decl(&m.decl);
+                self.generics(&m.generics);
+            }
+            clean::TyMethodItem(ref m) => {
+                self.decl(&m.decl);
+                self.generics(&m.generics);
+            }
+            clean::TypedefItem(ref t, _) => {
+                self.ty(&t.type_);
+                self.generics(&t.generics);
+            }
+            clean::StaticItem(ref s) | clean::ForeignStaticItem(ref s) => self.ty(&s.type_),
+            clean::ConstantItem(ref c) => self.ty(&c.type_),
+            clean::TraitItem(ref t) => {
+                self.items(&t.items);
+                self.generics(&t.generics);
+                self.bounds(&t.bounds);
+            }
+            clean::ImplItem(ref i) => {
+                self.generics(&i.generics);
+                if let Some(ref trait_) = i.trait_ {
+                    self.ty(trait_);
+                }
+                self.ty(&i.for_);
+                self.items(&i.items);
+            }
+            clean::AutoImplItem(ref i) => self.ty(&i.trait_),
+            clean::StructFieldItem(ref ty) => self.ty(ty),
+            clean::VariantItem(ref v) => match v.kind {
+                clean::VariantKind::CLike => {}
+                clean::VariantKind::Tuple(ref tys) => self.tys(tys),
+                clean::VariantKind::Struct(ref s) => self.items(&s.fields),
+            },
+            clean::AssociatedConstItem(ref ty, _) => self.ty(ty),
+            clean::AssociatedTypeItem(ref bounds, ref default) => {
+                self.bounds(bounds);
+                if let Some(ref ty) = *default {
+                    self.ty(ty);
+                }
+            }
+            clean::ExternCrateItem(..) |
+            clean::ForeignTypeItem |
+            clean::MacroItem(..) |
+            clean::PrimitiveItem(..) |
+            clean::StrippedItem(..) => {}
+        }
+    }
+
+    fn items(&mut self, items: &[clean::Item]) {
+        for item in items {
+            self.item(item);
+        }
+    }
+
+    fn decl(&mut self, decl: &clean::FnDecl) {
+        for arg in &decl.inputs.values {
+            self.ty(&arg.type_);
+        }
+        if let clean::Return(ref ty) = decl.output {
+            self.ty(ty);
+        }
+    }
+
+    fn generics(&mut self, generics: &clean::Generics) {
+        for param in &generics.type_params {
+            self.bounds(&param.bounds);
+            if let Some(ref ty) = param.default {
+                self.ty(ty);
+            }
+        }
+        for pred in &generics.where_predicates {
+            match *pred {
+                clean::WherePredicate::BoundPredicate { ref ty, ref bounds } => {
+                    self.ty(ty);
+                    self.bounds(bounds);
+                }
+                clean::WherePredicate::RegionPredicate { .. } => {}
+                clean::WherePredicate::EqPredicate { ref lhs, ref rhs } => {
+                    self.ty(lhs);
+                    self.ty(rhs);
+                }
+            }
+        }
+    }
+
+    fn bounds(&mut self, bounds: &[clean::TyParamBound]) {
+        for bound in bounds {
+            if let clean::TraitBound(ref poly, _) = *bound {
+                self.ty(&poly.trait_);
+            }
+        }
+    }
+
+    fn tys(&mut self, tys: &[clean::Type]) {
+        for ty in tys {
+            self.ty(ty);
+        }
+    }
+
+    fn ty(&mut self, ty: &clean::Type) {
+        match *ty {
+            clean::ResolvedPath { ref path, ref typarams, did, .. } => {
+                self.did(did);
+                for segment in &path.segments {
+                    match segment.params {
+                        clean::PathParameters::AngleBracketed { ref types, ref bindings, .. } => {
+                            self.tys(types);
+                            for binding in bindings {
+                                self.ty(&binding.ty);
+                            }
+                        }
+                        clean::PathParameters::Parenthesized { ref inputs, ref output } => {
+                            self.tys(inputs);
+                            if let Some(ref ty) = *output {
+                                self.ty(ty);
+                            }
+                        }
+                    }
+                }
+                if let Some(ref bounds) = *typarams {
+                    self.bounds(bounds);
+                }
+            }
+            clean::BareFunction(ref f) => {
+                self.decl(&f.decl);
+                self.generics(&f.generics);
+            }
+            clean::Tuple(ref tys) => self.tys(tys),
+            clean::Slice(ref ty) |
+            clean::Array(ref ty, _) |
+            clean::Unique(ref ty) |
+            clean::RawPointer(_, ref ty) |
+            clean::BorrowedRef { type_: ref ty, .. } => self.ty(ty),
+            clean::QPath { ref self_type, ref trait_, .. } => {
+                self.ty(self_type);
+                self.ty(trait_);
+            }
+            clean::ImplTrait(ref bounds) => self.bounds(bounds),
+            clean::Generic(..) | clean::Primitive(..) | clean::Never | clean::Infer => {}
+        }
+    }
+}
+
+fn id(did: DefId) -> String {
+    DEF_KEYS.with(|keys| match keys.borrow().get(&did) {
+        Some(key) => key.clone(),
+        // Every id rendered should have been found by `def_keys`, but an id that is only
+        // unique within this document beats failing the whole render.
+        None => format!("{}:{}", did.krate.as_u32(), did.index.as_u32()),
+    })
+}
+
+fn ids_json(dids: Option<&Vec<DefId>>) -> Json {
+    Json::Array(dids.into_iter().flat_map(|d| d).map(|&did| Json::String(id(did))).collect())
+}
+
+fn object(fields: Vec<(&'static str, Json)>) -> Json {
+    Json::Object(fields.into_iter().map(|(k, v)| (k.to_string(), v)).collect())
+}
+
+/// An enum value: `{"kind": kind, "inner": inner}`.
+fn tagged(kind: &str, inner: Json) -> Json {
+    object(vec![
+        ("kind", kind.to_json()),
+        ("inner", inner),
+    ])
+}
+
+fn non_empty(s: &str) -> Json {
+    if s.is_empty() { Json::Null } else { s.to_json() }
+}
+
+fn struct_type_name(struct_type: doctree::StructType) -> &'static str {
+    match struct_type {
+        doctree::Plain => "plain",
+        doctree::Tuple => "tuple",
+        doctree::Unit => "unit",
+    }
+}
+
+fn span_json(span: &clean::Span) -> Json {
+    if let FileName::Anon = span.filename {
+        return Json::Null
+    }
+    object(vec![
+        ("filename", span.filename.to_string().to_json()),
+        ("begin", vec![span.loline, span.locol].to_json()),
+        ("end", vec![span.hiline, span.hicol].to_json()),
+    ])
+}
+
+fn stability_json(stab: &clean::Stability) -> Json {
+    let level = match stab.level {
+        stability::Stable => "stable",
+        stability::Unstable => "unstable",
+    };
+    object(vec![
+        ("level", level.to_json()),
+        ("feature", non_empty(&stab.feature)),
+        ("since", non_empty(&stab.since)),
+        ("issue", stab.issue.to_json()),
+        ("unstable_reason", non_empty(&stab.unstable_reason)),
+        ("deprecated_since", non_empty(&stab.deprecated_since)),
+        ("deprecated_reason", non_empty(&stab.deprecated_reason)),
+    ])
+}
+
+fn deprecation_json(depr: &clean::Deprecation) -> Json {
+    object(vec![
+        ("since", non_empty(&depr.since)),
+        ("note", non_empty(&depr.note)),
+    ])
+}
+
+fn function_json(decl: &clean::FnDecl,
+                 generics: &clean::Generics,
+                 unsafety: hir::Unsafety,
+                 constness: Option<hir::Constness>,
+                 abi: Abi,
+                 has_body: bool) -> Json {
+    let inputs = decl.inputs.values.iter().map(|arg| {
+        Json::Array(vec![arg.name.to_json(), type_json(&arg.type_)])
+    }).collect();
+    let output = match decl.output {
+        clean::Return(ref ty) => type_json(ty),
+        clean::DefaultReturn => Json::Null,
+    };
+    object(vec![
+        ("decl", object(vec![
+            ("inputs", Json::Array(inputs)),
+            ("output", output),
+            ("variadic", decl.variadic.to_json()),
+        ])),
+        ("generics", generics_json(generics)),
+        ("header", object(vec![
+            ("is_unsafe", (unsafety == hir::Unsafety::Unsafe).to_json()),
+            ("is_const", (constness == Some(hir::Constness::Const)).to_json()),
+            ("abi", abi.name().to_json()),
+        ])),
+        ("has_body", has_body.to_json()),
+    ])
+}
+
+fn generics_json(generics: &clean::Generics) -> Json {
+    let params = generics.type_params.iter().map(|param| {
+        object(vec![
+            ("name", param.name.to_json()),
+            ("bounds", bounds_json(&param.bounds)),
+            ("default", param.default.as_ref().map(type_json).to_json()),
+        ])
+    }).collect();
+    let where_predicates = generics.where_predicates.iter().map(|pred| match *pred {
+        clean::WherePredicate::BoundPredicate { ref ty, ref bounds } => {
+            tagged("bound_predicate", object(vec![
+                ("type", type_json(ty)),
+                ("bounds", bounds_json(bounds)),
+            ]))
+        }
+        clean::WherePredicate::RegionPredicate { ref lifetime, ref bounds } => {
+            tagged("region_predicate", object(vec![
+                ("lifetime", lifetime.get_ref().to_json()),
+                ("bounds", lifetimes_json(bounds)),
+            ]))
+        }
+        clean::WherePredicate::EqPredicate { ref lhs, ref rhs } => {
+            tagged("eq_predicate", object(vec![
+                ("lhs", type_json(lhs)),
+                ("rhs", type_json(rhs)),
+            ]))
+        }
+    }).collect();
+    object(vec![
+        ("lifetimes", lifetimes_json(&generics.lifetimes)),
+        ("params", Json::Array(params)),
+        ("where_predicates", Json::Array(where_predicates)),
+    ])
+}
+
+fn lifetimes_json(lifetimes: &[clean::Lifetime]) -> Json {
+    Json::Array(lifetimes.iter().map(|l| l.get_ref().to_json()).collect())
+}
+
+fn bounds_json(bounds: &[clean::TyParamBound]) -> Json {
+    Json::Array(bounds.iter().map(|bound| match *bound {
+        clean::RegionBound(ref lifetime) => tagged("outlives", lifetime.get_ref().to_json()),
+        clean::TraitBound(ref poly, modifier) => {
+            let modifier = match modifier {
+                hir::TraitBoundModifier::None => "none",
+                hir::TraitBoundModifier::Maybe => "maybe",
+            };
+            tagged("trait_bound", object(vec![
+                ("trait", type_json(&poly.trait_)),
+                ("lifetimes", lifetimes_json(&poly.lifetimes)),
+                ("modifier", modifier.to_json()),
+            ]))
+        }
+    }).collect())
+}
+
+fn path_name(path: &clean::Path) -> String {
+    let names = path.segments.iter().map(|s| &s.name[..]).collect::<Vec<_>>();
+    let prefix = if path.global { "::" } else { "" };
+    format!("{}{}", prefix, names.join("::"))
+}
+
+fn path_params_json(params: &clean::PathParameters) -> Json {
+    match *params {
+        clean::PathParameters::AngleBracketed { ref lifetimes, ref types, ref bindings } => {
+            let bindings = bindings.iter().map(|b| {
+                object(vec![
+                    ("name", b.name.to_json()),
+                    ("type", type_json(&b.ty)),
+                ])
+            }).collect();
+            tagged("angle_bracketed", object(vec![
+                ("lifetimes", lifetimes_json(lifetimes)),
+                ("types", Json::Array(types.iter().map(type_json).collect())),
+                ("bindings", Json::Array(bindings)),
+            ]))
+        }
+        clean::PathParameters::Parenthesized { ref inputs, ref output } => {
+            tagged("parenthesized", object(vec![
+                ("inputs", Json::Array(inputs.iter().map(type_json).collect())),
+                ("output", output.as_ref().map(type_json).to_json()),
+            ]))
+        }
+    }
+}
+
+fn type_json(ty: &clean::Type) -> Json {
+    match *ty {
+        clean::ResolvedPath { ref path, ref typarams, did, is_generic } => {
+            let args = path.segments.last().map(|s| path_params_json(&s.params));
+            tagged("resolved_path", object(vec![
+                ("name", path_name(path).to_json()),
+                ("id", id(did).to_json()),
+                ("args", args.to_json()),
+                ("param_names", typarams.as_ref().map(|b| bounds_json(b)).to_json()),
+                ("is_generic", is_generic.to_json()),
+            ]))
+        }
+        clean::Generic(ref name) => tagged("generic", name.to_json()),
+        clean::Primitive(prim) => tagged("primitive", prim.as_str().to_json()),
+        clean::BareFunction(ref f) => {
+            tagged("function_pointer", function_json(&f.decl, &f.generics, f.unsafety,
+                                                     None, f.abi, false))
+        }
+        clean::Tuple(ref tys) => tagged("tuple", Json::Array(tys.iter().map(type_json).collect())),
+        clean::Slice(ref ty) => tagged("slice", type_json(ty)),
+        clean::Array(ref ty, ref len) => tagged("array", object(vec![
+            ("type", type_json(ty)),
+            ("len", len.to_json()),
+        ])),
+        clean::Never => tagged("never", Json::Null),
+        clean::Unique(ref ty) => tagged("box", type_json(ty)),
+        clean::RawPointer(mutability, ref ty) => tagged("raw_pointer", object(vec![
+            ("mutable", (mutability == clean::Mutable).to_json()),
+            ("type", type_json(ty)),
+        ])),
+        clean::BorrowedRef { ref lifetime, mutability, ref type_ } => {
+            tagged("borrowed_ref", object(vec![
+                ("lifetime", lifetime.as_ref().map(|l| l.get_ref().to_string()).to_json()),
+                ("mutable", (mutability == clean::Mutable).to_json()),
+                ("type", type_json(type_)),
+            ]))
+        }
+        clean::QPath { ref name, ref self_type, ref trait_ } => {
+            tagged("qualified_path", object(vec![
+                ("name", name.to_json()),
+                ("self_type", type_json(self_type)),
+                ("trait", type_json(trait_)),
+            ]))
+        }
+        clean::Infer => tagged("infer", Json::Null),
+        clean::ImplTrait(ref bounds) => tagged("impl_trait", bounds_json(bounds)),
+    }
+}
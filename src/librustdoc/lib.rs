@@ -66,14 +66,17 @@ use externalfiles::ExternalHtml;
 use rustc::session::search_paths::SearchPaths;
 use rustc::session::config::{ErrorOutputType, RustcOptGroup, nightly_options,
                              Externs};
+use serialize::json::ToJson;
 
 #[macro_use]
 pub mod externalfiles;
 
 pub mod clean;
 pub mod core;
+pub mod coverage;
 pub mod doctree;
 pub mod fold;
+pub mod json;
 pub mod html {
     pub mod highlight;
     pub mod escape;
@@ -87,6 +90,8 @@ pub mod html {
 pub mod markdown;
 pub mod passes;
 pub mod plugins;
+pub mod scrape_examples;
+pub mod span_map;
 pub mod visit_ast;
 pub mod visit_lib;
 pub mod test;
@@ -140,7 +145,7 @@ pub fn opts() -> Vec<RustcOptGroup> {
                      "[rust]")
         }),
         stable("w", |o| {
-            o.optopt("w", "output-format", "the output type to write", "[html]")
+            o.optopt("w", "output-format", "the output type to write", "[html|json]")
         }),
         stable("o", |o| o.optopt("o", "output", "where to place the output", "PATH")),
         stable("crate-name", |o| {
@@ -253,6 +258,26 @@ pub fn opts() -> Vec<RustcOptGroup> {
         unstable("linker", |o| {
             o.optopt("", "linker", "linker used for building executable test code", "PATH")
         }),
+        unstable("show-coverage", |o| {
+            o.optflag("", "show-coverage",
+                      "calculate the percentage of public items with documentation")
+        }),
+        unstable("scrape-examples-output-path", |o| {
+            o.optopt("", "scrape-examples-output-path",
+                     "write the calls this crate makes to the functions of the target crates \
+                      to PATH, instead of documenting it",
+                     "PATH")
+        }),
+        unstable("scrape-examples-target-crate", |o| {
+            o.optmulti("", "scrape-examples-target-crate",
+                       "crate whose functions to look for calls to",
+                       "NAME")
+        }),
+        unstable("with-examples", |o| {
+            o.optmulti("", "with-examples",
+                       "show the calls scraped from examples into PATH in the documentation",
+                       "PATH")
+        }),
     ]
 }
 
@@ -388,8 +413,37 @@ pub fn main_args(args: &[String]) -> isize {
     }
 
     let output_format = matches.opt_str("w");
+    let unstable_enabled = nightly_options::is_unstable_enabled(&matches);
+    let show_coverage = matches.opt_present("show-coverage");
+    let scrape_examples = matches.opt_present("scrape-examples-output-path");
+    if scrape_examples && matches.opt_strs("scrape-examples-target-crate").is_empty() {
+        print_error("`--scrape-examples-output-path` requires at least one \
+                     `--scrape-examples-target-crate`");
+        return 1;
+    }
     let res = acquire_input(PathBuf::from(input), externs, &matches, move |out| {
         let Output { krate, passes, renderinfo } = out;
+        if scrape_examples {
+            // The calls have been written out while the crate was analyzed.
+            return 0;
+        }
+        if show_coverage {
+            let report = coverage::CoverageReport::new(&krate);
+            return match output_format.as_ref().map(|s| &**s) {
+                Some("html") | None => {
+                    report.print_table();
+                    0
+                }
+                Some("json") => {
+                    println!("{}", report.to_json());
+                    0
+                }
+                Some(s) => {
+                    print_error(format!("unknown output format: {}", s));
+                    1
+                }
+            };
+        }
         info!("going to format");
         match output_format.as_ref().map(|s| &**s) {
             Some("html") | None => {
@@ -402,6 +456,16 @@ pub fn main_args(args: &[String]) -> isize {
                     .expect("failed to generate documentation");
                 0
             }
+            Some("json") if !unstable_enabled => {
+                print_error("the json output format is unstable and requires \
+                             `-Z unstable-options`");
+                1
+            }
+            Some("json") => {
+                json::run(krate, output.unwrap_or(PathBuf::from("doc")), renderinfo)
+                    .expect("failed to generate documentation");
+                0
+            }
             Some(s) => {
                 print_error(format!("unknown output format: {}", s));
                 1
@@ -488,6 +552,13 @@ where R: 'static + Send, F: 'static + Send + FnOnce(Output) -> R {
     let crate_name = matches.opt_str("crate-name");
     let crate_version = matches.opt_str("crate-version");
     let plugin_path = matches.opt_str("plugin-path");
+    let scrape_examples = matches.opt_str("scrape-examples-output-path").map(|path| {
+        scrape_examples::ScrapeOptions {
+            output_path: PathBuf::from(path),
+            target_crates: matches.opt_strs("scrape-examples-target-crate"),
+        }
+    });
+    let with_examples = matches.opt_strs("with-examples").into_iter().map(PathBuf::from).collect();
 
     info!("starting to run rustc");
     let display_warnings = matches.opt_present("display-warnings");
@@ -502,7 +573,8 @@ where R: 'static + Send, F: 'static + Send + FnOnce(Output) -> R {
 
         let (mut krate, renderinfo) =
             core::run_core(paths, cfgs, externs, Input::File(cratefile), triple, maybe_sysroot,
-                           display_warnings, force_unstable_if_unmarked);
+                           display_warnings, force_unstable_if_unmarked, scrape_examples,
+                           with_examples);
 
         info!("finished with rustc");
 
@@ -573,7 +645,6 @@ where R: 'static + Send, F: 'static + Send + FnOnce(Output) -> R {
 fn check_deprecated_options(matches: &getopts::Matches) {
     let deprecated_flags = [
        "input-format",
-       "output-format",
        "plugin-path",
        "plugins",
        "no-defaults",
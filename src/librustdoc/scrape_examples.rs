@@ -0,0 +1,208 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+
+//! Finds the calls to the functions of the documented crate in example crates, so that the
+//! documentation of those functions can show how they are used.
+//!
+//! This works in two steps. Documenting an example crate with `--scrape-examples-output-path`
+//! writes the calls it makes to the functions of the crates given with
+//! `--scrape-examples-target-crate` to a file. Documenting one of those crates with
+//! `--with-examples` pointing to such files then shows the calls under the documentation of
+//! each function and method.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::prelude::*;
+use std::mem;
+use std::path::PathBuf;
+
+use rustc::hir;
+use rustc::hir::def::Def;
+use rustc::hir::def_id::DefId;
+use rustc::hir::intravisit::{self, NestedVisitorMap, Visitor};
+use rustc::ty::{self, TyCtxt};
+use rustc::util::nodemap::FxHashMap;
+use serialize::json;
+use syntax_pos::{Span, SyntaxContext};
+
+use core::{def_key, DocContext};
+
+/// What to scrape out of an example crate, and where to write it.
+#[derive(Clone, Debug)]
+pub struct ScrapeOptions {
+    pub output_path: PathBuf,
+    /// The names of the crates whose functions to look for calls to.
+    pub target_crates: Vec<String>,
+}
+
+/// The calls to a function found in an item of an example.
+#[derive(Clone, Debug, RustcEncodable, RustcDecodable)]
+pub struct CallLocation {
+    /// The first and last lines of each call within the example file.
+    pub call_lines: Vec<(usize, usize)>,
+    /// The line the enclosing item starts on within the example file.
+    pub start_line: usize,
+    /// The source of the enclosing item.
+    pub code: String,
+}
+
+/// The calls to a function, keyed by the path of the example file they are in.
+pub type FnCallLocations = BTreeMap<String, Vec<CallLocation>>;
+
+/// The calls found in examples, keyed by the path of the function they call.
+pub type AllCallLocations = BTreeMap<String, FnCallLocations>;
+
+/// Writes the calls that the crate makes to the functions of the target crates.
+pub fn scrape(cx: &DocContext, options: &ScrapeOptions) {
+    let mut finder = CallFinder {
+        cx,
+        options,
+        tables: None,
+        enclosing: None,
+        calls: AllCallLocations::new(),
+    };
+    intravisit::walk_crate(&mut finder, cx.tcx.hir.krate());
+
+    let result = File::create(&options.output_path).and_then(|mut f| {
+        write!(f, "{}", json::as_json(&finder.calls))
+    });
+    if let Err(e) = result {
+        cx.sess().fatal(&format!("failed to write `{}`: {}", options.output_path.display(), e));
+    }
+}
+
+/// Loads the calls written by `scrape` to the given files, keeping those to the functions of
+/// the local crate.
+pub fn load(cx: &DocContext, paths: &[PathBuf]) -> FxHashMap<DefId, FnCallLocations> {
+    let mut calls = AllCallLocations::new();
+    for path in paths {
+        let mut contents = String::new();
+        let read = File::open(path).and_then(|mut f| f.read_to_string(&mut contents));
+        let loaded = match read {
+            Ok(_) => json::decode::<AllCallLocations>(&contents).map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        match loaded {
+            Ok(loaded) => {
+                for (key, files) in loaded {
+                    calls.entry(key).or_insert_with(BTreeMap::new).extend(files);
+                }
+            }
+            Err(e) => {
+                cx.sess().fatal(&format!("failed to load examples from `{}`: {}",
+                                         path.display(), e));
+            }
+        }
+    }
+    if calls.is_empty() {
+        return FxHashMap();
+    }
+
+    let tcx = cx.tcx;
+    let krate = tcx.hir.krate();
+    krate.items.keys().cloned()
+        .chain(krate.trait_items.keys().map(|id| id.node_id))
+        .chain(krate.impl_items.keys().map(|id| id.node_id))
+        .filter_map(|id| {
+            let did = tcx.hir.local_def_id(id);
+            calls.remove(&def_key(tcx, did)).map(|calls| (did, calls))
+        })
+        .collect()
+}
+
+struct CallFinder<'a, 'tcx: 'a, 'rcx: 'a> {
+    cx: &'a DocContext<'a, 'tcx, 'rcx>,
+    options: &'a ScrapeOptions,
+    /// The type-check results of the body being walked, used to resolve the called functions.
+    tables: Option<&'tcx ty::TypeckTables<'tcx>>,
+    /// The span of the item whose body is being walked, which is shown around its calls.
+    enclosing: Option<Span>,
+    calls: AllCallLocations,
+}
+
+impl<'a, 'tcx, 'rcx> CallFinder<'a, 'tcx, 'rcx> {
+    fn record(&mut self, did: DefId, span: Span) {
+        let enclosing = match self.enclosing {
+            Some(enclosing) => enclosing,
+            None => return,
+        };
+        // Calls from macro expansions can't be shown as they are written.
+        if did.is_local() || span.ctxt() != SyntaxContext::empty() ||
+           enclosing.ctxt() != SyntaxContext::empty() {
+            return;
+        }
+        let tcx = self.cx.tcx;
+        let krate = tcx.crate_name(did.krate).as_str();
+        if !self.options.target_crates.iter().any(|c| *c == *krate) {
+            return;
+        }
+
+        let cx = self.cx;
+        let codemap = cx.sess().codemap();
+        let code = match codemap.span_to_snippet(enclosing) {
+            Ok(code) => code,
+            Err(_) => return,
+        };
+        let file = codemap.span_to_filename(span).to_string();
+        let call_lines = (codemap.lookup_char_pos(span.lo()).line,
+                          codemap.lookup_char_pos(span.hi()).line);
+        let start_line = codemap.lookup_char_pos(enclosing.lo()).line;
+
+        let locations = self.calls.entry(def_key(tcx, did)).or_insert_with(BTreeMap::new)
+                                  .entry(file).or_insert_with(Vec::new);
+        if let Some(location) = locations.last_mut() {
+            if location.start_line == start_line {
+                location.call_lines.push(call_lines);
+                return;
+            }
+        }
+        locations.push(CallLocation {
+            call_lines: vec![call_lines],
+            start_line,
+            code,
+        });
+    }
+}
+
+impl<'a, 'tcx, 'rcx> Visitor<'tcx> for CallFinder<'a, 'tcx, 'rcx> {
+    fn nested_visit_map<'this>(&'this mut self) -> NestedVisitorMap<'this, 'tcx> {
+        NestedVisitorMap::All(&self.cx.tcx.hir)
+    }
+
+    fn visit_nested_body(&mut self, id: hir::BodyId) {
+        let tcx = self.cx.tcx;
+        // The calls in closures are shown with the whole item around them.
+        let enclosing = self.enclosing.unwrap_or_else(|| tcx.hir.span(tcx.hir.body_owner(id)));
+        let outer_enclosing = mem::replace(&mut self.enclosing, Some(enclosing));
+        let outer_tables = mem::replace(&mut self.tables, Some(tcx.body_tables(id)));
+        self.visit_body(tcx.hir.body(id));
+        self.tables = outer_tables;
+        self.enclosing = outer_enclosing;
+    }
+
+    fn visit_expr(&mut self, expr: &'tcx hir::Expr) {
+        if let Some(tables) = self.tables {
+            let def = match expr.node {
+                hir::ExprCall(ref callee, _) => match callee.node {
+                    hir::ExprPath(ref qpath) => Some(tables.qpath_def(qpath, callee.hir_id)),
+                    _ => None,
+                },
+                hir::ExprMethodCall(..) => tables.type_dependent_defs().get(expr.hir_id).cloned(),
+                _ => None,
+            };
+            match def {
+                Some(Def::Fn(did)) | Some(Def::Method(did)) => self.record(did, expr.span),
+                _ => {}
+            }
+        }
+        intravisit::walk_expr(self, expr);
+    }
+}
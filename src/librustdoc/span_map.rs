@@ -0,0 +1,325 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+
+//! Collects the targets of the identifiers in the local crate's source, so that the rendered
+//! source pages can link every use of an item to its definition.
+
+use std::mem;
+use std::path::PathBuf;
+
+use rustc::hir;
+use rustc::hir::def::Def;
+use rustc::hir::def_id::DefId;
+use rustc::hir::intravisit::{self, NestedVisitorMap, Visitor};
+use rustc::ty;
+use rustc::util::nodemap::FxHashMap;
+use syntax::ast::{Name, NodeId};
+use syntax_pos::{FileName, Span, SyntaxContext};
+
+use clean::{self, inline};
+use core::DocContext;
+
+/// Where an identifier in a source page links to.
+#[derive(Clone, Debug)]
+pub enum LinkTarget {
+    /// The documentation page of an item, with the fragment of the member on that page if the
+    /// identifier names a method, field or variant.
+    Docs(DefId, Option<String>),
+    /// A line of one of the local crate's source files.
+    Source(PathBuf, usize),
+}
+
+/// The link targets of the identifiers in each source file, keyed by the byte range of the
+/// identifier within the file.
+pub type SpanMap = FxHashMap<PathBuf, FxHashMap<(u32, u32), LinkTarget>>;
+
+/// Walks the local crate, linking the uses of items to their definitions and the names of
+/// definitions to their documentation.
+pub fn collect(cx: &DocContext) -> SpanMap {
+    let mut collector = SpanMapCollector {
+        cx,
+        tables: None,
+        map: FxHashMap(),
+    };
+    intravisit::walk_crate(&mut collector, cx.tcx.hir.krate());
+    collector.map
+}
+
+struct SpanMapCollector<'a, 'tcx: 'a, 'rcx: 'a> {
+    cx: &'a DocContext<'a, 'tcx, 'rcx>,
+    /// The type-check results of the body being walked, used to resolve method calls and field
+    /// accesses.
+    tables: Option<&'tcx ty::TypeckTables<'tcx>>,
+    map: SpanMap,
+}
+
+impl<'a, 'tcx, 'rcx> SpanMapCollector<'a, 'tcx, 'rcx> {
+    /// Links an occurrence of `name` within `span` to `target`. The names of definitions are
+    /// looked for from the start of their span, and the last segment of a path from its end.
+    fn link(&mut self, span: Span, name: Name, from_end: bool, target: LinkTarget) {
+        // Spans coming from macro expansions don't point at what is shown in the source page.
+        if span.ctxt() != SyntaxContext::empty() {
+            return;
+        }
+        let cx = self.cx;
+        let codemap = cx.sess().codemap();
+        let snippet = match codemap.span_to_snippet(span) {
+            Ok(snippet) => snippet,
+            Err(_) => return,
+        };
+        let name = name.as_str();
+        let offset = match find_word(&snippet, &*name, from_end) {
+            Some(offset) => offset as u32,
+            None => return,
+        };
+        let loc = codemap.lookup_byte_offset(span.lo());
+        let file = match loc.fm.name {
+            FileName::Real(ref file) => file.clone(),
+            _ => return,
+        };
+        let lo = loc.pos.0 + offset;
+        self.map.entry(file).or_insert_with(FxHashMap)
+            .insert((lo, lo + name.len() as u32), target);
+    }
+
+    /// Returns the source line of `span`, if it is in a real file.
+    fn source(&self, span: Span) -> Option<LinkTarget> {
+        let loc = self.cx.sess().codemap().lookup_char_pos(span.lo());
+        match loc.file.name {
+            FileName::Real(ref file) => Some(LinkTarget::Source(file.clone(), loc.line)),
+            _ => None,
+        }
+    }
+
+    /// Returns where a use of `def` links to: the definition in the source for local items and
+    /// variables, and the documentation for items of other crates.
+    fn target(&self, def: Def) -> Option<LinkTarget> {
+        let tcx = self.cx.tcx;
+        match def {
+            Def::Local(id) | Def::Upvar(id, ..) => return self.source(tcx.hir.span(id)),
+            Def::Label(..) | Def::PrimTy(..) | Def::SelfTy(..) | Def::Err => return None,
+            _ => {}
+        }
+        match tcx.hir.span_if_local(def.def_id()) {
+            Some(span) => self.source(span),
+            None => self.docs(def),
+        }
+    }
+
+    /// Returns the documentation of `def`: the page of the item itself, or that of its parent
+    /// for methods, associated items and variants.
+    fn docs(&self, def: Def) -> Option<LinkTarget> {
+        let tcx = self.cx.tcx;
+        let (did, fragment) = match def {
+            Def::Method(did) | Def::AssociatedTy(did) | Def::AssociatedConst(did) => {
+                let item = tcx.associated_item(did);
+                let parent = match item.container {
+                    ty::TraitContainer(did) => did,
+                    ty::ImplContainer(did) => tcx.type_of(did).ty_adt_def()?.did,
+                };
+                let kind = match item.kind {
+                    ty::AssociatedKind::Method => "method",
+                    ty::AssociatedKind::Type => "associatedtype",
+                    ty::AssociatedKind::Const => "associatedconstant",
+                };
+                (parent, Some(format!("{}.{}", kind, item.name)))
+            }
+            Def::Variant(did) => {
+                (tcx.parent_def_id(did)?, Some(format!("variant.{}", tcx.item_name(did))))
+            }
+            Def::VariantCtor(did, _) => {
+                let variant = tcx.parent_def_id(did)?;
+                (tcx.parent_def_id(variant)?, Some(format!("variant.{}", tcx.item_name(variant))))
+            }
+            Def::StructCtor(did, _) => (tcx.parent_def_id(did)?, None),
+            Def::Mod(did) | Def::Struct(did) | Def::Union(did) | Def::Enum(did) |
+            Def::Trait(did) | Def::TyAlias(did) | Def::TyForeign(did) | Def::Fn(did) |
+            Def::Static(did, _) | Def::Const(did) => (did, None),
+            _ => return None,
+        };
+        self.record_extern_path(did);
+        Some(LinkTarget::Docs(did, fragment))
+    }
+
+    /// Makes sure that the renderer knows the path of `did` if it comes from another crate, as
+    /// only the items reachable from the documented API are recorded otherwise.
+    fn record_extern_path(&self, did: DefId) {
+        if did.is_local() || self.cx.renderinfo.borrow().external_paths.contains_key(&did) {
+            return;
+        }
+        let kind = match self.cx.tcx.describe_def(did) {
+            Some(Def::Mod(_)) => clean::TypeKind::Module,
+            Some(Def::Struct(_)) => clean::TypeKind::Struct,
+            Some(Def::Union(_)) => clean::TypeKind::Union,
+            Some(Def::Enum(_)) => clean::TypeKind::Enum,
+            Some(Def::Trait(_)) => clean::TypeKind::Trait,
+            Some(Def::TyAlias(_)) => clean::TypeKind::Typedef,
+            Some(Def::TyForeign(_)) => clean::TypeKind::Foreign,
+            Some(Def::Fn(_)) => clean::TypeKind::Function,
+            Some(Def::Static(..)) => clean::TypeKind::Static,
+            Some(Def::Const(_)) => clean::TypeKind::Const,
+            _ => return,
+        };
+        inline::record_extern_fqn(self.cx, did, kind);
+    }
+
+    /// Links the name of a local definition to its documentation.
+    fn link_definition(&mut self, span: Span, name: Name, id: NodeId) {
+        let did = self.cx.tcx.hir.local_def_id(id);
+        if let Some(target) = self.cx.tcx.describe_def(did).and_then(|def| self.docs(def)) {
+            self.link(span, name, false, target);
+        }
+    }
+
+    /// Links the name of a field to the documentation of its struct, union or variant.
+    fn link_field(&mut self, span: Span, name: Name, did: DefId) {
+        let tcx = self.cx.tcx;
+        let parent = match tcx.parent_def_id(did) {
+            Some(parent) => parent,
+            None => return,
+        };
+        let target = match tcx.describe_def(parent) {
+            Some(Def::Variant(variant)) => {
+                let fragment = format!("variant.{}.field.{}", tcx.item_name(variant), name);
+                match self.docs(Def::Variant(variant)) {
+                    Some(LinkTarget::Docs(enum_did, _)) => {
+                        LinkTarget::Docs(enum_did, Some(fragment))
+                    }
+                    _ => return,
+                }
+            }
+            Some(def) => match self.docs(def) {
+                Some(LinkTarget::Docs(adt, _)) => {
+                    LinkTarget::Docs(adt, Some(format!("structfield.{}", name)))
+                }
+                _ => return,
+            },
+            None => return,
+        };
+        self.link(span, name, false, target);
+    }
+}
+
+impl<'a, 'tcx, 'rcx> Visitor<'tcx> for SpanMapCollector<'a, 'tcx, 'rcx> {
+    fn nested_visit_map<'this>(&'this mut self) -> NestedVisitorMap<'this, 'tcx> {
+        NestedVisitorMap::All(&self.cx.tcx.hir)
+    }
+
+    fn visit_nested_body(&mut self, id: hir::BodyId) {
+        let tables = mem::replace(&mut self.tables, Some(self.cx.tcx.body_tables(id)));
+        let body = self.cx.tcx.hir.body(id);
+        self.visit_body(body);
+        self.tables = tables;
+    }
+
+    fn visit_item(&mut self, item: &'tcx hir::Item) {
+        match item.node {
+            hir::ItemUse(..) | hir::ItemImpl(..) | hir::ItemForeignMod(..) |
+            hir::ItemGlobalAsm(..) => {}
+            _ => self.link_definition(item.span, item.name, item.id),
+        }
+        intravisit::walk_item(self, item);
+    }
+
+    fn visit_trait_item(&mut self, item: &'tcx hir::TraitItem) {
+        self.link_definition(item.span, item.name, item.id);
+        intravisit::walk_trait_item(self, item);
+    }
+
+    fn visit_impl_item(&mut self, item: &'tcx hir::ImplItem) {
+        self.link_definition(item.span, item.name, item.id);
+        intravisit::walk_impl_item(self, item);
+    }
+
+    fn visit_foreign_item(&mut self, item: &'tcx hir::ForeignItem) {
+        self.link_definition(item.span, item.name, item.id);
+        intravisit::walk_foreign_item(self, item);
+    }
+
+    fn visit_variant(&mut self,
+                     variant: &'tcx hir::Variant,
+                     generics: &'tcx hir::Generics,
+                     item_id: NodeId) {
+        self.link_definition(variant.span, variant.node.name, variant.node.data.id());
+        intravisit::walk_variant(self, variant, generics, item_id);
+    }
+
+    fn visit_struct_field(&mut self, field: &'tcx hir::StructField) {
+        // Tuple fields are named by their position, which doesn't appear in the source.
+        if !field.name.as_str().starts_with(|c: char| c.is_digit(10)) {
+            let did = self.cx.tcx.hir.local_def_id(field.id);
+            self.link_field(field.span, field.name, did);
+        }
+        intravisit::walk_struct_field(self, field);
+    }
+
+    fn visit_path(&mut self, path: &'tcx hir::Path, _: NodeId) {
+        if let Some(segment) = path.segments.last() {
+            if let Some(target) = self.target(path.def) {
+                self.link(path.span, segment.name, true, target);
+            }
+        }
+        intravisit::walk_path(self, path);
+    }
+
+    fn visit_expr(&mut self, expr: &'tcx hir::Expr) {
+        if let Some(tables) = self.tables {
+            match expr.node {
+                hir::ExprMethodCall(ref segment, span, _) => {
+                    let def = tables.type_dependent_defs().get(expr.hir_id).cloned();
+                    if let Some(target) = def.and_then(|def| self.target(def)) {
+                        self.link(span, segment.name, false, target);
+                    }
+                }
+                hir::ExprPath(hir::QPath::TypeRelative(_, ref segment)) => {
+                    let def = tables.type_dependent_defs().get(expr.hir_id).cloned();
+                    if let Some(target) = def.and_then(|def| self.target(def)) {
+                        self.link(expr.span, segment.name, true, target);
+                    }
+                }
+                hir::ExprField(ref base, ref name) => {
+                    let adt = tables.expr_ty_adjusted_opt(base).and_then(|ty| ty.ty_adt_def());
+                    let field = adt.and_then(|adt| {
+                        if adt.is_enum() {
+                            None
+                        } else {
+                            adt.struct_variant().find_field_named(name.node).map(|f| f.did)
+                        }
+                    });
+                    if let Some(did) = field {
+                        match self.cx.tcx.hir.span_if_local(did).map(|span| self.source(span)) {
+                            Some(Some(target)) => self.link(name.span, name.node, false, target),
+                            Some(None) => {}
+                            None => self.link_field(name.span, name.node, did),
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        intravisit::walk_expr(self, expr);
+    }
+}
+
+/// Finds the byte offset of `word` within `s` where it isn't part of a longer identifier,
+/// searching backwards if `from_end` is set.
+fn find_word(s: &str, word: &str, from_end: bool) -> Option<usize> {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+    let is_word = |i: usize| {
+        !s[..i].chars().next_back().map_or(false, &is_ident) &&
+            !s[i + word.len()..].chars().next().map_or(false, &is_ident)
+    };
+    if from_end {
+        s.rmatch_indices(word).map(|(i, _)| i).find(|&i| is_word(i))
+    } else {
+        s.match_indices(word).map(|(i, _)| i).find(|&i| is_word(i))
+    }
+}
@@ -185,11 +185,77 @@ fn run_test(test: &str, cratename: &str, filename: &FileName, cfgs: Vec<String>,
     // the test harness wants its own `main` & top level functions, so
     // never wrap the test in `fn main() { ... }`
     let test = make_test(test, Some(cratename), as_test_harness, opts);
+
+    // Shuffle around a few input and output handles here. We're going to pass
+    // an explicit handle into rustc to collect output messages, but we also
+    // want to catch the error message that rustc prints when it fails.
+    //
+    // We take our thread-local stderr (likely set by the test runner) and replace
+    // it with a sink that is also passed to rustc itself. When this function
+    // returns the output of the sink is copied onto the output of our own thread.
+    //
+    // The basic idea is to not use a default Handler for rustc, and then also
+    // not print things by default to the actual stderr.
+    struct Bomb(Arc<Mutex<Vec<u8>>>, Box<Write+Send>);
+    impl Drop for Bomb {
+        fn drop(&mut self) {
+            let _ = self.1.write_all(&self.0.lock().unwrap());
+        }
+    }
+    let data = Arc::new(Mutex::new(Vec::new()));
+    let old = io::set_panic(Some(box Sink(data.clone())));
+    let _bomb = Bomb(data.clone(), old.unwrap_or(box io::stdout()));
+
+    let (compile_result, outdir, libdir) = compile_test(test, filename, cfgs, libs, externs,
+                                                        no_run, as_test_harness, maybe_sysroot,
+                                                        linker, data.clone());
+
+    match (compile_result, compile_fail) {
+        (Ok(()), true) => {
+            panic!("test compiled while it wasn't supposed to")
+        }
+        (Ok(()), false) => {}
+        (Err(()), true) => {
+            if error_codes.len() > 0 {
+                let out = String::from_utf8(data.lock().unwrap().to_vec()).unwrap();
+                error_codes.retain(|err| !out.contains(err));
+            }
+        }
+        (Err(()), false) => {
+            panic!("couldn't compile the test")
+        }
+    }
+
+    if error_codes.len() > 0 {
+        panic!("Some expected error codes were not found: {:?}", error_codes);
+    }
+
+    if no_run { return }
+
+    run_test_binary(&outdir.path().join("rust_out"), None, &libdir, should_panic);
+}
+
+// A writer into the buffer that collects rustc's diagnostics for a test.
+struct Sink(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Sink {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        Write::write(&mut *self.0.lock().unwrap(), data)
+    }
+    fn flush(&mut self) -> io::Result<()> { Ok(()) }
+}
+
+// Compiles the complete test program `test` into `rust_out` in a new temporary directory, and
+// returns whether that succeeded along with the directory and the target's library path.
+fn compile_test(test: String, filename: &FileName, cfgs: Vec<String>, libs: SearchPaths,
+                externs: Externs, no_run: bool, as_test_harness: bool,
+                maybe_sysroot: Option<PathBuf>, linker: Option<PathBuf>,
+                output: Arc<Mutex<Vec<u8>>>) -> (Result<(), ()>, TempDir, PathBuf) {
     // FIXME(#44940): if doctests ever support path remapping, then this filename
     // needs to be the result of CodeMap::span_to_unmapped_path
     let input = config::Input::Str {
         name: filename.to_owned(),
-        input: test.to_owned(),
+        input: test,
     };
     let outputs = OutputTypes::new(&[(OutputType::Exe, None)]);
 
@@ -210,36 +276,10 @@ fn run_test(test: &str, cratename: &str, filename: &FileName, cfgs: Vec<String>,
         ..config::basic_options().clone()
     };
 
-    // Shuffle around a few input and output handles here. We're going to pass
-    // an explicit handle into rustc to collect output messages, but we also
-    // want to catch the error message that rustc prints when it fails.
-    //
-    // We take our thread-local stderr (likely set by the test runner) and replace
-    // it with a sink that is also passed to rustc itself. When this function
-    // returns the output of the sink is copied onto the output of our own thread.
-    //
-    // The basic idea is to not use a default Handler for rustc, and then also
-    // not print things by default to the actual stderr.
-    struct Sink(Arc<Mutex<Vec<u8>>>);
-    impl Write for Sink {
-        fn write(&mut self, data: &[u8]) -> io::Result<usize> {
-            Write::write(&mut *self.0.lock().unwrap(), data)
-        }
-        fn flush(&mut self) -> io::Result<()> { Ok(()) }
-    }
-    struct Bomb(Arc<Mutex<Vec<u8>>>, Box<Write+Send>);
-    impl Drop for Bomb {
-        fn drop(&mut self) {
-            let _ = self.1.write_all(&self.0.lock().unwrap());
-        }
-    }
-    let data = Arc::new(Mutex::new(Vec::new()));
     let codemap = Rc::new(CodeMap::new(sessopts.file_path_mapping()));
-    let emitter = errors::emitter::EmitterWriter::new(box Sink(data.clone()),
+    let emitter = errors::emitter::EmitterWriter::new(box Sink(output),
                                                       Some(codemap.clone()),
                                                       false);
-    let old = io::set_panic(Some(box Sink(data.clone())));
-    let _bomb = Bomb(data.clone(), old.unwrap_or(box io::stdout()));
 
     // Compile the code
     let diagnostic_handler = errors::Handler::with_emitter(true, false, box emitter);
@@ -251,12 +291,12 @@ fn run_test(test: &str, cratename: &str, filename: &FileName, cfgs: Vec<String>,
     rustc_trans::init(&sess);
     rustc_lint::register_builtins(&mut sess.lint_store.borrow_mut(), Some(&sess));
 
-    let outdir = Mutex::new(TempDir::new("rustdoctest").ok().expect("rustdoc needs a tempdir"));
+    let outdir = TempDir::new("rustdoctest").ok().expect("rustdoc needs a tempdir");
     let libdir = sess.target_filesearch(PathKind::All).get_lib_path();
     let mut control = driver::CompileController::basic();
     sess.parse_sess.config =
         config::build_configuration(&sess, config::parse_cfgspecs(cfgs.clone()));
-    let out = Some(outdir.lock().unwrap().path().to_path_buf());
+    let out = Some(outdir.path().to_path_buf());
 
     if no_run {
         control.after_analysis.stop = Compilation::Stop;
@@ -271,40 +311,28 @@ fn run_test(test: &str, cratename: &str, filename: &FileName, cfgs: Vec<String>,
         Err(_) | Ok(Err(CompileIncomplete::Errored(_))) => Err(())
     };
 
-    match (compile_result, compile_fail) {
-        (Ok(()), true) => {
-            panic!("test compiled while it wasn't supposed to")
-        }
-        (Ok(()), false) => {}
-        (Err(()), true) => {
-            if error_codes.len() > 0 {
-                let out = String::from_utf8(data.lock().unwrap().to_vec()).unwrap();
-                error_codes.retain(|err| !out.contains(err));
-            }
-        }
-        (Err(()), false) => {
-            panic!("couldn't compile the test")
-        }
-    }
-
-    if error_codes.len() > 0 {
-        panic!("Some expected error codes were not found: {:?}", error_codes);
-    }
-
-    if no_run { return }
+    (compile_result, outdir, libdir)
+}
 
+// Runs a compiled test, passing `merged_index` to the binary of merged doctests to select which
+// of them to run.
+fn run_test_binary(binary: &Path, merged_index: Option<usize>, libdir: &Path,
+                   should_panic: bool) {
     // Run the code!
     //
     // We're careful to prepend the *target* dylib search path to the child's
     // environment to ensure that the target loads the right libraries at
     // runtime. It would be a sad day if the *host* libraries were loaded as a
     // mistake.
-    let mut cmd = Command::new(&outdir.lock().unwrap().path().join("rust_out"));
+    let mut cmd = Command::new(binary);
+    if let Some(idx) = merged_index {
+        cmd.arg(idx.to_string());
+    }
     let var = DynamicLibrary::envvar();
     let newpath = {
         let path = env::var_os(var).unwrap_or(OsString::new());
         let mut path = env::split_paths(&path).collect::<Vec<_>>();
-        path.insert(0, libdir.clone());
+        path.insert(0, libdir.to_path_buf());
         env::join_paths(path).unwrap()
     };
     cmd.env(var, &newpath);
@@ -362,20 +390,7 @@ pub fn make_test(s: &str,
         }
     }
 
-    // FIXME (#21299): prefer libsyntax or some other actual parser over this
-    // best-effort ad hoc approach
-    let already_has_main = s.lines()
-        .map(|line| {
-            let comment = line.find("//");
-            if let Some(comment_begins) = comment {
-                &line[0..comment_begins]
-            } else {
-                line
-            }
-        })
-        .any(|code| code.contains("fn main"));
-
-    if dont_insert_main || already_has_main {
+    if dont_insert_main || has_main(s) {
         prog.push_str(&everything_else);
     } else {
         prog.push_str("fn main() {\n");
@@ -389,6 +404,99 @@ pub fn make_test(s: &str,
     prog
 }
 
+// FIXME (#21299): prefer libsyntax or some other actual parser over this
+// best-effort ad hoc approach
+fn has_main(s: &str) -> bool {
+    s.lines().map(strip_comment).any(|code| code.contains("fn main"))
+}
+
+fn strip_comment(line: &str) -> &str {
+    let comment = line.find("//");
+    if let Some(comment_begins) = comment {
+        &line[0..comment_begins]
+    } else {
+        line
+    }
+}
+
+// Whether a doctest can be compiled as a module of the merged doctest binary rather than as a
+// crate of its own. That rules out tests which need to be the crate root: the ones with crate
+// attributes or `extern crate` items, and the ones whose `use` paths would resolve from the root
+// of the merged crate instead of from the test, because they don't start with the name of a
+// crate.
+fn is_mergeable(s: &str, cratename: &str) -> bool {
+    let (crate_attrs, _) = partition_source(s);
+    if !crate_attrs.trim().is_empty() {
+        return false;
+    }
+
+    s.lines().map(strip_comment).all(|code| {
+        let code = code.trim();
+        if code.contains("extern crate") {
+            return false;
+        }
+        let path = if code.starts_with("use ") {
+            &code[4..]
+        } else if code.starts_with("pub use ") {
+            &code[8..]
+        } else {
+            return true;
+        };
+        let root = path.trim_left().trim_left_matches("::")
+                       .split(|c: char| !(c == '_' || c.is_alphanumeric()))
+                       .next()
+                       .unwrap_or("");
+        ["std", "core", "alloc", "self", "super", cratename].contains(&root)
+    })
+}
+
+// Builds the binary of merged doctests: each test becomes a module with a `__run` function, and
+// `main` runs the test whose index is the first argument to the binary.
+fn make_merged_test(tests: &[String], cratename: &str, opts: &TestOptions) -> String {
+    let mut prog = String::new();
+
+    if opts.attrs.is_empty() {
+        // See `make_test` for why these lints are allowed.
+        prog.push_str("#![allow(unused)]\n");
+    }
+    for attr in &opts.attrs {
+        prog.push_str(&format!("#![{}]\n", attr));
+    }
+
+    if !opts.no_crate_inject && cratename != "std" &&
+       tests.iter().any(|test| test.contains(cratename)) {
+        prog.push_str(&format!("extern crate {};\n", cratename));
+    }
+
+    for (idx, test) in tests.iter().enumerate() {
+        // The glob import makes the injected crate available by name, as it would be at the
+        // root of a test of its own. It is allowed to go unused so that it doesn't trip
+        // `#![deny(warnings)]` from the `doc(test(attr(...)))` attributes.
+        prog.push_str(&format!("pub mod __doctest_{} {{\n#[allow(unused_imports)]\nuse super::*;\n",
+                               idx));
+        if has_main(test) {
+            prog.push_str(test);
+        } else {
+            prog.push_str("fn main() {\n");
+            prog.push_str(test.trim());
+            prog.push_str("\n}");
+        }
+        prog.push_str("\npub fn __run() { main() }\n}\n");
+    }
+
+    prog.push_str("fn main() {\n");
+    prog.push_str("    let test = ::std::env::args().nth(1).and_then(|arg| arg.parse().ok());\n");
+    prog.push_str("    match test {\n");
+    for idx in 0..tests.len() {
+        prog.push_str(&format!("        Some({0}usize) => __doctest_{0}::__run(),\n", idx));
+    }
+    prog.push_str("        _ => panic!(\"expected the index of a doctest to run\"),\n    }\n}\n");
+
+    info!("merged test program: {}", prog);
+
+    prog
+}
+
 // FIXME(aburka): use a real parser to deal with multiline attributes
 fn partition_source(s: &str) -> (String, String) {
     use std_unicode::str::UnicodeStr;
@@ -414,6 +522,47 @@ fn partition_source(s: &str) -> (String, String) {
     (before, after)
 }
 
+// Doctests that are compiled together into a single binary instead of one each, which saves a
+// rustc invocation and a link per test. Whichever of them runs first compiles the binary, and if
+// that fails they fall back to being compiled on their own, so that the errors are reported
+// against the test they belong to.
+#[derive(Default)]
+struct MergedTests {
+    tests: Vec<String>,
+    // The temporary directory holding the binary and the target's library path, once compiled
+    compiled: Option<Result<(TempDir, PathBuf), ()>>,
+}
+
+// Returns the path to the binary of merged doctests and the target's library path, compiling
+// the binary if this is the first merged doctest to run.
+fn merged_test_binary(merged: &Mutex<MergedTests>, cratename: &str, cfgs: Vec<String>,
+                      libs: SearchPaths, externs: Externs, opts: &TestOptions,
+                      maybe_sysroot: Option<PathBuf>, linker: Option<PathBuf>)
+                      -> Option<(PathBuf, PathBuf)> {
+    let mut merged = merged.lock().unwrap();
+    if merged.compiled.is_none() {
+        let test = make_merged_test(&merged.tests, cratename, opts);
+        // The diagnostics are discarded, the tests that caused them report them when they
+        // are compiled on their own.
+        let res = rustc_driver::in_rustc_thread(move || {
+            compile_test(test, &FileName::Custom("merged doctests".to_owned()), cfgs, libs,
+                         externs, false, false, maybe_sysroot, linker,
+                         Arc::new(Mutex::new(Vec::new())))
+        });
+        merged.compiled = Some(match res {
+            Ok((Ok(()), outdir, libdir)) => Ok((outdir, libdir)),
+            _ => Err(()),
+        });
+    }
+
+    match merged.compiled {
+        Some(Ok((ref outdir, ref libdir))) => {
+            Some((outdir.path().join("rust_out"), libdir.clone()))
+        }
+        _ => None,
+    }
+}
+
 pub struct Collector {
     pub tests: Vec<testing::TestDescAndFn>,
     // to be removed when hoedown will be definitely gone
@@ -454,6 +603,7 @@ pub struct Collector {
     // to be removed when hoedown will be removed as well
     pub render_type: RenderType,
     linker: Option<PathBuf>,
+    merged: Arc<Mutex<MergedTests>>,
 }
 
 impl Collector {
@@ -477,6 +627,7 @@ impl Collector {
             filename,
             render_type,
             linker,
+            merged: Arc::new(Mutex::new(MergedTests::default())),
         }
     }
 
@@ -524,6 +675,16 @@ impl Collector {
         let opts = self.opts.clone();
         let maybe_sysroot = self.maybe_sysroot.clone();
         let linker = self.linker.clone();
+        // Tests which are expected not to compile, or which are never run, would keep the
+        // merged binary from compiling.
+        let merged = if !compile_fail && !as_test_harness && !should_ignore &&
+                        is_mergeable(&test, &cratename) {
+            let mut merged = self.merged.lock().unwrap();
+            merged.tests.push(test.clone());
+            Some((self.merged.clone(), merged.tests.len() - 1))
+        } else {
+            None
+        };
         debug!("Creating test {}: {}", name, test);
         self.tests.push(testing::TestDescAndFn {
             desc: testing::TestDesc {
@@ -540,6 +701,18 @@ impl Collector {
                     rustc_driver::in_rustc_thread(move || {
                         io::set_panic(panic);
                         io::set_print(print);
+                        if let Some((merged, idx)) = merged {
+                            let binary = merged_test_binary(&merged, &cratename, cfgs.clone(),
+                                                            libs.clone(), externs.clone(), &opts,
+                                                            maybe_sysroot.clone(),
+                                                            linker.clone());
+                            if let Some((binary, libdir)) = binary {
+                                if !no_run {
+                                    run_test_binary(&binary, Some(idx), &libdir, should_panic);
+                                }
+                                return;
+                            }
+                        }
                         run_test(&test,
                                  &cratename,
                                  &filename,
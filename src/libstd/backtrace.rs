@@ -0,0 +1,389 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+
+//! Support for capturing a stack backtrace of an OS thread
+//!
+//! This module contains the support necessary to capture a stack backtrace of
+//! a running OS thread from the OS thread itself. The `Backtrace` type
+//! supports capturing a stack trace via the `Backtrace::capture` and
+//! `Backtrace::force_capture` functions.
+//!
+//! A backtrace is typically quite handy to attach to errors (e.g. types
+//! implementing `std::error::Error`) to get a causal chain of where an error
+//! was generated.
+//!
+//! ## Accuracy
+//!
+//! Backtraces are attempted to be as accurate as possible, but no guarantees
+//! are provided about the exact accuracy of a backtrace. Instruction
+//! pointers, symbol names, filenames, line numbers, etc, may all be incorrect
+//! when reported.
+//!
+//! For most platforms a backtrace with a filename/line number requires that
+//! programs be compiled with debug information. Without debug information
+//! filenames/line numbers will not be reported.
+//!
+//! ## Platform support
+//!
+//! Not all platforms that libstd compiles for support capturing backtraces.
+//! Some platforms simply do nothing when capturing a backtrace. To check
+//! whether the platform supports capturing backtraces you can consult the
+//! `BacktraceStatus` enum as a result of `Backtrace::status`.
+//!
+//! ## Environment Variables
+//!
+//! The `Backtrace::capture` function may not actually capture a backtrace by
+//! default. Its behavior is governed by two environment variables:
+//!
+//! * `RUST_LIB_BACKTRACE` - if this is set to `0` then `Backtrace::capture`
+//!   will never capture a backtrace. Any other value this is set to will
+//!   enable `Backtrace::capture`.
+//!
+//! * `RUST_BACKTRACE` - if `RUST_LIB_BACKTRACE` is not set, then this
+//!   variable is consulted with the same rules of `RUST_LIB_BACKTRACE`.
+//!
+//! * If neither of the above env vars are set, then `Backtrace::capture` will
+//!   be disabled.
+//!
+//! Capturing a backtrace can be a quite expensive runtime operation, so the
+//! environment variables allow either forcibly disabling this runtime
+//! performance hit or allow selectively enabling it in some programs.
+//!
+//! Note that the `Backtrace::force_capture` function can be used to ignore
+//! these environment variables. Also note that the state of environment
+//! variables is cached once the first backtrace is created, so altering
+//! `RUST_LIB_BACKTRACE` or `RUST_BACKTRACE` at runtime may not actually
+//! change how backtraces are captured.
+
+#![unstable(feature = "backtrace", issue = "0")]
+
+use env;
+use fmt;
+use sync::atomic::{AtomicUsize, Ordering};
+#[cfg(feature = "backtrace")]
+use io::{self, Write};
+#[cfg(feature = "backtrace")]
+use ptr;
+#[cfg(feature = "backtrace")]
+use sync::Mutex;
+#[cfg(feature = "backtrace")]
+use sys_common::backtrace::{self, Frame, PrintFormat};
+
+/// A captured OS thread stack backtrace.
+///
+/// This type represents a stack backtrace for an OS thread captured at a
+/// previous point in time. In some instances the `Backtrace` type may
+/// internally be empty due to configuration. For more information see
+/// `Backtrace::capture`.
+///
+/// Symbols are only resolved the first time the backtrace is displayed, so
+/// capturing one is much cheaper than printing it.
+pub struct Backtrace {
+    inner: Inner,
+}
+
+/// The current status of a backtrace, indicating whether it was captured or
+/// whether it is empty for some other reason.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BacktraceStatus {
+    /// Capturing a backtrace is not supported, likely because it's not
+    /// implemented for the current platform.
+    Unsupported,
+    /// Capturing a backtrace has been disabled through either the
+    /// `RUST_LIB_BACKTRACE` or `RUST_BACKTRACE` environment variables.
+    Disabled,
+    /// A backtrace has been captured and the `Backtrace` should print
+    /// reasonable information when rendered.
+    Captured,
+}
+
+enum Inner {
+    Unsupported,
+    Disabled,
+    #[cfg(feature = "backtrace")]
+    Captured(Mutex<Capture>),
+}
+
+#[cfg(feature = "backtrace")]
+struct Capture {
+    actual_start: usize,
+    resolved: bool,
+    frames: Vec<BacktraceFrame>,
+}
+
+#[cfg(feature = "backtrace")]
+struct BacktraceFrame {
+    exact_position: usize,
+    symbol_addr: usize,
+    name: Option<String>,
+    locations: Vec<(Vec<u8>, u32)>,
+}
+
+impl Backtrace {
+    /// Returns whether backtrace captures are enabled through environment
+    /// variables.
+    fn enabled() -> bool {
+        // Cache the result of reading the environment variables to make
+        // backtrace captures speedy, because otherwise reading environment
+        // variables every time can be somewhat slow.
+        static ENABLED: AtomicUsize = AtomicUsize::new(0);
+        match ENABLED.load(Ordering::SeqCst) {
+            0 => {}
+            1 => return false,
+            _ => return true,
+        }
+        let enabled = match env::var_os("RUST_LIB_BACKTRACE") {
+            Some(x) => &x != "0",
+            None => match env::var_os("RUST_BACKTRACE") {
+                Some(x) => &x != "0",
+                None => false,
+            },
+        };
+        ENABLED.store(enabled as usize + 1, Ordering::SeqCst);
+        enabled
+    }
+
+    /// Capture a stack backtrace of the current thread.
+    ///
+    /// This function will capture a stack backtrace of the current OS thread
+    /// of execution, returning a `Backtrace` type which can be later used to
+    /// display the stack trace or inspect it.
+    ///
+    /// This function will be a noop if the `RUST_BACKTRACE` or
+    /// `RUST_LIB_BACKTRACE` backtrace variables are both not set. If either
+    /// environment variable is set and enabled then this function will
+    /// actually capture a backtrace. Capturing a backtrace can be both memory
+    /// intensive and slow, so these environment variables allow liberally
+    /// using `Backtrace::capture` and only incurring a slowdown when the
+    /// environment variables are set.
+    ///
+    /// To forcibly capture a backtrace regardless of environment variables,
+    /// use the `Backtrace::force_capture` function.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(backtrace)]
+    /// use std::backtrace::Backtrace;
+    ///
+    /// let backtrace = Backtrace::capture();
+    /// println!("{}", backtrace);
+    /// ```
+    #[inline(never)] // want to make sure there's a frame here to remove
+    pub fn capture() -> Backtrace {
+        if !Backtrace::enabled() {
+            return Backtrace { inner: Inner::Disabled };
+        }
+        Backtrace::create(Backtrace::capture as usize)
+    }
+
+    /// Forcibly captures a full backtrace, regardless of environment
+    /// variable configuration.
+    ///
+    /// This function behaves the same as `capture` except that it ignores
+    /// the values of the `RUST_BACKTRACE` and `RUST_LIB_BACKTRACE` environment
+    /// variables, always capturing a backtrace.
+    ///
+    /// Note that capturing a backtrace can be an expensive operation on some
+    /// platforms, so this should be used with caution in performance-sensitive
+    /// parts of code.
+    #[inline(never)] // want to make sure there's a frame here to remove
+    pub fn force_capture() -> Backtrace {
+        Backtrace::create(Backtrace::force_capture as usize)
+    }
+
+    // Capture a backtrace which starts just after the frame of the function
+    // at address `ip`, i.e. the caller of `capture` or `force_capture`.
+    #[cfg(feature = "backtrace")]
+    #[inline(never)]
+    fn create(ip: usize) -> Backtrace {
+        let mut frames = [Frame {
+            exact_position: ptr::null(),
+            symbol_addr: ptr::null(),
+        }; backtrace::MAX_NB_FRAMES];
+        let nb_frames = backtrace::lock(|| {
+            backtrace::unwind_backtrace(&mut frames).map(|(nb_frames, _)| nb_frames)
+        });
+        let frames = match nb_frames {
+            Ok(0) | Err(_) => return Backtrace { inner: Inner::Unsupported },
+            Ok(nb_frames) => &frames[..nb_frames],
+        };
+
+        // If no frame matches `ip`, for instance because the platform doesn't
+        // report the address of the enclosing function, keep all of them.
+        let actual_start = frames.iter()
+            .position(|frame| frame.symbol_addr as usize == ip)
+            .map_or(0, |i| i + 1);
+        let frames = frames.iter().map(|frame| {
+            BacktraceFrame {
+                exact_position: frame.exact_position as usize,
+                symbol_addr: frame.symbol_addr as usize,
+                name: None,
+                locations: Vec::new(),
+            }
+        }).collect();
+
+        let capture = Capture {
+            actual_start,
+            resolved: false,
+            frames,
+        };
+        Backtrace { inner: Inner::Captured(Mutex::new(capture)) }
+    }
+
+    #[cfg(not(feature = "backtrace"))]
+    fn create(_ip: usize) -> Backtrace {
+        Backtrace { inner: Inner::Unsupported }
+    }
+
+    /// Returns the status of this backtrace, indicating whether this
+    /// backtrace request was unsupported, disabled, or a stack trace was
+    /// actually captured.
+    pub fn status(&self) -> BacktraceStatus {
+        match self.inner {
+            Inner::Unsupported => BacktraceStatus::Unsupported,
+            Inner::Disabled => BacktraceStatus::Disabled,
+            #[cfg(feature = "backtrace")]
+            Inner::Captured(_) => BacktraceStatus::Captured,
+        }
+    }
+}
+
+/// Prints the backtrace, resolving symbols first if that hasn't happened yet.
+///
+/// The default format omits the frames of the runtime that called `main` or
+/// spawned the thread, and strips symbol hashes. The alternate format (`{:#}`)
+/// prints every frame along with its address.
+impl fmt::Display for Backtrace {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self.inner {
+            Inner::Unsupported => fmt.write_str("unsupported backtrace"),
+            Inner::Disabled => fmt.write_str("disabled backtrace"),
+            #[cfg(feature = "backtrace")]
+            Inner::Captured(ref capture) => {
+                let mut capture = capture.lock().unwrap();
+                capture.resolve();
+
+                let format = if fmt.alternate() {
+                    PrintFormat::Full
+                } else {
+                    PrintFormat::Short
+                };
+                let mut buf = Vec::new();
+                capture.print(&mut buf, format).map_err(|_| fmt::Error)?;
+                fmt.write_str(&String::from_utf8_lossy(&buf))
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Backtrace {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, fmt)
+    }
+}
+
+#[cfg(feature = "backtrace")]
+impl Capture {
+    fn resolve(&mut self) {
+        // If we're already resolved, nothing to do!
+        if self.resolved {
+            return;
+        }
+        self.resolved = true;
+
+        backtrace::lock(|| {
+            // Symbol lookups need the context of the platform's unwinder.
+            // Unwinding into an empty buffer is the portable way to get one;
+            // the frames it walks are thrown away.
+            let context = match backtrace::unwind_backtrace(&mut []) {
+                Ok((_, context)) => context,
+                Err(_) => return,
+            };
+            for frame in self.frames.iter_mut() {
+                let raw = frame.raw();
+                let name = &mut frame.name;
+                let _ = backtrace::resolve_symname(raw, |symname| {
+                    *name = symname.map(|s| s.to_string());
+                    Ok(())
+                }, &context);
+                let locations = &mut frame.locations;
+                let _ = backtrace::foreach_symbol_fileline(raw, |file, line| {
+                    locations.push((file.to_vec(), line));
+                    Ok(())
+                }, &context);
+            }
+        })
+    }
+
+    fn print(&self, w: &mut Write, format: PrintFormat) -> io::Result<()> {
+        let mut frames = &self.frames[self.actual_start..];
+        if format == PrintFormat::Short {
+            // Cut off the runtime frames below `main` or a thread's entry
+            // point, unless that would leave nothing to print.
+            let end = frames.iter().position(|frame| {
+                frame.name.as_ref().map_or(false, |name| {
+                    name.contains("__rust_begin_short_backtrace")
+                })
+            });
+            match end {
+                Some(0) | None => {}
+                Some(end) => frames = &frames[..end],
+            }
+        }
+
+        writeln!(w, "stack backtrace:")?;
+        for (index, frame) in frames.iter().enumerate() {
+            let name = frame.name.as_ref().map(|s| &s[..]);
+            backtrace::output(w, index, frame.raw(), name, format)?;
+            for &(ref file, line) in &frame.locations {
+                backtrace::output_fileline(w, file, line, format)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "backtrace")]
+impl BacktraceFrame {
+    fn raw(&self) -> Frame {
+        Frame {
+            exact_position: self.exact_position as *const u8,
+            symbol_addr: self.symbol_addr as *const u8,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Backtrace, BacktraceStatus};
+
+    #[test]
+    fn force_capture() {
+        let backtrace = Backtrace::force_capture();
+        assert!(backtrace.status() != BacktraceStatus::Disabled);
+
+        // Symbols are resolved on first use; make sure both formats work and
+        // that resolving twice is fine.
+        let short = backtrace.to_string();
+        let full = format!("{:#}", backtrace);
+        if backtrace.status() == BacktraceStatus::Captured {
+            assert!(short.starts_with("stack backtrace:"));
+            assert!(full.starts_with("stack backtrace:"));
+        }
+    }
+
+    #[test]
+    fn is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Backtrace>();
+    }
+}
@@ -0,0 +1,100 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::sync::{Arc, Condvar, Mutex, RwLock};
+use std::thread;
+use test::Bencher;
+
+const THREADS: usize = 4;
+const ITERS: usize = 1000;
+
+#[bench]
+fn mutex_contended(b: &mut Bencher) {
+    b.iter(|| {
+        let count = Arc::new(Mutex::new(0));
+        let threads = (0..THREADS).map(|_| {
+            let count = count.clone();
+            thread::spawn(move || {
+                for _ in 0..ITERS {
+                    *count.lock().unwrap() += 1;
+                }
+            })
+        }).collect::<Vec<_>>();
+        for t in threads {
+            t.join().unwrap();
+        }
+        assert_eq!(*count.lock().unwrap(), THREADS * ITERS);
+    })
+}
+
+#[bench]
+fn rwlock_contended(b: &mut Bencher) {
+    b.iter(|| {
+        let count = Arc::new(RwLock::new(0));
+        let threads = (0..THREADS).map(|i| {
+            let count = count.clone();
+            thread::spawn(move || {
+                for _ in 0..ITERS {
+                    // One writer, the rest are readers.
+                    if i == 0 {
+                        *count.write().unwrap() += 1;
+                    } else {
+                        let _ = *count.read().unwrap();
+                    }
+                }
+            })
+        }).collect::<Vec<_>>();
+        for t in threads {
+            t.join().unwrap();
+        }
+        assert_eq!(*count.read().unwrap(), ITERS);
+    })
+}
+
+#[bench]
+fn condvar_ping_pong(b: &mut Bencher) {
+    b.iter(|| {
+        let shared = Arc::new((Mutex::new(false), Condvar::new()));
+        let other = shared.clone();
+        let t = thread::spawn(move || {
+            let (ref turn, ref cond) = *other;
+            for _ in 0..ITERS {
+                let mut turn = turn.lock().unwrap();
+                while !*turn {
+                    turn = cond.wait(turn).unwrap();
+                }
+                *turn = false;
+                cond.notify_one();
+            }
+        });
+        let (ref turn, ref cond) = *shared;
+        for _ in 0..ITERS {
+            let mut turn = turn.lock().unwrap();
+            *turn = true;
+            cond.notify_one();
+            while *turn {
+                turn = cond.wait(turn).unwrap();
+            }
+        }
+        t.join().unwrap();
+    })
+}
+
+// The cost of spawning and joining the threads, which is included in each of
+// the contended benchmarks above.
+#[bench]
+fn baseline_spawn(b: &mut Bencher) {
+    b.iter(|| {
+        let threads = (0..THREADS).map(|_| thread::spawn(|| ())).collect::<Vec<_>>();
+        for t in threads {
+            t.join().unwrap();
+        }
+    })
+}
@@ -126,3 +126,67 @@ fn get_remove_insert(b: &mut Bencher) {
         k += 1;
     })
 }
+
+#[bench]
+fn find_existing_string(b: &mut Bencher) {
+    use super::map::HashMap;
+
+    let mut m = HashMap::new();
+
+    for i in 1..1001 {
+        m.insert(i.to_string(), i);
+    }
+
+    b.iter(|| {
+        for i in 1..1001 {
+            m.contains_key(&i.to_string());
+        }
+    });
+}
+
+#[bench]
+fn find_nonexisting_string(b: &mut Bencher) {
+    use super::map::HashMap;
+
+    let mut m = HashMap::new();
+
+    for i in 1..1001 {
+        m.insert(i.to_string(), i);
+    }
+
+    b.iter(|| {
+        for i in 1001..2001 {
+            m.contains_key(&i.to_string());
+        }
+    });
+}
+
+#[bench]
+fn iterate(b: &mut Bencher) {
+    use super::map::HashMap;
+
+    let mut m = HashMap::new();
+
+    for i in 1..1001 {
+        m.insert(i, i);
+    }
+
+    b.iter(|| {
+        m.values().sum::<i32>()
+    });
+}
+
+#[bench]
+fn clone_small(b: &mut Bencher) {
+    use super::map::HashMap;
+
+    let mut m = HashMap::new();
+
+    for i in 0..10 {
+        m.insert(i, i);
+    }
+
+    b.iter(|| {
+        m.clone()
+    });
+}
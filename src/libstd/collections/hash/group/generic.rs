@@ -0,0 +1,103 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+
+//! Portable implementation of `Group`, scanning 8 control bytes at once with
+//! plain integer arithmetic.
+
+use ptr;
+
+use super::BitMask;
+
+pub type BitMaskWord = u64;
+pub const BITMASK_STRIDE: usize = 8;
+pub const BITMASK_MASK: BitMaskWord = 0x8080_8080_8080_8080;
+
+/// Number of control bytes in a group.
+pub const WIDTH: usize = 8;
+
+/// Returns a word with every byte set to `byte`.
+#[inline]
+fn repeat(byte: u8) -> u64 {
+    (byte as u64).wrapping_mul(0x0101_0101_0101_0101)
+}
+
+/// A group of control bytes packed into a `u64`, in memory order.
+#[derive(Copy, Clone)]
+pub struct Group(u64);
+
+impl Group {
+    /// Loads a group of control bytes starting at the given address, which
+    /// need not be aligned.
+    #[inline]
+    pub unsafe fn load(ptr: *const u8) -> Group {
+        Group(ptr::read_unaligned(ptr as *const u64))
+    }
+
+    /// Loads a group of control bytes starting at the given address, which
+    /// must be aligned to `WIDTH`.
+    #[inline]
+    pub unsafe fn load_aligned(ptr: *const u8) -> Group {
+        debug_assert_eq!(ptr as usize % WIDTH, 0);
+        Group(ptr::read(ptr as *const u64))
+    }
+
+    /// Stores the group of control bytes at the given address, which must be
+    /// aligned to `WIDTH`.
+    #[inline]
+    pub unsafe fn store_aligned(self, ptr: *mut u8) {
+        debug_assert_eq!(ptr as usize % WIDTH, 0);
+        ptr::write(ptr as *mut u64, self.0);
+    }
+
+    /// Returns a `BitMask` of the bytes in the group which are equal to
+    /// `byte`.
+    ///
+    /// This uses the well known "determine if a word has a zero byte" trick,
+    /// which can report a false positive for a byte following a true match.
+    /// Callers always compare the corresponding elements anyway, so this is
+    /// harmless.
+    #[inline]
+    pub fn match_byte(self, byte: u8) -> BitMask {
+        let cmp = self.0 ^ repeat(byte);
+        BitMask((cmp.wrapping_sub(repeat(0x01)) & !cmp & repeat(0x80)).to_le())
+    }
+
+    /// Returns a `BitMask` of the bytes in the group which are `EMPTY`.
+    #[inline]
+    pub fn match_empty(self) -> BitMask {
+        // `EMPTY` is the only value with both of its top two bits set.
+        BitMask((self.0 & (self.0 << 1) & repeat(0x80)).to_le())
+    }
+
+    /// Returns a `BitMask` of the bytes in the group which are `EMPTY` or
+    /// `DELETED`.
+    #[inline]
+    pub fn match_empty_or_deleted(self) -> BitMask {
+        // Both special values, and only they, have the top bit set.
+        BitMask((self.0 & repeat(0x80)).to_le())
+    }
+
+    /// Returns a `BitMask` of the bytes in the group which are full.
+    #[inline]
+    pub fn match_full(self) -> BitMask {
+        self.match_empty_or_deleted().invert()
+    }
+
+    /// Turns `EMPTY` and `DELETED` bytes into `EMPTY` and full bytes into
+    /// `DELETED`.
+    #[inline]
+    pub fn convert_special_to_empty_and_full_to_deleted(self) -> Group {
+        // Full bytes end up as 0x7f + 0x01 and special ones as 0xff + 0x00,
+        // i.e. `DELETED` and `EMPTY` respectively.
+        let full = !self.0 & repeat(0x80);
+        Group(!full + (full >> 7))
+    }
+}
@@ -0,0 +1,127 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+
+//! Groups of control bytes.
+//!
+//! Every bucket of a `RawTable` has a control byte which says whether the
+//! bucket is empty, deleted, or full, and in the latter case holds the top 7
+//! bits of the hash of the element stored in it. Lookups work on a whole
+//! `Group` of consecutive control bytes at once: SSE2 instructions are used
+//! where they are available, and a portable implementation packing the
+//! bytes into a `u64` is used everywhere else.
+
+#[cfg(all(any(target_arch = "x86", target_arch = "x86_64"),
+          target_feature = "sse2"))]
+#[path = "sse2.rs"]
+mod imp;
+
+#[cfg(not(all(any(target_arch = "x86", target_arch = "x86_64"),
+              target_feature = "sse2")))]
+#[path = "generic.rs"]
+mod imp;
+
+pub use self::imp::{Group, WIDTH};
+use self::imp::{BitMaskWord, BITMASK_MASK, BITMASK_STRIDE};
+
+/// Control byte value for an empty bucket.
+pub const EMPTY: u8 = 0b1111_1111;
+
+/// Control byte value for a deleted bucket.
+pub const DELETED: u8 = 0b1000_0000;
+
+/// Checks whether a control byte represents a full bucket (top bit is clear).
+#[inline]
+pub fn is_full(ctrl: u8) -> bool {
+    ctrl & 0x80 == 0
+}
+
+/// Checks whether a special control byte is `EMPTY` rather than `DELETED`.
+#[inline]
+pub fn special_is_empty(ctrl: u8) -> bool {
+    debug_assert!(!is_full(ctrl));
+    ctrl & 0x01 != 0
+}
+
+#[repr(align(16))]
+struct AlignedBytes([u8; WIDTH]);
+
+static EMPTY_GROUP: AlignedBytes = AlignedBytes([EMPTY; WIDTH]);
+
+/// Returns a pointer to a full group of `EMPTY` control bytes.
+///
+/// This is what tables that haven't allocated yet point at, so that probing
+/// them finds nothing without having to special case them.
+#[inline]
+pub fn static_empty() -> *const u8 {
+    &EMPTY_GROUP.0 as *const [u8; WIDTH] as *const u8
+}
+
+/// A bit mask with one bit set for each control byte of a `Group` that
+/// matched some condition.
+///
+/// The mask is ordered by address: the lowest bit refers to the first control
+/// byte of the group. Each byte may take more than one bit of the underlying
+/// word, see `BITMASK_STRIDE`.
+#[derive(Copy, Clone)]
+pub struct BitMask(BitMaskWord);
+
+impl BitMask {
+    /// Returns a new `BitMask` with all bits inverted.
+    #[inline]
+    pub fn invert(self) -> BitMask {
+        BitMask(self.0 ^ BITMASK_MASK)
+    }
+
+    /// Returns a new `BitMask` with the lowest set bit cleared.
+    #[inline]
+    pub fn remove_lowest_bit(self) -> BitMask {
+        BitMask(self.0 & self.0.wrapping_sub(1))
+    }
+
+    /// Returns whether any byte matched.
+    #[inline]
+    pub fn any_bit_set(self) -> bool {
+        self.0 != 0
+    }
+
+    /// Returns the index of the first byte that matched, if any.
+    #[inline]
+    pub fn lowest_set_bit(self) -> Option<usize> {
+        if self.0 == 0 {
+            None
+        } else {
+            Some(self.trailing_zeros())
+        }
+    }
+
+    /// Returns the number of bytes before the first match.
+    #[inline]
+    pub fn trailing_zeros(self) -> usize {
+        self.0.trailing_zeros() as usize / BITMASK_STRIDE
+    }
+
+    /// Returns the number of bytes after the last match.
+    #[inline]
+    pub fn leading_zeros(self) -> usize {
+        self.0.leading_zeros() as usize / BITMASK_STRIDE
+    }
+}
+
+impl Iterator for BitMask {
+    type Item = usize;
+
+    #[inline]
+    fn next(&mut self) -> Option<usize> {
+        let bit = self.lowest_set_bit()?;
+        *self = self.remove_lowest_bit();
+        Some(bit)
+    }
+}
@@ -0,0 +1,114 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+
+//! SSE2 implementation of `Group`, scanning 16 control bytes at once.
+
+use ptr;
+
+use super::{BitMask, EMPTY};
+
+pub type BitMaskWord = u16;
+pub const BITMASK_STRIDE: usize = 1;
+pub const BITMASK_MASK: BitMaskWord = 0xffff;
+
+/// Number of control bytes in a group.
+pub const WIDTH: usize = 16;
+
+#[repr(simd)]
+#[derive(Copy, Clone)]
+#[allow(non_camel_case_types)]
+struct i8x16(i8, i8, i8, i8, i8, i8, i8, i8, i8, i8, i8, i8, i8, i8, i8, i8);
+
+impl i8x16 {
+    #[inline]
+    fn splat(x: i8) -> i8x16 {
+        i8x16(x, x, x, x, x, x, x, x, x, x, x, x, x, x, x, x)
+    }
+}
+
+extern "platform-intrinsic" {
+    fn simd_eq<T, U>(x: T, y: T) -> U;
+    fn simd_gt<T, U>(x: T, y: T) -> U;
+    fn simd_or<T>(x: T, y: T) -> T;
+    fn x86_mm_movemask_epi8(x: i8x16) -> i32;
+}
+
+/// A group of control bytes held in an SSE2 register.
+#[derive(Copy, Clone)]
+pub struct Group(i8x16);
+
+impl Group {
+    /// Loads a group of control bytes starting at the given address, which
+    /// need not be aligned.
+    #[inline]
+    pub unsafe fn load(ptr: *const u8) -> Group {
+        Group(ptr::read_unaligned(ptr as *const i8x16))
+    }
+
+    /// Loads a group of control bytes starting at the given address, which
+    /// must be aligned to `WIDTH`.
+    #[inline]
+    pub unsafe fn load_aligned(ptr: *const u8) -> Group {
+        debug_assert_eq!(ptr as usize % WIDTH, 0);
+        Group(ptr::read(ptr as *const i8x16))
+    }
+
+    /// Stores the group of control bytes at the given address, which must be
+    /// aligned to `WIDTH`.
+    #[inline]
+    pub unsafe fn store_aligned(self, ptr: *mut u8) {
+        debug_assert_eq!(ptr as usize % WIDTH, 0);
+        ptr::write(ptr as *mut i8x16, self.0);
+    }
+
+    /// Returns a `BitMask` of the bytes in the group which are equal to
+    /// `byte`.
+    #[inline]
+    pub fn match_byte(self, byte: u8) -> BitMask {
+        unsafe {
+            let cmp: i8x16 = simd_eq(self.0, i8x16::splat(byte as i8));
+            BitMask(x86_mm_movemask_epi8(cmp) as u16)
+        }
+    }
+
+    /// Returns a `BitMask` of the bytes in the group which are `EMPTY`.
+    #[inline]
+    pub fn match_empty(self) -> BitMask {
+        self.match_byte(EMPTY)
+    }
+
+    /// Returns a `BitMask` of the bytes in the group which are `EMPTY` or
+    /// `DELETED`.
+    #[inline]
+    pub fn match_empty_or_deleted(self) -> BitMask {
+        // Both special values, and only they, have the top bit set.
+        unsafe { BitMask(x86_mm_movemask_epi8(self.0) as u16) }
+    }
+
+    /// Returns a `BitMask` of the bytes in the group which are full.
+    #[inline]
+    pub fn match_full(self) -> BitMask {
+        self.match_empty_or_deleted().invert()
+    }
+
+    /// Turns `EMPTY` and `DELETED` bytes into `EMPTY` and full bytes into
+    /// `DELETED`.
+    #[inline]
+    pub fn convert_special_to_empty_and_full_to_deleted(self) -> Group {
+        // Special bytes are negative as `i8`, so comparing against zero
+        // yields `0xff` (`EMPTY`) for them and `0x00` for full ones, which
+        // then only need the top bit set to become `DELETED`.
+        unsafe {
+            let special: i8x16 = simd_gt(i8x16::splat(0), self.0);
+            Group(simd_or(special, i8x16::splat(0x80u8 as i8)))
+        }
+    }
+}
@@ -8,965 +8,1010 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use alloc::heap::{Heap, Alloc, Layout};
+use alloc::heap::{Heap, Alloc, Layout, CollectionAllocErr};
 
 use cmp;
 use hash::{BuildHasher, Hash, Hasher};
+use isize;
 use marker;
-use mem::{align_of, size_of, needs_drop};
-use mem;
+use mem::{self, needs_drop, ManuallyDrop};
 use ops::{Deref, DerefMut};
 use ptr::{self, Unique, Shared};
 
-use self::BucketState::*;
+use super::group::{self, BitMask, Group, DELETED, EMPTY};
 
-/// Integer type used for stored hash values.
-///
-/// No more than bit_width(usize) bits are needed to select a bucket.
+// The table is an open addressing design along the lines of Google's
+// SwissTable, storing the (K, V) pairs in one array and a control byte per
+// bucket in a separate, parallel array.
+//
+// A control byte is either `EMPTY`, `DELETED` (a tombstone), or, for a full
+// bucket, the top 7 bits of the element's hash (`h2`). The remaining bits
+// (`h1`) pick the position where probing starts. Probing doesn't look at one
+// bucket at a time but at a whole `Group` of control bytes, which are compared
+// against `h2` in parallel; only the buckets whose control byte matched have
+// their keys compared. A lookup ends at the first group that contains an
+// `EMPTY` byte, since an insertion would have stopped there too.
+//
+// Groups are loaded from arbitrary (unaligned) positions. To make probing near
+// the end of the table work without wrapping around, the control byte array is
+// `Group::WIDTH` bytes longer than the number of buckets, and those trailing
+// bytes mirror the first `Group::WIDTH` control bytes. Tables with fewer
+// buckets than `Group::WIDTH` keep the bytes in between `EMPTY`.
+//
+// Successive groups are visited following triangular numbers, which visits
+// every group exactly once since the number of buckets is a power of two.
+//
+// Removing an element may not simply mark its bucket as `EMPTY`, as that could
+// cut short the probe sequence of some other element. It only does so when no
+// probe window covering the bucket has been completely full, and leaves a
+// `DELETED` tombstone otherwise. Tombstones are reused by insertions and are
+// cleared out by rehashing the table in place when there are too many of them.
+//
+// The maximum load factor is 7/8. Tables that have allocated always have at
+// least 4 buckets, and those that haven't point at a static group of `EMPTY`
+// control bytes so that lookups don't need to special case them.
+//
+// Relevant papers/articles:
+//
+// 1. Matt Kulukundis. "Designing a Fast, Efficient, Cache-friendly Hash Table,
+//    Step by Step", CppCon 2017.
+// 2. Abseil. ["Swiss Tables Design Notes"](https://abseil.io/about/design/swisstables)
+
+/// The raw hashtable, storing `(K, V)` pairs in an array of buckets next to
+/// an array of control bytes.
 ///
-/// The most significant bit is ours to use for tagging `SafeHash`.
+/// `RawTable` doesn't know how to hash or compare keys. Lookups take the hash
+/// and an equality predicate, and the operations which may need to move
+/// elements around take a function computing the hash of a key.
 ///
-/// (Even if we could have usize::MAX bytes allocated for buckets,
-/// each bucket stores at least a `HashUint`, so there can be no more than
-/// usize::MAX / size_of(usize) buckets.)
-type HashUint = usize;
-
-const EMPTY_BUCKET: HashUint = 0;
-const EMPTY: usize = 1;
+/// The allocator is cloned whenever the table needs a second allocation to
+/// exist alongside the current one, such as while resizing.
+pub struct RawTable<K, V, A: Alloc + Clone = Heap> {
+    // Mask to get an index from a hash value. The value is one less than the
+    // number of buckets in the table.
+    bucket_mask: usize,
 
-/// Special `Unique<HashUint>` that uses the lower bit of the pointer
-/// to expose a boolean tag.
-/// Note: when the pointer is initialized to EMPTY `.ptr()` will return
-/// null and the tag functions shouldn't be used.
-struct TaggedHashUintPtr(Unique<HashUint>);
+    // Pointer to the array of control bytes.
+    ctrl: Unique<u8>,
 
-impl TaggedHashUintPtr {
-    #[inline]
-    unsafe fn new(ptr: *mut HashUint) -> Self {
-        debug_assert!(ptr as usize & 1 == 0 || ptr as usize == EMPTY as usize);
-        TaggedHashUintPtr(Unique::new_unchecked(ptr))
-    }
+    // Pointer to the array of buckets.
+    data: Unique<(K, V)>,
 
-    #[inline]
-    fn set_tag(&mut self, value: bool) {
-        let mut usize_ptr = self.0.as_ptr() as usize;
-        unsafe {
-            if value {
-                usize_ptr |= 1;
-            } else {
-                usize_ptr &= !1;
-            }
-            self.0 = Unique::new_unchecked(usize_ptr as *mut HashUint)
-        }
-    }
+    // Number of elements that can be inserted before we need to grow the
+    // table.
+    growth_left: usize,
 
-    #[inline]
-    fn tag(&self) -> bool {
-        (self.0.as_ptr() as usize) & 1 == 1
-    }
+    // Number of elements in the table.
+    items: usize,
 
-    #[inline]
-    fn ptr(&self) -> *mut HashUint {
-        (self.0.as_ptr() as usize & !1) as *mut HashUint
-    }
+    alloc: A,
 }
 
-/// The raw hashtable, providing safe-ish access to the unzipped and highly
-/// optimized arrays of hashes, and key-value pairs.
-///
-/// This design is a lot faster than the naive
-/// `Vec<Option<(u64, K, V)>>`, because we don't pay for the overhead of an
-/// option on every element, and we get a generally more cache-aware design.
-///
-/// Essential invariants of this structure:
-///
-///   - if t.hashes[i] == EMPTY_BUCKET, then `Bucket::at_index(&t, i).raw`
-///     points to 'undefined' contents. Don't read from it. This invariant is
-///     enforced outside this module with the `EmptyBucket`, `FullBucket`,
-///     and `SafeHash` types.
-///
-///   - An `EmptyBucket` is only constructed at an index with
-///     a hash of EMPTY_BUCKET.
-///
-///   - A `FullBucket` is only constructed at an index with a
-///     non-EMPTY_BUCKET hash.
-///
-///   - A `SafeHash` is only constructed for non-`EMPTY_BUCKET` hash. We get
-///     around hashes of zero by changing them to 0x8000_0000_0000_0000,
-///     which will likely map to the same bucket, while not being confused
-///     with "empty".
-///
-///   - Both "arrays represented by pointers" are the same length:
-///     `capacity`. This is set at creation and never changes. The arrays
-///     are unzipped and are more cache aware (scanning through 8 hashes
-///     brings in at most 2 cache lines, since they're all right beside each
-///     other). This layout may waste space in padding such as in a map from
-///     u64 to u8, but is a more cache conscious layout as the key-value pairs
-///     are only very shortly probed and the desired value will be in the same
-///     or next cache line.
-///
-/// You can kind of think of this module/data structure as a safe wrapper
-/// around just the "table" part of the hashtable. It enforces some
-/// invariants at the type level and employs some performance trickery,
-/// but in general is just a tricked out `Vec<Option<(u64, K, V)>>`.
+/// A reference to a bucket of a `RawTable`.
 ///
-/// The hashtable also exposes a special boolean tag. The tag defaults to false
-/// when the RawTable is created and is accessible with the `tag` and `set_tag`
-/// functions.
-pub struct RawTable<K, V> {
-    capacity_mask: usize,
-    size: usize,
-    hashes: TaggedHashUintPtr,
-
-    // Because K/V do not appear directly in any of the types in the struct,
-    // inform rustc that in fact instances of K and V are reachable from here.
-    marker: marker::PhantomData<(K, V)>,
-}
-
-// An unsafe view of a RawTable bucket
-// Valid indexes are within [0..table_capacity)
-pub struct RawBucket<K, V> {
-    hash_start: *mut HashUint,
+/// This is just a raw pointer: it's up to the user not to use it once the
+/// bucket has been emptied or the table has been resized or dropped.
+pub struct Bucket<K, V> {
     // We use *const to ensure covariance with respect to K and V
-    pair_start: *const (K, V),
-    idx: usize,
-    _marker: marker::PhantomData<(K, V)>,
+    ptr: *const (K, V),
 }
 
-impl<K, V> Copy for RawBucket<K, V> {}
-impl<K, V> Clone for RawBucket<K, V> {
-    fn clone(&self) -> RawBucket<K, V> {
+impl<K, V> Copy for Bucket<K, V> {}
+impl<K, V> Clone for Bucket<K, V> {
+    fn clone(&self) -> Bucket<K, V> {
         *self
     }
 }
 
-pub struct Bucket<K, V, M> {
-    raw: RawBucket<K, V>,
-    table: M,
-}
+impl<K, V> Bucket<K, V> {
+    /// Creates the bucket at `index` of the array starting at `base`.
+    #[inline]
+    unsafe fn from_base_index(base: *const (K, V), index: usize) -> Bucket<K, V> {
+        let ptr = if mem::size_of::<(K, V)>() == 0 {
+            // Zero-sized pairs all live at the same address, so we encode the
+            // index in the pointer instead to be able to get it back.
+            (mem::align_of::<(K, V)>() * (index + 1)) as *const (K, V)
+        } else {
+            base.offset(index as isize)
+        };
+        Bucket { ptr }
+    }
 
-impl<K, V, M: Copy> Copy for Bucket<K, V, M> {}
-impl<K, V, M: Copy> Clone for Bucket<K, V, M> {
-    fn clone(&self) -> Bucket<K, V, M> {
-        *self
+    /// Returns the index of the bucket in the array starting at `base`.
+    #[inline]
+    unsafe fn to_base_index(&self, base: *const (K, V)) -> usize {
+        if mem::size_of::<(K, V)>() == 0 {
+            self.ptr as usize / mem::align_of::<(K, V)>() - 1
+        } else {
+            (self.ptr as usize - base as usize) / mem::size_of::<(K, V)>()
+        }
     }
-}
 
-pub struct EmptyBucket<K, V, M> {
-    raw: RawBucket<K, V>,
-    table: M,
-}
+    /// Returns the bucket `offset` buckets past this one.
+    #[inline]
+    unsafe fn add(&self, offset: usize) -> Bucket<K, V> {
+        let ptr = if mem::size_of::<(K, V)>() == 0 {
+            (self.ptr as usize + mem::align_of::<(K, V)>() * offset) as *const (K, V)
+        } else {
+            self.ptr.offset(offset as isize)
+        };
+        Bucket { ptr }
+    }
 
-pub struct FullBucket<K, V, M> {
-    raw: RawBucket<K, V>,
-    table: M,
-}
+    #[inline]
+    fn pair(&self) -> *mut (K, V) {
+        self.ptr as *mut (K, V)
+    }
 
-pub type FullBucketMut<'table, K, V> = FullBucket<K, V, &'table mut RawTable<K, V>>;
+    /// Drops the pair in the bucket.
+    #[inline]
+    pub unsafe fn drop(&self) {
+        ptr::drop_in_place(self.pair());
+    }
 
-pub enum BucketState<K, V, M> {
-    Empty(EmptyBucket<K, V, M>),
-    Full(FullBucket<K, V, M>),
-}
+    /// Moves the pair out of the bucket.
+    #[inline]
+    pub unsafe fn read(&self) -> (K, V) {
+        ptr::read(self.pair())
+    }
 
-// A GapThenFull encapsulates the state of two consecutive buckets at once.
-// The first bucket, called the gap, is known to be empty.
-// The second bucket is full.
-pub struct GapThenFull<K, V, M> {
-    gap: EmptyBucket<K, V, ()>,
-    full: FullBucket<K, V, M>,
-}
+    /// Writes a pair into the bucket, without dropping what was there.
+    #[inline]
+    unsafe fn write(&self, key: K, value: V) {
+        ptr::write(self.pair(), (key, value));
+    }
 
-/// A hash that is not zero, since we use a hash of zero to represent empty
-/// buckets.
-#[derive(PartialEq, Copy, Clone)]
-pub struct SafeHash {
-    hash: HashUint,
-}
+    /// Returns references to the key and value in the bucket, for an arbitrary
+    /// lifetime chosen by the caller.
+    #[inline]
+    pub unsafe fn into_refs<'a>(self) -> (&'a K, &'a V) {
+        let pair = &*self.pair();
+        (&pair.0, &pair.1)
+    }
 
-impl SafeHash {
-    /// Peek at the hash value, which is guaranteed to be non-zero.
-    #[inline(always)]
-    pub fn inspect(&self) -> HashUint {
-        self.hash
+    /// Returns mutable references to the key and value in the bucket, for an
+    /// arbitrary lifetime chosen by the caller.
+    #[inline]
+    pub unsafe fn into_mut_refs<'a>(self) -> (&'a mut K, &'a mut V) {
+        let pair = &mut *self.pair();
+        (&mut pair.0, &mut pair.1)
     }
 
-    #[inline(always)]
-    pub fn new(hash: u64) -> Self {
-        // We need to avoid 0 in order to prevent collisions with
-        // EMPTY_HASH. We can maintain our precious uniform distribution
-        // of initial indexes by unconditionally setting the MSB,
-        // effectively reducing the hashes by one bit.
-        //
-        // Truncate hash to fit in `HashUint`.
-        let hash_bits = size_of::<HashUint>() * 8;
-        SafeHash { hash: (1 << (hash_bits - 1)) | (hash as HashUint) }
+    #[inline]
+    unsafe fn copy_from_nonoverlapping(&self, other: &Bucket<K, V>) {
+        ptr::copy_nonoverlapping(other.pair(), self.pair(), 1);
     }
 }
 
-/// We need to remove hashes of 0. That's reserved for empty buckets.
-/// This function wraps up `hash_keyed` to be the only way outside this
-/// module to generate a SafeHash.
-pub fn make_hash<T: ?Sized, S>(hash_state: &S, t: &T) -> SafeHash
+/// Returns the full hash of a key.
+pub fn make_hash<T: ?Sized, S>(hash_state: &S, t: &T) -> u64
     where T: Hash,
           S: BuildHasher
 {
     let mut state = hash_state.build_hasher();
     t.hash(&mut state);
-    SafeHash::new(state.finish())
+    state.finish()
 }
 
-// `replace` casts a `*HashUint` to a `*SafeHash`. Since we statically
-// ensure that a `FullBucket` points to an index with a non-zero hash,
-// and a `SafeHash` is just a `HashUint` with a different name, this is
-// safe.
-//
-// This test ensures that a `SafeHash` really IS the same size as a
-// `HashUint`. If you need to change the size of `SafeHash` (and
-// consequently made this test fail), `replace` needs to be
-// modified to no longer assume this.
-#[test]
-fn can_alias_safehash_as_hash() {
-    assert_eq!(size_of::<SafeHash>(), size_of::<HashUint>())
+/// Primary hash function, used to select the initial bucket to probe from.
+#[inline]
+fn h1(hash: u64) -> usize {
+    hash as usize
 }
 
-// RawBucket methods are unsafe as it's possible to
-// make a RawBucket point to invalid memory using safe code.
-impl<K, V> RawBucket<K, V> {
-    unsafe fn hash(&self) -> *mut HashUint {
-        self.hash_start.offset(self.idx as isize)
-    }
-    unsafe fn pair(&self) -> *mut (K, V) {
-        self.pair_start.offset(self.idx as isize) as *mut (K, V)
-    }
-    unsafe fn hash_pair(&self) -> (*mut HashUint, *mut (K, V)) {
-        (self.hash(), self.pair())
-    }
+/// Secondary hash function, saved in the low 7 bits of the control byte.
+#[inline]
+fn h2(hash: u64) -> u8 {
+    // Grab the top 7 bits of the hash. While the hash is normally a full
+    // 64-bit value, some hash functions only produce a `usize`, which means
+    // that the top 32 bits are 0 on 32-bit platforms.
+    let hash_len = cmp::min(mem::size_of::<usize>(), mem::size_of::<u64>());
+    let top7 = hash >> (hash_len * 8 - 7);
+    (top7 & 0x7f) as u8
 }
 
-// Buckets hold references to the table.
-impl<K, V, M> FullBucket<K, V, M> {
-    /// Borrow a reference to the table.
-    pub fn table(&self) -> &M {
-        &self.table
-    }
-    /// Borrow a mutable reference to the table.
-    pub fn table_mut(&mut self) -> &mut M {
-        &mut self.table
-    }
-    /// Move out the reference to the table.
-    pub fn into_table(self) -> M {
-        self.table
-    }
-    /// Get the raw index.
-    pub fn index(&self) -> usize {
-        self.raw.idx
-    }
-    /// Get the raw bucket.
-    pub fn raw(&self) -> RawBucket<K, V> {
-        self.raw
-    }
+/// Probe sequence based on triangular numbers, which is guaranteed (since the
+/// number of buckets is a power of two) to visit every group of buckets
+/// exactly once.
+struct ProbeSeq {
+    bucket_mask: usize,
+    pos: usize,
+    stride: usize,
 }
 
-impl<K, V, M> EmptyBucket<K, V, M> {
-    /// Borrow a reference to the table.
-    pub fn table(&self) -> &M {
-        &self.table
+impl Iterator for ProbeSeq {
+    type Item = usize;
+
+    #[inline]
+    fn next(&mut self) -> Option<usize> {
+        // We should have found an empty bucket by now and ended the probe.
+        debug_assert!(self.stride <= self.bucket_mask, "went past end of probe sequence");
+
+        let result = self.pos;
+        self.stride += group::WIDTH;
+        self.pos += self.stride;
+        self.pos &= self.bucket_mask;
+        Some(result)
     }
-    /// Borrow a mutable reference to the table.
-    pub fn table_mut(&mut self) -> &mut M {
-        &mut self.table
+}
+
+/// Returns the number of buckets needed to hold the given number of items,
+/// taking the maximum load factor into account.
+fn capacity_to_buckets(cap: usize) -> Option<usize> {
+    // For small tables we require at least 1 empty bucket so that lookups are
+    // guaranteed to terminate if an element doesn't exist in the table.
+    if cap < 8 {
+        return Some(if cap < 4 { 4 } else { 8 });
     }
+
+    // Otherwise require 1/8 buckets to be empty (87.5% load). Rounding up to
+    // the next power of two only lowers that further.
+    let adjusted_cap = cap.checked_mul(8)? / 7;
+    adjusted_cap.checked_next_power_of_two()
 }
 
-impl<K, V, M> Bucket<K, V, M> {
-    /// Get the raw index.
-    pub fn index(&self) -> usize {
-        self.raw.idx
+/// Returns the maximum effective capacity for the given bucket mask, taking
+/// the maximum load factor into account.
+fn bucket_mask_to_capacity(bucket_mask: usize) -> usize {
+    if bucket_mask < 8 {
+        // For tables with 1/2/4/8 buckets, we always reserve one empty bucket
+        // to make sure lookups always terminate.
+        bucket_mask
+    } else {
+        ((bucket_mask + 1) / 8) * 7
     }
-    /// get the table.
-    pub fn into_table(self) -> M {
-        self.table
+}
+
+/// Returns the layout of the allocation for the given number of buckets, and
+/// the offset of the pairs in it. The control bytes come first.
+fn calculate_layout<K, V>(buckets: usize) -> Option<(Layout, usize)> {
+    debug_assert!(buckets.is_power_of_two());
+
+    let data = Layout::array::<(K, V)>(buckets)?;
+    let ctrl = Layout::from_size_align(buckets.checked_add(group::WIDTH)?, group::WIDTH)?;
+    ctrl.extend(data)
+}
+
+/// Unwraps the result of an allocating operation, panicking on capacity
+/// overflow and going through the allocator's `oom` on allocation failure.
+fn infallible<T, A: Alloc + Clone>(alloc: &A, result: Result<T, CollectionAllocErr>) -> T {
+    match result {
+        Ok(value) => value,
+        Err(CollectionAllocErr::CapacityOverflow) => panic!("capacity overflow"),
+        Err(CollectionAllocErr::AllocErr(e)) => alloc.clone().oom(e),
     }
 }
 
-impl<K, V, M> Deref for FullBucket<K, V, M>
-    where M: Deref<Target = RawTable<K, V>>
+/// Runs `dropfn` on `value` when dropped, unless it's been forgotten. This
+/// makes it possible to restore the table's invariants if a user supplied
+/// function panics halfway through some operation.
+struct ScopeGuard<T, F>
+    where F: FnMut(&mut T)
 {
-    type Target = RawTable<K, V>;
-    fn deref(&self) -> &RawTable<K, V> {
-        &self.table
-    }
+    dropfn: F,
+    value: T,
 }
 
-/// `Put` is implemented for types which provide access to a table and cannot be invalidated
-///  by filling a bucket. A similar implementation for `Take` is possible.
-pub trait Put<K, V> {
-    unsafe fn borrow_table_mut(&mut self) -> &mut RawTable<K, V>;
+fn guard<T, F>(value: T, dropfn: F) -> ScopeGuard<T, F>
+    where F: FnMut(&mut T)
+{
+    ScopeGuard { dropfn, value }
 }
 
+impl<T, F> Deref for ScopeGuard<T, F>
+    where F: FnMut(&mut T)
+{
+    type Target = T;
 
-impl<'t, K, V> Put<K, V> for &'t mut RawTable<K, V> {
-    unsafe fn borrow_table_mut(&mut self) -> &mut RawTable<K, V> {
-        *self
+    fn deref(&self) -> &T {
+        &self.value
     }
 }
 
-impl<K, V, M> Put<K, V> for Bucket<K, V, M>
-    where M: Put<K, V>
+impl<T, F> DerefMut for ScopeGuard<T, F>
+    where F: FnMut(&mut T)
 {
-    unsafe fn borrow_table_mut(&mut self) -> &mut RawTable<K, V> {
-        self.table.borrow_table_mut()
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
     }
 }
 
-impl<K, V, M> Put<K, V> for FullBucket<K, V, M>
-    where M: Put<K, V>
+impl<T, F> Drop for ScopeGuard<T, F>
+    where F: FnMut(&mut T)
 {
-    unsafe fn borrow_table_mut(&mut self) -> &mut RawTable<K, V> {
-        self.table.borrow_table_mut()
+    fn drop(&mut self) {
+        (self.dropfn)(&mut self.value)
     }
 }
 
-impl<K, V, M: Deref<Target = RawTable<K, V>>> Bucket<K, V, M> {
-    pub fn new(table: M, hash: SafeHash) -> Bucket<K, V, M> {
-        Bucket::at_index(table, hash.inspect() as usize)
+impl<K, V, A: Alloc + Clone> RawTable<K, V, A> {
+    /// Creates a new empty table in `alloc`, without allocating any memory.
+    #[inline]
+    pub fn new_in(alloc: A) -> RawTable<K, V, A> {
+        RawTable {
+            bucket_mask: 0,
+            ctrl: unsafe { Unique::new_unchecked(group::static_empty() as *mut u8) },
+            data: Unique::empty(),
+            growth_left: 0,
+            items: 0,
+            alloc,
+        }
     }
 
-    pub fn new_from(r: RawBucket<K, V>, t: M)
-        -> Bucket<K, V, M>
-    {
-        Bucket {
-            raw: r,
-            table: t,
+    /// Allocates a table with the given number of buckets, all of them empty.
+    fn try_with_buckets(buckets: usize, mut alloc: A)
+                        -> Result<RawTable<K, V, A>, CollectionAllocErr> {
+        let (layout, data_offset) = calculate_layout::<K, V>(buckets)
+            .ok_or(CollectionAllocErr::CapacityOverflow)?;
+
+        // We need to guarantee that the allocation is no larger than
+        // `isize::MAX` bytes, since we index it with `offset`.
+        if layout.size() > isize::MAX as usize {
+            return Err(CollectionAllocErr::CapacityOverflow);
         }
-    }
 
-    pub fn at_index(table: M, ib_index: usize) -> Bucket<K, V, M> {
-        // if capacity is 0, then the RawBucket will be populated with bogus pointers.
-        // This is an uncommon case though, so avoid it in release builds.
-        debug_assert!(table.capacity() > 0,
-                      "Table should have capacity at this point");
-        let ib_index = ib_index & table.capacity_mask;
-        Bucket {
-            raw: table.raw_bucket_at(ib_index),
-            table,
+        unsafe {
+            let ctrl = alloc.alloc(layout)?;
+            ptr::write_bytes(ctrl, EMPTY, buckets + group::WIDTH);
+
+            Ok(RawTable {
+                bucket_mask: buckets - 1,
+                ctrl: Unique::new_unchecked(ctrl),
+                data: Unique::new_unchecked(ctrl.offset(data_offset as isize) as *mut (K, V)),
+                growth_left: bucket_mask_to_capacity(buckets - 1),
+                items: 0,
+                alloc,
+            })
         }
     }
 
-    pub fn first(table: M) -> Bucket<K, V, M> {
-        Bucket {
-            raw: table.raw_bucket_at(0),
-            table,
+    /// Tries to create a table in `alloc` that can hold at least `capacity`
+    /// elements without reallocating.
+    pub fn try_with_capacity_in(capacity: usize, alloc: A)
+                                -> Result<RawTable<K, V, A>, CollectionAllocErr> {
+        if capacity == 0 {
+            Ok(RawTable::new_in(alloc))
+        } else {
+            let buckets = capacity_to_buckets(capacity)
+                .ok_or(CollectionAllocErr::CapacityOverflow)?;
+            RawTable::try_with_buckets(buckets, alloc)
         }
     }
 
-    // "So a few of the first shall be last: for many be called,
-    // but few chosen."
-    //
-    // We'll most likely encounter a few buckets at the beginning that
-    // have their initial buckets near the end of the table. They were
-    // placed at the beginning as the probe wrapped around the table
-    // during insertion. We must skip forward to a bucket that won't
-    // get reinserted too early and won't unfairly steal others spot.
-    // This eliminates the need for robin hood.
-    pub fn head_bucket(table: M) -> Bucket<K, V, M> {
-        let mut bucket = Bucket::first(table);
-
-        loop {
-            bucket = match bucket.peek() {
-                Full(full) => {
-                    if full.displacement() == 0 {
-                        // This bucket occupies its ideal spot.
-                        // It indicates the start of another "cluster".
-                        bucket = full.into_bucket();
-                        break;
-                    }
-                    // Leaving this bucket in the last cluster for later.
-                    full.into_bucket()
-                }
-                Empty(b) => {
-                    // Encountered a hole between clusters.
-                    b.into_bucket()
-                }
-            };
-            bucket.next();
-        }
-        bucket
-    }
-
-    /// Reads a bucket at a given index, returning an enum indicating whether
-    /// it's initialized or not. You need to match on this enum to get
-    /// the appropriate types to call most of the other functions in
-    /// this module.
-    pub fn peek(self) -> BucketState<K, V, M> {
-        match unsafe { *self.raw.hash() } {
-            EMPTY_BUCKET => {
-                Empty(EmptyBucket {
-                    raw: self.raw,
-                    table: self.table,
-                })
-            }
-            _ => {
-                Full(FullBucket {
-                    raw: self.raw,
-                    table: self.table,
-                })
-            }
-        }
+    /// Creates a table in `alloc` that can hold at least `capacity` elements
+    /// without reallocating.
+    pub fn with_capacity_in(capacity: usize, alloc: A) -> RawTable<K, V, A> {
+        let result = RawTable::try_with_capacity_in(capacity, alloc.clone());
+        infallible(&alloc, result)
     }
 
-    /// Modifies the bucket in place to make it point to the next slot.
-    pub fn next(&mut self) {
-        self.raw.idx = self.raw.idx.wrapping_add(1) & self.table.capacity_mask;
+    /// Returns a reference to the underlying allocator.
+    #[inline]
+    pub fn alloc(&self) -> &A {
+        &self.alloc
     }
 
-    /// Modifies the bucket in place to make it point to the previous slot.
-    pub fn prev(&mut self) {
-        self.raw.idx = self.raw.idx.wrapping_sub(1) & self.table.capacity_mask;
+    /// Deallocates the table without dropping any elements.
+    unsafe fn free_buckets(&mut self) {
+        let (layout, _) = calculate_layout::<K, V>(self.buckets()).unwrap();
+        self.alloc.dealloc(self.ctrl.as_ptr(), layout);
     }
-}
 
-impl<K, V, M: Deref<Target = RawTable<K, V>>> EmptyBucket<K, V, M> {
+    /// Returns whether this is the shared, unallocated table.
     #[inline]
-    pub fn next(self) -> Bucket<K, V, M> {
-        let mut bucket = self.into_bucket();
-        bucket.next();
-        bucket
+    pub fn is_empty_singleton(&self) -> bool {
+        self.bucket_mask == 0
     }
 
+    /// Returns the number of buckets in the table.
     #[inline]
-    pub fn into_bucket(self) -> Bucket<K, V, M> {
-        Bucket {
-            raw: self.raw,
-            table: self.table,
-        }
+    pub fn buckets(&self) -> usize {
+        self.bucket_mask + 1
     }
 
-    pub fn gap_peek(self) -> Result<GapThenFull<K, V, M>, Bucket<K, V, M>> {
-        let gap = EmptyBucket {
-            raw: self.raw,
-            table: (),
-        };
-
-        match self.next().peek() {
-            Full(bucket) => {
-                Ok(GapThenFull {
-                    gap,
-                    full: bucket,
-                })
-            }
-            Empty(e) => Err(e.into_bucket()),
-        }
+    /// Returns a pointer to the control byte at `index`.
+    #[inline]
+    unsafe fn ctrl(&self, index: usize) -> *mut u8 {
+        debug_assert!(index < self.buckets() + group::WIDTH);
+        self.ctrl.as_ptr().offset(index as isize)
     }
-}
-
-impl<K, V, M> EmptyBucket<K, V, M>
-    where M: Put<K, V>
-{
-    /// Puts given key and value pair, along with the key's hash,
-    /// into this bucket in the hashtable. Note how `self` is 'moved' into
-    /// this function, because this slot will no longer be empty when
-    /// we return! A `FullBucket` is returned for later use, pointing to
-    /// the newly-filled slot in the hashtable.
-    ///
-    /// Use `make_hash` to construct a `SafeHash` to pass to this function.
-    pub fn put(mut self, hash: SafeHash, key: K, value: V) -> FullBucket<K, V, M> {
-        unsafe {
-            *self.raw.hash() = hash.inspect();
-            ptr::write(self.raw.pair(), (key, value));
-
-            self.table.borrow_table_mut().size += 1;
-        }
 
-        FullBucket {
-            raw: self.raw,
-            table: self.table,
-        }
+    /// Returns the bucket at `index`.
+    #[inline]
+    unsafe fn bucket(&self, index: usize) -> Bucket<K, V> {
+        debug_assert!(!self.is_empty_singleton());
+        debug_assert!(index < self.buckets());
+        Bucket::from_base_index(self.data.as_ptr(), index)
     }
 
-    /// Puts given key, remain value uninitialized.
-    /// It is only used for inplacement insertion.
-    pub unsafe fn put_key(mut self, hash: SafeHash, key: K) -> FullBucket<K, V, M> {
-        *self.raw.hash() = hash.inspect();
-        let pair_ptr = self.raw.pair();
-        ptr::write(&mut (*pair_ptr).0, key);
-
-        self.table.borrow_table_mut().size += 1;
-
-        FullBucket {
-            raw: self.raw,
-            table: self.table,
-        }
+    /// Returns the index of a bucket.
+    #[inline]
+    unsafe fn bucket_index(&self, bucket: &Bucket<K, V>) -> usize {
+        bucket.to_base_index(self.data.as_ptr())
     }
-}
 
-impl<K, V, M: Deref<Target = RawTable<K, V>>> FullBucket<K, V, M> {
+    /// Sets the control byte of a bucket, and its mirror among the trailing
+    /// control bytes.
     #[inline]
-    pub fn next(self) -> Bucket<K, V, M> {
-        let mut bucket = self.into_bucket();
-        bucket.next();
-        bucket
+    unsafe fn set_ctrl(&self, index: usize, ctrl: u8) {
+        // For tables with at least `Group::WIDTH` buckets the mirror of bucket
+        // `index < Group::WIDTH` is at `buckets + index`, and the other
+        // buckets have none, which the formula below maps onto `index` itself.
+        //
+        // For smaller tables, the bytes between `buckets` and
+        // `Group::WIDTH` are always `EMPTY`, and the mirror of bucket `index`
+        // is at `Group::WIDTH + index`.
+        let index2 = (index.wrapping_sub(group::WIDTH) & self.bucket_mask) + group::WIDTH;
+
+        *self.ctrl(index) = ctrl;
+        *self.ctrl(index2) = ctrl;
     }
 
+    /// Returns the probe sequence for a hash.
     #[inline]
-    pub fn into_bucket(self) -> Bucket<K, V, M> {
-        Bucket {
-            raw: self.raw,
-            table: self.table,
+    fn probe_seq(&self, hash: u64) -> ProbeSeq {
+        ProbeSeq {
+            bucket_mask: self.bucket_mask,
+            pos: h1(hash) & self.bucket_mask,
+            stride: 0,
         }
     }
 
-    /// Duplicates the current position. This can be useful for operations
-    /// on two or more buckets.
-    pub fn stash(self) -> FullBucket<K, V, Self> {
-        FullBucket {
-            raw: self.raw,
-            table: self,
+    /// Searches for an empty or deleted bucket which is suitable for inserting
+    /// an element with the given hash, and returns its index.
+    ///
+    /// There must be at least one empty bucket in the table.
+    fn find_insert_slot(&self, hash: u64) -> usize {
+        for pos in self.probe_seq(hash) {
+            unsafe {
+                let group = Group::load(self.ctrl(pos));
+                if let Some(bit) = group.match_empty_or_deleted().lowest_set_bit() {
+                    let result = (pos + bit) & self.bucket_mask;
+
+                    // In tables smaller than the group width, the bytes past
+                    // the end of the buckets are `EMPTY`. They can match, but
+                    // once masked end up pointing at some other bucket which
+                    // may well be full. In that case we scan again from the
+                    // start of the table, where there is guaranteed to be a
+                    // free bucket before those trailing bytes.
+                    if group::is_full(*self.ctrl(result)) {
+                        debug_assert!(self.bucket_mask < group::WIDTH);
+                        debug_assert!(pos != 0);
+                        return Group::load_aligned(self.ctrl(0))
+                            .match_empty_or_deleted()
+                            .lowest_set_bit()
+                            .unwrap();
+                    }
+                    return result;
+                }
+            }
         }
-    }
 
-    /// Get the distance between this bucket and the 'ideal' location
-    /// as determined by the key's hash stored in it.
-    ///
-    /// In the cited blog posts above, this is called the "distance to
-    /// initial bucket", or DIB. Also known as "probe count".
-    pub fn displacement(&self) -> usize {
-        // Calculates the distance one has to travel when going from
-        // `hash mod capacity` onwards to `idx mod capacity`, wrapping around
-        // if the destination is not reached before the end of the table.
-        (self.raw.idx.wrapping_sub(self.hash().inspect() as usize)) & self.table.capacity_mask
+        // The probe sequence never ends.
+        unreachable!();
     }
 
+    /// Searches for an element in the table.
     #[inline]
-    pub fn hash(&self) -> SafeHash {
-        unsafe { SafeHash { hash: *self.raw.hash() } }
-    }
-
-    /// Gets references to the key and value at a given index.
-    pub fn read(&self) -> (&K, &V) {
+    pub fn find<F>(&self, hash: u64, mut is_match: F) -> Option<Bucket<K, V>>
+        where F: FnMut(&K) -> bool
+    {
         unsafe {
-            let pair_ptr = self.raw.pair();
-            (&(*pair_ptr).0, &(*pair_ptr).1)
+            for pos in self.probe_seq(hash) {
+                let group = Group::load(self.ctrl(pos));
+                for bit in group.match_byte(h2(hash)) {
+                    let index = (pos + bit) & self.bucket_mask;
+                    let bucket = self.bucket(index);
+                    if is_match(&(*bucket.pair()).0) {
+                        return Some(bucket);
+                    }
+                }
+                if group.match_empty().any_bit_set() {
+                    return None;
+                }
+            }
         }
+
+        // The probe sequence never ends.
+        unreachable!();
     }
-}
 
-// We take a mutable reference to the table instead of accepting anything that
-// implements `DerefMut` to prevent fn `take` from being called on `stash`ed
-// buckets.
-impl<'t, K, V> FullBucket<K, V, &'t mut RawTable<K, V>> {
-    /// Removes this bucket's key and value from the hashtable.
+    /// Inserts a new element into the table, without growing it.
     ///
-    /// This works similarly to `put`, building an `EmptyBucket` out of the
-    /// taken bucket.
-    pub fn take(self) -> (EmptyBucket<K, V, &'t mut RawTable<K, V>>, K, V) {
-        self.table.size -= 1;
-
+    /// The caller must have made sure there's room for it beforehand, by
+    /// reserving space for at least one element. The key must not already be
+    /// in the table.
+    pub fn insert_no_grow(&mut self, hash: u64, key: K, value: V) -> Bucket<K, V> {
         unsafe {
-            *self.raw.hash() = EMPTY_BUCKET;
-            let (k, v) = ptr::read(self.raw.pair());
-            (EmptyBucket {
-                 raw: self.raw,
-                 table: self.table,
-             },
-            k,
-            v)
+            let index = self.find_insert_slot(hash);
+            let bucket = self.bucket(index);
+
+            // If we are replacing a DELETED entry then we don't need to update
+            // the load counter.
+            let old_ctrl = *self.ctrl(index);
+            if group::special_is_empty(old_ctrl) {
+                debug_assert!(self.growth_left != 0);
+                self.growth_left -= 1;
+            }
+
+            self.set_ctrl(index, h2(hash));
+            bucket.write(key, value);
+            self.items += 1;
+            bucket
         }
     }
 
-    /// Remove this bucket's `key` from the hashtable.
-    /// Only used for inplacement insertion.
-    /// NOTE: `Value` is uninitialized when this function is called, don't try to drop the `Value`.
-    pub unsafe fn remove_key(&mut self) {
-        self.table.size -= 1;
-
-        *self.raw.hash() = EMPTY_BUCKET;
-        let pair_ptr = self.raw.pair();
-        ptr::drop_in_place(&mut (*pair_ptr).0); // only drop key
+    /// Removes an element from the table, without dropping it.
+    ///
+    /// The bucket must be full and belong to this table. The element can be
+    /// read out of it afterwards.
+    pub unsafe fn erase_no_drop(&mut self, item: &Bucket<K, V>) {
+        let index = self.bucket_index(item);
+        let index_before = index.wrapping_sub(group::WIDTH) & self.bucket_mask;
+        let empty_before = Group::load(self.ctrl(index_before)).match_empty();
+        let empty_after = Group::load(self.ctrl(index)).match_empty();
+
+        // If the bucket is inside a run of at least `Group::WIDTH` full or
+        // deleted buckets, some probe window may have seen a full group
+        // there and moved on. The bucket must then stay non-empty so that
+        // lookups still continue past it.
+        let ctrl = if empty_before.leading_zeros() + empty_after.trailing_zeros()
+                      >= group::WIDTH {
+            DELETED
+        } else {
+            self.growth_left += 1;
+            EMPTY
+        };
+        self.set_ctrl(index, ctrl);
+        self.items -= 1;
     }
-}
-
-// This use of `Put` is misleading and restrictive, but safe and sufficient for our use cases
-// where `M` is a full bucket or table reference type with mutable access to the table.
-impl<K, V, M> FullBucket<K, V, M>
-    where M: Put<K, V>
-{
-    pub fn replace(&mut self, h: SafeHash, k: K, v: V) -> (SafeHash, K, V) {
-        unsafe {
-            let old_hash = ptr::replace(self.raw.hash() as *mut SafeHash, h);
-            let (old_key, old_val) = ptr::replace(self.raw.pair(), (k, v));
 
-            (old_hash, old_key, old_val)
+    /// Marks all buckets as empty, without dropping their contents.
+    fn clear_no_drop(&mut self) {
+        if !self.is_empty_singleton() {
+            unsafe {
+                ptr::write_bytes(self.ctrl(0), EMPTY, self.buckets() + group::WIDTH);
+            }
         }
+        self.items = 0;
+        self.growth_left = bucket_mask_to_capacity(self.bucket_mask);
     }
-}
 
-impl<K, V, M> FullBucket<K, V, M>
-    where M: Deref<Target = RawTable<K, V>> + DerefMut
-{
-    /// Gets mutable references to the key and value at a given index.
-    pub fn read_mut(&mut self) -> (&mut K, &mut V) {
-        unsafe {
-            let pair_ptr = self.raw.pair();
-            (&mut (*pair_ptr).0, &mut (*pair_ptr).1)
-        }
-    }
-}
+    /// Shrinks the table to fit `max(self.size(), min_size)` elements.
+    pub fn shrink_to<H>(&mut self, min_size: usize, hasher: H)
+        where H: Fn(&K) -> u64
+    {
+        let min_size = cmp::max(self.items, min_size);
 
-impl<'t, K, V, M> FullBucket<K, V, M>
-    where M: Deref<Target = RawTable<K, V>> + 't
-{
-    /// Exchange a bucket state for immutable references into the table.
-    /// Because the underlying reference to the table is also consumed,
-    /// no further changes to the structure of the table are possible;
-    /// in exchange for this, the returned references have a longer lifetime
-    /// than the references returned by `read()`.
-    pub fn into_refs(self) -> (&'t K, &'t V) {
-        unsafe {
-            let pair_ptr = self.raw.pair();
-            (&(*pair_ptr).0, &(*pair_ptr).1)
+        // Drop the allocation altogether if we're not going to hold anything.
+        if min_size == 0 {
+            *self = RawTable::new_in(self.alloc.clone());
+            return;
         }
-    }
-}
 
-impl<'t, K, V, M> FullBucket<K, V, M>
-    where M: Deref<Target = RawTable<K, V>> + DerefMut + 't
-{
-    /// This works similarly to `into_refs`, exchanging a bucket state
-    /// for mutable references into the table.
-    pub fn into_mut_refs(self) -> (&'t mut K, &'t mut V) {
-        unsafe {
-            let pair_ptr = self.raw.pair();
-            (&mut (*pair_ptr).0, &mut (*pair_ptr).1)
+        let min_buckets = match capacity_to_buckets(min_size) {
+            Some(buckets) => buckets,
+            None => return,
+        };
+
+        // If we have more buckets than we need, shrink the table.
+        if min_buckets < self.buckets() {
+            if self.items == 0 {
+                *self = RawTable::with_capacity_in(min_size, self.alloc.clone());
+            } else {
+                let result = self.resize(min_size, hasher);
+                infallible(&self.alloc, result);
+            }
         }
     }
-}
 
-impl<K, V, M> GapThenFull<K, V, M>
-    where M: Deref<Target = RawTable<K, V>>
-{
+    /// Ensures that at least `additional` more elements can be inserted
+    /// without reallocating.
     #[inline]
-    pub fn full(&self) -> &FullBucket<K, V, M> {
-        &self.full
+    pub fn try_reserve<H>(&mut self, additional: usize, hasher: H)
+                          -> Result<(), CollectionAllocErr>
+        where H: Fn(&K) -> u64
+    {
+        if additional > self.growth_left {
+            self.reserve_rehash(additional, hasher)
+        } else {
+            Ok(())
+        }
     }
 
-    pub fn into_table(self) -> M {
-        self.full.into_table()
+    /// Out-of-line slow path for `try_reserve`.
+    #[inline(never)]
+    #[cold]
+    fn reserve_rehash<H>(&mut self, additional: usize, hasher: H)
+                         -> Result<(), CollectionAllocErr>
+        where H: Fn(&K) -> u64
+    {
+        let new_items = self.items.checked_add(additional)
+            .ok_or(CollectionAllocErr::CapacityOverflow)?;
+
+        // Rehash in place if the table is at most half full once the new
+        // elements are in: the missing room must then be taken up by
+        // tombstones, and getting rid of them is cheaper than reallocating.
+        // Otherwise grow the table, to at least the next size up so that a
+        // table full of tombstones doesn't keep getting rehashed.
+        let full_capacity = bucket_mask_to_capacity(self.bucket_mask);
+        if new_items <= full_capacity / 2 {
+            self.rehash_in_place(hasher);
+            Ok(())
+        } else {
+            self.resize(cmp::max(new_items, full_capacity + 1), hasher)
+        }
     }
 
-    pub fn shift(mut self) -> Result<GapThenFull<K, V, M>, Bucket<K, V, M>> {
+    /// Rehashes the contents of the table in place, turning all tombstones
+    /// back into empty buckets.
+    fn rehash_in_place<H>(&mut self, hasher: H)
+        where H: Fn(&K) -> u64
+    {
         unsafe {
-            let (gap_hash, gap_pair) = self.gap.raw.hash_pair();
-            let (full_hash, full_pair) = self.full.raw.hash_pair();
-            *gap_hash = mem::replace(&mut *full_hash, EMPTY_BUCKET);
-            ptr::copy_nonoverlapping(full_pair, gap_pair, 1);
-        }
-
-        let FullBucket { raw: prev_raw, .. } = self.full;
-
-        match self.full.next().peek() {
-            Full(bucket) => {
-                self.gap.raw = prev_raw;
-
-                self.full = bucket;
-
-                Ok(self)
+            // Bulk convert all full control bytes to DELETED, and all DELETED
+            // control bytes to EMPTY. This effectively frees up all buckets
+            // containing a DELETED entry, and leaves the ones that still have
+            // to be rehashed marked as DELETED.
+            let mut i = 0;
+            while i < self.buckets() {
+                let group = Group::load_aligned(self.ctrl(i));
+                let group = group.convert_special_to_empty_and_full_to_deleted();
+                group.store_aligned(self.ctrl(i));
+                i += group::WIDTH;
             }
-            Empty(b) => Err(b.into_bucket()),
-        }
-    }
-}
-
-
-/// Rounds up to a multiple of a power of two. Returns the closest multiple
-/// of `target_alignment` that is higher or equal to `unrounded`.
-///
-/// # Panics
-///
-/// Panics if `target_alignment` is not a power of two.
-#[inline]
-fn round_up_to_next(unrounded: usize, target_alignment: usize) -> usize {
-    assert!(target_alignment.is_power_of_two());
-    (unrounded + target_alignment - 1) & !(target_alignment - 1)
-}
-
-#[test]
-fn test_rounding() {
-    assert_eq!(round_up_to_next(0, 4), 0);
-    assert_eq!(round_up_to_next(1, 4), 4);
-    assert_eq!(round_up_to_next(2, 4), 4);
-    assert_eq!(round_up_to_next(3, 4), 4);
-    assert_eq!(round_up_to_next(4, 4), 4);
-    assert_eq!(round_up_to_next(5, 4), 8);
-}
-
-// Returns a tuple of (pairs_offset, end_of_pairs_offset),
-// from the start of a mallocated array.
-#[inline]
-fn calculate_offsets(hashes_size: usize,
-                     pairs_size: usize,
-                     pairs_align: usize)
-                     -> (usize, usize, bool) {
-    let pairs_offset = round_up_to_next(hashes_size, pairs_align);
-    let (end_of_pairs, oflo) = pairs_offset.overflowing_add(pairs_size);
-
-    (pairs_offset, end_of_pairs, oflo)
-}
-
-// Returns a tuple of (minimum required malloc alignment,
-// array_size), from the start of a mallocated array.
-fn calculate_allocation(hash_size: usize,
-                        hash_align: usize,
-                        pairs_size: usize,
-                        pairs_align: usize)
-                        -> (usize, usize, bool) {
-    let (_, end_of_pairs, oflo) = calculate_offsets(hash_size, pairs_size, pairs_align);
-
-    let align = cmp::max(hash_align, pairs_align);
-
-    (align, end_of_pairs, oflo)
-}
 
-#[test]
-fn test_offset_calculation() {
-    assert_eq!(calculate_allocation(128, 8, 16, 8), (8, 144, false));
-    assert_eq!(calculate_allocation(3, 1, 2, 1), (1, 5, false));
-    assert_eq!(calculate_allocation(6, 2, 12, 4), (4, 20, false));
-    assert_eq!(calculate_offsets(128, 15, 4), (128, 143, false));
-    assert_eq!(calculate_offsets(3, 2, 4), (4, 6, false));
-    assert_eq!(calculate_offsets(6, 12, 4), (8, 20, false));
-}
+            // Fix up the trailing control bytes. See the comments in
+            // `set_ctrl` for the handling of tables smaller than the group
+            // width.
+            if self.buckets() < group::WIDTH {
+                ptr::copy(self.ctrl(0), self.ctrl(group::WIDTH), self.buckets());
+                ptr::write_bytes(self.ctrl(self.buckets()), EMPTY,
+                                 group::WIDTH - self.buckets());
+            } else {
+                ptr::copy(self.ctrl(0), self.ctrl(self.buckets()), group::WIDTH);
+            }
 
-impl<K, V> RawTable<K, V> {
-    /// Does not initialize the buckets. The caller should ensure they,
-    /// at the very least, set every hash to EMPTY_BUCKET.
-    unsafe fn new_uninitialized(capacity: usize) -> RawTable<K, V> {
-        if capacity == 0 {
-            return RawTable {
-                size: 0,
-                capacity_mask: capacity.wrapping_sub(1),
-                hashes: TaggedHashUintPtr::new(EMPTY as *mut HashUint),
-                marker: marker::PhantomData,
-            };
-        }
+            // If the hash function panics then properly clean up any elements
+            // that we haven't rehashed yet. We unfortunately can't preserve
+            // them since we lost their hash and have no way of recovering it
+            // without risking another panic.
+            let mut guard = guard(self, |self_| {
+                for i in 0..self_.buckets() {
+                    if *self_.ctrl(i) == DELETED {
+                        self_.set_ctrl(i, EMPTY);
+                        self_.bucket(i).drop();
+                        self_.items -= 1;
+                    }
+                }
+                self_.growth_left = bucket_mask_to_capacity(self_.bucket_mask) - self_.items;
+            });
+
+            // At this point, DELETED elements are elements that we haven't
+            // rehashed yet. Find them and re-insert them at their ideal
+            // position.
+            let bucket_mask = guard.bucket_mask;
+            'outer: for i in 0..guard.buckets() {
+                if *guard.ctrl(i) != DELETED {
+                    continue;
+                }
 
-        // No need for `checked_mul` before a more restrictive check performed
-        // later in this method.
-        let hashes_size = capacity.wrapping_mul(size_of::<HashUint>());
-        let pairs_size = capacity.wrapping_mul(size_of::<(K, V)>());
+                'inner: loop {
+                    // Hash the current item
+                    let item = guard.bucket(i);
+                    let hash = hasher(&(*item.pair()).0);
+
+                    // Search for a suitable place to put it
+                    let new_i = guard.find_insert_slot(hash);
+
+                    // Probing works by scanning through all of the control
+                    // bytes in groups, which may not be aligned to the group
+                    // size. If both the new and old position fall within the
+                    // same unaligned group, then there is no benefit in moving
+                    // it and we can just continue to the next item.
+                    let probe_start = h1(hash) & bucket_mask;
+                    let probe_index = |pos: usize| {
+                        (pos.wrapping_sub(probe_start) & bucket_mask) / group::WIDTH
+                    };
+                    if probe_index(i) == probe_index(new_i) {
+                        guard.set_ctrl(i, h2(hash));
+                        continue 'outer;
+                    }
 
-        // Allocating hashmaps is a little tricky. We need to allocate two
-        // arrays, but since we know their sizes and alignments up front,
-        // we just allocate a single array, and then have the subarrays
-        // point into it.
-        //
-        // This is great in theory, but in practice getting the alignment
-        // right is a little subtle. Therefore, calculating offsets has been
-        // factored out into a different function.
-        let (alignment, size, oflo) = calculate_allocation(hashes_size,
-                                                           align_of::<HashUint>(),
-                                                           pairs_size,
-                                                           align_of::<(K, V)>());
-        assert!(!oflo, "capacity overflow");
-
-        // One check for overflow that covers calculation and rounding of size.
-        let size_of_bucket = size_of::<HashUint>().checked_add(size_of::<(K, V)>()).unwrap();
-        assert!(size >=
-                capacity.checked_mul(size_of_bucket)
-                    .expect("capacity overflow"),
-                "capacity overflow");
-
-        let buffer = Heap.alloc(Layout::from_size_align(size, alignment).unwrap())
-            .unwrap_or_else(|e| Heap.oom(e));
-
-        let hashes = buffer as *mut HashUint;
+                    // We are moving the current item to a new position. Write
+                    // our H2 to the control byte of the new position.
+                    let prev_ctrl = *guard.ctrl(new_i);
+                    guard.set_ctrl(new_i, h2(hash));
+
+                    if prev_ctrl == EMPTY {
+                        // If the target slot is empty, simply move the current
+                        // element into the new slot and clear the old control
+                        // byte.
+                        guard.set_ctrl(i, EMPTY);
+                        guard.bucket(new_i).copy_from_nonoverlapping(&item);
+                        continue 'outer;
+                    } else {
+                        // If the target slot is occupied, swap the two elements
+                        // and then continue processing the element that we just
+                        // swapped into the old slot.
+                        debug_assert_eq!(prev_ctrl, DELETED);
+                        ptr::swap(guard.bucket(new_i).pair(), item.pair());
+                        continue 'inner;
+                    }
+                }
+            }
 
-        RawTable {
-            capacity_mask: capacity.wrapping_sub(1),
-            size: 0,
-            hashes: TaggedHashUintPtr::new(hashes),
-            marker: marker::PhantomData,
+            guard.growth_left = bucket_mask_to_capacity(guard.bucket_mask) - guard.items;
+            mem::forget(guard);
         }
     }
 
-    fn raw_bucket_at(&self, index: usize) -> RawBucket<K, V> {
-        let hashes_size = self.capacity() * size_of::<HashUint>();
-        let pairs_size = self.capacity() * size_of::<(K, V)>();
-
-        let (pairs_offset, _, oflo) =
-            calculate_offsets(hashes_size, pairs_size, align_of::<(K, V)>());
-        debug_assert!(!oflo, "capacity overflow");
-
-        let buffer = self.hashes.ptr() as *mut u8;
+    /// Moves the contents of the table into a new allocation that can hold at
+    /// least `capacity` elements.
+    fn resize<H>(&mut self, capacity: usize, hasher: H) -> Result<(), CollectionAllocErr>
+        where H: Fn(&K) -> u64
+    {
         unsafe {
-            RawBucket {
-                hash_start: buffer as *mut HashUint,
-                pair_start: buffer.offset(pairs_offset as isize) as *const (K, V),
-                idx: index,
-                _marker: marker::PhantomData,
+            debug_assert!(self.items <= capacity);
+
+            // Allocate and initialize the new table.
+            let mut new_table = RawTable::try_with_capacity_in(capacity, self.alloc.clone())?;
+            new_table.growth_left -= self.items;
+            new_table.items = self.items;
+
+            // The hash function may panic, in which case we simply free the
+            // new table without dropping any elements that may have been
+            // copied into it.
+            let mut new_table = guard(ManuallyDrop::new(new_table), |new_table| {
+                if !new_table.is_empty_singleton() {
+                    new_table.free_buckets();
+                }
+                ptr::drop_in_place(&mut new_table.alloc);
+            });
+
+            // Copy all elements to the new table.
+            for item in self.raw_iter() {
+                // This may panic.
+                let hash = hasher(&(*item.pair()).0);
+
+                // We can use a simpler version of insert() here since there
+                // are no DELETED entries and we know there is enough space
+                // in the table.
+                let index = new_table.find_insert_slot(hash);
+                new_table.set_ctrl(index, h2(hash));
+                new_table.bucket(index).copy_from_nonoverlapping(&item);
             }
+
+            // We successfully copied all elements without panicking. Now
+            // replace self with the new table. The old table will have its
+            // memory freed but the items will not be dropped (since they have
+            // been moved into the new table).
+            mem::swap(self, &mut **new_table);
+
+            Ok(())
         }
     }
 
-    /// Creates a new raw table from a given capacity. All buckets are
-    /// initially empty.
-    pub fn new(capacity: usize) -> RawTable<K, V> {
+    /// Keeps only the elements for which the predicate returns `true`.
+    pub fn retain<F>(&mut self, mut f: F)
+        where F: FnMut(&K, &mut V) -> bool
+    {
         unsafe {
-            let ret = RawTable::new_uninitialized(capacity);
-            ptr::write_bytes(ret.hashes.ptr(), 0, capacity);
-            ret
+            for item in self.raw_iter() {
+                let keep = {
+                    let (key, value) = item.into_mut_refs();
+                    f(&*key, value)
+                };
+                if !keep {
+                    // Erase the element from the table first since drop might
+                    // panic.
+                    self.erase_no_drop(&item);
+                    item.drop();
+                }
+            }
         }
     }
 
-    /// The hashtable's capacity, similar to a vector's.
+    /// Returns the number of elements the table can hold without
+    /// reallocating.
+    #[inline]
     pub fn capacity(&self) -> usize {
-        self.capacity_mask.wrapping_add(1)
+        self.items + self.growth_left
     }
 
     /// The number of elements ever `put` in the hashtable, minus the number
     /// of elements ever `take`n.
+    #[inline]
     pub fn size(&self) -> usize {
-        self.size
+        self.items
     }
 
-    fn raw_buckets(&self) -> RawBuckets<K, V> {
-        RawBuckets {
-            raw: self.raw_bucket_at(0),
-            elems_left: self.size,
-            marker: marker::PhantomData,
+    /// Returns an iterator over the buckets of the table which are full.
+    /// The iterator doesn't borrow the table.
+    #[inline]
+    unsafe fn raw_iter(&self) -> RawIter<K, V> {
+        let data = Bucket::from_base_index(self.data.as_ptr(), 0);
+        RawIter {
+            iter: RawIterRange::new(self.ctrl.as_ptr(), data, self.buckets()),
+            items: self.items,
         }
     }
 
     pub fn iter(&self) -> Iter<K, V> {
         Iter {
-            iter: self.raw_buckets(),
+            iter: unsafe { self.raw_iter() },
+            marker: marker::PhantomData,
         }
     }
 
     pub fn iter_mut(&mut self) -> IterMut<K, V> {
         IterMut {
-            iter: self.raw_buckets(),
+            iter: unsafe { self.raw_iter() },
             _marker: marker::PhantomData,
         }
     }
 
-    pub fn into_iter(self) -> IntoIter<K, V> {
-        let RawBuckets { raw, elems_left, .. } = self.raw_buckets();
-        // Replace the marker regardless of lifetime bounds on parameters.
-        IntoIter {
-            iter: RawBuckets {
-                raw,
-                elems_left,
+    pub fn into_iter(self) -> IntoIter<K, V, A> {
+        unsafe {
+            let iter = self.raw_iter();
+            let allocation = if self.is_empty_singleton() {
+                None
+            } else {
+                let (layout, _) = calculate_layout::<K, V>(self.buckets()).unwrap();
+                Some((self.ctrl, layout))
+            };
+            let alloc = ptr::read(&self.alloc);
+            mem::forget(self);
+
+            IntoIter {
+                iter,
+                allocation,
+                alloc,
                 marker: marker::PhantomData,
-            },
-            table: self,
+            }
         }
     }
 
-    pub fn drain(&mut self) -> Drain<K, V> {
-        let RawBuckets { raw, elems_left, .. } = self.raw_buckets();
-        // Replace the marker regardless of lifetime bounds on parameters.
-        Drain {
-            iter: RawBuckets {
-                raw,
-                elems_left,
+    pub fn drain(&mut self) -> Drain<K, V, A> {
+        unsafe {
+            let iter = self.raw_iter();
+
+            // The table is swapped out for the duration of the drain, so that
+            // it is left empty rather than full of moved-out elements should
+            // the `Drain` be leaked.
+            let alloc = self.alloc.clone();
+            let table = mem::replace(self, RawTable::new_in(alloc));
+            Drain {
+                iter,
+                table: ManuallyDrop::new(table),
+                orig_table: Shared::from(self),
                 marker: marker::PhantomData,
-            },
-            table: Shared::from(self),
-            marker: marker::PhantomData,
+            }
         }
     }
+}
 
-    /// Drops buckets in reverse order. It leaves the table in an inconsistent
-    /// state and should only be used for dropping the table's remaining
-    /// entries. It's used in the implementation of Drop.
-    unsafe fn rev_drop_buckets(&mut self) {
-        // initialize the raw bucket past the end of the table
-        let mut raw = self.raw_bucket_at(self.capacity());
-        let mut elems_left = self.size;
-
-        while elems_left != 0 {
-            raw.idx -= 1;
+/// An iterator over the full buckets of a table, which doesn't borrow it.
+struct RawIter<K, V> {
+    iter: RawIterRange<K, V>,
+    items: usize,
+}
 
-            if *raw.hash() != EMPTY_BUCKET {
-                elems_left -= 1;
-                ptr::drop_in_place(raw.pair());
-            }
+impl<K, V> Clone for RawIter<K, V> {
+    fn clone(&self) -> RawIter<K, V> {
+        RawIter {
+            iter: self.iter.clone(),
+            items: self.items,
         }
     }
+}
 
-    /// Set the table tag
-    pub fn set_tag(&mut self, value: bool) {
-        self.hashes.set_tag(value)
+impl<K, V> Iterator for RawIter<K, V> {
+    type Item = Bucket<K, V>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Bucket<K, V>> {
+        match self.iter.next() {
+            Some(bucket) => {
+                self.items -= 1;
+                Some(bucket)
+            }
+            None => {
+                debug_assert_eq!(self.items, 0);
+                None
+            }
+        }
     }
 
-    /// Get the table tag
-    pub fn tag(&self) -> bool {
-        self.hashes.tag()
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.items, Some(self.items))
     }
 }
 
-/// A raw iterator. The basis for some other iterators in this module. Although
-/// this interface is safe, it's not used outside this module.
-struct RawBuckets<'a, K, V> {
-    raw: RawBucket<K, V>,
-    elems_left: usize,
-
-    // Strictly speaking, this should be &'a (K,V), but that would
-    // require that K:'a, and we often use RawBuckets<'static...> for
-    // move iterations, so that messes up a lot of other things. So
-    // just use `&'a (K,V)` as this is not a publicly exposed type
-    // anyway.
-    marker: marker::PhantomData<&'a ()>,
+/// Iterator over the full buckets in a range of a table, one group at a
+/// time.
+struct RawIterRange<K, V> {
+    // Mask of full buckets in the current group. Bits are cleared from it as
+    // we iterate.
+    current_group: BitMask,
+
+    // Bucket of the first element of the current group.
+    data: Bucket<K, V>,
+
+    // Pointer to the next group of control bytes, which is aligned to
+    // `Group::WIDTH`.
+    next_ctrl: *const u8,
+
+    // Pointer one past the last control byte of this range.
+    end: *const u8,
 }
 
-// FIXME(#26925) Remove in favor of `#[derive(Clone)]`
-impl<'a, K, V> Clone for RawBuckets<'a, K, V> {
-    fn clone(&self) -> RawBuckets<'a, K, V> {
-        RawBuckets {
-            raw: self.raw,
-            elems_left: self.elems_left,
-            marker: marker::PhantomData,
+impl<K, V> RawIterRange<K, V> {
+    /// Returns a `RawIterRange` covering the `len` buckets starting at
+    /// `data`, whose control bytes start at `ctrl`.
+    #[inline]
+    unsafe fn new(ctrl: *const u8, data: Bucket<K, V>, len: usize) -> RawIterRange<K, V> {
+        let end = ctrl.offset(len as isize);
+
+        // Load the first group and advance next_ctrl to point to the next
+        // group.
+        let current_group = Group::load_aligned(ctrl).match_full();
+        let next_ctrl = ctrl.offset(group::WIDTH as isize);
+
+        RawIterRange {
+            current_group,
+            data,
+            next_ctrl,
+            end,
         }
     }
 }
 
+impl<K, V> Clone for RawIterRange<K, V> {
+    fn clone(&self) -> RawIterRange<K, V> {
+        RawIterRange {
+            current_group: self.current_group,
+            data: self.data,
+            next_ctrl: self.next_ctrl,
+            end: self.end,
+        }
+    }
+}
 
-impl<'a, K, V> Iterator for RawBuckets<'a, K, V> {
-    type Item = RawBucket<K, V>;
+impl<K, V> Iterator for RawIterRange<K, V> {
+    type Item = Bucket<K, V>;
 
-    fn next(&mut self) -> Option<RawBucket<K, V>> {
-        if self.elems_left == 0 {
-            return None;
-        }
+    #[inline]
+    fn next(&mut self) -> Option<Bucket<K, V>> {
+        unsafe {
+            loop {
+                if let Some(index) = self.current_group.lowest_set_bit() {
+                    self.current_group = self.current_group.remove_lowest_bit();
+                    return Some(self.data.add(index));
+                }
 
-        loop {
-            unsafe {
-                let item = self.raw;
-                self.raw.idx += 1;
-                if *item.hash() != EMPTY_BUCKET {
-                    self.elems_left -= 1;
-                    return Some(item);
+                if self.next_ctrl >= self.end {
+                    return None;
                 }
+
+                // We might read past self.end up to the next group boundary,
+                // but this is fine because it only occurs on tables smaller
+                // than the group size where the trailing control bytes are all
+                // EMPTY. On larger tables self.end is guaranteed to be aligned
+                // to the group size (since tables are power-of-two sized).
+                self.current_group = Group::load_aligned(self.next_ctrl).match_full();
+                self.data = self.data.add(group::WIDTH);
+                self.next_ctrl = self.next_ctrl.offset(group::WIDTH as isize);
             }
         }
     }
-
-    fn size_hint(&self) -> (usize, Option<usize>) {
-        (self.elems_left, Some(self.elems_left))
-    }
-}
-
-impl<'a, K, V> ExactSizeIterator for RawBuckets<'a, K, V> {
-    fn len(&self) -> usize {
-        self.elems_left
-    }
 }
 
 /// Iterator over shared references to entries in a table.
 pub struct Iter<'a, K: 'a, V: 'a> {
-    iter: RawBuckets<'a, K, V>,
+    iter: RawIter<K, V>,
+    marker: marker::PhantomData<&'a (K, V)>,
 }
 
 unsafe impl<'a, K: Sync, V: Sync> Sync for Iter<'a, K, V> {}
@@ -977,13 +1022,14 @@ impl<'a, K, V> Clone for Iter<'a, K, V> {
     fn clone(&self) -> Iter<'a, K, V> {
         Iter {
             iter: self.iter.clone(),
+            marker: marker::PhantomData,
         }
     }
 }
 
 /// Iterator over mutable references to entries in a table.
 pub struct IterMut<'a, K: 'a, V: 'a> {
-    iter: RawBuckets<'a, K, V>,
+    iter: RawIter<K, V>,
     // To ensure invariance with respect to V
     _marker: marker::PhantomData<&'a mut V>,
 }
@@ -997,41 +1043,51 @@ impl<'a, K: 'a, V: 'a> IterMut<'a, K, V> {
     pub fn iter(&self) -> Iter<K, V> {
         Iter {
             iter: self.iter.clone(),
+            marker: marker::PhantomData,
         }
     }
 }
 
 /// Iterator over the entries in a table, consuming the table.
-pub struct IntoIter<K, V> {
-    table: RawTable<K, V>,
-    iter: RawBuckets<'static, K, V>,
+pub struct IntoIter<K, V, A: Alloc + Clone = Heap> {
+    iter: RawIter<K, V>,
+    allocation: Option<(Unique<u8>, Layout)>,
+    alloc: A,
+    marker: marker::PhantomData<(K, V)>,
 }
 
-unsafe impl<K: Sync, V: Sync> Sync for IntoIter<K, V> {}
-unsafe impl<K: Send, V: Send> Send for IntoIter<K, V> {}
+unsafe impl<K: Sync, V: Sync, A: Alloc + Clone + Sync> Sync for IntoIter<K, V, A> {}
+unsafe impl<K: Send, V: Send, A: Alloc + Clone + Send> Send for IntoIter<K, V, A> {}
 
-impl<K, V> IntoIter<K, V> {
+impl<K, V, A: Alloc + Clone> IntoIter<K, V, A> {
     pub fn iter(&self) -> Iter<K, V> {
         Iter {
             iter: self.iter.clone(),
+            marker: marker::PhantomData,
         }
     }
 }
 
 /// Iterator over the entries in a table, clearing the table.
-pub struct Drain<'a, K: 'a, V: 'a> {
-    table: Shared<RawTable<K, V>>,
-    iter: RawBuckets<'static, K, V>,
-    marker: marker::PhantomData<&'a RawTable<K, V>>,
+pub struct Drain<'a, K: 'a, V: 'a, A: Alloc + Clone + 'a = Heap> {
+    iter: RawIter<K, V>,
+
+    // The table being drained, which is moved out of `orig_table` and put
+    // back once the `Drain` is dropped.
+    table: ManuallyDrop<RawTable<K, V, A>>,
+    orig_table: Shared<RawTable<K, V, A>>,
+
+    marker: marker::PhantomData<&'a RawTable<K, V, A>>,
 }
 
-unsafe impl<'a, K: Sync, V: Sync> Sync for Drain<'a, K, V> {}
-unsafe impl<'a, K: Send, V: Send> Send for Drain<'a, K, V> {}
+unsafe impl<'a, K: Sync, V: Sync, A: Alloc + Clone + Sync> Sync for Drain<'a, K, V, A> {}
+unsafe impl<'a, K: Send, V: Send, A: Alloc + Clone + Send> Send for Drain<'a, K, V, A> {}
 
-impl<'a, K, V> Drain<'a, K, V> {
+impl<'a, K, V, A: Alloc + Clone> Drain<'a, K, V, A> {
     pub fn iter(&self) -> Iter<K, V> {
         Iter {
             iter: self.iter.clone(),
+            marker: marker::PhantomData,
         }
     }
 }
@@ -1040,10 +1096,7 @@ impl<'a, K, V> Iterator for Iter<'a, K, V> {
     type Item = (&'a K, &'a V);
 
     fn next(&mut self) -> Option<(&'a K, &'a V)> {
-        self.iter.next().map(|raw| unsafe {
-            let pair_ptr = raw.pair();
-            (&(*pair_ptr).0, &(*pair_ptr).1)
-        })
+        self.iter.next().map(|bucket| unsafe { bucket.into_refs() })
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
@@ -1053,7 +1106,7 @@ impl<'a, K, V> Iterator for Iter<'a, K, V> {
 
 impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {
     fn len(&self) -> usize {
-        self.iter.len()
+        self.iter.items
     }
 }
 
@@ -1061,9 +1114,9 @@ impl<'a, K, V> Iterator for IterMut<'a, K, V> {
     type Item = (&'a K, &'a mut V);
 
     fn next(&mut self) -> Option<(&'a K, &'a mut V)> {
-        self.iter.next().map(|raw| unsafe {
-            let pair_ptr = raw.pair();
-            (&(*pair_ptr).0, &mut (*pair_ptr).1)
+        self.iter.next().map(|bucket| unsafe {
+            let (key, value) = bucket.into_mut_refs();
+            (&*key, value)
         })
     }
 
@@ -1074,21 +1127,15 @@ impl<'a, K, V> Iterator for IterMut<'a, K, V> {
 
 impl<'a, K, V> ExactSizeIterator for IterMut<'a, K, V> {
     fn len(&self) -> usize {
-        self.iter.len()
+        self.iter.items
     }
 }
 
-impl<K, V> Iterator for IntoIter<K, V> {
-    type Item = (SafeHash, K, V);
+impl<K, V, A: Alloc + Clone> Iterator for IntoIter<K, V, A> {
+    type Item = (K, V);
 
-    fn next(&mut self) -> Option<(SafeHash, K, V)> {
-        self.iter.next().map(|raw| {
-            self.table.size -= 1;
-            unsafe {
-                let (k, v) = ptr::read(raw.pair());
-                (SafeHash { hash: *raw.hash() }, k, v)
-            }
-        })
+    fn next(&mut self) -> Option<(K, V)> {
+        self.iter.next().map(|bucket| unsafe { bucket.read() })
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
@@ -1096,24 +1143,36 @@ impl<K, V> Iterator for IntoIter<K, V> {
     }
 }
 
-impl<K, V> ExactSizeIterator for IntoIter<K, V> {
+impl<K, V, A: Alloc + Clone> ExactSizeIterator for IntoIter<K, V, A> {
     fn len(&self) -> usize {
-        self.iter().len()
+        self.iter.items
     }
 }
 
-impl<'a, K, V> Iterator for Drain<'a, K, V> {
-    type Item = (SafeHash, K, V);
+unsafe impl<#[may_dangle] K, #[may_dangle] V, A: Alloc + Clone> Drop for IntoIter<K, V, A> {
+    fn drop(&mut self) {
+        unsafe {
+            // Drop all remaining elements
+            if needs_drop::<(K, V)>() {
+                while let Some(bucket) = self.iter.next() {
+                    bucket.drop();
+                }
+            }
 
-    #[inline]
-    fn next(&mut self) -> Option<(SafeHash, K, V)> {
-        self.iter.next().map(|raw| {
-            unsafe {
-                self.table.as_mut().size -= 1;
-                let (k, v) = ptr::read(raw.pair());
-                (SafeHash { hash: ptr::replace(&mut *raw.hash(), EMPTY_BUCKET) }, k, v)
+            // Free the table
+            if let Some((ptr, layout)) = self.allocation {
+                self.alloc.dealloc(ptr.as_ptr(), layout);
             }
-        })
+        }
+    }
+}
+
+impl<'a, K, V, A: Alloc + Clone> Iterator for Drain<'a, K, V, A> {
+    type Item = (K, V);
+
+    #[inline]
+    fn next(&mut self) -> Option<(K, V)> {
+        self.iter.next().map(|bucket| unsafe { bucket.read() })
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
@@ -1121,77 +1180,75 @@ impl<'a, K, V> Iterator for Drain<'a, K, V> {
     }
 }
 
-impl<'a, K, V> ExactSizeIterator for Drain<'a, K, V> {
+impl<'a, K, V, A: Alloc + Clone> ExactSizeIterator for Drain<'a, K, V, A> {
     fn len(&self) -> usize {
-        self.iter.len()
+        self.iter.items
     }
 }
 
-impl<'a, K: 'a, V: 'a> Drop for Drain<'a, K, V> {
+impl<'a, K: 'a, V: 'a, A: Alloc + Clone> Drop for Drain<'a, K, V, A> {
     fn drop(&mut self) {
-        for _ in self {}
+        unsafe {
+            // Drop all remaining elements. Note that this may panic.
+            if needs_drop::<(K, V)>() {
+                while let Some(bucket) = self.iter.next() {
+                    bucket.drop();
+                }
+            }
+
+            // Reset the contents of the table now that all elements have been
+            // dropped, and move it back into its original place, dropping the
+            // empty table that stood in for it.
+            self.table.clear_no_drop();
+            *self.orig_table.as_ptr() = ptr::read(&*self.table);
+        }
     }
 }
 
-impl<K: Clone, V: Clone> Clone for RawTable<K, V> {
-    fn clone(&self) -> RawTable<K, V> {
+impl<K: Clone, V: Clone, A: Alloc + Clone> Clone for RawTable<K, V, A> {
+    fn clone(&self) -> RawTable<K, V, A> {
+        if self.is_empty_singleton() {
+            return RawTable::new_in(self.alloc.clone());
+        }
+
         unsafe {
-            let cap = self.capacity();
-            let mut new_ht = RawTable::new_uninitialized(cap);
-
-            let mut new_buckets = new_ht.raw_bucket_at(0);
-            let mut buckets = self.raw_bucket_at(0);
-            while buckets.idx < cap {
-                *new_buckets.hash() = *buckets.hash();
-                if *new_buckets.hash() != EMPTY_BUCKET {
-                    let pair_ptr = buckets.pair();
-                    let kv = ((*pair_ptr).0.clone(), (*pair_ptr).1.clone());
-                    ptr::write(new_buckets.pair(), kv);
-                }
-                buckets.idx += 1;
-                new_buckets.idx += 1;
+            let result = RawTable::try_with_buckets(self.buckets(), self.alloc.clone());
+            let mut new_table = infallible(&self.alloc, result);
+
+            // Elements are only marked as present once they've been cloned,
+            // so that dropping the new table after a panic in `clone` drops
+            // exactly those.
+            for from in self.raw_iter() {
+                let index = self.bucket_index(&from);
+                let (key, value) = from.into_refs();
+                new_table.bucket(index).write(key.clone(), value.clone());
+                new_table.set_ctrl(index, *self.ctrl(index));
+                new_table.items += 1;
             }
 
-            new_ht.size = self.size();
-            new_ht.set_tag(self.tag());
+            // Tombstones haven't been copied over, so this may be less than
+            // what the new table could take.
+            new_table.growth_left = self.growth_left;
 
-            new_ht
+            new_table
         }
     }
 }
 
-unsafe impl<#[may_dangle] K, #[may_dangle] V> Drop for RawTable<K, V> {
+unsafe impl<#[may_dangle] K, #[may_dangle] V, A: Alloc + Clone> Drop for RawTable<K, V, A> {
     fn drop(&mut self) {
-        if self.capacity() == 0 {
+        if self.is_empty_singleton() {
             return;
         }
 
-        // This is done in reverse because we've likely partially taken
-        // some elements out with `.into_iter()` from the front.
-        // Check if the size is 0, so we don't do a useless scan when
-        // dropping empty tables such as on resize.
-        // Also avoid double drop of elements that have been already moved out.
         unsafe {
+            // avoid linear runtime for types that don't need drop
             if needs_drop::<(K, V)>() {
-                // avoid linear runtime for types that don't need drop
-                self.rev_drop_buckets();
+                for bucket in self.raw_iter() {
+                    bucket.drop();
+                }
             }
-        }
-
-        let hashes_size = self.capacity() * size_of::<HashUint>();
-        let pairs_size = self.capacity() * size_of::<(K, V)>();
-        let (align, size, oflo) = calculate_allocation(hashes_size,
-                                                       align_of::<HashUint>(),
-                                                       pairs_size,
-                                                       align_of::<(K, V)>());
-
-        debug_assert!(!oflo, "should be impossible");
-
-        unsafe {
-            Heap.dealloc(self.hashes.ptr() as *mut u8,
-                         Layout::from_size_align(size, align).unwrap());
-            // Remember how everything was allocated out of one buffer
-            // during initialization? We only need one call to free here.
+            self.free_buckets();
         }
     }
 }
@@ -53,6 +53,7 @@
 
 use alloc::allocator;
 use any::TypeId;
+use backtrace::Backtrace;
 use borrow::Cow;
 use cell;
 use char;
@@ -153,6 +154,54 @@ pub trait Error: Debug + Display {
     #[stable(feature = "rust1", since = "1.0.0")]
     fn cause(&self) -> Option<&Error> { None }
 
+    /// Returns a stack backtrace, if available, of where this error occurred.
+    ///
+    /// This lets an error record where in the code it was created, for
+    /// example by capturing a [`Backtrace`] when it's constructed and handing
+    /// it out here. Errors that don't record a backtrace return `None`, which
+    /// is the default.
+    ///
+    /// [`Backtrace`]: ../backtrace/struct.Backtrace.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(backtrace)]
+    /// use std::backtrace::Backtrace;
+    /// use std::error::Error;
+    /// use std::fmt;
+    ///
+    /// #[derive(Debug)]
+    /// struct MyError {
+    ///     backtrace: Backtrace,
+    /// }
+    ///
+    /// impl fmt::Display for MyError {
+    ///     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    ///         write!(f, "something went wrong")
+    ///     }
+    /// }
+    ///
+    /// impl Error for MyError {
+    ///     fn description(&self) -> &str {
+    ///         "something went wrong"
+    ///     }
+    ///
+    ///     fn backtrace(&self) -> Option<&Backtrace> {
+    ///         Some(&self.backtrace)
+    ///     }
+    /// }
+    ///
+    /// let err = MyError { backtrace: Backtrace::capture() };
+    /// if let Some(backtrace) = err.backtrace() {
+    ///     println!("{} at:\n{}", err, backtrace);
+    /// }
+    /// ```
+    #[unstable(feature = "backtrace", issue = "0")]
+    fn backtrace(&self) -> Option<&Backtrace> {
+        None
+    }
+
     /// Get the `TypeId` of `self`
     #[doc(hidden)]
     #[unstable(feature = "error_type_id",
@@ -257,6 +306,13 @@ impl Error for allocator::CannotReallocInPlace {
     }
 }
 
+#[unstable(feature = "try_reserve", reason = "new API", issue = "0")]
+impl Error for allocator::CollectionAllocErr {
+    fn description(&self) -> &str {
+        allocator::CollectionAllocErr::description(self)
+    }
+}
+
 #[stable(feature = "rust1", since = "1.0.0")]
 impl Error for str::ParseBoolError {
     fn description(&self) -> &str { "failed to parse bool" }
@@ -334,6 +390,10 @@ impl<T: Error> Error for Box<T> {
     fn cause(&self) -> Option<&Error> {
         Error::cause(&**self)
     }
+
+    fn backtrace(&self) -> Option<&Backtrace> {
+        Error::backtrace(&**self)
+    }
 }
 
 #[stable(feature = "fmt_error", since = "1.11.0")]
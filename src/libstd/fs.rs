@@ -19,7 +19,7 @@
 
 use fmt;
 use ffi::OsString;
-use io::{self, SeekFrom, Seek, Read, Initializer, Write};
+use io::{self, SeekFrom, Seek, Read, Initializer, Write, IoSlice, IoSliceMut};
 use path::{Path, PathBuf};
 use sys::fs as fs_imp;
 use sys_common::{AsInnerMut, FromInner, AsInner, IntoInner};
@@ -529,6 +529,181 @@ impl File {
     pub fn set_permissions(&self, perm: Permissions) -> io::Result<()> {
         self.inner.set_permissions(perm.0)
     }
+
+    /// Acquires an exclusive advisory lock on the file, blocking until it can
+    /// be acquired.
+    ///
+    /// At most one handle may hold an exclusive lock on a file at a time, and
+    /// no other handle may hold a shared lock while it does. The lock is
+    /// released by [`unlock`], or when the handle is closed.
+    ///
+    /// # Platform-specific behavior
+    ///
+    /// This function currently corresponds to the `flock` function on Unix
+    /// with `LOCK_EX`, and the `LockFileEx` function on Windows with
+    /// `LOCKFILE_EXCLUSIVE_LOCK`. Note that, this
+    /// [may change in the future][changes].
+    ///
+    /// On Unix the lock is advisory: it only affects other callers of these
+    /// locking methods, not reads and writes. It is shared with handles
+    /// created by [`try_clone`] and only released once all of them are
+    /// closed. If this handle already holds a lock, it is converted to an
+    /// exclusive one.
+    ///
+    /// On Windows the lock is mandatory: while it is held, reads and writes
+    /// through any other handle fail. It belongs to this handle alone. Locks
+    /// taken through the same handle are never converted. Asking for an
+    /// exclusive lock while this handle already holds a lock blocks, or fails
+    /// with `try_lock`. A shared lock taken on top of an existing lock is
+    /// added to it, and each lock needs its own call to [`unlock`].
+    ///
+    /// [changes]: ../io/index.html#platform-specific-behavior
+    /// [`unlock`]: #method.unlock
+    /// [`try_clone`]: #method.try_clone
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(file_lock)]
+    /// use std::fs::File;
+    ///
+    /// # fn foo() -> std::io::Result<()> {
+    /// let f = File::create("foo.lock")?;
+    /// f.lock()?;
+    /// // ... access the shared resource ...
+    /// f.unlock()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[unstable(feature = "file_lock", issue = "0")]
+    pub fn lock(&self) -> io::Result<()> {
+        self.inner.lock()
+    }
+
+    /// Acquires a shared advisory lock on the file, blocking until it can be
+    /// acquired.
+    ///
+    /// Any number of handles may hold a shared lock on a file at once, as
+    /// long as no handle holds an exclusive one. See [`lock`] for how locks
+    /// are released.
+    ///
+    /// # Platform-specific behavior
+    ///
+    /// This function currently corresponds to the `flock` function on Unix
+    /// with `LOCK_SH`, and the `LockFileEx` function on Windows. Note that,
+    /// this [may change in the future][changes].
+    ///
+    /// On Windows a shared lock is mandatory, so writes through any other
+    /// handle fail while it is held. See [`lock`] for how locks taken through
+    /// the same handle interact.
+    ///
+    /// [changes]: ../io/index.html#platform-specific-behavior
+    /// [`lock`]: #method.lock
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(file_lock)]
+    /// use std::fs::File;
+    ///
+    /// # fn foo() -> std::io::Result<()> {
+    /// let f = File::open("foo.lock")?;
+    /// f.lock_shared()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[unstable(feature = "file_lock", issue = "0")]
+    pub fn lock_shared(&self) -> io::Result<()> {
+        self.inner.lock_shared()
+    }
+
+    /// Tries to acquire an exclusive advisory lock on the file without
+    /// blocking.
+    ///
+    /// # Errors
+    ///
+    /// If another handle holds a lock on the file, this returns an error of
+    /// kind [`ErrorKind::WouldBlock`].
+    ///
+    /// [`ErrorKind::WouldBlock`]: ../io/enum.ErrorKind.html#variant.WouldBlock
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(file_lock)]
+    /// use std::fs::File;
+    /// use std::io::ErrorKind;
+    ///
+    /// # fn foo() -> std::io::Result<()> {
+    /// let f = File::create("foo.lock")?;
+    /// match f.try_lock() {
+    ///     Ok(()) => println!("got the lock"),
+    ///     Err(ref e) if e.kind() == ErrorKind::WouldBlock => println!("lock is busy"),
+    ///     Err(e) => return Err(e),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[unstable(feature = "file_lock", issue = "0")]
+    pub fn try_lock(&self) -> io::Result<()> {
+        self.inner.try_lock()
+    }
+
+    /// Tries to acquire a shared advisory lock on the file without blocking.
+    ///
+    /// # Errors
+    ///
+    /// If another handle holds an exclusive lock on the file, this returns
+    /// an error of kind [`ErrorKind::WouldBlock`].
+    ///
+    /// [`ErrorKind::WouldBlock`]: ../io/enum.ErrorKind.html#variant.WouldBlock
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(file_lock)]
+    /// use std::fs::File;
+    ///
+    /// # fn foo() -> std::io::Result<()> {
+    /// let f = File::open("foo.lock")?;
+    /// if f.try_lock_shared().is_ok() {
+    ///     println!("got a shared lock");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[unstable(feature = "file_lock", issue = "0")]
+    pub fn try_lock_shared(&self) -> io::Result<()> {
+        self.inner.try_lock_shared()
+    }
+
+    /// Releases any lock this handle holds on the file.
+    ///
+    /// # Platform-specific behavior
+    ///
+    /// On Windows, where locks taken through the same handle add up instead
+    /// of replacing each other, this releases only one of them, starting with
+    /// the exclusive one. See [`lock`].
+    ///
+    /// [`lock`]: #method.lock
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(file_lock)]
+    /// use std::fs::File;
+    ///
+    /// # fn foo() -> std::io::Result<()> {
+    /// let f = File::create("foo.lock")?;
+    /// f.lock()?;
+    /// f.unlock()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[unstable(feature = "file_lock", issue = "0")]
+    pub fn unlock(&self) -> io::Result<()> {
+        self.inner.unlock()
+    }
 }
 
 impl AsInner<fs_imp::File> for File {
@@ -558,6 +733,10 @@ impl Read for File {
         self.inner.read(buf)
     }
 
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut]) -> io::Result<usize> {
+        self.inner.read_vectored(bufs)
+    }
+
     #[inline]
     unsafe fn initializer(&self) -> Initializer {
         Initializer::nop()
@@ -568,6 +747,10 @@ impl Write for File {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
         self.inner.write(buf)
     }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice]) -> io::Result<usize> {
+        self.inner.write_vectored(bufs)
+    }
     fn flush(&mut self) -> io::Result<()> { self.inner.flush() }
 }
 #[stable(feature = "rust1", since = "1.0.0")]
@@ -582,6 +765,10 @@ impl<'a> Read for &'a File {
         self.inner.read(buf)
     }
 
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut]) -> io::Result<usize> {
+        self.inner.read_vectored(bufs)
+    }
+
     #[inline]
     unsafe fn initializer(&self) -> Initializer {
         Initializer::nop()
@@ -592,6 +779,10 @@ impl<'a> Write for &'a File {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
         self.inner.write(buf)
     }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice]) -> io::Result<usize> {
+        self.inner.write_vectored(bufs)
+    }
     fn flush(&mut self) -> io::Result<()> { self.inner.flush() }
 }
 #[stable(feature = "rust1", since = "1.0.0")]
@@ -1491,6 +1682,9 @@ pub fn rename<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> io::Result<()>
 /// This function currently corresponds to the `open` function in Unix
 /// with `O_RDONLY` for `from` and `O_WRONLY`, `O_CREAT`, and `O_TRUNC` for `to`.
 /// `O_CLOEXEC` is set for returned file descriptors.
+/// On Linux, the data is copied with `copy_file_range` when possible, falling
+/// back to `sendfile` or a read/write loop otherwise, and holes in a sparse
+/// `from` file are kept as holes in `to`.
 /// On Windows, this function currently corresponds to `CopyFileEx`. Alternate
 /// NTFS streams are copied but only the size of the main stream is returned by
 /// this function.
@@ -1986,7 +2180,7 @@ mod tests {
     use io::prelude::*;
 
     use fs::{self, File, OpenOptions};
-    use io::{ErrorKind, SeekFrom};
+    use io::{self, ErrorKind, IoSlice, IoSliceMut, SeekFrom};
     use path::Path;
     use rand::{StdRng, Rng};
     use str;
@@ -2070,6 +2264,62 @@ mod tests {
         check!(fs::remove_file(filename));
     }
 
+    #[test]
+    fn file_test_io_vectored() {
+        let tmpdir = tmpdir();
+        let filename = &tmpdir.join("file_rt_io_vectored.txt");
+        {
+            let mut write_stream = check!(File::create(filename));
+            let bufs = [IoSlice::new(b"hello"), IoSlice::new(b""), IoSlice::new(b" world")];
+            let n = check!(write_stream.write_vectored(&bufs));
+            assert!(n > 0);
+            check!(write_stream.write_all(&b"hello world"[n..]));
+        }
+        {
+            let mut read_stream = check!(File::open(filename));
+            let mut first = [0; 5];
+            let mut second = [0; 6];
+            let n = {
+                let mut bufs = [IoSliceMut::new(&mut first), IoSliceMut::new(&mut second)];
+                check!(read_stream.read_vectored(&mut bufs))
+            };
+            assert!(n > 0);
+            let mut contents = first.to_vec();
+            contents.extend_from_slice(&second);
+            check!(read_stream.read_exact(&mut contents[n..]));
+            assert_eq!(&contents[..], b"hello world");
+        }
+        check!(fs::remove_file(filename));
+    }
+
+    #[test]
+    fn file_test_io_vectored_many_buffers() {
+        // More buffers than `IOV_MAX` on any platform; the excess must be
+        // ignored rather than make the call fail.
+        let tmpdir = tmpdir();
+        let filename = &tmpdir.join("file_rt_io_vectored_many_buffers.txt");
+        let data = (0..2048).map(|i| i as u8).collect::<Vec<u8>>();
+        {
+            let mut write_stream = check!(File::create(filename));
+            let bufs = data.chunks(1).map(IoSlice::new).collect::<Vec<_>>();
+            let n = check!(write_stream.write_vectored(&bufs));
+            assert!(n > 0);
+            check!(write_stream.write_all(&data[n..]));
+        }
+        {
+            let mut read_stream = check!(File::open(filename));
+            let mut contents = vec![0; data.len()];
+            let n = {
+                let mut bufs = contents.chunks_mut(1).map(IoSliceMut::new).collect::<Vec<_>>();
+                check!(read_stream.read_vectored(&mut bufs))
+            };
+            assert!(n > 0);
+            check!(read_stream.read_exact(&mut contents[n..]));
+            assert_eq!(contents, data);
+        }
+        check!(fs::remove_file(filename));
+    }
+
     #[test]
     fn invalid_path_raises() {
         let tmpdir = tmpdir();
@@ -2722,6 +2972,47 @@ mod tests {
         assert_eq!(check!(out_path.metadata()).len(), copied_len);
     }
 
+    #[test]
+    fn copy_file_with_holes() {
+        let tmp = tmpdir();
+        let in_path = tmp.join("in.txt");
+        let out_path = tmp.join("out.txt");
+        let mut f = check!(File::create(&in_path));
+        check!(f.write(b"head"));
+        check!(f.seek(SeekFrom::Start(1 << 20)));
+        check!(f.write(b"middle"));
+        check!(f.set_len(2 << 20));
+        drop(f);
+
+        assert_eq!(check!(fs::copy(&in_path, &out_path)), 2 << 20);
+        let mut contents = Vec::new();
+        check!(check!(File::open(&out_path)).read_to_end(&mut contents));
+        assert_eq!(contents.len(), 2 << 20);
+        assert_eq!(&contents[..4], b"head");
+        assert_eq!(&contents[1 << 20..(1 << 20) + 6], b"middle");
+        assert!(contents[4..1 << 20].iter().all(|&b| b == 0));
+        assert!(contents[(1 << 20) + 6..].iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn io_copy_between_files() {
+        let tmp = tmpdir();
+        let in_path = tmp.join("in.txt");
+        let out_path = tmp.join("out.txt");
+        check!(check!(File::create(&in_path)).write(b"lettuce"));
+
+        let mut reader = check!(File::open(&in_path));
+        check!(reader.seek(SeekFrom::Start(3)));
+        let mut writer = check!(File::create(&out_path));
+        check!(writer.write(b"carrot "));
+        assert_eq!(check!(io::copy(&mut reader, &mut writer)), 4);
+        assert_eq!(check!(reader.seek(SeekFrom::Current(0))), 7);
+
+        let mut contents = String::new();
+        check!(check!(File::open(&out_path)).read_to_string(&mut contents));
+        assert_eq!(contents, "carrot tuce");
+    }
+
     #[test]
     fn symlinks_work() {
         let tmpdir = tmpdir();
@@ -3075,6 +3366,56 @@ mod tests {
         check!(f1.write_all(b"!"));
     }
 
+    #[test]
+    fn file_lock_multiple_shared() {
+        let tmpdir = tmpdir();
+        let filename = &tmpdir.join("file_lock_multiple_shared_test.txt");
+        let f1 = check!(File::create(filename));
+        let f2 = check!(OpenOptions::new().write(true).open(filename));
+
+        // Check that we can acquire concurrent shared locks
+        check!(f1.lock_shared());
+        check!(f2.lock_shared());
+        check!(f1.unlock());
+        check!(f2.unlock());
+        check!(f1.try_lock_shared());
+        check!(f2.try_lock_shared());
+    }
+
+    #[test]
+    fn file_lock_blocking() {
+        let tmpdir = tmpdir();
+        let filename = &tmpdir.join("file_lock_blocking_test.txt");
+        let f1 = check!(File::create(filename));
+        let f2 = check!(OpenOptions::new().write(true).open(filename));
+
+        // Check that shared locks block exclusive locks
+        check!(f1.lock_shared());
+        assert_eq!(f2.try_lock().unwrap_err().kind(), ErrorKind::WouldBlock);
+        check!(f1.unlock());
+        check!(f2.try_lock());
+
+        // Check that exclusive locks block shared and exclusive locks
+        assert_eq!(f1.try_lock_shared().unwrap_err().kind(), ErrorKind::WouldBlock);
+        assert_eq!(f1.try_lock().unwrap_err().kind(), ErrorKind::WouldBlock);
+        check!(f2.unlock());
+        check!(f1.try_lock());
+    }
+
+    #[test]
+    fn file_lock_drop() {
+        let tmpdir = tmpdir();
+        let filename = &tmpdir.join("file_lock_drop_test.txt");
+        let f1 = check!(File::create(filename));
+        let f2 = check!(OpenOptions::new().write(true).open(filename));
+
+        // Check that the lock is released when the file is closed
+        check!(f1.lock());
+        assert_eq!(f2.try_lock().unwrap_err().kind(), ErrorKind::WouldBlock);
+        drop(f1);
+        check!(f2.try_lock());
+    }
+
     #[test]
     #[cfg(not(windows))]
     fn unlink_readonly() {
@@ -12,7 +12,7 @@ use io::prelude::*;
 
 use core::convert::TryInto;
 use cmp;
-use io::{self, Initializer, SeekFrom, Error, ErrorKind};
+use io::{self, Initializer, SeekFrom, Error, ErrorKind, IoSlice, IoSliceMut};
 
 /// A `Cursor` wraps another type and provides it with a
 /// [`Seek`] implementation.
@@ -230,6 +230,18 @@ impl<T> Read for Cursor<T> where T: AsRef<[u8]> {
         Ok(n)
     }
 
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut]) -> io::Result<usize> {
+        let mut nread = 0;
+        for buf in bufs {
+            let n = self.read(buf)?;
+            nread += n;
+            if n < buf.len() {
+                break;
+            }
+        }
+        Ok(nread)
+    }
+
     fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
         let n = buf.len();
         Read::read_exact(&mut self.fill_buf()?, buf)?;
@@ -261,6 +273,19 @@ impl<'a> Write for Cursor<&'a mut [u8]> {
         self.pos += amt as u64;
         Ok(amt)
     }
+
+    #[inline]
+    fn write_vectored(&mut self, bufs: &[IoSlice]) -> io::Result<usize> {
+        let mut nwritten = 0;
+        for buf in bufs {
+            let n = self.write(buf)?;
+            nwritten += n;
+            if n < buf.len() {
+                break;
+            }
+        }
+        Ok(nwritten)
+    }
     fn flush(&mut self) -> io::Result<()> { Ok(()) }
 }
 
@@ -291,6 +316,14 @@ impl Write for Cursor<Vec<u8>> {
         self.set_position((pos + buf.len()) as u64);
         Ok(buf.len())
     }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice]) -> io::Result<usize> {
+        let mut nwritten = 0;
+        for buf in bufs {
+            nwritten += self.write(buf)?;
+        }
+        Ok(nwritten)
+    }
     fn flush(&mut self) -> io::Result<()> { Ok(()) }
 }
 
@@ -303,13 +336,26 @@ impl Write for Cursor<Box<[u8]>> {
         self.pos += amt as u64;
         Ok(amt)
     }
+
+    #[inline]
+    fn write_vectored(&mut self, bufs: &[IoSlice]) -> io::Result<usize> {
+        let mut nwritten = 0;
+        for buf in bufs {
+            let n = self.write(buf)?;
+            nwritten += n;
+            if n < buf.len() {
+                break;
+            }
+        }
+        Ok(nwritten)
+    }
     fn flush(&mut self) -> io::Result<()> { Ok(()) }
 }
 
 #[cfg(test)]
 mod tests {
     use io::prelude::*;
-    use io::{Cursor, SeekFrom};
+    use io::{Cursor, SeekFrom, IoSlice, IoSliceMut};
 
     #[test]
     fn test_vec_writer() {
@@ -331,6 +377,17 @@ mod tests {
         assert_eq!(&writer.get_ref()[..], b);
     }
 
+    #[test]
+    fn test_mem_writer_vectored() {
+        let mut writer = Cursor::new(Vec::new());
+        assert_eq!(writer.write_vectored(&[IoSlice::new(&[0])]).unwrap(), 1);
+        assert_eq!(writer.write_vectored(&[IoSlice::new(&[1, 2]),
+                                           IoSlice::new(&[3])]).unwrap(), 3);
+        assert_eq!(writer.position(), 4);
+        let b: &[_] = &[0, 1, 2, 3];
+        assert_eq!(&writer.get_ref()[..], b);
+    }
+
     #[test]
     fn test_box_slice_writer() {
         let mut writer = Cursor::new(vec![0u8; 9].into_boxed_slice());
@@ -370,6 +427,23 @@ mod tests {
         assert_eq!(buf, b);
     }
 
+    #[test]
+    fn test_buf_writer_vectored() {
+        let mut buf = [0 as u8; 5];
+        {
+            let mut writer = Cursor::new(&mut buf[..]);
+            assert_eq!(writer.write_vectored(&[IoSlice::new(&[0, 1]),
+                                               IoSlice::new(&[]),
+                                               IoSlice::new(&[2])]).unwrap(), 3);
+            assert_eq!(writer.position(), 3);
+            assert_eq!(writer.write_vectored(&[IoSlice::new(&[3, 4, 5]),
+                                               IoSlice::new(&[6])]).unwrap(), 2);
+            assert_eq!(writer.position(), 5);
+        }
+        let b: &[_] = &[0, 1, 2, 3, 4];
+        assert_eq!(buf, b);
+    }
+
     #[test]
     fn test_buf_writer_seek() {
         let mut buf = [0 as u8; 8];
@@ -430,6 +504,23 @@ mod tests {
         assert_eq!(reader.read(&mut buf).unwrap(), 0);
     }
 
+    #[test]
+    fn test_mem_reader_vectored() {
+        let mut reader = Cursor::new(vec![0, 1, 2, 3, 4, 5, 6, 7]);
+        let mut buf1 = [0; 2];
+        let mut buf2 = [0; 4];
+        assert_eq!(reader.read_vectored(&mut [IoSliceMut::new(&mut buf1),
+                                              IoSliceMut::new(&mut []),
+                                              IoSliceMut::new(&mut buf2)]).unwrap(), 6);
+        assert_eq!(reader.position(), 6);
+        assert_eq!(buf1, [0, 1]);
+        assert_eq!(buf2, [2, 3, 4, 5]);
+        assert_eq!(reader.read_vectored(&mut [IoSliceMut::new(&mut buf1),
+                                              IoSliceMut::new(&mut buf2)]).unwrap(), 2);
+        assert_eq!(buf1, [6, 7]);
+        assert_eq!(reader.read_vectored(&mut [IoSliceMut::new(&mut buf1)]).unwrap(), 0);
+    }
+
     #[test]
     fn test_boxed_slice_reader() {
         let mut reader = Cursor::new(vec![0, 1, 2, 3, 4, 5, 6, 7].into_boxed_slice());
@@ -13,6 +13,7 @@
 use fmt;
 use io::{self, Read, Initializer, Write, ErrorKind, BufRead};
 use mem;
+use sys;
 
 /// Copies the entire contents of a reader into a writer.
 ///
@@ -29,6 +30,18 @@ use mem;
 /// `write` returns an error. All instances of `ErrorKind::Interrupted` are
 /// handled by this function and the underlying operation is retried.
 ///
+/// # Platform-specific behavior
+///
+/// On Linux, if both `reader` and `writer` are unbuffered file descriptors,
+/// such as a [`File`], [`TcpStream`] or a child process pipe, the data may be
+/// moved within the kernel using `copy_file_range`, `sendfile` or `splice`
+/// instead of being read into a buffer. Note that this [may change in the
+/// future][changes].
+///
+/// [`File`]: ../fs/struct.File.html
+/// [`TcpStream`]: ../net/struct.TcpStream.html
+/// [changes]: ../io/index.html#platform-specific-behavior
+///
 /// # Examples
 ///
 /// ```
@@ -48,6 +61,15 @@ use mem;
 #[stable(feature = "rust1", since = "1.0.0")]
 pub fn copy<R: ?Sized, W: ?Sized>(reader: &mut R, writer: &mut W) -> io::Result<u64>
     where R: Read, W: Write
+{
+    sys::copy_impl(reader, writer)
+}
+
+/// The read/write loop behind `copy`, used whenever the platform has no
+/// faster way to move data between `reader` and `writer`.
+pub(crate) fn generic_copy<R: ?Sized, W: ?Sized>(reader: &mut R, writer: &mut W)
+                                                -> io::Result<u64>
+    where R: Read, W: Write
 {
     let mut buf = unsafe {
         let mut buf: [u8; super::DEFAULT_BUF_SIZE] = mem::uninitialized();
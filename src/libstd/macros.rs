@@ -210,57 +210,203 @@ macro_rules! eprintln {
     ($fmt:expr, $($arg:tt)*) => (eprint!(concat!($fmt, "\n"), $($arg)*));
 }
 
-/// A macro to select an event from a number of receivers.
+/// Waits on a set of channel operations and runs the first one to complete.
 ///
-/// This macro is used to wait for the first event to occur on a number of
-/// receivers. It places no restrictions on the types of receivers given to
-/// this macro, this can be viewed as a heterogeneous select.
+/// This macro blocks until one of the listed send or receive operations on
+/// [`mpmc`] channels becomes ready, performs it, and evaluates the body of
+/// its case. If several operations are ready at the same time, a random one
+/// is chosen. Each case has one of these forms:
+///
+/// * `recv(r) -> res => body` receives from `r`, binding a
+///   `Result<T, RecvError>` to the pattern `res`.
+/// * `send(s, msg) -> res => body` sends `msg` into `s`, binding a
+///   `Result<(), SendError<T>>` to `res`. `msg` is only evaluated if this
+///   operation is the one selected.
+/// * `default => body` runs if no operation is ready right away.
+/// * `default(timeout) => body` runs if no operation becomes ready within
+///   the given `Duration`.
+///
+/// At most one `default` case may be given. Without one, the macro blocks
+/// until an operation completes. For a set of operations only known at
+/// runtime, use [`Select`] instead.
+///
+/// [`mpmc`]: sync/mpmc/index.html
+/// [`Select`]: sync/mpmc/struct.Select.html
 ///
 /// # Examples
 ///
 /// ```
-/// #![feature(mpsc_select)]
+/// #![feature(mpmc_channel, mpmc_select)]
 ///
+/// use std::sync::mpmc;
 /// use std::thread;
-/// use std::sync::mpsc;
+/// use std::time::Duration;
 ///
 /// // two placeholder functions for now
-/// fn long_running_thread() {}
+/// fn long_running_thread() { thread::sleep(Duration::from_millis(100)); }
 /// fn calculate_the_answer() -> u32 { 42 }
 ///
-/// let (tx1, rx1) = mpsc::channel();
-/// let (tx2, rx2) = mpsc::channel();
+/// let (tx1, rx1) = mpmc::channel();
+/// let (tx2, rx2) = mpmc::channel();
 ///
 /// thread::spawn(move|| { long_running_thread(); tx1.send(()).unwrap(); });
 /// thread::spawn(move|| { tx2.send(calculate_the_answer()).unwrap(); });
 ///
 /// select! {
-///     _ = rx1.recv() => println!("the long running thread finished first"),
-///     answer = rx2.recv() => {
+///     recv(rx1) -> _ => println!("the long running thread finished first"),
+///     recv(rx2) -> answer => {
 ///         println!("the answer was: {}", answer.unwrap());
 ///     }
 /// }
-/// # drop(rx1.recv());
-/// # drop(rx2.recv());
 /// ```
 ///
-/// For more information about select, see the `std::sync::mpsc::Select` structure.
+/// Sending with a timeout:
+///
+/// ```
+/// #![feature(mpmc_channel, mpmc_select)]
+///
+/// use std::sync::mpmc;
+/// use std::time::Duration;
+///
+/// let (tx, _rx) = mpmc::sync_channel(0);
+///
+/// let sent = select! {
+///     send(tx, 1) -> res => res.is_ok(),
+///     default(Duration::from_millis(10)) => false,
+/// };
+/// assert!(!sent);
+/// ```
 #[macro_export]
-#[unstable(feature = "mpsc_select", issue = "27800")]
+#[unstable(feature = "mpmc_select", issue = "0")]
+#[allow_internal_unstable]
 macro_rules! select {
-    (
-        $($name:pat = $rx:ident.$meth:ident() => $code:expr),+
-    ) => ({
-        use $crate::sync::mpsc::Select;
-        let sel = Select::new();
-        $( let mut $rx = sel.handle(&$rx); )+
-        unsafe {
-            $( $rx.add(); )+
+    // Parsing: collect the operation cases and the `default` cases into two
+    // separate lists.
+    (@parse [$($cases:tt)*] [$($default:tt)*]) => (
+        select!(@bind [] [$($cases)*] [$($default)*])
+    );
+    (@parse $cases:tt $default:tt recv($r:expr) -> $res:pat => $($tail:tt)*) => (
+        select!(@body $cases $default (recv ($r) ($res)) $($tail)*)
+    );
+    (@parse $cases:tt $default:tt send($s:expr, $m:expr) -> $res:pat => $($tail:tt)*) => (
+        select!(@body $cases $default (send ($s) ($m) ($res)) $($tail)*)
+    );
+    (@parse $cases:tt $default:tt default => $($tail:tt)*) => (
+        select!(@body $cases $default (default) $($tail)*)
+    );
+    (@parse $cases:tt $default:tt default($t:expr) => $($tail:tt)*) => (
+        select!(@body $cases $default (timeout ($t)) $($tail)*)
+    );
+    (@parse $cases:tt $default:tt $($tail:tt)*) => (
+        compile_error!("expected `recv(..)`, `send(..)` or `default` case in `select!`")
+    );
+
+    // Split off the body of a case, turning it into a block. A body starting
+    // with braces is always taken to be a block.
+    (@body $cases:tt $default:tt ($($case:tt)*) { $($body:tt)* }, $($tail:tt)*) => (
+        select!(@push $cases $default ($($case)* { $($body)* }) $($tail)*)
+    );
+    (@body $cases:tt $default:tt ($($case:tt)*) { $($body:tt)* } $($tail:tt)*) => (
+        select!(@push $cases $default ($($case)* { $($body)* }) $($tail)*)
+    );
+    (@body $cases:tt $default:tt ($($case:tt)*) $body:expr, $($tail:tt)*) => (
+        select!(@push $cases $default ($($case)* { $body }) $($tail)*)
+    );
+    (@body $cases:tt $default:tt ($($case:tt)*) $body:expr) => (
+        select!(@push $cases $default ($($case)* { $body }))
+    );
+    (@push [$($cases:tt)*] [$($default:tt)*] (default $($case:tt)*) $($tail:tt)*) => (
+        select!(@parse [$($cases)*] [$($default)* (default $($case)*)] $($tail)*)
+    );
+    (@push [$($cases:tt)*] [$($default:tt)*] (timeout $($case:tt)*) $($tail:tt)*) => (
+        select!(@parse [$($cases)*] [$($default)* (timeout $($case)*)] $($tail)*)
+    );
+    (@push [$($cases:tt)*] $default:tt $case:tt $($tail:tt)*) => (
+        select!(@parse [$($cases)* $case] $default $($tail)*)
+    );
+
+    // Evaluate every channel expression exactly once, binding it to a fresh
+    // variable.
+    (@bind [$($done:tt)*] [(recv ($r:expr) $($rest:tt)*) $($cases:tt)*] $default:tt) => (
+        match &$r {
+            r => select!(@bind [$($done)* (recv r $($rest)*)] [$($cases)*] $default),
         }
-        let ret = sel.wait();
-        $( if ret == $rx.id() { let $name = $rx.$meth(); $code } else )+
-        { unreachable!() }
-    })
+    );
+    (@bind [$($done:tt)*] [(send ($s:expr) $($rest:tt)*) $($cases:tt)*] $default:tt) => (
+        match &$s {
+            s => select!(@bind [$($done)* (send s $($rest)*)] [$($cases)*] $default),
+        }
+    );
+    (@bind [$($cases:tt)*] [] $default:tt) => ({
+        let mut sel = $crate::sync::mpmc::Select::new();
+        $( select!(@register sel $cases); )*
+        select!(@select sel [$($cases)*] $default)
+    });
+
+    (@register $sel:ident (recv $r:ident $($rest:tt)*)) => ({
+        $sel.recv($r);
+    });
+    (@register $sel:ident (send $s:ident $($rest:tt)*)) => ({
+        $sel.send($s);
+    });
+
+    // Select an operation, falling back to the `default` case if any.
+    (@select $sel:ident $cases:tt []) => ({
+        let oper = $sel.select();
+        let index = oper.index();
+        select!(@complete oper index $cases)
+    });
+    (@select $sel:ident $cases:tt [(default $body:block)]) => (
+        match $sel.try_select() {
+            Ok(oper) => {
+                let index = oper.index();
+                select!(@complete oper index $cases)
+            }
+            Err(_) => $body,
+        }
+    );
+    (@select $sel:ident $cases:tt [(timeout ($t:expr) $body:block)]) => (
+        match $sel.select_timeout($t) {
+            Ok(oper) => {
+                let index = oper.index();
+                select!(@complete oper index $cases)
+            }
+            Err(_) => $body,
+        }
+    );
+    (@select $sel:ident $cases:tt $default:tt) => (
+        compile_error!("`select!` accepts at most one `default` case")
+    );
+
+    // Complete the selected operation, found by counting down its index.
+    (@complete $oper:ident $index:ident []) => ({
+        let _ = $index;
+        unreachable!()
+    });
+    (@complete $oper:ident $index:ident
+     [(recv $r:ident ($res:pat) $body:block) $($cases:tt)*]) => (
+        if $index == 0 {
+            let $res = $oper.recv($r);
+            $body
+        } else {
+            let $index = $index - 1;
+            select!(@complete $oper $index [$($cases)*])
+        }
+    );
+    (@complete $oper:ident $index:ident
+     [(send $s:ident ($m:expr) ($res:pat) $body:block) $($cases:tt)*]) => (
+        if $index == 0 {
+            let $res = $oper.send($s, $m);
+            $body
+        } else {
+            let $index = $index - 1;
+            select!(@complete $oper $index [$($cases)*])
+        }
+    );
+
+    ($($tokens:tt)*) => (
+        select!(@parse [] [] $($tokens)*)
+    );
 }
 
 #[cfg(test)]
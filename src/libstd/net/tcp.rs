@@ -11,7 +11,7 @@
 use io::prelude::*;
 
 use fmt;
-use io::{self, Initializer};
+use io::{self, Initializer, IoSlice, IoSliceMut};
 use net::{ToSocketAddrs, SocketAddr, Shutdown};
 use sys_common::net as net_imp;
 use sys_common::{AsInner, FromInner, IntoInner};
@@ -548,6 +548,10 @@ impl TcpStream {
 impl Read for TcpStream {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> { self.0.read(buf) }
 
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut]) -> io::Result<usize> {
+        self.0.read_vectored(bufs)
+    }
+
     #[inline]
     unsafe fn initializer(&self) -> Initializer {
         Initializer::nop()
@@ -556,12 +560,21 @@ impl Read for TcpStream {
 #[stable(feature = "rust1", since = "1.0.0")]
 impl Write for TcpStream {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.write(buf) }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice]) -> io::Result<usize> {
+        self.0.write_vectored(bufs)
+    }
+
     fn flush(&mut self) -> io::Result<()> { Ok(()) }
 }
 #[stable(feature = "rust1", since = "1.0.0")]
 impl<'a> Read for &'a TcpStream {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> { self.0.read(buf) }
 
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut]) -> io::Result<usize> {
+        self.0.read_vectored(bufs)
+    }
+
     #[inline]
     unsafe fn initializer(&self) -> Initializer {
         Initializer::nop()
@@ -570,6 +583,11 @@ impl<'a> Read for &'a TcpStream {
 #[stable(feature = "rust1", since = "1.0.0")]
 impl<'a> Write for &'a TcpStream {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.write(buf) }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice]) -> io::Result<usize> {
+        self.0.write_vectored(bufs)
+    }
+
     fn flush(&mut self) -> io::Result<()> { Ok(()) }
 }
 
@@ -887,7 +905,7 @@ impl fmt::Debug for TcpListener {
 
 #[cfg(all(test, not(target_os = "emscripten")))]
 mod tests {
-    use io::ErrorKind;
+    use io::{ErrorKind, IoSlice, IoSliceMut};
     use io::prelude::*;
     use net::*;
     use net::test::{next_test_ip4, next_test_ip6};
@@ -989,6 +1007,35 @@ mod tests {
         })
     }
 
+    #[test]
+    fn read_write_vectored() {
+        each_ip(&mut |addr| {
+            let acceptor = t!(TcpListener::bind(&addr));
+
+            // More buffers than `IOV_MAX` on any platform; the excess must be
+            // ignored rather than make the call fail.
+            let data = (0..2048).map(|i| i as u8).collect::<Vec<u8>>();
+            let expected = data.clone();
+            let _t = thread::spawn(move|| {
+                let mut stream = t!(TcpStream::connect(&addr));
+                let bufs = data.chunks(1).map(IoSlice::new).collect::<Vec<_>>();
+                let n = t!(stream.write_vectored(&bufs));
+                assert!(n > 0);
+                t!(stream.write_all(&data[n..]));
+            });
+
+            let (mut stream, _) = t!(acceptor.accept());
+            let mut received = vec![0; expected.len()];
+            let n = {
+                let mut bufs = received.chunks_mut(1).map(IoSliceMut::new).collect::<Vec<_>>();
+                t!(stream.read_vectored(&mut bufs))
+            };
+            assert!(n > 0);
+            t!(stream.read_exact(&mut received[n..]));
+            assert_eq!(received, expected);
+        })
+    }
+
     #[test]
     fn read_eof() {
         each_ip(&mut |addr| {
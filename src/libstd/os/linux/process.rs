@@ -0,0 +1,238 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+
+//! Linux-specific extensions to primitives in the `std::process` module.
+
+#![unstable(feature = "linux_pidfd", issue = "0")]
+
+use fmt;
+use io::{self, Error, ErrorKind};
+use libc;
+use os::unix::io::{AsFd, AsRawFd, BorrowedFd, FromRawFd, IntoRawFd, OwnedFd, RawFd};
+use process;
+use sys;
+use sys::fd::FileDesc;
+use sys_common::{AsInner, AsInnerMut, FromInner, IntoInner};
+
+/// A file descriptor referring to a process, also known as a pidfd.
+///
+/// Unlike a process ID, a pidfd always refers to the same process, even
+/// after it has exited and been reaped, so it can't be used to accidentally
+/// signal an unrelated process that was given the same ID.
+///
+/// The descriptor becomes readable once the process has exited. This makes
+/// it possible to wait for it together with other events with `poll` or
+/// `epoll`, after which [`Child::wait`] returns without blocking.
+///
+/// A pidfd for a child is requested with [`CommandExt::create_pidfd`] and
+/// obtained through [`ChildExt`]. Closing it has no effect on the process.
+///
+/// [`Child::wait`]: ../../../process/struct.Child.html#method.wait
+/// [`CommandExt::create_pidfd`]: trait.CommandExt.html#tymethod.create_pidfd
+/// [`ChildExt`]: trait.ChildExt.html
+#[unstable(feature = "linux_pidfd", issue = "0")]
+pub struct PidFd {
+    inner: FileDesc,
+}
+
+#[unstable(feature = "linux_pidfd", issue = "0")]
+impl PidFd {
+    /// Forces the process to exit by sending it `SIGKILL`.
+    ///
+    /// Unlike [`Child::kill`], this can't tell whether the process has
+    /// already exited, in which case the signal is silently dropped.
+    ///
+    /// [`Child::kill`]: ../../../process/struct.Child.html#method.kill
+    #[unstable(feature = "linux_pidfd", issue = "0")]
+    pub fn kill(&self) -> io::Result<()> {
+        self.signal(libc::SIGKILL)
+    }
+
+    /// Sends the signal `signal` to the process.
+    #[unstable(feature = "linux_pidfd", issue = "0")]
+    pub fn signal(&self, signal: i32) -> io::Result<()> {
+        sys::process::pidfd_send_signal(&self.inner, signal)
+    }
+
+    /// Waits for the process to exit and returns its exit status.
+    ///
+    /// Unlike [`Child::wait`], this doesn't reap the process: it stays a
+    /// zombie, so its process ID can't be reused, until the `Child` it
+    /// belongs to is waited on. That wait then returns the same status
+    /// without blocking.
+    ///
+    /// # Errors
+    ///
+    /// Waiting on a pidfd requires Linux 5.4 or later. It also fails if the
+    /// process isn't a child of the calling process or was already reaped.
+    ///
+    /// [`Child::wait`]: ../../../process/struct.Child.html#method.wait
+    #[unstable(feature = "linux_pidfd", issue = "0")]
+    pub fn wait(&self) -> io::Result<process::ExitStatus> {
+        sys::process::pidfd_wait(&self.inner).map(process::ExitStatus::from_inner)
+    }
+}
+
+impl AsInner<FileDesc> for PidFd {
+    fn as_inner(&self) -> &FileDesc { &self.inner }
+}
+
+impl FromInner<FileDesc> for PidFd {
+    fn from_inner(inner: FileDesc) -> PidFd {
+        PidFd { inner: inner }
+    }
+}
+
+impl IntoInner<FileDesc> for PidFd {
+    fn into_inner(self) -> FileDesc { self.inner }
+}
+
+#[unstable(feature = "linux_pidfd", issue = "0")]
+impl AsRawFd for PidFd {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.raw()
+    }
+}
+
+#[unstable(feature = "linux_pidfd", issue = "0")]
+impl FromRawFd for PidFd {
+    unsafe fn from_raw_fd(fd: RawFd) -> PidFd {
+        PidFd::from_inner(FileDesc::new(fd))
+    }
+}
+
+#[unstable(feature = "linux_pidfd", issue = "0")]
+impl IntoRawFd for PidFd {
+    fn into_raw_fd(self) -> RawFd {
+        self.inner.into_raw()
+    }
+}
+
+#[unstable(feature = "linux_pidfd", issue = "0")]
+impl AsFd for PidFd {
+    fn as_fd(&self) -> BorrowedFd {
+        unsafe { BorrowedFd::borrow_raw_fd(self.as_raw_fd()) }
+    }
+}
+
+#[unstable(feature = "linux_pidfd", issue = "0")]
+impl From<OwnedFd> for PidFd {
+    fn from(owned_fd: OwnedFd) -> PidFd {
+        unsafe { PidFd::from_raw_fd(owned_fd.into_raw_fd()) }
+    }
+}
+
+#[unstable(feature = "linux_pidfd", issue = "0")]
+impl From<PidFd> for OwnedFd {
+    fn from(pidfd: PidFd) -> OwnedFd {
+        unsafe { OwnedFd::from_raw_fd(pidfd.into_raw_fd()) }
+    }
+}
+
+#[unstable(feature = "linux_pidfd", issue = "0")]
+impl fmt::Debug for PidFd {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("PidFd").field("fd", &self.inner.raw()).finish()
+    }
+}
+
+/// Linux-specific extensions to `std::process::Command`
+#[unstable(feature = "linux_pidfd", issue = "0")]
+pub trait CommandExt {
+    /// Sets whether a [`PidFd`] should be created for the spawned child.
+    ///
+    /// Once set, [`Child::kill`] and `os::unix::process::ChildExt::signal`
+    /// go through the pidfd for as long as the child owns it.
+    ///
+    /// Creating the pidfd requires Linux 5.3 or later. On older kernels the
+    /// spawn still succeeds and the error is reported by [`ChildExt::pidfd`]
+    /// and [`ChildExt::take_pidfd`] instead.
+    ///
+    /// [`PidFd`]: struct.PidFd.html
+    /// [`Child::kill`]: ../../../process/struct.Child.html#method.kill
+    /// [`ChildExt::pidfd`]: trait.ChildExt.html#tymethod.pidfd
+    /// [`ChildExt::take_pidfd`]: trait.ChildExt.html#tymethod.take_pidfd
+    #[unstable(feature = "linux_pidfd", issue = "0")]
+    fn create_pidfd(&mut self, val: bool) -> &mut process::Command;
+}
+
+#[unstable(feature = "linux_pidfd", issue = "0")]
+impl CommandExt for process::Command {
+    fn create_pidfd(&mut self, val: bool) -> &mut process::Command {
+        self.as_inner_mut().create_pidfd(val);
+        self
+    }
+}
+
+/// Linux-specific extensions to `std::process::Child`
+#[unstable(feature = "linux_pidfd", issue = "0")]
+pub trait ChildExt {
+    /// Obtains a reference to the [`PidFd`] created for this child.
+    ///
+    /// # Errors
+    ///
+    /// Fails if [`CommandExt::create_pidfd`] wasn't used to spawn the child,
+    /// if the pidfd could not be created, or if it was already taken with
+    /// [`take_pidfd`].
+    ///
+    /// [`PidFd`]: struct.PidFd.html
+    /// [`CommandExt::create_pidfd`]: trait.CommandExt.html#tymethod.create_pidfd
+    /// [`take_pidfd`]: #tymethod.take_pidfd
+    #[unstable(feature = "linux_pidfd", issue = "0")]
+    fn pidfd(&self) -> io::Result<&PidFd>;
+
+    /// Takes ownership of the [`PidFd`] created for this child.
+    ///
+    /// Afterwards, signals sent through the `Child` use its process ID again.
+    ///
+    /// # Errors
+    ///
+    /// Fails in the same cases as [`pidfd`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(linux_pidfd)]
+    ///
+    /// use std::os::linux::process::{ChildExt, CommandExt};
+    /// use std::os::unix::io::AsRawFd;
+    /// use std::process::Command;
+    ///
+    /// # fn foo() -> std::io::Result<()> {
+    /// let mut child = Command::new("sleep").arg("100").create_pidfd(true).spawn()?;
+    /// let pidfd = child.take_pidfd()?;
+    /// println!("poll {} to find out when the child exits", pidfd.as_raw_fd());
+    /// pidfd.kill()?;
+    /// child.wait()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`PidFd`]: struct.PidFd.html
+    /// [`pidfd`]: #tymethod.pidfd
+    #[unstable(feature = "linux_pidfd", issue = "0")]
+    fn take_pidfd(&mut self) -> io::Result<PidFd>;
+}
+
+#[unstable(feature = "linux_pidfd", issue = "0")]
+impl ChildExt for process::Child {
+    fn pidfd(&self) -> io::Result<&PidFd> {
+        self.as_inner().pidfd().ok_or_else(no_pidfd)
+    }
+
+    fn take_pidfd(&mut self) -> io::Result<PidFd> {
+        self.as_inner_mut().take_pidfd().ok_or_else(no_pidfd)
+    }
+}
+
+fn no_pidfd() -> Error {
+    Error::new(ErrorKind::Other, "no pidfd was created for this child")
+}
@@ -187,6 +187,10 @@ impl AsInner<imp::Process> for Child {
     fn as_inner(&self) -> &imp::Process { &self.handle }
 }
 
+impl AsInnerMut<imp::Process> for Child {
+    fn as_inner_mut(&mut self) -> &mut imp::Process { &mut self.handle }
+}
+
 impl FromInner<(imp::Process, imp::StdioPipes)> for Child {
     fn from_inner((handle, io): (imp::Process, imp::StdioPipes)) -> Child {
         Child {
@@ -1535,6 +1539,107 @@ mod tests {
         assert!(Command::new("/bin/ls").uid(0).gid(0).spawn().is_err());
     }
 
+    #[test]
+    #[cfg_attr(target_os = "android", ignore)]
+    #[cfg(all(unix, not(target_os = "fuchsia")))]
+    fn signal_works() {
+        use os::unix::process::{ChildExt, ExitStatusExt};
+        use libc;
+
+        let mut p = Command::new("/bin/sh")
+                            .arg("-c").arg("read a")
+                            .stdin(Stdio::piped())
+                            .spawn().unwrap();
+        p.signal(libc::SIGTERM).unwrap();
+        assert_eq!(p.wait().unwrap().signal(), Some(libc::SIGTERM));
+        assert!(p.signal(libc::SIGTERM).is_err());
+    }
+
+    #[test]
+    #[cfg_attr(target_os = "android", ignore)]
+    #[cfg(all(unix, not(target_os = "fuchsia")))]
+    fn process_group_and_setsid_work() {
+        use os::unix::process::CommandExt;
+        use libc;
+
+        let mut p = Command::new("/bin/sh")
+                            .arg("-c").arg("read a")
+                            .stdin(Stdio::piped())
+                            .process_group(0)
+                            .spawn().unwrap();
+        let pid = p.id() as libc::pid_t;
+        assert_eq!(unsafe { libc::getpgid(pid) }, pid);
+        assert_eq!(unsafe { libc::getsid(pid) }, unsafe { libc::getsid(0) });
+        p.kill().unwrap();
+        p.wait().unwrap();
+
+        let mut p = Command::new("/bin/sh")
+                            .arg("-c").arg("read a")
+                            .stdin(Stdio::piped())
+                            .setsid(true)
+                            .spawn().unwrap();
+        let pid = p.id() as libc::pid_t;
+        assert_eq!(unsafe { libc::getpgid(pid) }, pid);
+        assert_eq!(unsafe { libc::getsid(pid) }, pid);
+        p.kill().unwrap();
+        p.wait().unwrap();
+
+        assert!(Command::new("true").setsid(true).process_group(0).spawn().is_err());
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn pidfd_works() {
+        use os::linux::process::{ChildExt, CommandExt};
+        use os::unix::process::ExitStatusExt;
+
+        let mut p = Command::new("/bin/sh")
+                            .arg("-c").arg("read a")
+                            .stdin(Stdio::piped())
+                            .create_pidfd(true)
+                            .spawn().unwrap();
+        let pidfd = match p.take_pidfd() {
+            Ok(pidfd) => pidfd,
+            // Kernels before 5.3 don't support pidfds.
+            Err(_) => {
+                p.kill().unwrap();
+                p.wait().unwrap();
+                return
+            }
+        };
+        assert!(p.pidfd().is_err());
+        pidfd.kill().unwrap();
+        assert_eq!(p.wait().unwrap().signal(), Some(9));
+
+        let mut p = Command::new("true").spawn().unwrap();
+        assert!(p.pidfd().is_err());
+        p.wait().unwrap();
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn pidfd_wait() {
+        use os::linux::process::{ChildExt, CommandExt};
+
+        let mut p = Command::new("/bin/sh")
+                            .arg("-c").arg("exit 3")
+                            .create_pidfd(true)
+                            .spawn().unwrap();
+        // Kernels before 5.3 don't support pidfds, and those before 5.4
+        // can't wait on them.
+        let status = match p.pidfd().and_then(|pidfd| pidfd.wait()) {
+            Ok(status) => status,
+            Err(_) => {
+                assert_eq!(p.wait().unwrap().code(), Some(3));
+                return
+            }
+        };
+        assert_eq!(status.code(), Some(3));
+        // Waiting on the pidfd left reaping the process to the `Child`.
+        assert_eq!(p.pidfd().unwrap().wait().unwrap().code(), Some(3));
+        assert_eq!(p.wait().unwrap().code(), Some(3));
+    }
+
     #[test]
     #[cfg_attr(target_os = "android", ignore)]
     fn test_process_status() {
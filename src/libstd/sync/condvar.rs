@@ -9,10 +9,8 @@
 // except according to those terms.
 
 use fmt;
-use sync::atomic::{AtomicUsize, Ordering};
 use sync::{mutex, MutexGuard, PoisonError};
 use sys_common::condvar as sys;
-use sys_common::mutex as sys_mutex;
 use sys_common::poison::{self, LockResult};
 use time::Duration;
 
@@ -118,8 +116,7 @@ impl WaitTimeoutResult {
 /// ```
 #[stable(feature = "rust1", since = "1.0.0")]
 pub struct Condvar {
-    inner: Box<sys::Condvar>,
-    mutex: AtomicUsize,
+    inner: sys::MovableCondvar,
 }
 
 impl Condvar {
@@ -134,15 +131,11 @@ impl Condvar {
     /// let condvar = Condvar::new();
     /// ```
     #[stable(feature = "rust1", since = "1.0.0")]
-    pub fn new() -> Condvar {
-        let mut c = Condvar {
-            inner: box sys::Condvar::new(),
-            mutex: AtomicUsize::new(0),
-        };
-        unsafe {
-            c.inner.init();
+    #[rustc_const_unstable(feature = "const_locks")]
+    pub const fn new() -> Condvar {
+        Condvar {
+            inner: sys::MovableCondvar::new(),
         }
-        c
     }
 
     /// Blocks the current thread until this condition variable receives a
@@ -167,10 +160,11 @@ impl Condvar {
     ///
     /// # Panics
     ///
-    /// This function will [`panic!`] if it is used with more than one mutex
+    /// This function may [`panic!`] if it is used with more than one mutex
     /// over time. Each condition variable is dynamically bound to exactly one
-    /// mutex to ensure defined behavior across platforms. If this functionality
-    /// is not desired, then unsafe primitives in `sys` are provided.
+    /// mutex to ensure defined behavior across platforms. On Linux and Android
+    /// condition variables work with any mutex, so this panic no longer
+    /// happens there.
     ///
     /// [`notify_one`]: #method.notify_one
     /// [`notify_all`]: #method.notify_all
@@ -208,7 +202,6 @@ impl Condvar {
                        -> LockResult<MutexGuard<'a, T>> {
         let poisoned = unsafe {
             let lock = mutex::guard_lock(&guard);
-            self.verify(lock);
             self.inner.wait(lock);
             mutex::guard_poison(&guard).get()
         };
@@ -342,7 +335,6 @@ impl Condvar {
                                -> LockResult<(MutexGuard<'a, T>, WaitTimeoutResult)> {
         let (poisoned, result) = unsafe {
             let lock = mutex::guard_lock(&guard);
-            self.verify(lock);
             let success = self.inner.wait_timeout(lock, dur);
             (mutex::guard_poison(&guard).get(), WaitTimeoutResult(!success))
         };
@@ -434,24 +426,6 @@ impl Condvar {
     pub fn notify_all(&self) {
         unsafe { self.inner.notify_all() }
     }
-
-    fn verify(&self, mutex: &sys_mutex::Mutex) {
-        let addr = mutex as *const _ as usize;
-        match self.mutex.compare_and_swap(0, addr, Ordering::SeqCst) {
-            // If we got out 0, then we have successfully bound the mutex to
-            // this cvar.
-            0 => {}
-
-            // If we get out a value that's the same as `addr`, then someone
-            // already beat us to the punch.
-            n if n == addr => {}
-
-            // Anything else and we're using more than one mutex on this cvar,
-            // which is currently disallowed.
-            _ => panic!("attempted to use a condition variable with two \
-                         mutexes"),
-        }
-    }
 }
 
 #[stable(feature = "std_debug", since = "1.16.0")]
@@ -469,13 +443,6 @@ impl Default for Condvar {
     }
 }
 
-#[stable(feature = "rust1", since = "1.0.0")]
-impl Drop for Condvar {
-    fn drop(&mut self) {
-        unsafe { self.inner.destroy() }
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use sync::mpsc::channel;
@@ -509,6 +476,23 @@ mod tests {
         drop(g);
     }
 
+    #[test]
+    fn const_new() {
+        static M: Mutex<bool> = Mutex::new(false);
+        static C: Condvar = Condvar::new();
+
+        let t = thread::spawn(|| {
+            *M.lock().unwrap() = true;
+            C.notify_one();
+        });
+        let mut ready = M.lock().unwrap();
+        while !*ready {
+            ready = C.wait(ready).unwrap();
+        }
+        drop(ready);
+        t.join().unwrap();
+    }
+
     #[test]
     #[cfg_attr(target_os = "emscripten", ignore)]
     fn notify_all() {
@@ -605,6 +589,7 @@ mod tests {
     #[test]
     #[should_panic]
     #[cfg_attr(target_os = "emscripten", ignore)]
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
     fn two_mutexes() {
         let m = Arc::new(Mutex::new(()));
         let m2 = m.clone();
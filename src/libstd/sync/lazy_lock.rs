@@ -0,0 +1,211 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+
+use cell::Cell;
+use fmt;
+use ops::Deref;
+use panic::{RefUnwindSafe, UnwindSafe};
+use sync::OnceLock;
+
+/// A value which is initialized on the first access.
+///
+/// This type is a thread-safe `Lazy`, and can be used in statics. Threads
+/// racing to access an uninitialized `LazyLock` block until the first of
+/// them has run the initializing function.
+///
+/// If the initializing function panics, the `LazyLock` is poisoned and
+/// every later access panics as well. Accessing a `LazyLock` from its own
+/// initializing function panics.
+///
+/// # Examples
+///
+/// ```
+/// #![feature(once_cell)]
+///
+/// use std::collections::HashMap;
+/// use std::sync::LazyLock;
+///
+/// static HASHMAP: LazyLock<HashMap<i32, String>> = LazyLock::new(|| {
+///     println!("initializing");
+///     let mut m = HashMap::new();
+///     m.insert(13, "Spica".to_string());
+///     m.insert(74, "Hoyten".to_string());
+///     m
+/// });
+///
+/// fn main() {
+///     println!("ready");
+///     std::thread::spawn(|| {
+///         println!("{:?}", HASHMAP.get(&13));
+///     }).join().unwrap();
+///     println!("{:?}", HASHMAP.get(&74));
+///
+///     // Prints:
+///     //   ready
+///     //   initializing
+///     //   Some("Spica")
+///     //   Some("Hoyten")
+/// }
+/// ```
+#[unstable(feature = "once_cell", issue = "0")]
+pub struct LazyLock<T, F = fn() -> T> {
+    cell: OnceLock<T>,
+    init: Cell<Option<F>>,
+}
+
+// We never create a `&F` from a `&LazyLock<T, F>` so it is fine to not
+// impl `Sync` for `F`. We do create a `&mut Option<F>` in `force`, but this
+// is guarded by the `OnceLock`.
+#[unstable(feature = "once_cell", issue = "0")]
+unsafe impl<T, F: Send> Sync for LazyLock<T, F> where OnceLock<T>: Sync {}
+
+#[unstable(feature = "once_cell", issue = "0")]
+impl<T, F: UnwindSafe> RefUnwindSafe for LazyLock<T, F> where OnceLock<T>: RefUnwindSafe {}
+#[unstable(feature = "once_cell", issue = "0")]
+impl<T, F: UnwindSafe> UnwindSafe for LazyLock<T, F> where OnceLock<T>: UnwindSafe {}
+
+#[unstable(feature = "once_cell", issue = "0")]
+impl<T: fmt::Debug, F> fmt::Debug for LazyLock<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("LazyLock").field("cell", &self.cell).field("init", &"..").finish()
+    }
+}
+
+impl<T, F> LazyLock<T, F> {
+    /// Creates a new lazy value with the given initializing function.
+    #[unstable(feature = "once_cell", issue = "0")]
+    pub const fn new(f: F) -> LazyLock<T, F> {
+        LazyLock { cell: OnceLock::new(), init: Cell::new(Some(f)) }
+    }
+}
+
+impl<T, F: FnOnce() -> T> LazyLock<T, F> {
+    /// Forces the evaluation of this lazy value and returns a reference to
+    /// the result.
+    ///
+    /// This is equivalent to the `Deref` impl, but is explicit.
+    ///
+    /// # Panics
+    ///
+    /// If the initializing function panics, the panic is propagated to the
+    /// caller, and the lazy value is poisoned: every later access panics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(once_cell)]
+    ///
+    /// use std::sync::LazyLock;
+    ///
+    /// let lazy = LazyLock::new(|| 92);
+    ///
+    /// assert_eq!(LazyLock::force(&lazy), &92);
+    /// assert_eq!(&*lazy, &92);
+    /// ```
+    #[unstable(feature = "once_cell", issue = "0")]
+    pub fn force(this: &LazyLock<T, F>) -> &T {
+        // The `OnceLock` runs at most one initializer at a time, so `init`
+        // is never accessed concurrently. A panicking initializer leaves the
+        // `OnceLock` empty but `init` taken, which is how poisoning is
+        // detected on the next access.
+        this.cell.get_or_init(|| match this.init.take() {
+            Some(f) => f(),
+            None => panic!("LazyLock instance has previously been poisoned"),
+        })
+    }
+}
+
+#[unstable(feature = "once_cell", issue = "0")]
+impl<T, F: FnOnce() -> T> Deref for LazyLock<T, F> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        LazyLock::force(self)
+    }
+}
+
+#[unstable(feature = "once_cell", issue = "0")]
+impl<T: Default> Default for LazyLock<T> {
+    /// Creates a new lazy value using `Default` as the initializing function.
+    fn default() -> LazyLock<T> {
+        LazyLock::new(T::default)
+    }
+}
+
+#[cfg(all(test, not(target_os = "emscripten")))]
+mod tests {
+    use cell::Cell;
+    use panic;
+    use sync::atomic::{AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
+    use thread;
+    use super::LazyLock;
+
+    #[test]
+    fn lazy_lock_default() {
+        static CALLED: AtomicUsize = ATOMIC_USIZE_INIT;
+
+        struct Foo(u8);
+        impl Default for Foo {
+            fn default() -> Foo {
+                CALLED.fetch_add(1, Ordering::SeqCst);
+                Foo(42)
+            }
+        }
+
+        let lazy: LazyLock<Foo> = LazyLock::default();
+        assert_eq!(CALLED.load(Ordering::SeqCst), 0);
+        assert_eq!(lazy.0, 42);
+        assert_eq!(CALLED.load(Ordering::SeqCst), 1);
+        assert_eq!(lazy.0, 42);
+        assert_eq!(CALLED.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn lazy_lock_new() {
+        let called = Cell::new(0);
+        let x = LazyLock::new(|| {
+            called.set(called.get() + 1);
+            92
+        });
+
+        assert_eq!(called.get(), 0);
+        assert_eq!(*x, 92);
+        assert_eq!(called.get(), 1);
+        assert_eq!(*x, 92);
+        assert_eq!(called.get(), 1);
+    }
+
+    #[test]
+    fn static_sync_lazy() {
+        static XS: LazyLock<Vec<i32>> = LazyLock::new(|| {
+            let mut xs = Vec::new();
+            xs.push(1);
+            xs.push(2);
+            xs.push(3);
+            xs
+        });
+
+        thread::spawn(|| {
+            assert_eq!(&*XS, &vec![1, 2, 3]);
+        }).join().unwrap();
+
+        assert_eq!(&*XS, &vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn lazy_lock_poisoning() {
+        let x: LazyLock<String> = LazyLock::new(|| panic!("kaboom"));
+        for _ in 0..2 {
+            let res = panic::catch_unwind(panic::AssertUnwindSafe(|| x.len()));
+            assert!(res.is_err());
+        }
+    }
+}
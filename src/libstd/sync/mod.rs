@@ -34,11 +34,18 @@ pub use self::once::{Once, OnceState, ONCE_INIT};
 pub use sys_common::poison::{PoisonError, TryLockError, TryLockResult, LockResult};
 #[stable(feature = "rust1", since = "1.0.0")]
 pub use self::rwlock::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+#[unstable(feature = "once_cell", issue = "0")]
+pub use self::once_lock::OnceLock;
+#[unstable(feature = "once_cell", issue = "0")]
+pub use self::lazy_lock::LazyLock;
 
+pub mod mpmc;
 pub mod mpsc;
 
 mod barrier;
 mod condvar;
+mod lazy_lock;
 mod mutex;
 mod once;
+mod once_lock;
 mod rwlock;
@@ -0,0 +1,142 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Benchmarks of the channels in the SPSC, MPSC, MPMC and rendezvous cases.
+
+#![cfg(test)]
+
+extern crate test;
+
+use self::test::Bencher;
+use super::{channel, sync_channel, Receiver, Sender};
+use thread;
+
+const MESSAGES: usize = 10_000;
+const THREADS: usize = 4;
+const CAP: usize = 64;
+
+// One thread sends everything to one receiver.
+fn spsc(b: &mut Bencher, new: fn() -> (Sender<usize>, Receiver<usize>)) {
+    b.iter(|| {
+        let (tx, rx) = new();
+        let sender = thread::spawn(move || {
+            for i in 0..MESSAGES {
+                tx.send(i).unwrap();
+            }
+        });
+        for _ in 0..MESSAGES {
+            rx.recv().unwrap();
+        }
+        sender.join().unwrap();
+    })
+}
+
+// `THREADS` threads send to one receiver.
+fn mpsc(b: &mut Bencher, new: fn() -> (Sender<usize>, Receiver<usize>)) {
+    b.iter(|| {
+        let (tx, rx) = new();
+        let senders = (0..THREADS).map(|_| {
+            let tx = tx.clone();
+            thread::spawn(move || {
+                for i in 0..MESSAGES / THREADS {
+                    tx.send(i).unwrap();
+                }
+            })
+        }).collect::<Vec<_>>();
+        for _ in 0..MESSAGES / THREADS * THREADS {
+            rx.recv().unwrap();
+        }
+        for sender in senders {
+            sender.join().unwrap();
+        }
+    })
+}
+
+// `THREADS` threads send to `THREADS` receivers.
+fn mpmc(b: &mut Bencher, new: fn() -> (Sender<usize>, Receiver<usize>)) {
+    b.iter(|| {
+        let (tx, rx) = new();
+        let threads = (0..THREADS).flat_map(|_| {
+            let tx = tx.clone();
+            let rx = rx.clone();
+            let sender = thread::spawn(move || {
+                for i in 0..MESSAGES / THREADS {
+                    tx.send(i).unwrap();
+                }
+            });
+            let receiver = thread::spawn(move || {
+                for _ in 0..MESSAGES / THREADS {
+                    rx.recv().unwrap();
+                }
+            });
+            vec![sender, receiver]
+        }).collect::<Vec<_>>();
+        for t in threads {
+            t.join().unwrap();
+        }
+    })
+}
+
+fn unbounded() -> (Sender<usize>, Receiver<usize>) {
+    channel()
+}
+
+fn bounded() -> (Sender<usize>, Receiver<usize>) {
+    sync_channel(CAP)
+}
+
+fn rendezvous() -> (Sender<usize>, Receiver<usize>) {
+    sync_channel(0)
+}
+
+#[bench]
+fn spsc_unbounded(b: &mut Bencher) {
+    spsc(b, unbounded)
+}
+
+#[bench]
+fn spsc_bounded(b: &mut Bencher) {
+    spsc(b, bounded)
+}
+
+#[bench]
+fn spsc_rendezvous(b: &mut Bencher) {
+    spsc(b, rendezvous)
+}
+
+#[bench]
+fn mpsc_unbounded(b: &mut Bencher) {
+    mpsc(b, unbounded)
+}
+
+#[bench]
+fn mpsc_bounded(b: &mut Bencher) {
+    mpsc(b, bounded)
+}
+
+#[bench]
+fn mpsc_rendezvous(b: &mut Bencher) {
+    mpsc(b, rendezvous)
+}
+
+#[bench]
+fn mpmc_unbounded(b: &mut Bencher) {
+    mpmc(b, unbounded)
+}
+
+#[bench]
+fn mpmc_bounded(b: &mut Bencher) {
+    mpmc(b, bounded)
+}
+
+#[bench]
+fn mpmc_rendezvous(b: &mut Bencher) {
+    mpmc(b, rendezvous)
+}
@@ -0,0 +1,993 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+
+//! Multi-producer, multi-consumer FIFO queue communication primitives.
+//!
+//! This module provides message-based communication over channels whose
+//! sending and receiving halves can both be cloned and shared between
+//! threads. Channels are created with one of two functions:
+//!
+//! * [`channel`] creates a channel of unbounded capacity.
+//!
+//! * [`sync_channel`] creates a channel of bounded capacity. A bound of zero
+//!   makes every send wait for a matching receive, handing the message over
+//!   directly.
+//!
+//! Every message is received by exactly one of the [`Receiver`]s. Once all
+//! [`Sender`]s are dropped, receivers drain the remaining messages and then
+//! get an error. Once all [`Receiver`]s are dropped, sends fail and any
+//! messages still buffered are dropped.
+//!
+//! Waiting on several channel operations at once is done with the
+//! [`select!`] macro or, for a dynamic set of operations, [`Select`].
+//!
+//! [`channel`]: fn.channel.html
+//! [`sync_channel`]: fn.sync_channel.html
+//! [`Sender`]: struct.Sender.html
+//! [`Receiver`]: struct.Receiver.html
+//! [`select!`]: ../../macro.select.html
+//! [`Select`]: struct.Select.html
+//!
+//! # Examples
+//!
+//! Distributing work among several consumers:
+//!
+//! ```
+//! #![feature(mpmc_channel)]
+//!
+//! use std::sync::mpmc::channel;
+//! use std::thread;
+//!
+//! let (tx, rx) = channel();
+//!
+//! let workers = (0..4).map(|_| {
+//!     let rx = rx.clone();
+//!     thread::spawn(move || rx.iter().sum::<u32>())
+//! }).collect::<Vec<_>>();
+//! drop(rx);
+//!
+//! for i in 1..101 {
+//!     tx.send(i).unwrap();
+//! }
+//! drop(tx);
+//!
+//! let total: u32 = workers.into_iter().map(|w| w.join().unwrap()).sum();
+//! assert_eq!(total, 5050);
+//! ```
+
+#![unstable(feature = "mpmc_channel", issue = "0")]
+
+use fmt;
+use panic::{RefUnwindSafe, UnwindSafe};
+use time::{Duration, Instant};
+
+#[unstable(feature = "mpmc_channel", issue = "0")]
+pub use self::error::{RecvError, RecvTimeoutError, SendError, SendTimeoutError};
+#[unstable(feature = "mpmc_channel", issue = "0")]
+pub use self::error::{TryRecvError, TrySendError};
+#[unstable(feature = "mpmc_select", issue = "0")]
+pub use self::error::{SelectTimeoutError, TrySelectError};
+#[unstable(feature = "mpmc_select", issue = "0")]
+pub use self::select::{Select, SelectedOperation};
+
+use self::select::{Operation, SelectHandle, Token};
+
+mod array;
+mod bench;
+mod cache_aligned;
+mod context;
+mod counter;
+mod error;
+mod list;
+mod select;
+mod utils;
+mod waker;
+mod zero;
+
+/// Creates a channel of unbounded capacity.
+///
+/// Sending never blocks. Both halves can be cloned to get multiple senders
+/// and multiple receivers.
+///
+/// # Examples
+///
+/// ```
+/// #![feature(mpmc_channel)]
+///
+/// use std::sync::mpmc::channel;
+/// use std::thread;
+///
+/// let (tx, rx) = channel();
+/// let rx2 = rx.clone();
+///
+/// thread::spawn(move || {
+///     tx.send(1).unwrap();
+///     tx.send(2).unwrap();
+/// });
+///
+/// assert_eq!(rx.recv().unwrap() + rx2.recv().unwrap(), 3);
+/// ```
+#[unstable(feature = "mpmc_channel", issue = "0")]
+pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
+    let (s, r) = counter::new(list::Channel::new());
+    let s = Sender { flavor: SenderFlavor::List(s) };
+    let r = Receiver { flavor: ReceiverFlavor::List(r) };
+    (s, r)
+}
+
+/// Creates a channel of bounded capacity.
+///
+/// The channel holds at most `cap` messages at a time, and sending blocks
+/// while it is full. A capacity of zero creates a rendezvous channel, where
+/// each send blocks until a receiver takes the message.
+///
+/// # Examples
+///
+/// ```
+/// #![feature(mpmc_channel)]
+///
+/// use std::sync::mpmc::{sync_channel, TrySendError};
+///
+/// let (tx, rx) = sync_channel(1);
+///
+/// tx.send(1).unwrap();
+/// assert_eq!(tx.try_send(2), Err(TrySendError::Full(2)));
+/// assert_eq!(rx.recv(), Ok(1));
+/// ```
+#[unstable(feature = "mpmc_channel", issue = "0")]
+pub fn sync_channel<T>(cap: usize) -> (Sender<T>, Receiver<T>) {
+    if cap == 0 {
+        let (s, r) = counter::new(zero::Channel::new());
+        let s = Sender { flavor: SenderFlavor::Zero(s) };
+        let r = Receiver { flavor: ReceiverFlavor::Zero(r) };
+        (s, r)
+    } else {
+        let (s, r) = counter::new(array::Channel::with_capacity(cap));
+        let s = Sender { flavor: SenderFlavor::Array(s) };
+        let r = Receiver { flavor: ReceiverFlavor::Array(r) };
+        (s, r)
+    }
+}
+
+/// The sending side of a channel.
+///
+/// Senders can be cloned and shared between threads.
+#[unstable(feature = "mpmc_channel", issue = "0")]
+pub struct Sender<T> {
+    flavor: SenderFlavor<T>,
+}
+
+/// Sender flavors.
+enum SenderFlavor<T> {
+    /// Bounded channel based on a preallocated array.
+    Array(counter::Sender<array::Channel<T>>),
+
+    /// Unbounded channel implemented as a linked list.
+    List(counter::Sender<list::Channel<T>>),
+
+    /// Zero-capacity channel.
+    Zero(counter::Sender<zero::Channel<T>>),
+}
+
+#[unstable(feature = "mpmc_channel", issue = "0")]
+unsafe impl<T: Send> Send for Sender<T> {}
+#[unstable(feature = "mpmc_channel", issue = "0")]
+unsafe impl<T: Send> Sync for Sender<T> {}
+
+#[unstable(feature = "mpmc_channel", issue = "0")]
+impl<T> UnwindSafe for Sender<T> {}
+#[unstable(feature = "mpmc_channel", issue = "0")]
+impl<T> RefUnwindSafe for Sender<T> {}
+
+impl<T> Sender<T> {
+    /// Attempts to send a message into the channel without blocking.
+    ///
+    /// This fails if the channel is full or disconnected. A zero-capacity
+    /// channel only accepts the message if a receiver is waiting for it.
+    #[unstable(feature = "mpmc_channel", issue = "0")]
+    pub fn try_send(&self, msg: T) -> Result<(), TrySendError<T>> {
+        match self.flavor {
+            SenderFlavor::Array(ref chan) => chan.try_send(msg),
+            SenderFlavor::List(ref chan) => chan.try_send(msg),
+            SenderFlavor::Zero(ref chan) => chan.try_send(msg),
+        }
+    }
+
+    /// Sends a message into the channel, blocking while it is full.
+    ///
+    /// If the channel is disconnected, the message is returned in the error.
+    #[unstable(feature = "mpmc_channel", issue = "0")]
+    pub fn send(&self, msg: T) -> Result<(), SendError<T>> {
+        let res = match self.flavor {
+            SenderFlavor::Array(ref chan) => chan.send(msg, None),
+            SenderFlavor::List(ref chan) => chan.send(msg, None),
+            SenderFlavor::Zero(ref chan) => chan.send(msg, None),
+        };
+        res.map_err(|err| match err {
+            SendTimeoutError::Disconnected(msg) => SendError(msg),
+            SendTimeoutError::Timeout(_) => unreachable!(),
+        })
+    }
+
+    /// Waits for a message to be sent into the channel, but only for a
+    /// limited time.
+    ///
+    /// If the channel stays full for the whole `timeout`, or is
+    /// disconnected, the message is returned in the error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(mpmc_channel)]
+    ///
+    /// use std::sync::mpmc::{sync_channel, SendTimeoutError};
+    /// use std::time::Duration;
+    ///
+    /// let (tx, _rx) = sync_channel(0);
+    ///
+    /// assert_eq!(tx.send_timeout(1, Duration::from_millis(10)),
+    ///            Err(SendTimeoutError::Timeout(1)));
+    /// ```
+    #[unstable(feature = "mpmc_channel", issue = "0")]
+    pub fn send_timeout(&self, msg: T, timeout: Duration) -> Result<(), SendTimeoutError<T>> {
+        self.send_deadline(msg, Instant::now() + timeout)
+    }
+
+    /// Waits for a message to be sent into the channel, but only until a
+    /// given deadline.
+    ///
+    /// If the channel stays full until `deadline`, or is disconnected, the
+    /// message is returned in the error.
+    #[unstable(feature = "mpmc_channel", issue = "0")]
+    pub fn send_deadline(&self, msg: T, deadline: Instant) -> Result<(), SendTimeoutError<T>> {
+        match self.flavor {
+            SenderFlavor::Array(ref chan) => chan.send(msg, Some(deadline)),
+            SenderFlavor::List(ref chan) => chan.send(msg, Some(deadline)),
+            SenderFlavor::Zero(ref chan) => chan.send(msg, Some(deadline)),
+        }
+    }
+
+    /// Returns `true` if the channel is empty.
+    ///
+    /// Zero-capacity channels are always empty.
+    #[unstable(feature = "mpmc_channel", issue = "0")]
+    pub fn is_empty(&self) -> bool {
+        match self.flavor {
+            SenderFlavor::Array(ref chan) => chan.is_empty(),
+            SenderFlavor::List(ref chan) => chan.is_empty(),
+            SenderFlavor::Zero(ref chan) => chan.is_empty(),
+        }
+    }
+
+    /// Returns `true` if the channel is full.
+    ///
+    /// Zero-capacity channels are always full, unbounded ones never are.
+    #[unstable(feature = "mpmc_channel", issue = "0")]
+    pub fn is_full(&self) -> bool {
+        match self.flavor {
+            SenderFlavor::Array(ref chan) => chan.is_full(),
+            SenderFlavor::List(ref chan) => chan.is_full(),
+            SenderFlavor::Zero(ref chan) => chan.is_full(),
+        }
+    }
+
+    /// Returns the number of messages in the channel.
+    #[unstable(feature = "mpmc_channel", issue = "0")]
+    pub fn len(&self) -> usize {
+        match self.flavor {
+            SenderFlavor::Array(ref chan) => chan.len(),
+            SenderFlavor::List(ref chan) => chan.len(),
+            SenderFlavor::Zero(ref chan) => chan.len(),
+        }
+    }
+
+    /// Returns the capacity of the channel, or `None` if it is unbounded.
+    #[unstable(feature = "mpmc_channel", issue = "0")]
+    pub fn capacity(&self) -> Option<usize> {
+        match self.flavor {
+            SenderFlavor::Array(ref chan) => chan.capacity(),
+            SenderFlavor::List(ref chan) => chan.capacity(),
+            SenderFlavor::Zero(ref chan) => chan.capacity(),
+        }
+    }
+
+    /// Returns `true` if both senders belong to the same channel.
+    #[unstable(feature = "mpmc_channel", issue = "0")]
+    pub fn same_channel(&self, other: &Sender<T>) -> bool {
+        match (&self.flavor, &other.flavor) {
+            (&SenderFlavor::Array(ref a), &SenderFlavor::Array(ref b)) => a == b,
+            (&SenderFlavor::List(ref a), &SenderFlavor::List(ref b)) => a == b,
+            (&SenderFlavor::Zero(ref a), &SenderFlavor::Zero(ref b)) => a == b,
+            _ => false,
+        }
+    }
+
+    /// Writes a message into the channel after a successful selection.
+    unsafe fn write(&self, token: &mut Token, msg: T) -> Result<(), T> {
+        match self.flavor {
+            SenderFlavor::Array(ref chan) => chan.write(token, msg),
+            SenderFlavor::List(ref chan) => chan.write(token, msg),
+            SenderFlavor::Zero(ref chan) => chan.write(token, msg),
+        }
+    }
+}
+
+#[unstable(feature = "mpmc_channel", issue = "0")]
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        unsafe {
+            match self.flavor {
+                SenderFlavor::Array(ref chan) => chan.release(|c| c.disconnect_senders()),
+                SenderFlavor::List(ref chan) => chan.release(|c| c.disconnect_senders()),
+                SenderFlavor::Zero(ref chan) => chan.release(|c| c.disconnect()),
+            }
+        }
+    }
+}
+
+#[unstable(feature = "mpmc_channel", issue = "0")]
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Sender<T> {
+        let flavor = match self.flavor {
+            SenderFlavor::Array(ref chan) => SenderFlavor::Array(chan.acquire()),
+            SenderFlavor::List(ref chan) => SenderFlavor::List(chan.acquire()),
+            SenderFlavor::Zero(ref chan) => SenderFlavor::Zero(chan.acquire()),
+        };
+
+        Sender { flavor: flavor }
+    }
+}
+
+#[unstable(feature = "mpmc_channel", issue = "0")]
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("Sender { .. }")
+    }
+}
+
+impl<T> SelectHandle for Sender<T> {
+    fn try_select(&self, token: &mut Token) -> bool {
+        match self.flavor {
+            SenderFlavor::Array(ref chan) => chan.sender().try_select(token),
+            SenderFlavor::List(ref chan) => chan.sender().try_select(token),
+            SenderFlavor::Zero(ref chan) => chan.sender().try_select(token),
+        }
+    }
+
+    fn register(&self, oper: Operation, cx: &context::Context) -> bool {
+        match self.flavor {
+            SenderFlavor::Array(ref chan) => chan.sender().register(oper, cx),
+            SenderFlavor::List(ref chan) => chan.sender().register(oper, cx),
+            SenderFlavor::Zero(ref chan) => chan.sender().register(oper, cx),
+        }
+    }
+
+    fn unregister(&self, oper: Operation) {
+        match self.flavor {
+            SenderFlavor::Array(ref chan) => chan.sender().unregister(oper),
+            SenderFlavor::List(ref chan) => chan.sender().unregister(oper),
+            SenderFlavor::Zero(ref chan) => chan.sender().unregister(oper),
+        }
+    }
+
+    fn accept(&self, token: &mut Token, cx: &context::Context) -> bool {
+        match self.flavor {
+            SenderFlavor::Array(ref chan) => chan.sender().accept(token, cx),
+            SenderFlavor::List(ref chan) => chan.sender().accept(token, cx),
+            SenderFlavor::Zero(ref chan) => chan.sender().accept(token, cx),
+        }
+    }
+}
+
+/// The receiving side of a channel.
+///
+/// Receivers can be cloned and shared between threads. Each message is
+/// delivered to exactly one receiver.
+#[unstable(feature = "mpmc_channel", issue = "0")]
+pub struct Receiver<T> {
+    flavor: ReceiverFlavor<T>,
+}
+
+/// Receiver flavors.
+enum ReceiverFlavor<T> {
+    /// Bounded channel based on a preallocated array.
+    Array(counter::Receiver<array::Channel<T>>),
+
+    /// Unbounded channel implemented as a linked list.
+    List(counter::Receiver<list::Channel<T>>),
+
+    /// Zero-capacity channel.
+    Zero(counter::Receiver<zero::Channel<T>>),
+}
+
+#[unstable(feature = "mpmc_channel", issue = "0")]
+unsafe impl<T: Send> Send for Receiver<T> {}
+#[unstable(feature = "mpmc_channel", issue = "0")]
+unsafe impl<T: Send> Sync for Receiver<T> {}
+
+#[unstable(feature = "mpmc_channel", issue = "0")]
+impl<T> UnwindSafe for Receiver<T> {}
+#[unstable(feature = "mpmc_channel", issue = "0")]
+impl<T> RefUnwindSafe for Receiver<T> {}
+
+impl<T> Receiver<T> {
+    /// Attempts to receive a message from the channel without blocking.
+    ///
+    /// This fails if the channel is empty, or if it is empty and
+    /// disconnected.
+    #[unstable(feature = "mpmc_channel", issue = "0")]
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        match self.flavor {
+            ReceiverFlavor::Array(ref chan) => chan.try_recv(),
+            ReceiverFlavor::List(ref chan) => chan.try_recv(),
+            ReceiverFlavor::Zero(ref chan) => chan.try_recv(),
+        }
+    }
+
+    /// Blocks the current thread until a message is received or the channel
+    /// is empty and disconnected.
+    #[unstable(feature = "mpmc_channel", issue = "0")]
+    pub fn recv(&self) -> Result<T, RecvError> {
+        let res = match self.flavor {
+            ReceiverFlavor::Array(ref chan) => chan.recv(None),
+            ReceiverFlavor::List(ref chan) => chan.recv(None),
+            ReceiverFlavor::Zero(ref chan) => chan.recv(None),
+        };
+        res.map_err(|_| RecvError)
+    }
+
+    /// Waits for a message to be received from the channel, but only for a
+    /// limited time.
+    #[unstable(feature = "mpmc_channel", issue = "0")]
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        // Do an optimistic `try_recv` to avoid the cost of `Instant::now()`
+        // when a message is already available.
+        match self.try_recv() {
+            Ok(msg) => Ok(msg),
+            Err(TryRecvError::Disconnected) => Err(RecvTimeoutError::Disconnected),
+            Err(TryRecvError::Empty) => self.recv_deadline(Instant::now() + timeout),
+        }
+    }
+
+    /// Waits for a message to be received from the channel, but only until a
+    /// given deadline.
+    #[unstable(feature = "mpmc_channel", issue = "0")]
+    pub fn recv_deadline(&self, deadline: Instant) -> Result<T, RecvTimeoutError> {
+        match self.flavor {
+            ReceiverFlavor::Array(ref chan) => chan.recv(Some(deadline)),
+            ReceiverFlavor::List(ref chan) => chan.recv(Some(deadline)),
+            ReceiverFlavor::Zero(ref chan) => chan.recv(Some(deadline)),
+        }
+    }
+
+    /// Returns an iterator that blocks waiting for messages, until the channel
+    /// is disconnected.
+    #[unstable(feature = "mpmc_channel", issue = "0")]
+    pub fn iter(&self) -> Iter<T> {
+        Iter { rx: self }
+    }
+
+    /// Returns an iterator over the messages currently in the channel,
+    /// without blocking.
+    #[unstable(feature = "mpmc_channel", issue = "0")]
+    pub fn try_iter(&self) -> TryIter<T> {
+        TryIter { rx: self }
+    }
+
+    /// Returns `true` if the channel is empty.
+    ///
+    /// Zero-capacity channels are always empty.
+    #[unstable(feature = "mpmc_channel", issue = "0")]
+    pub fn is_empty(&self) -> bool {
+        match self.flavor {
+            ReceiverFlavor::Array(ref chan) => chan.is_empty(),
+            ReceiverFlavor::List(ref chan) => chan.is_empty(),
+            ReceiverFlavor::Zero(ref chan) => chan.is_empty(),
+        }
+    }
+
+    /// Returns `true` if the channel is full.
+    ///
+    /// Zero-capacity channels are always full, unbounded ones never are.
+    #[unstable(feature = "mpmc_channel", issue = "0")]
+    pub fn is_full(&self) -> bool {
+        match self.flavor {
+            ReceiverFlavor::Array(ref chan) => chan.is_full(),
+            ReceiverFlavor::List(ref chan) => chan.is_full(),
+            ReceiverFlavor::Zero(ref chan) => chan.is_full(),
+        }
+    }
+
+    /// Returns the number of messages in the channel.
+    #[unstable(feature = "mpmc_channel", issue = "0")]
+    pub fn len(&self) -> usize {
+        match self.flavor {
+            ReceiverFlavor::Array(ref chan) => chan.len(),
+            ReceiverFlavor::List(ref chan) => chan.len(),
+            ReceiverFlavor::Zero(ref chan) => chan.len(),
+        }
+    }
+
+    /// Returns the capacity of the channel, or `None` if it is unbounded.
+    #[unstable(feature = "mpmc_channel", issue = "0")]
+    pub fn capacity(&self) -> Option<usize> {
+        match self.flavor {
+            ReceiverFlavor::Array(ref chan) => chan.capacity(),
+            ReceiverFlavor::List(ref chan) => chan.capacity(),
+            ReceiverFlavor::Zero(ref chan) => chan.capacity(),
+        }
+    }
+
+    /// Returns `true` if both receivers belong to the same channel.
+    #[unstable(feature = "mpmc_channel", issue = "0")]
+    pub fn same_channel(&self, other: &Receiver<T>) -> bool {
+        match (&self.flavor, &other.flavor) {
+            (&ReceiverFlavor::Array(ref a), &ReceiverFlavor::Array(ref b)) => a == b,
+            (&ReceiverFlavor::List(ref a), &ReceiverFlavor::List(ref b)) => a == b,
+            (&ReceiverFlavor::Zero(ref a), &ReceiverFlavor::Zero(ref b)) => a == b,
+            _ => false,
+        }
+    }
+
+    /// Reads a message from the channel after a successful selection.
+    unsafe fn read(&self, token: &mut Token) -> Result<T, ()> {
+        match self.flavor {
+            ReceiverFlavor::Array(ref chan) => chan.read(token),
+            ReceiverFlavor::List(ref chan) => chan.read(token),
+            ReceiverFlavor::Zero(ref chan) => chan.read(token),
+        }
+    }
+}
+
+#[unstable(feature = "mpmc_channel", issue = "0")]
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        unsafe {
+            match self.flavor {
+                ReceiverFlavor::Array(ref chan) => chan.release(|c| c.disconnect_receivers()),
+                ReceiverFlavor::List(ref chan) => chan.release(|c| c.disconnect_receivers()),
+                ReceiverFlavor::Zero(ref chan) => chan.release(|c| c.disconnect()),
+            }
+        }
+    }
+}
+
+#[unstable(feature = "mpmc_channel", issue = "0")]
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Receiver<T> {
+        let flavor = match self.flavor {
+            ReceiverFlavor::Array(ref chan) => ReceiverFlavor::Array(chan.acquire()),
+            ReceiverFlavor::List(ref chan) => ReceiverFlavor::List(chan.acquire()),
+            ReceiverFlavor::Zero(ref chan) => ReceiverFlavor::Zero(chan.acquire()),
+        };
+
+        Receiver { flavor: flavor }
+    }
+}
+
+#[unstable(feature = "mpmc_channel", issue = "0")]
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("Receiver { .. }")
+    }
+}
+
+impl<T> SelectHandle for Receiver<T> {
+    fn try_select(&self, token: &mut Token) -> bool {
+        match self.flavor {
+            ReceiverFlavor::Array(ref chan) => chan.receiver().try_select(token),
+            ReceiverFlavor::List(ref chan) => chan.receiver().try_select(token),
+            ReceiverFlavor::Zero(ref chan) => chan.receiver().try_select(token),
+        }
+    }
+
+    fn register(&self, oper: Operation, cx: &context::Context) -> bool {
+        match self.flavor {
+            ReceiverFlavor::Array(ref chan) => chan.receiver().register(oper, cx),
+            ReceiverFlavor::List(ref chan) => chan.receiver().register(oper, cx),
+            ReceiverFlavor::Zero(ref chan) => chan.receiver().register(oper, cx),
+        }
+    }
+
+    fn unregister(&self, oper: Operation) {
+        match self.flavor {
+            ReceiverFlavor::Array(ref chan) => chan.receiver().unregister(oper),
+            ReceiverFlavor::List(ref chan) => chan.receiver().unregister(oper),
+            ReceiverFlavor::Zero(ref chan) => chan.receiver().unregister(oper),
+        }
+    }
+
+    fn accept(&self, token: &mut Token, cx: &context::Context) -> bool {
+        match self.flavor {
+            ReceiverFlavor::Array(ref chan) => chan.receiver().accept(token, cx),
+            ReceiverFlavor::List(ref chan) => chan.receiver().accept(token, cx),
+            ReceiverFlavor::Zero(ref chan) => chan.receiver().accept(token, cx),
+        }
+    }
+}
+
+/// An iterator over messages on a [`Receiver`], created by [`iter`].
+///
+/// [`Receiver`]: struct.Receiver.html
+/// [`iter`]: struct.Receiver.html#method.iter
+#[unstable(feature = "mpmc_channel", issue = "0")]
+#[derive(Debug)]
+pub struct Iter<'a, T: 'a> {
+    rx: &'a Receiver<T>,
+}
+
+/// An iterator over the messages currently in a [`Receiver`], created by
+/// [`try_iter`].
+///
+/// [`Receiver`]: struct.Receiver.html
+/// [`try_iter`]: struct.Receiver.html#method.try_iter
+#[unstable(feature = "mpmc_channel", issue = "0")]
+#[derive(Debug)]
+pub struct TryIter<'a, T: 'a> {
+    rx: &'a Receiver<T>,
+}
+
+/// An owning iterator over messages on a [`Receiver`].
+///
+/// [`Receiver`]: struct.Receiver.html
+#[unstable(feature = "mpmc_channel", issue = "0")]
+#[derive(Debug)]
+pub struct IntoIter<T> {
+    rx: Receiver<T>,
+}
+
+#[unstable(feature = "mpmc_channel", issue = "0")]
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.rx.recv().ok()
+    }
+}
+
+#[unstable(feature = "mpmc_channel", issue = "0")]
+impl<'a, T> Iterator for TryIter<'a, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.rx.try_recv().ok()
+    }
+}
+
+#[unstable(feature = "mpmc_channel", issue = "0")]
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.rx.recv().ok()
+    }
+}
+
+#[unstable(feature = "mpmc_channel", issue = "0")]
+impl<'a, T> IntoIterator for &'a Receiver<T> {
+    type Item = T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+#[unstable(feature = "mpmc_channel", issue = "0")]
+impl<T> IntoIterator for Receiver<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter { rx: self }
+    }
+}
+
+#[cfg(all(test, not(target_os = "emscripten")))]
+mod tests {
+    use super::*;
+    use sync::Arc;
+    use sync::atomic::{AtomicUsize, Ordering};
+    use thread;
+    use time::{Duration, Instant};
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn smoke() {
+        for &cap in &[None, Some(0), Some(1), Some(10)] {
+            let (tx, rx) = match cap {
+                None => channel(),
+                Some(cap) => sync_channel(cap),
+            };
+            let t = thread::spawn(move || tx.send(7).unwrap());
+            assert_eq!(rx.recv(), Ok(7));
+            t.join().unwrap();
+            assert_eq!(rx.recv(), Err(RecvError));
+        }
+    }
+
+    #[test]
+    fn capacity_and_len() {
+        let (tx, rx) = channel::<i32>();
+        assert_eq!(tx.capacity(), None);
+        tx.send(1).unwrap();
+        tx.send(2).unwrap();
+        assert_eq!(rx.len(), 2);
+        assert!(!rx.is_full());
+
+        let (tx, rx) = sync_channel::<i32>(2);
+        assert_eq!(rx.capacity(), Some(2));
+        tx.send(1).unwrap();
+        tx.send(2).unwrap();
+        assert!(tx.is_full());
+        assert_eq!(tx.try_send(3), Err(TrySendError::Full(3)));
+
+        let (tx, rx) = sync_channel::<i32>(0);
+        assert_eq!(tx.capacity(), Some(0));
+        assert!(rx.is_empty() && rx.is_full());
+        assert_eq!(tx.try_send(1), Err(TrySendError::Full(1)));
+    }
+
+    #[test]
+    fn multiple_receivers() {
+        const N: usize = 1000;
+        const THREADS: usize = 4;
+
+        for &cap in &[None, Some(0), Some(3)] {
+            let (tx, rx) = match cap {
+                None => channel(),
+                Some(cap) => sync_channel(cap),
+            };
+            let total = Arc::new(AtomicUsize::new(0));
+
+            let workers = (0..THREADS).map(|_| {
+                let rx = rx.clone();
+                let total = total.clone();
+                thread::spawn(move || {
+                    for i in rx.iter() {
+                        total.fetch_add(i, Ordering::SeqCst);
+                    }
+                })
+            }).collect::<Vec<_>>();
+            drop(rx);
+
+            let senders = (0..THREADS).map(|_| {
+                let tx = tx.clone();
+                thread::spawn(move || {
+                    for i in 0..N {
+                        tx.send(i).unwrap();
+                    }
+                })
+            }).collect::<Vec<_>>();
+            drop(tx);
+
+            for t in senders.into_iter().chain(workers) {
+                t.join().unwrap();
+            }
+            assert_eq!(total.load(Ordering::SeqCst), THREADS * N * (N - 1) / 2);
+        }
+    }
+
+    #[test]
+    fn rendezvous() {
+        let (tx, rx) = sync_channel(0);
+        let t = thread::spawn(move || {
+            thread::sleep(ms(100));
+            rx.recv().unwrap()
+        });
+
+        let start = Instant::now();
+        tx.send(5).unwrap();
+        assert!(start.elapsed() >= ms(100));
+        assert_eq!(t.join().unwrap(), 5);
+        assert_eq!(tx.send(6), Err(SendError(6)));
+    }
+
+    #[test]
+    fn send_timeout() {
+        let (tx, rx) = sync_channel(1);
+        tx.send(1).unwrap();
+        assert_eq!(tx.send_timeout(2, ms(50)), Err(SendTimeoutError::Timeout(2)));
+
+        let t = thread::spawn(move || {
+            thread::sleep(ms(50));
+            rx.recv().unwrap()
+        });
+        assert_eq!(tx.send_timeout(2, ms(5000)), Ok(()));
+        assert_eq!(t.join().unwrap(), 1);
+        assert_eq!(tx.send_timeout(3, ms(50)), Err(SendTimeoutError::Disconnected(3)));
+    }
+
+    #[test]
+    fn recv_timeout() {
+        for &cap in &[None, Some(0), Some(1)] {
+            let (tx, rx) = match cap {
+                None => channel::<i32>(),
+                Some(cap) => sync_channel::<i32>(cap),
+            };
+            assert_eq!(rx.recv_timeout(ms(50)), Err(RecvTimeoutError::Timeout));
+            drop(tx);
+            assert_eq!(rx.recv_timeout(ms(50)), Err(RecvTimeoutError::Disconnected));
+        }
+    }
+
+    #[test]
+    fn disconnected_receivers_drop_messages() {
+        struct Counted(Arc<AtomicUsize>);
+
+        impl Drop for Counted {
+            fn drop(&mut self) {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        for &cap in &[None, Some(100)] {
+            let drops = Arc::new(AtomicUsize::new(0));
+            let (tx, rx) = match cap {
+                None => channel(),
+                Some(cap) => sync_channel(cap),
+            };
+            for _ in 0..50 {
+                tx.send(Counted(drops.clone())).unwrap();
+            }
+            drop(rx.clone());
+            assert_eq!(drops.load(Ordering::SeqCst), 0);
+            drop(rx);
+            assert_eq!(drops.load(Ordering::SeqCst), 50);
+            assert!(tx.send(Counted(drops.clone())).is_err());
+            assert_eq!(drops.load(Ordering::SeqCst), 51);
+        }
+    }
+
+    #[test]
+    fn same_channel() {
+        let (tx, rx) = channel::<()>();
+        let (tx2, rx2) = channel::<()>();
+        assert!(tx.same_channel(&tx.clone()));
+        assert!(!tx.same_channel(&tx2));
+        assert!(rx.same_channel(&rx.clone()));
+        assert!(!rx.same_channel(&rx2));
+    }
+
+    #[test]
+    fn select_recv() {
+        let (tx1, rx1) = channel::<i32>();
+        let (tx2, rx2) = sync_channel::<i32>(0);
+
+        let t = thread::spawn(move || {
+            thread::sleep(ms(50));
+            tx2.send(2).unwrap();
+            drop(tx1);
+            tx2
+        });
+
+        select! {
+            recv(rx1) -> _ => panic!(),
+            recv(rx2) -> msg => assert_eq!(msg, Ok(2)),
+        }
+        select! {
+            recv(rx1) -> msg => assert_eq!(msg, Err(RecvError)),
+            recv(rx2) -> _ => panic!(),
+        }
+        t.join().unwrap();
+    }
+
+    #[test]
+    fn select_send() {
+        let (tx1, rx1) = sync_channel::<i32>(0);
+        let (tx2, rx2) = sync_channel::<i32>(1);
+        tx2.send(0).unwrap();
+
+        let t = thread::spawn(move || rx1.recv().unwrap());
+
+        let mut evaluated = 0;
+        select! {
+            send(tx1, 1) -> res => res.unwrap(),
+            send(tx2, { evaluated += 1; 2 }) -> _ => panic!(),
+        }
+        assert_eq!(evaluated, 0);
+        assert_eq!(t.join().unwrap(), 1);
+        assert_eq!(rx2.recv(), Ok(0));
+    }
+
+    #[test]
+    fn select_default() {
+        let (tx, rx) = channel::<i32>();
+
+        let got = select! {
+            recv(rx) -> _ => true,
+            default => false,
+        };
+        assert!(!got);
+
+        let start = Instant::now();
+        let got = select! {
+            recv(rx) -> _ => true,
+            default(ms(50)) => false,
+        };
+        assert!(!got);
+        assert!(start.elapsed() >= ms(50));
+
+        tx.send(1).unwrap();
+        let got = select! {
+            recv(rx) -> msg => msg.unwrap(),
+            default => 0,
+        };
+        assert_eq!(got, 1);
+    }
+
+    #[test]
+    fn select_dynamic() {
+        let chans = (0..5).map(|_| sync_channel::<usize>(0)).collect::<Vec<_>>();
+
+        let t = {
+            let tx = chans[3].0.clone();
+            thread::spawn(move || {
+                thread::sleep(ms(50));
+                tx.send(3).unwrap();
+            })
+        };
+
+        let mut sel = Select::new();
+        for &(_, ref rx) in &chans {
+            sel.recv(rx);
+        }
+        assert!(sel.try_select().is_err());
+
+        let oper = sel.select();
+        let i = oper.index();
+        assert_eq!(i, 3);
+        assert_eq!(oper.recv(&chans[i].1), Ok(3));
+        t.join().unwrap();
+    }
+
+    #[test]
+    fn select_stress() {
+        const N: usize = 10000;
+
+        let (tx1, rx1) = sync_channel::<usize>(0);
+        let (tx2, rx2) = channel::<usize>();
+
+        let t = thread::spawn(move || {
+            for i in 0..N {
+                if i % 2 == 0 {
+                    tx1.send(i).unwrap();
+                } else {
+                    tx2.send(i).unwrap();
+                }
+            }
+        });
+
+        let mut sum = 0;
+        for _ in 0..N {
+            select! {
+                recv(rx1) -> msg => sum += msg.unwrap(),
+                recv(rx2) -> msg => sum += msg.unwrap(),
+            }
+        }
+        t.join().unwrap();
+        assert_eq!(sum, N * (N - 1) / 2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn selected_operation_must_complete() {
+        let (tx, rx) = channel::<i32>();
+        tx.send(1).unwrap();
+
+        let mut sel = Select::new();
+        sel.recv(&rx);
+        let _oper = sel.select();
+    }
+}
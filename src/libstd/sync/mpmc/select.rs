@@ -0,0 +1,571 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+
+//! Selection over a set of send and receive operations.
+
+use fmt;
+use marker::PhantomData;
+use mem;
+use time::{Duration, Instant};
+
+use super::{Receiver, Sender};
+use super::array::ArrayToken;
+use super::context::Context;
+use super::error::{RecvError, SelectTimeoutError, SendError, TrySelectError};
+use super::list::ListToken;
+use super::utils;
+use super::zero::ZeroToken;
+
+/// Temporary data that gets initialized during select or a blocking
+/// operation, and is consumed by `read` or `write`.
+///
+/// Each field contains data associated with a specific channel flavor.
+#[derive(Debug, Default)]
+pub struct Token {
+    pub array: ArrayToken,
+    pub list: ListToken,
+    pub zero: ZeroToken,
+}
+
+/// Identifier associated with an operation by a specific thread on a specific
+/// channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Operation(usize);
+
+impl Operation {
+    /// Creates an operation identifier from a mutable reference.
+    ///
+    /// This function essentially just turns the address of the reference into
+    /// a number. The reference should point to a variable that is specific to
+    /// the thread and the operation, and is alive for the entire duration of a
+    /// blocking operation.
+    #[inline]
+    pub fn hook<T>(r: &mut T) -> Operation {
+        let val = r as *mut T as usize;
+        // Make sure that the pointer address doesn't equal the numerical
+        // representation of `Selected::{Waiting, Aborted, Disconnected}`.
+        assert!(val > 2);
+        Operation(val)
+    }
+}
+
+/// Current state of a blocking operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Selected {
+    /// Still waiting for an operation.
+    Waiting,
+
+    /// The attempt to block the current thread has been aborted.
+    Aborted,
+
+    /// An operation became ready because a channel is disconnected.
+    Disconnected,
+
+    /// An operation became ready because a message can be sent or received.
+    Operation(Operation),
+}
+
+impl From<usize> for Selected {
+    #[inline]
+    fn from(val: usize) -> Selected {
+        match val {
+            0 => Selected::Waiting,
+            1 => Selected::Aborted,
+            2 => Selected::Disconnected,
+            oper => Selected::Operation(Operation(oper)),
+        }
+    }
+}
+
+impl From<Selected> for usize {
+    #[inline]
+    fn from(val: Selected) -> usize {
+        match val {
+            Selected::Waiting => 0,
+            Selected::Aborted => 1,
+            Selected::Disconnected => 2,
+            Selected::Operation(Operation(val)) => val,
+        }
+    }
+}
+
+/// A receiver or a sender that can participate in select.
+///
+/// This is a handle that assists select in executing an operation,
+/// registration, deciding on the appropriate deadline for blocking, etc.
+pub trait SelectHandle {
+    /// Attempts to select an operation and returns `true` on success.
+    fn try_select(&self, token: &mut Token) -> bool;
+
+    /// Registers an operation for execution and returns `true` if it is now
+    /// ready.
+    fn register(&self, oper: Operation, cx: &Context) -> bool;
+
+    /// Unregisters an operation for execution.
+    fn unregister(&self, oper: Operation);
+
+    /// Attempts to select an operation the thread got woken up for and returns
+    /// `true` on success.
+    fn accept(&self, token: &mut Token, cx: &Context) -> bool;
+}
+
+/// How long select is allowed to block.
+#[derive(Clone, Copy)]
+enum Timeout {
+    /// No blocking.
+    Now,
+
+    /// Block forever.
+    Never,
+
+    /// Block until the given instant.
+    At(Instant),
+}
+
+/// Runs until one of the operations is selected, potentially blocking the
+/// current thread.
+///
+/// Successful receive operations will have to be followed up by
+/// `Receiver::read()` and successful send operations by `Sender::write()`.
+fn run_select(handles: &mut [(&SelectHandle, usize, *const u8)],
+              timeout: Timeout)
+              -> Option<(Token, usize, *const u8)> {
+    if handles.is_empty() {
+        // Wait until the timeout and return.
+        match timeout {
+            Timeout::Now => return None,
+            Timeout::Never => {
+                utils::sleep_until(None);
+                unreachable!();
+            }
+            Timeout::At(when) => {
+                utils::sleep_until(Some(when));
+                return None;
+            }
+        }
+    }
+
+    // Shuffle the operations for fairness.
+    utils::shuffle(handles);
+
+    // The token is initialized here and consumed by the call to `read` or
+    // `write` that completes the selected operation.
+    let mut token = Token::default();
+
+    // Try selecting one of the operations without blocking.
+    for &(handle, i, ptr) in handles.iter() {
+        if handle.try_select(&mut token) {
+            return Some((token, i, ptr));
+        }
+    }
+
+    loop {
+        // Prepare for blocking.
+        let res = Context::with(|cx| {
+            let mut sel = Selected::Waiting;
+            let mut registered_count = 0;
+            let mut index_ready = None;
+
+            if let Timeout::Now = timeout {
+                cx.try_select(Selected::Aborted).unwrap();
+            }
+
+            // Register all operations.
+            for &mut (ref mut handle, i, _) in handles.iter_mut() {
+                registered_count += 1;
+
+                // If registration returns `true`, the operation has just
+                // become ready.
+                let oper = Operation::hook(handle);
+                if handle.register(oper, cx) {
+                    // Try aborting select.
+                    sel = match cx.try_select(Selected::Aborted) {
+                        Ok(()) => {
+                            index_ready = Some(i);
+                            Selected::Aborted
+                        }
+                        Err(s) => s,
+                    };
+                    break;
+                }
+
+                // If another thread has already selected one of the
+                // operations, stop registration.
+                sel = cx.selected();
+                if sel != Selected::Waiting {
+                    break;
+                }
+            }
+
+            if sel == Selected::Waiting {
+                let deadline = match timeout {
+                    Timeout::Now => unreachable!(),
+                    Timeout::Never => None,
+                    Timeout::At(when) => Some(when),
+                };
+
+                // Block the current thread.
+                sel = cx.wait_until(deadline);
+            }
+
+            // Unregister all registered operations.
+            for &mut (ref mut handle, _, _) in handles.iter_mut().take(registered_count) {
+                let oper = Operation::hook(handle);
+                handle.unregister(oper);
+            }
+
+            match sel {
+                Selected::Waiting => unreachable!(),
+                Selected::Aborted => {
+                    // If an operation became ready during registration, try
+                    // selecting it.
+                    if let Some(index_ready) = index_ready {
+                        for &(handle, i, ptr) in handles.iter() {
+                            if i == index_ready && handle.try_select(&mut token) {
+                                return Some((i, ptr));
+                            }
+                        }
+                    }
+                }
+                Selected::Disconnected => {}
+                Selected::Operation(_) => {
+                    // Find the selected operation.
+                    for &mut (ref mut handle, i, ptr) in handles.iter_mut() {
+                        // Is this the selected operation?
+                        let oper = Operation::hook(handle);
+                        if sel == Selected::Operation(oper) {
+                            // Try selecting this operation.
+                            if handle.accept(&mut token, cx) {
+                                return Some((i, ptr));
+                            }
+                        }
+                    }
+                }
+            }
+
+            None
+        });
+
+        // Return if an operation was selected.
+        if let Some((i, ptr)) = res {
+            return Some((token, i, ptr));
+        }
+
+        // Try selecting one of the operations without blocking.
+        for &(handle, i, ptr) in handles.iter() {
+            if handle.try_select(&mut token) {
+                return Some((token, i, ptr));
+            }
+        }
+
+        match timeout {
+            Timeout::Now => return None,
+            Timeout::Never => {}
+            Timeout::At(when) => {
+                if Instant::now() >= when {
+                    return None;
+                }
+            }
+        }
+    }
+}
+
+/// Selects from a set of channel operations.
+///
+/// `Select` allows you to define a set of channel operations, wait until any
+/// one of them becomes ready, and finally execute it. If multiple operations
+/// are ready at the same time, a random one among them is selected.
+///
+/// An operation is considered to be ready if it doesn't have to block. Note
+/// that it is ready even when it will simply return an error because the
+/// channel is disconnected.
+///
+/// The [`select!`] macro is a convenience wrapper around `Select`. However, it
+/// cannot select over a dynamically created list of channel operations.
+///
+/// Once an operation has been selected, it must be completed with
+/// [`SelectedOperation::send`] or [`SelectedOperation::recv`].
+///
+/// [`select!`]: ../../macro.select.html
+/// [`SelectedOperation::send`]: struct.SelectedOperation.html#method.send
+/// [`SelectedOperation::recv`]: struct.SelectedOperation.html#method.recv
+///
+/// # Examples
+///
+/// ```
+/// #![feature(mpmc_channel, mpmc_select)]
+///
+/// use std::sync::mpmc::{self, Select};
+/// use std::thread;
+/// use std::time::Duration;
+///
+/// let (s1, r1) = mpmc::channel();
+/// let (s2, r2) = mpmc::channel();
+///
+/// thread::spawn(move || {
+///     thread::sleep(Duration::from_millis(100));
+///     s1.send(10).unwrap();
+/// });
+/// thread::spawn(move || s2.send(20).unwrap());
+///
+/// let mut sel = Select::new();
+/// let oper1 = sel.recv(&r1);
+/// let oper2 = sel.recv(&r2);
+///
+/// // The second operation will be selected because it becomes ready first.
+/// let oper = sel.select();
+/// match oper.index() {
+///     i if i == oper1 => assert_eq!(oper.recv(&r1), Ok(10)),
+///     i if i == oper2 => assert_eq!(oper.recv(&r2), Ok(20)),
+///     _ => unreachable!(),
+/// }
+/// ```
+#[unstable(feature = "mpmc_select", issue = "0")]
+pub struct Select<'a> {
+    /// A list of senders and receivers participating in selection.
+    handles: Vec<(&'a SelectHandle, usize, *const u8)>,
+
+    /// The next index to assign to an operation.
+    next_index: usize,
+}
+
+impl<'a> Select<'a> {
+    /// Creates an empty list of channel operations for selection.
+    #[unstable(feature = "mpmc_select", issue = "0")]
+    pub fn new() -> Select<'a> {
+        Select {
+            handles: Vec::with_capacity(4),
+            next_index: 0,
+        }
+    }
+
+    /// Adds a send operation and returns its index.
+    ///
+    /// Indices are assigned in ascending order, starting from zero.
+    #[unstable(feature = "mpmc_select", issue = "0")]
+    pub fn send<T>(&mut self, s: &'a Sender<T>) -> usize {
+        let i = self.next_index;
+        let ptr = s as *const Sender<T> as *const u8;
+        self.handles.push((s, i, ptr));
+        self.next_index += 1;
+        i
+    }
+
+    /// Adds a receive operation and returns its index.
+    ///
+    /// Indices are assigned in ascending order, starting from zero.
+    #[unstable(feature = "mpmc_select", issue = "0")]
+    pub fn recv<T>(&mut self, r: &'a Receiver<T>) -> usize {
+        let i = self.next_index;
+        let ptr = r as *const Receiver<T> as *const u8;
+        self.handles.push((r, i, ptr));
+        self.next_index += 1;
+        i
+    }
+
+    /// Removes a previously added operation.
+    ///
+    /// This is useful when an operation is selected because the channel got
+    /// disconnected and we want to try again to select a different operation
+    /// instead.
+    ///
+    /// # Panics
+    ///
+    /// An attempt to remove a non-existing or already removed operation will
+    /// panic.
+    #[unstable(feature = "mpmc_select", issue = "0")]
+    pub fn remove(&mut self, index: usize) {
+        assert!(index < self.next_index,
+                "index out of bounds; {} >= {}", index, self.next_index);
+
+        let i = self.handles
+            .iter()
+            .position(|&(_, i, _)| i == index)
+            .expect("no operation with this index");
+
+        self.handles.swap_remove(i);
+    }
+
+    /// Attempts to select one of the operations without blocking.
+    ///
+    /// If an operation is ready, it is selected and returned. If multiple
+    /// operations are ready at the same time, a random one among them is
+    /// selected. If none of the operations are ready, an error is returned.
+    #[unstable(feature = "mpmc_select", issue = "0")]
+    pub fn try_select(&mut self) -> Result<SelectedOperation<'a>, TrySelectError> {
+        match run_select(&mut self.handles, Timeout::Now) {
+            None => Err(TrySelectError),
+            Some((token, index, ptr)) => Ok(SelectedOperation::new(token, index, ptr)),
+        }
+    }
+
+    /// Blocks until one of the operations becomes ready and selects it.
+    ///
+    /// If multiple operations are ready at the same time, a random one among
+    /// them is selected.
+    ///
+    /// # Panics
+    ///
+    /// Blocks forever if no operations have been added.
+    #[unstable(feature = "mpmc_select", issue = "0")]
+    pub fn select(&mut self) -> SelectedOperation<'a> {
+        let (token, index, ptr) = run_select(&mut self.handles, Timeout::Never).unwrap();
+        SelectedOperation::new(token, index, ptr)
+    }
+
+    /// Blocks for a limited time until one of the operations becomes ready and
+    /// selects it.
+    ///
+    /// If the timeout elapses before an operation becomes ready, an error is
+    /// returned.
+    #[unstable(feature = "mpmc_select", issue = "0")]
+    pub fn select_timeout(&mut self, timeout: Duration)
+                          -> Result<SelectedOperation<'a>, SelectTimeoutError> {
+        self.select_deadline(Instant::now() + timeout)
+    }
+
+    /// Blocks until a given deadline, or until one of the operations becomes
+    /// ready and selects it.
+    ///
+    /// If the deadline is reached before an operation becomes ready, an error
+    /// is returned.
+    #[unstable(feature = "mpmc_select", issue = "0")]
+    pub fn select_deadline(&mut self, deadline: Instant)
+                           -> Result<SelectedOperation<'a>, SelectTimeoutError> {
+        match run_select(&mut self.handles, Timeout::At(deadline)) {
+            None => Err(SelectTimeoutError),
+            Some((token, index, ptr)) => Ok(SelectedOperation::new(token, index, ptr)),
+        }
+    }
+}
+
+#[unstable(feature = "mpmc_select", issue = "0")]
+impl<'a> Clone for Select<'a> {
+    fn clone(&self) -> Select<'a> {
+        Select {
+            handles: self.handles.clone(),
+            next_index: self.next_index,
+        }
+    }
+}
+
+#[unstable(feature = "mpmc_select", issue = "0")]
+impl<'a> Default for Select<'a> {
+    fn default() -> Select<'a> {
+        Select::new()
+    }
+}
+
+#[unstable(feature = "mpmc_select", issue = "0")]
+impl<'a> fmt::Debug for Select<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("Select { .. }")
+    }
+}
+
+/// A selected operation that needs to be completed.
+///
+/// To complete the operation, call [`send`] or [`recv`].
+///
+/// # Panics
+///
+/// Forgetting to complete the operation is an error and might lead to
+/// deadlocks. If a `SelectedOperation` is dropped without completion, a panic
+/// occurs.
+///
+/// [`send`]: struct.SelectedOperation.html#method.send
+/// [`recv`]: struct.SelectedOperation.html#method.recv
+#[unstable(feature = "mpmc_select", issue = "0")]
+#[must_use]
+pub struct SelectedOperation<'a> {
+    /// Token needed to complete the operation.
+    token: Token,
+
+    /// The index of the selected operation.
+    index: usize,
+
+    /// The address of the selected `Sender` or `Receiver`.
+    ptr: *const u8,
+
+    /// Indicates that `Sender`s and `Receiver`s are borrowed.
+    _marker: PhantomData<&'a ()>,
+}
+
+impl<'a> SelectedOperation<'a> {
+    fn new(token: Token, index: usize, ptr: *const u8) -> SelectedOperation<'a> {
+        SelectedOperation {
+            token: token,
+            index: index,
+            ptr: ptr,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the index of the selected operation.
+    #[unstable(feature = "mpmc_select", issue = "0")]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Completes the send operation.
+    ///
+    /// The passed [`Sender`] reference must be the same one that was used in
+    /// [`Select::send`] when the operation was added.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an incorrect [`Sender`] reference is passed.
+    ///
+    /// [`Sender`]: struct.Sender.html
+    /// [`Select::send`]: struct.Select.html#method.send
+    #[unstable(feature = "mpmc_select", issue = "0")]
+    pub fn send<T>(mut self, s: &Sender<T>, msg: T) -> Result<(), SendError<T>> {
+        assert!(s as *const Sender<T> as *const u8 == self.ptr,
+                "passed a sender that wasn't selected");
+        let res = unsafe { s.write(&mut self.token, msg) };
+        mem::forget(self);
+        res.map_err(SendError)
+    }
+
+    /// Completes the receive operation.
+    ///
+    /// The passed [`Receiver`] reference must be the same one that was used in
+    /// [`Select::recv`] when the operation was added.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an incorrect [`Receiver`] reference is passed.
+    ///
+    /// [`Receiver`]: struct.Receiver.html
+    /// [`Select::recv`]: struct.Select.html#method.recv
+    #[unstable(feature = "mpmc_select", issue = "0")]
+    pub fn recv<T>(mut self, r: &Receiver<T>) -> Result<T, RecvError> {
+        assert!(r as *const Receiver<T> as *const u8 == self.ptr,
+                "passed a receiver that wasn't selected");
+        let res = unsafe { r.read(&mut self.token) };
+        mem::forget(self);
+        res.map_err(|_| RecvError)
+    }
+}
+
+#[unstable(feature = "mpmc_select", issue = "0")]
+impl<'a> fmt::Debug for SelectedOperation<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("SelectedOperation { .. }")
+    }
+}
+
+#[unstable(feature = "mpmc_select", issue = "0")]
+impl<'a> Drop for SelectedOperation<'a> {
+    fn drop(&mut self) {
+        panic!("dropped `SelectedOperation` without completing the operation");
+    }
+}
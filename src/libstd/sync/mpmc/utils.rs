@@ -0,0 +1,114 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+
+//! Miscellaneous utilities shared by the channel flavors.
+
+use cell::Cell;
+use num::Wrapping;
+use sync::atomic;
+use thread;
+use time::{Duration, Instant};
+
+/// The number of steps after which `Backoff` stops spinning.
+const SPIN_LIMIT: u32 = 6;
+
+/// Performs quadratic backoff in spin loops.
+pub struct Backoff {
+    step: Cell<u32>,
+}
+
+impl Backoff {
+    /// Creates a new `Backoff`.
+    pub fn new() -> Backoff {
+        Backoff { step: Cell::new(0) }
+    }
+
+    /// Backs off using lightweight spinning.
+    ///
+    /// This should be used when retrying an operation because another thread
+    /// made progress, i.e. after a failed compare-and-swap.
+    #[inline]
+    pub fn spin_light(&self) {
+        let step = if self.step.get() < SPIN_LIMIT { self.step.get() } else { SPIN_LIMIT };
+        for _ in 0..step * step {
+            atomic::spin_loop_hint();
+        }
+        self.step.set(self.step.get() + 1);
+    }
+
+    /// Backs off using heavyweight spinning, eventually yielding the thread.
+    ///
+    /// This should be used in loops waiting for another thread to finish a
+    /// step it has already committed to.
+    #[inline]
+    pub fn spin_heavy(&self) {
+        let step = self.step.get();
+        if step <= SPIN_LIMIT {
+            for _ in 0..step * step {
+                atomic::spin_loop_hint();
+            }
+        } else {
+            thread::yield_now();
+        }
+        self.step.set(step.saturating_add(1));
+    }
+
+    /// Returns `true` once spinning is no longer worthwhile and the thread
+    /// should block instead.
+    #[inline]
+    pub fn is_completed(&self) -> bool {
+        self.step.get() > SPIN_LIMIT
+    }
+}
+
+/// Randomly shuffles a slice, used to keep `select` fair.
+pub fn shuffle<T>(v: &mut [T]) {
+    let len = v.len();
+    if len <= 1 {
+        return;
+    }
+
+    thread_local! {
+        static RNG: Cell<Wrapping<u32>> = Cell::new(Wrapping(1406868647));
+    }
+
+    let _ = RNG.try_with(|rng| {
+        for i in 1..len {
+            // This is the 32-bit variant of Xorshift.
+            let mut x = rng.get();
+            x ^= x << 13;
+            x ^= x >> 17;
+            x ^= x << 5;
+            rng.set(x);
+
+            // A fast alternative to `x % (i + 1)`.
+            let n = i + 1;
+            let j = ((x.0 as u64).wrapping_mul(n as u64) >> 32) as u32 as usize;
+            v.swap(i, j);
+        }
+    });
+}
+
+/// Sleeps until the deadline, or forever if there is none.
+pub fn sleep_until(deadline: Option<Instant>) {
+    loop {
+        match deadline {
+            None => thread::sleep(Duration::from_secs(1000)),
+            Some(d) => {
+                let now = Instant::now();
+                if now >= d {
+                    break;
+                }
+                thread::sleep(d - now);
+            }
+        }
+    }
+}
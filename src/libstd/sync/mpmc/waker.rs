@@ -0,0 +1,217 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+
+//! Queues of threads blocked on channel operations.
+
+use ptr;
+use sync::Mutex;
+use sync::atomic::{AtomicBool, Ordering};
+
+use super::context::Context;
+use super::select::{Operation, Selected};
+
+/// Represents a thread blocked on a specific channel operation.
+pub struct Entry {
+    /// The operation.
+    pub oper: Operation,
+
+    /// Optional packet.
+    pub packet: *mut (),
+
+    /// Context associated with the thread owning this operation.
+    pub cx: Context,
+}
+
+/// A queue of threads blocked on channel operations.
+///
+/// This data structure is used by threads to register blocking operations and
+/// get woken up once an operation becomes ready.
+pub struct Waker {
+    /// A list of select operations.
+    selectors: Vec<Entry>,
+}
+
+impl Waker {
+    /// Creates a new `Waker`.
+    #[inline]
+    pub fn new() -> Waker {
+        Waker { selectors: Vec::new() }
+    }
+
+    /// Registers a select operation.
+    #[inline]
+    pub fn register(&mut self, oper: Operation, cx: &Context) {
+        self.register_with_packet(oper, ptr::null_mut(), cx);
+    }
+
+    /// Registers a select operation and a packet.
+    #[inline]
+    pub fn register_with_packet(&mut self, oper: Operation, packet: *mut (), cx: &Context) {
+        self.selectors.push(Entry {
+            oper: oper,
+            packet: packet,
+            cx: cx.clone(),
+        });
+    }
+
+    /// Unregisters a select operation.
+    #[inline]
+    pub fn unregister(&mut self, oper: Operation) -> Option<Entry> {
+        match self.selectors.iter().position(|entry| entry.oper == oper) {
+            Some(i) => Some(self.selectors.remove(i)),
+            None => None,
+        }
+    }
+
+    /// Attempts to find another thread's entry, select the operation, and wake
+    /// it up.
+    #[inline]
+    pub fn try_select(&mut self) -> Option<Entry> {
+        if self.selectors.is_empty() {
+            return None;
+        }
+
+        let thread_id = current_thread_id();
+        let pos = self.selectors.iter().position(|selector| {
+            // Does the entry belong to a different thread, and can we select
+            // its operation?
+            selector.cx.thread_id() != thread_id &&
+                selector.cx.try_select(Selected::Operation(selector.oper)).is_ok() && {
+                    // Provide the packet and wake the thread up.
+                    selector.cx.store_packet(selector.packet);
+                    selector.cx.unpark();
+                    true
+                }
+        });
+
+        // Remove the entry from the queue to keep it clean and improve
+        // performance.
+        pos.map(|pos| self.selectors.remove(pos))
+    }
+
+    /// Returns `true` if there is an entry which can be selected by the
+    /// current thread.
+    #[inline]
+    pub fn can_select(&self) -> bool {
+        if self.selectors.is_empty() {
+            return false;
+        }
+
+        let thread_id = current_thread_id();
+        self.selectors.iter().any(|entry| {
+            entry.cx.thread_id() != thread_id && entry.cx.selected() == Selected::Waiting
+        })
+    }
+
+    /// Notifies all registered operations that the channel is disconnected.
+    #[inline]
+    pub fn disconnect(&mut self) {
+        for entry in self.selectors.iter() {
+            if entry.cx.try_select(Selected::Disconnected).is_ok() {
+                // Wake the thread up.
+                //
+                // The entry stays in the queue: registered threads unregister
+                // themselves, and might want to recover and destroy the packet.
+                entry.cx.unpark();
+            }
+        }
+    }
+
+    /// Returns `true` if no operations are registered.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.selectors.is_empty()
+    }
+}
+
+impl Drop for Waker {
+    #[inline]
+    fn drop(&mut self) {
+        debug_assert_eq!(self.selectors.len(), 0);
+    }
+}
+
+/// A waker that can be shared among threads without locking.
+///
+/// This is a simple wrapper around `Waker` that internally uses a mutex for
+/// synchronization, and skips locking entirely while the queue is empty.
+pub struct SyncWaker {
+    /// The inner `Waker`.
+    inner: Mutex<Waker>,
+
+    /// `true` if the waker is empty.
+    is_empty: AtomicBool,
+}
+
+impl SyncWaker {
+    /// Creates a new `SyncWaker`.
+    #[inline]
+    pub fn new() -> SyncWaker {
+        SyncWaker {
+            inner: Mutex::new(Waker::new()),
+            is_empty: AtomicBool::new(true),
+        }
+    }
+
+    /// Registers the current thread with an operation.
+    #[inline]
+    pub fn register(&self, oper: Operation, cx: &Context) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.register(oper, cx);
+        self.is_empty.store(inner.is_empty(), Ordering::SeqCst);
+    }
+
+    /// Unregisters an operation previously registered by the current thread.
+    #[inline]
+    pub fn unregister(&self, oper: Operation) -> Option<Entry> {
+        let mut inner = self.inner.lock().unwrap();
+        let entry = inner.unregister(oper);
+        self.is_empty.store(inner.is_empty(), Ordering::SeqCst);
+        entry
+    }
+
+    /// Attempts to find one thread (not the current one), select its
+    /// operation, and wake it up.
+    #[inline]
+    pub fn notify(&self) {
+        if !self.is_empty.load(Ordering::SeqCst) {
+            let mut inner = self.inner.lock().unwrap();
+            if !self.is_empty.load(Ordering::SeqCst) {
+                inner.try_select();
+                self.is_empty.store(inner.is_empty(), Ordering::SeqCst);
+            }
+        }
+    }
+
+    /// Notifies all threads that the channel is disconnected.
+    #[inline]
+    pub fn disconnect(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.disconnect();
+        self.is_empty.store(inner.is_empty(), Ordering::SeqCst);
+    }
+}
+
+impl Drop for SyncWaker {
+    #[inline]
+    fn drop(&mut self) {
+        debug_assert_eq!(self.is_empty.load(Ordering::SeqCst), true);
+    }
+}
+
+/// Returns a unique id for the current thread.
+#[inline]
+pub fn current_thread_id() -> usize {
+    // `u8` has no destructor, so this stays usable while the thread's other
+    // locals are being destroyed, unlike `thread::current()`.
+    thread_local! { static DUMMY: u8 = 0 }
+    DUMMY.with(|x| x as *const u8 as usize)
+}
@@ -64,6 +64,7 @@
 // You'll find a few more details in the implementation, but that's the gist of
 // it!
 
+use cell::Cell;
 use fmt;
 use marker;
 use ptr;
@@ -93,6 +94,10 @@ pub struct Once {
     // This `state` word is actually an encoded version of just a pointer to a
     // `Waiter`, so we add the `PhantomData` appropriately.
     state: AtomicUsize,
+    // Identifies the thread running the closure while in the RUNNING state
+    // (see `current_thread_key`), and is 0 otherwise, so that a reentrant call
+    // from that closure can panic instead of waiting on itself forever.
+    owner: AtomicUsize,
     _marker: marker::PhantomData<*mut Waiter>,
 }
 
@@ -112,6 +117,9 @@ unsafe impl Send for Once {}
 #[derive(Debug)]
 pub struct OnceState {
     poisoned: bool,
+    // Set by `OnceLock` when its initialization failed without panicking,
+    // so that the `Once` is left poisoned and the next caller tries again.
+    set_poisoned: Cell<bool>,
 }
 
 /// Initialization value for static [`Once`] values.
@@ -147,10 +155,11 @@ struct Waiter {
 }
 
 // Helper struct used to clean up after a closure call with a `Drop`
-// implementation to also run on panic.
-struct Finish {
-    panicked: bool,
-    me: &'static Once,
+// implementation to also run on panic. The `Once` is poisoned unless the
+// closure returned without asking for it to be.
+struct Finish<'a> {
+    poison: bool,
+    me: &'a Once,
 }
 
 impl Once {
@@ -159,6 +168,7 @@ impl Once {
     pub const fn new() -> Once {
         Once {
             state: AtomicUsize::new(INCOMPLETE),
+            owner: AtomicUsize::new(0),
             _marker: marker::PhantomData,
         }
     }
@@ -215,6 +225,9 @@ impl Once {
     ///
     /// This is similar to [poisoning with mutexes][poison].
     ///
+    /// Calling `call_once` from within `f` on the same `Once` panics, as it
+    /// could otherwise never return.
+    ///
     /// [poison]: struct.Mutex.html#poisoning
     #[stable(feature = "rust1", since = "1.0.0")]
     pub fn call_once<F>(&'static self, f: F) where F: FnOnce() {
@@ -227,6 +240,11 @@ impl Once {
         self.call_inner(false, &mut |_| f.take().unwrap()());
     }
 
+    /// Returns whether some initialization has completed successfully.
+    pub(crate) fn is_completed(&self) -> bool {
+        self.state.load(Ordering::SeqCst) == COMPLETE
+    }
+
     /// Performs the same function as [`call_once`] except ignores poisoning.
     ///
     /// Unlike [`call_once`], if this `Once` has been poisoned (i.e. a previous
@@ -281,9 +299,7 @@ impl Once {
         }
 
         let mut f = Some(f);
-        self.call_inner(true, &mut |p| {
-            f.take().unwrap()(&OnceState { poisoned: p })
-        });
+        self.call_inner(true, &mut |p| f.take().unwrap()(p));
     }
 
     // This is a non-generic function to reduce the monomorphization cost of
@@ -297,10 +313,14 @@ impl Once {
     // Finally, this takes an `FnMut` instead of a `FnOnce` because there's
     // currently no way to take an `FnOnce` and call it via virtual dispatch
     // without some allocation overhead.
+    //
+    // Unlike the public methods, this doesn't require a `'static` `Once`, so
+    // that `OnceLock` can use it: borrowing the `Once` already prevents it
+    // from being freed while threads are waiting on it.
     #[cold]
-    fn call_inner(&'static self,
-                  ignore_poisoning: bool,
-                  init: &mut FnMut(bool)) {
+    pub(crate) fn call_inner(&self,
+                             ignore_poisoning: bool,
+                             init: &mut FnMut(&OnceState)) {
         let mut state = self.state.load(Ordering::SeqCst);
 
         'outer: loop {
@@ -327,6 +347,7 @@ impl Once {
                         state = old;
                         continue
                     }
+                    self.owner.store(current_thread_key(), Ordering::SeqCst);
 
                     // Run the initialization routine, letting it know if we're
                     // poisoned or not. The `Finish` struct is then dropped, and
@@ -334,11 +355,15 @@ impl Once {
                     // up other waiters both in the normal return and panicking
                     // case.
                     let mut complete = Finish {
-                        panicked: true,
+                        poison: true,
                         me: self,
                     };
-                    init(state == POISONED);
-                    complete.panicked = false;
+                    let once_state = OnceState {
+                        poisoned: state == POISONED,
+                        set_poisoned: Cell::new(false),
+                    };
+                    init(&once_state);
+                    complete.poison = once_state.set_poisoned.get();
                     return
                 }
 
@@ -349,6 +374,10 @@ impl Once {
                 // not RUNNING.
                 _ => {
                     assert!(state & STATE_MASK == RUNNING);
+                    let key = current_thread_key();
+                    if key != 0 && self.owner.load(Ordering::SeqCst) == key {
+                        panic!("reentrant init");
+                    }
                     let mut node = Waiter {
                         thread: Some(thread::current()),
                         signaled: AtomicBool::new(false),
@@ -389,11 +418,20 @@ impl fmt::Debug for Once {
     }
 }
 
-impl Drop for Finish {
+// The address of a thread-local, which is unique to the current thread for as
+// long as it runs, or 0 if its thread-local storage has been torn down already.
+fn current_thread_key() -> usize {
+    thread_local!(static KEY: u8 = 0);
+    KEY.try_with(|key| key as *const u8 as usize).unwrap_or(0)
+}
+
+impl<'a> Drop for Finish<'a> {
     fn drop(&mut self) {
+        self.me.owner.store(0, Ordering::SeqCst);
+
         // Swap out our state with however we finished. We should only ever see
         // an old state which was RUNNING.
-        let queue = if self.panicked {
+        let queue = if self.poison {
             self.me.state.swap(POISONED, Ordering::SeqCst)
         } else {
             self.me.state.swap(COMPLETE, Ordering::SeqCst)
@@ -463,6 +501,12 @@ impl OnceState {
     pub fn poisoned(&self) -> bool {
         self.poisoned
     }
+
+    /// Leaves the `Once` poisoned once the closure returns, instead of
+    /// marking it complete.
+    pub(crate) fn poison(&self) {
+        self.set_poisoned.set(true);
+    }
 }
 
 #[cfg(all(test, not(target_os = "emscripten")))]
@@ -582,4 +626,22 @@ mod tests {
         assert!(t2.join().is_ok());
 
     }
+
+    #[test]
+    fn reentrant_call_once() {
+        static O: Once = Once::new();
+
+        let t = panic::catch_unwind(|| {
+            O.call_once(|| O.call_once(|| {}));
+        });
+        assert!(t.is_err());
+
+        // the reentrant call poisoned the once rather than leaving it running
+        let mut called = false;
+        O.call_once_force(|p| {
+            assert!(p.poisoned());
+            called = true;
+        });
+        assert!(called);
+    }
 }
@@ -0,0 +1,440 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+
+use cell::UnsafeCell;
+use fmt;
+use panic::{RefUnwindSafe, UnwindSafe};
+use sync::Once;
+
+/// A synchronization primitive which can be written to only once.
+///
+/// This type is a thread-safe `OnceCell`, and can be used in statics.
+///
+/// Initialization is driven by a [`Once`], so concurrent callers of
+/// [`get_or_init`] block until the first of them has finished, and every
+/// caller observes the same value. Unlike `Once` however, a `OnceLock` does
+/// not stay poisoned: if the initializing closure panics, the lock is left
+/// uninitialized and the next caller runs its own closure.
+///
+/// [`Once`]: struct.Once.html
+/// [`get_or_init`]: #method.get_or_init
+///
+/// # Examples
+///
+/// ```
+/// #![feature(once_cell)]
+///
+/// use std::sync::OnceLock;
+/// use std::thread;
+///
+/// static CELL: OnceLock<String> = OnceLock::new();
+/// assert!(CELL.get().is_none());
+///
+/// thread::spawn(|| {
+///     let value: &String = CELL.get_or_init(|| {
+///         "Hello, World!".to_string()
+///     });
+///     assert_eq!(value, "Hello, World!");
+/// }).join().unwrap();
+///
+/// let value: Option<&String> = CELL.get();
+/// assert!(value.is_some());
+/// assert_eq!(value.unwrap().as_str(), "Hello, World!");
+/// ```
+#[unstable(feature = "once_cell", issue = "0")]
+pub struct OnceLock<T> {
+    once: Once,
+    // Whether or not the value is initialized is tracked by `once`.
+    value: UnsafeCell<Option<T>>,
+}
+
+// Why do we need `T: Send`?
+// Thread A creates a `OnceLock` and shares it with scoped thread B, which
+// fills the cell, which is then destroyed by A. That is, the destructor
+// observes a sent value.
+#[unstable(feature = "once_cell", issue = "0")]
+unsafe impl<T: Sync + Send> Sync for OnceLock<T> {}
+#[unstable(feature = "once_cell", issue = "0")]
+unsafe impl<T: Send> Send for OnceLock<T> {}
+
+#[unstable(feature = "once_cell", issue = "0")]
+impl<T: RefUnwindSafe + UnwindSafe> RefUnwindSafe for OnceLock<T> {}
+#[unstable(feature = "once_cell", issue = "0")]
+impl<T: UnwindSafe> UnwindSafe for OnceLock<T> {}
+
+#[unstable(feature = "once_cell", issue = "0")]
+impl<T> Default for OnceLock<T> {
+    fn default() -> OnceLock<T> {
+        OnceLock::new()
+    }
+}
+
+#[unstable(feature = "once_cell", issue = "0")]
+impl<T: fmt::Debug> fmt::Debug for OnceLock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.get() {
+            Some(v) => f.debug_tuple("OnceLock").field(v).finish(),
+            None => f.write_str("OnceLock(Uninit)"),
+        }
+    }
+}
+
+#[unstable(feature = "once_cell", issue = "0")]
+impl<T: Clone> Clone for OnceLock<T> {
+    fn clone(&self) -> OnceLock<T> {
+        let cell = OnceLock::new();
+        if let Some(value) = self.get() {
+            match cell.set(value.clone()) {
+                Ok(()) => (),
+                Err(_) => unreachable!(),
+            }
+        }
+        cell
+    }
+}
+
+#[unstable(feature = "once_cell", issue = "0")]
+impl<T> From<T> for OnceLock<T> {
+    fn from(value: T) -> OnceLock<T> {
+        let cell = OnceLock::new();
+        match cell.set(value) {
+            Ok(()) => cell,
+            Err(_) => unreachable!(),
+        }
+    }
+}
+
+#[unstable(feature = "once_cell", issue = "0")]
+impl<T: PartialEq> PartialEq for OnceLock<T> {
+    fn eq(&self, other: &OnceLock<T>) -> bool {
+        self.get() == other.get()
+    }
+}
+
+#[unstable(feature = "once_cell", issue = "0")]
+impl<T: Eq> Eq for OnceLock<T> {}
+
+impl<T> OnceLock<T> {
+    /// Creates a new empty cell.
+    #[unstable(feature = "once_cell", issue = "0")]
+    pub const fn new() -> OnceLock<T> {
+        OnceLock {
+            once: Once::new(),
+            value: UnsafeCell::new(None),
+        }
+    }
+
+    /// Gets the reference to the underlying value.
+    ///
+    /// Returns `None` if the cell is empty, or being initialized. This
+    /// method never blocks.
+    #[unstable(feature = "once_cell", issue = "0")]
+    pub fn get(&self) -> Option<&T> {
+        if self.once.is_completed() {
+            // Safe because the value is initialized and never written to
+            // again.
+            unsafe { &*self.value.get() }.as_ref()
+        } else {
+            None
+        }
+    }
+
+    /// Gets the mutable reference to the underlying value.
+    ///
+    /// Returns `None` if the cell is empty. This method never blocks.
+    #[unstable(feature = "once_cell", issue = "0")]
+    pub fn get_mut(&mut self) -> Option<&mut T> {
+        // Safe because we have unique access.
+        unsafe { &mut *self.value.get() }.as_mut()
+    }
+
+    /// Sets the contents of this cell to `value`.
+    ///
+    /// May block if another thread is currently attempting to initialize
+    /// the cell. The cell is guaranteed to contain a value when `set`
+    /// returns, though not necessarily the one provided.
+    ///
+    /// Returns `Ok(())` if the cell's value was set by this call.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(once_cell)]
+    ///
+    /// use std::sync::OnceLock;
+    ///
+    /// static CELL: OnceLock<i32> = OnceLock::new();
+    ///
+    /// assert!(CELL.get().is_none());
+    /// assert_eq!(CELL.set(92), Ok(()));
+    /// assert_eq!(CELL.set(62), Err(62));
+    /// assert_eq!(CELL.get(), Some(&92));
+    /// ```
+    #[unstable(feature = "once_cell", issue = "0")]
+    pub fn set(&self, value: T) -> Result<(), T> {
+        let mut value = Some(value);
+        self.get_or_init(|| value.take().unwrap());
+        match value {
+            None => Ok(()),
+            Some(value) => Err(value),
+        }
+    }
+
+    /// Gets the contents of the cell, initializing it with `f` if the cell
+    /// was empty.
+    ///
+    /// Many threads may call `get_or_init` concurrently with different
+    /// initializing functions, but it is guaranteed that only one function
+    /// will be executed.
+    ///
+    /// # Panics
+    ///
+    /// If `f` panics, the panic is propagated to the caller, and the cell
+    /// remains uninitialized: the next caller runs its own function.
+    ///
+    /// It is an error to reentrantly initialize the cell from `f`. Doing
+    /// so results in a panic, like with `OnceCell`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(once_cell)]
+    ///
+    /// use std::sync::OnceLock;
+    ///
+    /// let cell = OnceLock::new();
+    /// let value = cell.get_or_init(|| 92);
+    /// assert_eq!(value, &92);
+    /// let value = cell.get_or_init(|| unreachable!());
+    /// assert_eq!(value, &92);
+    /// ```
+    #[unstable(feature = "once_cell", issue = "0")]
+    pub fn get_or_init<F>(&self, f: F) -> &T where F: FnOnce() -> T {
+        match self.get_or_try_init(|| Ok::<T, ()>(f())) {
+            Ok(value) => value,
+            Err(()) => unreachable!(),
+        }
+    }
+
+    /// Gets the contents of the cell, initializing it with `f` if the cell
+    /// was empty. If the cell was empty and `f` failed, an error is
+    /// returned and the cell remains uninitialized.
+    ///
+    /// # Panics
+    ///
+    /// If `f` panics, the panic is propagated to the caller, and the cell
+    /// remains uninitialized.
+    ///
+    /// It is an error to reentrantly initialize the cell from `f`. Doing
+    /// so results in a panic.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(once_cell)]
+    ///
+    /// use std::sync::OnceLock;
+    ///
+    /// let cell = OnceLock::new();
+    /// assert_eq!(cell.get_or_try_init(|| Err(())), Err(()));
+    /// assert!(cell.get().is_none());
+    /// let value = cell.get_or_try_init(|| -> Result<i32, ()> {
+    ///     Ok(92)
+    /// });
+    /// assert_eq!(value, Ok(&92));
+    /// assert_eq!(cell.get(), Some(&92))
+    /// ```
+    #[unstable(feature = "once_cell", issue = "0")]
+    pub fn get_or_try_init<F, E>(&self, f: F) -> Result<&T, E>
+        where F: FnOnce() -> Result<T, E>
+    {
+        // Fast path check
+        if let Some(value) = self.get() {
+            return Ok(value);
+        }
+        self.initialize(f)?;
+
+        // Safe because `initialize` only returns `Ok` once `once` is
+        // complete.
+        Ok(unsafe { &*self.value.get() }.as_ref().unwrap())
+    }
+
+    /// Consumes the `OnceLock`, returning the wrapped value. Returns
+    /// `None` if the cell was empty.
+    #[unstable(feature = "once_cell", issue = "0")]
+    pub fn into_inner(mut self) -> Option<T> {
+        self.take()
+    }
+
+    /// Takes the value out of this `OnceLock`, moving it back to an
+    /// uninitialized state.
+    ///
+    /// Has no effect and returns `None` if the `OnceLock` hasn't been
+    /// initialized.
+    #[unstable(feature = "once_cell", issue = "0")]
+    pub fn take(&mut self) -> Option<T> {
+        if self.once.is_completed() {
+            self.once = Once::new();
+            // Safe because `once` has been reset and we have unique access.
+            unsafe { &mut *self.value.get() }.take()
+        } else {
+            None
+        }
+    }
+
+    // This is kept out of line, like `Once::call_inner`, so that the fast
+    // path in `get_or_try_init` stays small.
+    //
+    // Poisoning is ignored: a panic in `f` leaves `once` poisoned, and the
+    // next caller simply runs its own function. A failed `f` poisons it on
+    // purpose so that `once` is not marked complete with no value stored.
+    #[cold]
+    fn initialize<F, E>(&self, f: F) -> Result<(), E>
+        where F: FnOnce() -> Result<T, E>
+    {
+        let mut f = Some(f);
+        let mut res: Result<(), E> = Ok(());
+        let slot = &self.value;
+        self.once.call_inner(true, &mut |p| {
+            match f.take().unwrap()() {
+                Ok(value) => unsafe { *slot.get() = Some(value) },
+                Err(e) => {
+                    res = Err(e);
+                    p.poison();
+                }
+            }
+        });
+        res
+    }
+}
+
+
+#[cfg(all(test, not(target_os = "emscripten")))]
+mod tests {
+    use panic;
+    use sync::mpsc::channel;
+    use thread;
+    use super::OnceLock;
+
+    #[test]
+    fn sync_once_lock() {
+        static ONCE_LOCK: OnceLock<i32> = OnceLock::new();
+
+        assert!(ONCE_LOCK.get().is_none());
+
+        thread::spawn(|| {
+            ONCE_LOCK.get_or_init(|| 92);
+            assert_eq!(ONCE_LOCK.get(), Some(&92));
+        }).join().unwrap();
+
+        ONCE_LOCK.get_or_init(|| panic!("get_or_init called twice"));
+        assert_eq!(ONCE_LOCK.get(), Some(&92));
+    }
+
+    #[test]
+    fn stampede_once_lock() {
+        static O: OnceLock<usize> = OnceLock::new();
+        let (tx, rx) = channel();
+        for i in 0..10 {
+            let tx = tx.clone();
+            thread::spawn(move|| {
+                for _ in 0..4 { thread::yield_now() }
+                let value = *O.get_or_init(|| i);
+                assert!(value < 10);
+                tx.send(value).unwrap();
+            });
+        }
+
+        let first = rx.recv().unwrap();
+        for _ in 1..10 {
+            assert_eq!(rx.recv().unwrap(), first);
+        }
+        assert_eq!(O.get(), Some(&first));
+    }
+
+    #[test]
+    fn once_lock_set() {
+        let cell = OnceLock::new();
+        assert_eq!(cell.set(92), Ok(()));
+        assert_eq!(cell.set(62), Err(62));
+        assert_eq!(cell.get(), Some(&92));
+    }
+
+    #[test]
+    fn once_lock_get_mut_and_take() {
+        let mut cell: OnceLock<String> = OnceLock::new();
+        assert!(cell.get_mut().is_none());
+        assert!(cell.take().is_none());
+
+        cell.get_or_init(|| "hello".to_string());
+        cell.get_mut().unwrap().push_str(", world");
+        assert_eq!(cell.take(), Some("hello, world".to_string()));
+        assert!(cell.get().is_none());
+
+        assert_eq!(cell.set("again".to_string()), Ok(()));
+        assert_eq!(cell.into_inner(), Some("again".to_string()));
+    }
+
+    #[test]
+    fn once_lock_get_or_try_init() {
+        let cell: OnceLock<String> = OnceLock::new();
+        assert!(cell.get().is_none());
+
+        let res = panic::catch_unwind(|| cell.get_or_try_init(|| -> Result<_, ()> { panic!() }));
+        assert!(res.is_err());
+        assert!(cell.get().is_none());
+
+        assert_eq!(cell.get_or_try_init(|| Err(())), Err(()));
+        assert!(cell.get().is_none());
+
+        assert_eq!(cell.get_or_try_init(|| Ok::<_, ()>("hello".to_string())),
+                   Ok(&"hello".to_string()));
+        assert_eq!(cell.get(), Some(&"hello".to_string()));
+    }
+
+    #[test]
+    fn once_lock_retries_after_panic() {
+        static O: OnceLock<i32> = OnceLock::new();
+
+        let t = panic::catch_unwind(|| {
+            O.get_or_init(|| panic!());
+        });
+        assert!(t.is_err());
+        assert!(O.get().is_none());
+
+        // A panicking initializer doesn't poison the lock for good.
+        assert_eq!(O.get_or_init(|| 92), &92);
+    }
+
+    #[test]
+    fn once_lock_reentrant_init() {
+        static O: OnceLock<i32> = OnceLock::new();
+
+        let res = panic::catch_unwind(|| {
+            O.get_or_init(|| *O.get_or_init(|| 1) + 1);
+        });
+        assert!(res.is_err());
+        assert!(O.get().is_none());
+
+        // The lock isn't left running, so it can still be initialized.
+        assert_eq!(O.get_or_init(|| 92), &92);
+    }
+
+    #[test]
+    fn once_lock_clone_and_eq() {
+        let a = OnceLock::from(5);
+        let b = a.clone();
+        assert_eq!(a, b);
+        assert!(OnceLock::<i32>::new() != b);
+        assert_eq!(format!("{:?}", b), "OnceLock(5)");
+        assert_eq!(format!("{:?}", OnceLock::<i32>::default()), "OnceLock(Uninit)");
+    }
+}
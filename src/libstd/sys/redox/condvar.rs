@@ -15,6 +15,11 @@ use time::Duration;
 
 use sys::mutex::{mutex_unlock, Mutex};
 use sys::syscall::{futex, TimeSpec, FUTEX_WAIT, FUTEX_WAKE, FUTEX_REQUEUE};
+use sys_common::condvar::SameMutexCheck;
+use sys_common::lazy_box::{LazyBox, LazyInit};
+
+pub type MovableCondvar = LazyBox<Condvar>;
+pub type CondvarCheck = SameMutexCheck;
 
 pub struct Condvar {
     lock: UnsafeCell<*mut i32>,
@@ -116,6 +121,18 @@ impl Condvar {
     }
 }
 
+impl LazyInit for Condvar {
+    fn init() -> Box<Condvar> {
+        let condvar = box Condvar::new();
+        unsafe { condvar.init() };
+        condvar
+    }
+
+    unsafe fn destroy(&self) {
+        Condvar::destroy(self)
+    }
+}
+
 unsafe impl Send for Condvar {}
 
 unsafe impl Sync for Condvar {}
@@ -12,7 +12,7 @@ use os::unix::prelude::*;
 
 use ffi::{OsString, OsStr};
 use fmt;
-use io::{self, Error, ErrorKind, SeekFrom};
+use io::{self, Error, ErrorKind, SeekFrom, IoSlice, IoSliceMut};
 use path::{Path, PathBuf};
 use sync::Arc;
 use sys::fd::FileDesc;
@@ -285,10 +285,18 @@ impl File {
         self.0.read(buf)
     }
 
+    pub fn read_vectored(&self, bufs: &mut [IoSliceMut]) -> io::Result<usize> {
+        io::default_read_vectored(|buf| self.read(buf), bufs)
+    }
+
     pub fn write(&self, buf: &[u8]) -> io::Result<usize> {
         self.0.write(buf)
     }
 
+    pub fn write_vectored(&self, bufs: &[IoSlice]) -> io::Result<usize> {
+        io::default_write_vectored(|buf| self.write(buf), bufs)
+    }
+
     pub fn flush(&self) -> io::Result<()> { Ok(()) }
 
     pub fn seek(&self, pos: SeekFrom) -> io::Result<u64> {
@@ -316,6 +324,26 @@ impl File {
         set_perm(&self.path()?, perm)
     }
 
+    pub fn lock(&self) -> io::Result<()> {
+        lock_unsupported()
+    }
+
+    pub fn lock_shared(&self) -> io::Result<()> {
+        lock_unsupported()
+    }
+
+    pub fn try_lock(&self) -> io::Result<()> {
+        lock_unsupported()
+    }
+
+    pub fn try_lock_shared(&self) -> io::Result<()> {
+        lock_unsupported()
+    }
+
+    pub fn unlock(&self) -> io::Result<()> {
+        lock_unsupported()
+    }
+
     pub fn path(&self) -> io::Result<PathBuf> {
         let mut buf: [u8; 4096] = [0; 4096];
         let count = cvt(syscall::fpath(*self.fd().as_inner() as usize, &mut buf))?;
@@ -327,6 +355,10 @@ impl File {
     pub fn into_fd(self) -> FileDesc { self.0 }
 }
 
+fn lock_unsupported() -> io::Result<()> {
+    Err(Error::new(ErrorKind::Other, "file locking is not supported on this platform"))
+}
+
 impl DirBuilder {
     pub fn new() -> DirBuilder {
         DirBuilder { mode: 0o777 }
@@ -14,6 +14,7 @@ use io::{self, ErrorKind};
 
 pub use libc::strlen;
 pub use self::rand::hashmap_random_keys;
+pub(crate) use io::generic_copy as copy_impl;
 
 pub mod args;
 #[cfg(feature = "backtrace")]
@@ -25,6 +26,7 @@ pub mod ext;
 pub mod fast_thread_local;
 pub mod fd;
 pub mod fs;
+pub mod io;
 pub mod memchr;
 pub mod mutex;
 pub mod net;
@@ -13,6 +13,7 @@ use intrinsics::{atomic_cxchg, atomic_xchg};
 use ptr;
 
 use sys::syscall::{futex, getpid, FUTEX_WAIT, FUTEX_WAKE};
+use sys_common::lazy_box::{LazyBox, LazyInit};
 
 pub unsafe fn mutex_try_lock(m: *mut i32) -> bool {
     atomic_cxchg(m, 0, 1).0 == 0
@@ -55,6 +56,8 @@ pub unsafe fn mutex_unlock(m: *mut i32) {
     let _ = futex(m, FUTEX_WAKE, 1, 0, ptr::null_mut());
 }
 
+pub type MovableMutex = LazyBox<Mutex>;
+
 pub struct Mutex {
     pub lock: UnsafeCell<i32>,
 }
@@ -96,6 +99,18 @@ impl Mutex {
     }
 }
 
+impl LazyInit for Mutex {
+    fn init() -> Box<Mutex> {
+        let mutex = box Mutex::new();
+        unsafe { mutex.init() };
+        mutex
+    }
+
+    unsafe fn destroy(&self) {
+        Mutex::destroy(self)
+    }
+}
+
 unsafe impl Send for Mutex {}
 
 unsafe impl Sync for Mutex {}
@@ -9,6 +9,9 @@
 // except according to those terms.
 
 use super::mutex::Mutex;
+use sys_common::lazy_box::{LazyBox, LazyInit};
+
+pub type MovableRWLock = LazyBox<RWLock>;
 
 pub struct RWLock {
     mutex: Mutex
@@ -59,3 +62,13 @@ impl RWLock {
         self.mutex.destroy();
     }
 }
+
+impl LazyInit for RWLock {
+    fn init() -> Box<RWLock> {
+        box RWLock::new()
+    }
+
+    unsafe fn destroy(&self) {
+        RWLock::destroy(self)
+    }
+}
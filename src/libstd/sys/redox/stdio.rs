@@ -8,7 +8,7 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use io;
+use io::{self, IoSlice};
 use sys::{cvt, syscall};
 use sys::fd::FileDesc;
 
@@ -37,6 +37,10 @@ impl Stdout {
         ret
     }
 
+    pub fn write_vectored(&self, bufs: &[IoSlice]) -> io::Result<usize> {
+        io::default_write_vectored(|data| self.write(data), bufs)
+    }
+
     pub fn flush(&self) -> io::Result<()> {
         cvt(syscall::fsync(1)).and(Ok(()))
     }
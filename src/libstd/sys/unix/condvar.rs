@@ -10,9 +10,14 @@
 
 use cell::UnsafeCell;
 use libc;
-use sys::mutex::{self, Mutex};
+use super::mutex::{self, Mutex};
+use sys_common::condvar::SameMutexCheck;
+use sys_common::lazy_box::{LazyBox, LazyInit};
 use time::Duration;
 
+pub type MovableCondvar = LazyBox<Condvar>;
+pub type CondvarCheck = SameMutexCheck;
+
 pub struct Condvar { inner: UnsafeCell<libc::pthread_cond_t> }
 
 unsafe impl Send for Condvar {}
@@ -185,3 +190,15 @@ impl Condvar {
         debug_assert!(r == 0 || r == libc::EINVAL);
     }
 }
+
+impl LazyInit for Condvar {
+    fn init() -> Box<Condvar> {
+        let mut condvar = box Condvar::new();
+        unsafe { condvar.init() };
+        condvar
+    }
+
+    unsafe fn destroy(&self) {
+        Condvar::destroy(self)
+    }
+}
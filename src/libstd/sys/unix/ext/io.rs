@@ -12,9 +12,13 @@
 
 #![stable(feature = "rust1", since = "1.0.0")]
 
+use fmt;
 use fs;
+use marker::PhantomData;
+use mem;
 use os::raw;
 use sys;
+use sys::fd::FileDesc;
 use io;
 use sys_common::{AsInner, FromInner, IntoInner};
 use libc;
@@ -73,6 +77,170 @@ pub trait IntoRawFd {
     fn into_raw_fd(self) -> RawFd;
 }
 
+/// A borrowed file descriptor.
+///
+/// This has a lifetime parameter to tie it to the lifetime of something that
+/// owns the file descriptor, so the descriptor can't be used after it has
+/// been closed. Unlike a `RawFd`, a `BorrowedFd` is never `-1`.
+#[derive(Copy, Clone)]
+#[unstable(feature = "io_safety", issue = "0")]
+pub struct BorrowedFd<'fd> {
+    fd: RawFd,
+    _phantom: PhantomData<&'fd OwnedFd>,
+}
+
+/// An owned file descriptor.
+///
+/// This closes the file descriptor on drop. Unlike a `RawFd`, an `OwnedFd`
+/// is never `-1`.
+#[unstable(feature = "io_safety", issue = "0")]
+pub struct OwnedFd {
+    fd: RawFd,
+}
+
+impl<'fd> BorrowedFd<'fd> {
+    /// Returns a `BorrowedFd` holding the given raw file descriptor.
+    ///
+    /// # Safety
+    ///
+    /// The resource pointed to by `fd` must remain open for the duration of
+    /// the returned `BorrowedFd`, and it must not be `-1`.
+    #[unstable(feature = "io_safety", issue = "0")]
+    pub unsafe fn borrow_raw_fd(fd: RawFd) -> BorrowedFd<'fd> {
+        assert!(fd != -1);
+        BorrowedFd { fd, _phantom: PhantomData }
+    }
+}
+
+impl OwnedFd {
+    /// Creates a new `OwnedFd` instance that shares the same underlying file
+    /// description as the existing `OwnedFd` instance.
+    ///
+    /// The new descriptor has the close-on-exec flag set.
+    #[unstable(feature = "io_safety", issue = "0")]
+    pub fn try_clone(&self) -> io::Result<OwnedFd> {
+        let fd = FileDesc::new(self.fd);
+        let ret = fd.duplicate();
+        fd.into_raw();
+        ret.map(|fd| OwnedFd { fd: fd.into_raw() })
+    }
+}
+
+#[unstable(feature = "io_safety", issue = "0")]
+impl<'fd> AsRawFd for BorrowedFd<'fd> {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd
+    }
+}
+
+#[unstable(feature = "io_safety", issue = "0")]
+impl AsRawFd for OwnedFd {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd
+    }
+}
+
+#[unstable(feature = "io_safety", issue = "0")]
+impl IntoRawFd for OwnedFd {
+    fn into_raw_fd(self) -> RawFd {
+        let fd = self.fd;
+        mem::forget(self);
+        fd
+    }
+}
+
+#[unstable(feature = "io_safety", issue = "0")]
+impl FromRawFd for OwnedFd {
+    /// Constructs a new instance of `Self` from the given raw file descriptor.
+    ///
+    /// # Safety
+    ///
+    /// The resource pointed to by `fd` must be open and suitable for assuming
+    /// ownership, and it must not be `-1`.
+    unsafe fn from_raw_fd(fd: RawFd) -> OwnedFd {
+        assert!(fd != -1);
+        OwnedFd { fd }
+    }
+}
+
+#[unstable(feature = "io_safety", issue = "0")]
+impl Drop for OwnedFd {
+    fn drop(&mut self) {
+        // Errors are ignored when closing a file descriptor, for the same
+        // reasons as in `sys::fd::FileDesc`.
+        unsafe {
+            let _ = libc::close(self.fd);
+        }
+    }
+}
+
+#[unstable(feature = "io_safety", issue = "0")]
+impl<'fd> fmt::Debug for BorrowedFd<'fd> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BorrowedFd").field("fd", &self.fd).finish()
+    }
+}
+
+#[unstable(feature = "io_safety", issue = "0")]
+impl fmt::Debug for OwnedFd {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("OwnedFd").field("fd", &self.fd).finish()
+    }
+}
+
+/// A trait to borrow the file descriptor from an underlying object.
+///
+/// This is the safe counterpart of `AsRawFd`: the returned `BorrowedFd`
+/// can't outlive the object it was borrowed from.
+#[unstable(feature = "io_safety", issue = "0")]
+pub trait AsFd {
+    /// Borrows the file descriptor.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(io_safety)]
+    /// use std::fs::File;
+    /// use std::os::unix::io::{AsFd, BorrowedFd};
+    ///
+    /// let f = File::open("foo.txt").unwrap();
+    /// let borrowed_fd: BorrowedFd = f.as_fd();
+    /// ```
+    #[unstable(feature = "io_safety", issue = "0")]
+    fn as_fd(&self) -> BorrowedFd;
+}
+
+#[unstable(feature = "io_safety", issue = "0")]
+impl<'a, T: AsFd> AsFd for &'a T {
+    fn as_fd(&self) -> BorrowedFd {
+        T::as_fd(self)
+    }
+}
+
+#[unstable(feature = "io_safety", issue = "0")]
+impl<'a, T: AsFd> AsFd for &'a mut T {
+    fn as_fd(&self) -> BorrowedFd {
+        T::as_fd(self)
+    }
+}
+
+#[unstable(feature = "io_safety", issue = "0")]
+impl<'fd> AsFd for BorrowedFd<'fd> {
+    fn as_fd(&self) -> BorrowedFd {
+        *self
+    }
+}
+
+#[unstable(feature = "io_safety", issue = "0")]
+impl AsFd for OwnedFd {
+    fn as_fd(&self) -> BorrowedFd {
+        // Safety: `OwnedFd` and `BorrowedFd` have the same validity
+        // invariants, and the `BorrowedFd` is bounded by the lifetime of
+        // `&self`.
+        unsafe { BorrowedFd::borrow_raw_fd(self.as_raw_fd()) }
+    }
+}
+
 #[stable(feature = "rust1", since = "1.0.0")]
 impl AsRawFd for fs::File {
     fn as_raw_fd(&self) -> RawFd {
@@ -106,3 +274,45 @@ impl AsRawFd for io::Stdout {
 impl AsRawFd for io::Stderr {
     fn as_raw_fd(&self) -> RawFd { libc::STDERR_FILENO }
 }
+
+#[unstable(feature = "io_safety", issue = "0")]
+impl AsFd for fs::File {
+    fn as_fd(&self) -> BorrowedFd {
+        unsafe { BorrowedFd::borrow_raw_fd(self.as_raw_fd()) }
+    }
+}
+
+#[unstable(feature = "io_safety", issue = "0")]
+impl From<fs::File> for OwnedFd {
+    fn from(file: fs::File) -> OwnedFd {
+        unsafe { OwnedFd::from_raw_fd(file.into_raw_fd()) }
+    }
+}
+
+#[unstable(feature = "io_safety", issue = "0")]
+impl From<OwnedFd> for fs::File {
+    fn from(owned_fd: OwnedFd) -> fs::File {
+        unsafe { fs::File::from_raw_fd(owned_fd.into_raw_fd()) }
+    }
+}
+
+#[unstable(feature = "io_safety", issue = "0")]
+impl AsFd for io::Stdin {
+    fn as_fd(&self) -> BorrowedFd {
+        unsafe { BorrowedFd::borrow_raw_fd(libc::STDIN_FILENO) }
+    }
+}
+
+#[unstable(feature = "io_safety", issue = "0")]
+impl AsFd for io::Stdout {
+    fn as_fd(&self) -> BorrowedFd {
+        unsafe { BorrowedFd::borrow_raw_fd(libc::STDOUT_FILENO) }
+    }
+}
+
+#[unstable(feature = "io_safety", issue = "0")]
+impl AsFd for io::Stderr {
+    fn as_fd(&self) -> BorrowedFd {
+        unsafe { BorrowedFd::borrow_raw_fd(libc::STDERR_FILENO) }
+    }
+}
@@ -0,0 +1,514 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+
+//! Ancillary data ("control messages") for Unix domain sockets.
+
+use cmp;
+use fmt;
+use io::{self, IoSlice, IoSliceMut};
+use libc;
+use marker::PhantomData;
+use mem;
+use os::unix::io::{FromRawFd, OwnedFd, RawFd};
+use path::Path;
+use ptr;
+use sys::cvt;
+use sys::fd::max_iov;
+use sys::net::Socket;
+use sys_common::AsInner;
+
+use super::{sockaddr_un, SocketAddr, MSG_NOSIGNAL};
+
+// The `CMSG_*` macros from `<sys/socket.h>`. Control messages are padded to
+// the alignment of `size_t` on the platforms supported here.
+fn cmsg_align(len: usize) -> usize {
+    let align = mem::size_of::<usize>();
+    (len + align - 1) & !(align - 1)
+}
+
+fn cmsg_len(len: usize) -> usize {
+    cmsg_align(mem::size_of::<libc::cmsghdr>()) + len
+}
+
+fn cmsg_space(len: usize) -> usize {
+    cmsg_align(mem::size_of::<libc::cmsghdr>()) + cmsg_align(len)
+}
+
+pub fn recv_vectored_with_ancillary_from(socket: &Socket,
+                                         bufs: &mut [IoSliceMut],
+                                         ancillary: &mut SocketAncillary)
+                                         -> io::Result<(usize, bool, io::Result<SocketAddr>)> {
+    // Anything left over from a previous receive is ours to close.
+    ancillary.clear();
+    unsafe {
+        let mut msg_name: libc::sockaddr_un = mem::zeroed();
+        let mut msg: libc::msghdr = mem::zeroed();
+        msg.msg_name = &mut msg_name as *mut _ as *mut _;
+        msg.msg_namelen = mem::size_of::<libc::sockaddr_un>() as libc::socklen_t;
+        msg.msg_iov = bufs.as_mut_ptr() as *mut libc::iovec;
+        msg.msg_iovlen = cmp::min(bufs.len(), max_iov()) as _;
+        msg.msg_controllen = ancillary.buffer.len() as _;
+        if msg.msg_controllen > 0 {
+            msg.msg_control = ancillary.buffer.as_mut_ptr() as *mut _;
+        }
+
+        let count = cvt(libc::recvmsg(*socket.as_inner(), &mut msg, libc::MSG_CMSG_CLOEXEC))?;
+
+        ancillary.length = msg.msg_controllen as usize;
+        ancillary.truncated = msg.msg_flags & libc::MSG_CTRUNC == libc::MSG_CTRUNC;
+        ancillary.owns_fds = true;
+
+        let truncated = msg.msg_flags & libc::MSG_TRUNC == libc::MSG_TRUNC;
+        let addr = SocketAddr::from_parts(msg_name, msg.msg_namelen);
+
+        Ok((count as usize, truncated, addr))
+    }
+}
+
+pub fn send_vectored_with_ancillary_to(socket: &Socket,
+                                       path: Option<&Path>,
+                                       bufs: &[IoSlice],
+                                       ancillary: &mut SocketAncillary)
+                                       -> io::Result<usize> {
+    unsafe {
+        let (mut msg_name, msg_namelen) = match path {
+            Some(path) => sockaddr_un(path)?,
+            None => (mem::zeroed(), 0),
+        };
+
+        let mut msg: libc::msghdr = mem::zeroed();
+        if msg_namelen > 0 {
+            msg.msg_name = &mut msg_name as *mut _ as *mut _;
+            msg.msg_namelen = msg_namelen;
+        }
+        msg.msg_iov = bufs.as_ptr() as *mut libc::iovec;
+        msg.msg_iovlen = cmp::min(bufs.len(), max_iov()) as _;
+        msg.msg_controllen = ancillary.length as _;
+        if msg.msg_controllen > 0 {
+            msg.msg_control = ancillary.buffer.as_mut_ptr() as *mut _;
+        }
+
+        ancillary.truncated = false;
+
+        let count = cvt(libc::sendmsg(*socket.as_inner(), &msg, MSG_NOSIGNAL))?;
+        Ok(count as usize)
+    }
+}
+
+/// Unix credentials, as carried by an `SCM_CREDENTIALS` control message.
+#[unstable(feature = "unix_socket_ancillary_data", issue = "0")]
+#[derive(Clone, Copy)]
+pub struct SocketCred(libc::ucred);
+
+impl SocketCred {
+    /// Creates a new set of credentials with the PID, UID and GID all zero.
+    #[unstable(feature = "unix_socket_ancillary_data", issue = "0")]
+    pub fn new() -> SocketCred {
+        SocketCred(libc::ucred { pid: 0, uid: 0, gid: 0 })
+    }
+
+    /// Sets the PID.
+    #[unstable(feature = "unix_socket_ancillary_data", issue = "0")]
+    pub fn set_pid(&mut self, pid: libc::pid_t) {
+        self.0.pid = pid;
+    }
+
+    /// Returns the PID.
+    #[unstable(feature = "unix_socket_ancillary_data", issue = "0")]
+    pub fn get_pid(&self) -> libc::pid_t {
+        self.0.pid
+    }
+
+    /// Sets the UID.
+    #[unstable(feature = "unix_socket_ancillary_data", issue = "0")]
+    pub fn set_uid(&mut self, uid: libc::uid_t) {
+        self.0.uid = uid;
+    }
+
+    /// Returns the UID.
+    #[unstable(feature = "unix_socket_ancillary_data", issue = "0")]
+    pub fn get_uid(&self) -> libc::uid_t {
+        self.0.uid
+    }
+
+    /// Sets the GID.
+    #[unstable(feature = "unix_socket_ancillary_data", issue = "0")]
+    pub fn set_gid(&mut self, gid: libc::gid_t) {
+        self.0.gid = gid;
+    }
+
+    /// Returns the GID.
+    #[unstable(feature = "unix_socket_ancillary_data", issue = "0")]
+    pub fn get_gid(&self) -> libc::gid_t {
+        self.0.gid
+    }
+}
+
+#[unstable(feature = "unix_socket_ancillary_data", issue = "0")]
+impl fmt::Debug for SocketCred {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SocketCred")
+            .field("pid", &self.0.pid)
+            .field("uid", &self.0.uid)
+            .field("gid", &self.0.gid)
+            .finish()
+    }
+}
+
+struct AncillaryDataIter<'a, T> {
+    data: &'a [u8],
+    phantom: PhantomData<T>,
+}
+
+impl<'a, T> AncillaryDataIter<'a, T> {
+    fn new(data: &'a [u8]) -> AncillaryDataIter<'a, T> {
+        AncillaryDataIter { data, phantom: PhantomData }
+    }
+}
+
+impl<'a, T> Iterator for AncillaryDataIter<'a, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let size = mem::size_of::<T>();
+        if self.data.len() < size {
+            return None
+        }
+        // The control buffer is a plain byte slice, so its contents are not
+        // necessarily aligned for `T`.
+        let unit = unsafe { ptr::read_unaligned(self.data.as_ptr() as *const T) };
+        self.data = &self.data[size..];
+        Some(unit)
+    }
+}
+
+/// An iterator over the file descriptors of an `SCM_RIGHTS` control message.
+///
+/// The descriptors are only borrowed: the [`SocketAncillary`] they were
+/// received into still owns them, and closes them when it is cleared, reused
+/// or dropped. They must not be closed or wrapped in an owning type such as
+/// `File`; use [`SocketAncillary::take_fds`] to take ownership of them.
+///
+/// [`SocketAncillary`]: struct.SocketAncillary.html
+/// [`SocketAncillary::take_fds`]: struct.SocketAncillary.html#method.take_fds
+#[unstable(feature = "unix_socket_ancillary_data", issue = "0")]
+pub struct ScmRights<'a>(AncillaryDataIter<'a, RawFd>);
+
+#[unstable(feature = "unix_socket_ancillary_data", issue = "0")]
+impl<'a> Iterator for ScmRights<'a> {
+    type Item = RawFd;
+
+    fn next(&mut self) -> Option<RawFd> {
+        self.0.next()
+    }
+}
+
+#[unstable(feature = "unix_socket_ancillary_data", issue = "0")]
+impl<'a> fmt::Debug for ScmRights<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("ScmRights { .. }")
+    }
+}
+
+/// An iterator over the credentials of an `SCM_CREDENTIALS` control message.
+#[unstable(feature = "unix_socket_ancillary_data", issue = "0")]
+pub struct ScmCredentials<'a>(AncillaryDataIter<'a, libc::ucred>);
+
+#[unstable(feature = "unix_socket_ancillary_data", issue = "0")]
+impl<'a> Iterator for ScmCredentials<'a> {
+    type Item = SocketCred;
+
+    fn next(&mut self) -> Option<SocketCred> {
+        self.0.next().map(SocketCred)
+    }
+}
+
+#[unstable(feature = "unix_socket_ancillary_data", issue = "0")]
+impl<'a> fmt::Debug for ScmCredentials<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("ScmCredentials { .. }")
+    }
+}
+
+/// The error returned for a control message this module doesn't understand.
+#[unstable(feature = "unix_socket_ancillary_data", issue = "0")]
+#[derive(Debug)]
+pub enum AncillaryError {
+    /// A control message with an unknown level or type.
+    Unknown {
+        /// The `cmsg_level` of the message.
+        cmsg_level: i32,
+        /// The `cmsg_type` of the message.
+        cmsg_type: i32,
+    },
+}
+
+/// A single control message received into a [`SocketAncillary`].
+///
+/// [`SocketAncillary`]: struct.SocketAncillary.html
+#[unstable(feature = "unix_socket_ancillary_data", issue = "0")]
+#[derive(Debug)]
+pub enum AncillaryData<'a> {
+    /// File descriptors passed with `SCM_RIGHTS`.
+    ScmRights(ScmRights<'a>),
+    /// Process credentials passed with `SCM_CREDENTIALS`.
+    ScmCredentials(ScmCredentials<'a>),
+}
+
+impl<'a> AncillaryData<'a> {
+    fn try_from_cmsg(cmsg: &libc::cmsghdr, data: &'a [u8])
+                     -> Result<AncillaryData<'a>, AncillaryError> {
+        match (cmsg.cmsg_level, cmsg.cmsg_type) {
+            (libc::SOL_SOCKET, libc::SCM_RIGHTS) => {
+                Ok(AncillaryData::ScmRights(ScmRights(AncillaryDataIter::new(data))))
+            }
+            (libc::SOL_SOCKET, libc::SCM_CREDENTIALS) => {
+                Ok(AncillaryData::ScmCredentials(ScmCredentials(AncillaryDataIter::new(data))))
+            }
+            (cmsg_level, cmsg_type) => {
+                Err(AncillaryError::Unknown { cmsg_level, cmsg_type })
+            }
+        }
+    }
+}
+
+/// An iterator over the control messages in a [`SocketAncillary`].
+///
+/// [`SocketAncillary`]: struct.SocketAncillary.html
+#[unstable(feature = "unix_socket_ancillary_data", issue = "0")]
+pub struct Messages<'a> {
+    buffer: &'a [u8],
+    offset: usize,
+}
+
+#[unstable(feature = "unix_socket_ancillary_data", issue = "0")]
+impl<'a> Iterator for Messages<'a> {
+    type Item = Result<AncillaryData<'a>, AncillaryError>;
+
+    fn next(&mut self) -> Option<Result<AncillaryData<'a>, AncillaryError>> {
+        let rest = &self.buffer[self.offset..];
+        if rest.len() < mem::size_of::<libc::cmsghdr>() {
+            return None
+        }
+        let cmsg = unsafe { ptr::read_unaligned(rest.as_ptr() as *const libc::cmsghdr) };
+        let len = cmsg.cmsg_len as usize;
+        if len < cmsg_len(0) || len > rest.len() {
+            return None
+        }
+        self.offset += cmp::min(cmsg_align(len), rest.len());
+        Some(AncillaryData::try_from_cmsg(&cmsg, &rest[cmsg_len(0)..len]))
+    }
+}
+
+#[unstable(feature = "unix_socket_ancillary_data", issue = "0")]
+impl<'a> fmt::Debug for Messages<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("Messages { .. }")
+    }
+}
+
+/// A buffer for the ancillary data sent or received alongside a message on a
+/// Unix domain socket.
+///
+/// File descriptors received with `SCM_RIGHTS` are owned by the buffer: they
+/// are closed when it is cleared, reused for another receive or dropped,
+/// unless ownership is taken first with [`take_fds`].
+///
+/// This type is only available on Linux and Android.
+///
+/// [`take_fds`]: #method.take_fds
+///
+/// # Examples
+///
+/// ```no_run
+/// #![feature(unix_socket_ancillary_data, iovec)]
+/// use std::fs::File;
+/// use std::io::{self, IoSliceMut};
+/// use std::os::unix::net::{AncillaryData, SocketAncillary, UnixStream};
+///
+/// # fn foo() -> io::Result<()> {
+/// let sock = UnixStream::connect("/tmp/sock")?;
+///
+/// let mut buf = [0; 100];
+/// let mut ancillary_buffer = [0; 128];
+/// let mut ancillary = SocketAncillary::new(&mut ancillary_buffer[..]);
+/// sock.recv_vectored_with_ancillary(&mut [IoSliceMut::new(&mut buf)], &mut ancillary)?;
+///
+/// for message in ancillary.messages() {
+///     if let Ok(AncillaryData::ScmRights(rights)) = message {
+///         println!("received {} fds", rights.count());
+///     }
+/// }
+///
+/// let files = ancillary.take_fds()
+///     .into_iter()
+///     .map(File::from)
+///     .collect::<Vec<_>>();
+/// # drop(files);
+/// # Ok(())
+/// # }
+/// ```
+#[unstable(feature = "unix_socket_ancillary_data", issue = "0")]
+pub struct SocketAncillary<'a> {
+    buffer: &'a mut [u8],
+    length: usize,
+    truncated: bool,
+    owns_fds: bool,
+}
+
+impl<'a> SocketAncillary<'a> {
+    /// Creates an ancillary data buffer backed by `buffer`.
+    #[unstable(feature = "unix_socket_ancillary_data", issue = "0")]
+    pub fn new(buffer: &'a mut [u8]) -> SocketAncillary<'a> {
+        SocketAncillary { buffer, length: 0, truncated: false, owns_fds: false }
+    }
+
+    /// Returns the capacity of the buffer, in bytes.
+    #[unstable(feature = "unix_socket_ancillary_data", issue = "0")]
+    pub fn capacity(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Returns the number of bytes of ancillary data in the buffer.
+    #[unstable(feature = "unix_socket_ancillary_data", issue = "0")]
+    pub fn len(&self) -> usize {
+        self.length
+    }
+
+    /// Returns `true` if the buffer holds no ancillary data.
+    #[unstable(feature = "unix_socket_ancillary_data", issue = "0")]
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    /// Returns an iterator over the control messages in the buffer.
+    #[unstable(feature = "unix_socket_ancillary_data", issue = "0")]
+    pub fn messages(&self) -> Messages {
+        Messages { buffer: &self.buffer[..self.length], offset: 0 }
+    }
+
+    /// Returns `true` if the last receive had to discard control messages
+    /// because the buffer was too small.
+    #[unstable(feature = "unix_socket_ancillary_data", issue = "0")]
+    pub fn truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Appends an `SCM_RIGHTS` message carrying `fds` to the buffer.
+    ///
+    /// The descriptors are only borrowed; the caller keeps ownership of them.
+    /// Returns `false` if the buffer does not have enough room left. Any
+    /// data left over from a previous receive is cleared first.
+    #[unstable(feature = "unix_socket_ancillary_data", issue = "0")]
+    pub fn add_fds(&mut self, fds: &[RawFd]) -> bool {
+        self.add_to_ancillary_data(fds, libc::SOL_SOCKET, libc::SCM_RIGHTS)
+    }
+
+    /// Appends an `SCM_CREDENTIALS` message carrying `creds` to the buffer.
+    ///
+    /// Returns `false` if the buffer does not have enough room left. Any
+    /// data left over from a previous receive is cleared first.
+    #[unstable(feature = "unix_socket_ancillary_data", issue = "0")]
+    pub fn add_creds(&mut self, creds: &[SocketCred]) -> bool {
+        let creds = creds.iter().map(|c| c.0).collect::<Vec<_>>();
+        self.add_to_ancillary_data(&creds, libc::SOL_SOCKET, libc::SCM_CREDENTIALS)
+    }
+
+    /// Takes ownership of every file descriptor received into the buffer.
+    ///
+    /// The descriptors are closed when the returned `OwnedFd`s are dropped,
+    /// unless they are converted into another owning type first.
+    #[unstable(feature = "unix_socket_ancillary_data", issue = "0")]
+    pub fn take_fds(&mut self) -> Vec<OwnedFd> {
+        if !self.owns_fds {
+            return Vec::new()
+        }
+        self.owns_fds = false;
+        self.received_fds().into_iter().map(|fd| unsafe { OwnedFd::from_raw_fd(fd) }).collect()
+    }
+
+    /// Removes all data from the buffer, closing any received file
+    /// descriptors that have not been taken.
+    #[unstable(feature = "unix_socket_ancillary_data", issue = "0")]
+    pub fn clear(&mut self) {
+        if self.owns_fds {
+            for fd in self.received_fds() {
+                unsafe { libc::close(fd); }
+            }
+            self.owns_fds = false;
+        }
+        self.length = 0;
+        self.truncated = false;
+    }
+
+    fn received_fds(&self) -> Vec<RawFd> {
+        let mut fds = Vec::new();
+        for message in self.messages() {
+            if let Ok(AncillaryData::ScmRights(rights)) = message {
+                fds.extend(rights);
+            }
+        }
+        fds
+    }
+
+    fn add_to_ancillary_data<T>(&mut self, source: &[T], cmsg_level: libc::c_int,
+                                cmsg_type: libc::c_int) -> bool {
+        if self.owns_fds {
+            self.clear();
+        }
+        self.truncated = false;
+
+        let source_len = match source.len().checked_mul(mem::size_of::<T>()) {
+            Some(len) => len,
+            None => return false,
+        };
+        let new_length = match self.length.checked_add(cmsg_space(source_len)) {
+            Some(len) if len <= self.buffer.len() => len,
+            _ => return false,
+        };
+
+        unsafe {
+            let mut cmsg: libc::cmsghdr = mem::zeroed();
+            cmsg.cmsg_len = cmsg_len(source_len) as _;
+            cmsg.cmsg_level = cmsg_level;
+            cmsg.cmsg_type = cmsg_type;
+
+            let start = self.buffer.as_mut_ptr().offset(self.length as isize);
+            ptr::write_unaligned(start as *mut libc::cmsghdr, cmsg);
+            ptr::copy_nonoverlapping(source.as_ptr() as *const u8,
+                                     start.offset(cmsg_len(0) as isize),
+                                     source_len);
+        }
+        for byte in &mut self.buffer[self.length + cmsg_len(source_len)..new_length] {
+            *byte = 0;
+        }
+        self.length = new_length;
+        true
+    }
+}
+
+#[unstable(feature = "unix_socket_ancillary_data", issue = "0")]
+impl<'a> Drop for SocketAncillary<'a> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+#[unstable(feature = "unix_socket_ancillary_data", issue = "0")]
+impl<'a> fmt::Debug for SocketAncillary<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SocketAncillary")
+            .field("capacity", &self.buffer.len())
+            .field("len", &self.length)
+            .field("truncated", &self.truncated)
+            .finish()
+    }
+}
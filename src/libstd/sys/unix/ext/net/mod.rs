@@ -28,17 +28,24 @@ mod libc {
 use ascii;
 use ffi::OsStr;
 use fmt;
-use io::{self, Initializer};
+use io::{self, Initializer, IoSlice, IoSliceMut};
 use mem;
 use net::{self, Shutdown};
 use os::unix::ffi::OsStrExt;
-use os::unix::io::{RawFd, AsRawFd, FromRawFd, IntoRawFd};
+use os::unix::io::{RawFd, AsRawFd, FromRawFd, IntoRawFd, AsFd, BorrowedFd, OwnedFd};
 use path::Path;
 use time::Duration;
 use sys::{self, cvt};
 use sys::net::Socket;
 use sys_common::{self, AsInner, FromInner, IntoInner};
 
+#[cfg(any(target_os = "android", target_os = "linux"))]
+mod ancillary;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[unstable(feature = "unix_socket_ancillary_data", issue = "0")]
+pub use self::ancillary::{AncillaryData, AncillaryError, Messages, ScmCredentials, ScmRights,
+                          SocketAncillary, SocketCred};
+
 #[cfg(any(target_os = "linux", target_os = "android",
           target_os = "dragonfly", target_os = "freebsd",
           target_os = "openbsd", target_os = "netbsd",
@@ -519,6 +526,109 @@ impl UnixStream {
     pub fn shutdown(&self, how: Shutdown) -> io::Result<()> {
         self.0.shutdown(how)
     }
+
+    /// Receives data and ancillary data from the socket.
+    ///
+    /// On success, returns the number of bytes read and whether the data was
+    /// truncated. Control messages are written to `ancillary`, which takes
+    /// ownership of any received file descriptors.
+    ///
+    /// This method is only available on Linux and Android.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(unix_socket_ancillary_data, iovec)]
+    /// use std::io::IoSliceMut;
+    /// use std::os::unix::net::{SocketAncillary, UnixStream};
+    ///
+    /// let sock = UnixStream::connect("/tmp/sock").unwrap();
+    /// let mut buf = [0; 64];
+    /// let mut ancillary_buffer = [0; 128];
+    /// let mut ancillary = SocketAncillary::new(&mut ancillary_buffer[..]);
+    /// let (n, truncated) =
+    ///     sock.recv_vectored_with_ancillary(&mut [IoSliceMut::new(&mut buf)], &mut ancillary)
+    ///         .expect("recv_vectored_with_ancillary function failed");
+    /// println!("received {} bytes (truncated: {}) and {} fds",
+    ///          n, truncated, ancillary.take_fds().len());
+    /// ```
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    #[unstable(feature = "unix_socket_ancillary_data", issue = "0")]
+    pub fn recv_vectored_with_ancillary(&self,
+                                        bufs: &mut [IoSliceMut],
+                                        ancillary: &mut SocketAncillary)
+                                        -> io::Result<(usize, bool)> {
+        let (count, truncated, _) =
+            ancillary::recv_vectored_with_ancillary_from(&self.0, bufs, ancillary)?;
+        Ok((count, truncated))
+    }
+
+    /// Sends data and ancillary data on the socket.
+    ///
+    /// On success, returns the number of bytes written.
+    ///
+    /// This method is only available on Linux and Android.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(unix_socket_ancillary_data, iovec)]
+    /// use std::fs::File;
+    /// use std::io::IoSlice;
+    /// use std::os::unix::io::AsRawFd;
+    /// use std::os::unix::net::{SocketAncillary, UnixStream};
+    ///
+    /// let sock = UnixStream::connect("/tmp/sock").unwrap();
+    /// let file = File::open("/etc/hostname").unwrap();
+    /// let mut ancillary_buffer = [0; 128];
+    /// let mut ancillary = SocketAncillary::new(&mut ancillary_buffer[..]);
+    /// ancillary.add_fds(&[file.as_raw_fd()]);
+    /// sock.send_vectored_with_ancillary(&[IoSlice::new(b"file")], &mut ancillary)
+    ///     .expect("send_vectored_with_ancillary function failed");
+    /// ```
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    #[unstable(feature = "unix_socket_ancillary_data", issue = "0")]
+    pub fn send_vectored_with_ancillary(&self,
+                                        bufs: &[IoSlice],
+                                        ancillary: &mut SocketAncillary)
+                                        -> io::Result<usize> {
+        ancillary::send_vectored_with_ancillary_to(&self.0, None, bufs, ancillary)
+    }
+
+    /// Enables or disables the `SO_PASSCRED` option, which makes the kernel
+    /// attach the sender's credentials to every received message as an
+    /// `SCM_CREDENTIALS` control message.
+    ///
+    /// This method is only available on Linux and Android.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(unix_socket_ancillary_data)]
+    /// use std::os::unix::net::UnixStream;
+    ///
+    /// let sock = UnixStream::connect("/tmp/sock").unwrap();
+    /// sock.set_passcred(true).expect("set_passcred function failed");
+    /// ```
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    #[unstable(feature = "unix_socket_ancillary_data", issue = "0")]
+    pub fn set_passcred(&self, passcred: bool) -> io::Result<()> {
+        sys_common::net::setsockopt(&self.0, libc::SOL_SOCKET, libc::SO_PASSCRED,
+                                    passcred as libc::c_int)
+    }
+
+    /// Returns whether the `SO_PASSCRED` option is enabled; see [`set_passcred`].
+    ///
+    /// This method is only available on Linux and Android.
+    ///
+    /// [`set_passcred`]: #method.set_passcred
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    #[unstable(feature = "unix_socket_ancillary_data", issue = "0")]
+    pub fn passcred(&self) -> io::Result<bool> {
+        let passcred: libc::c_int = sys_common::net::getsockopt(&self.0, libc::SOL_SOCKET,
+                                                                libc::SO_PASSCRED)?;
+        Ok(passcred != 0)
+    }
 }
 
 #[stable(feature = "unix_socket", since = "1.10.0")]
@@ -527,6 +637,10 @@ impl io::Read for UnixStream {
         io::Read::read(&mut &*self, buf)
     }
 
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut]) -> io::Result<usize> {
+        io::Read::read_vectored(&mut &*self, bufs)
+    }
+
     #[inline]
     unsafe fn initializer(&self) -> Initializer {
         Initializer::nop()
@@ -539,6 +653,10 @@ impl<'a> io::Read for &'a UnixStream {
         self.0.read(buf)
     }
 
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut]) -> io::Result<usize> {
+        self.0.read_vectored(bufs)
+    }
+
     #[inline]
     unsafe fn initializer(&self) -> Initializer {
         Initializer::nop()
@@ -551,6 +669,10 @@ impl io::Write for UnixStream {
         io::Write::write(&mut &*self, buf)
     }
 
+    fn write_vectored(&mut self, bufs: &[IoSlice]) -> io::Result<usize> {
+        io::Write::write_vectored(&mut &*self, bufs)
+    }
+
     fn flush(&mut self) -> io::Result<()> {
         io::Write::flush(&mut &*self)
     }
@@ -562,6 +684,10 @@ impl<'a> io::Write for &'a UnixStream {
         self.0.write(buf)
     }
 
+    fn write_vectored(&mut self, bufs: &[IoSlice]) -> io::Result<usize> {
+        self.0.write_vectored(bufs)
+    }
+
     fn flush(&mut self) -> io::Result<()> {
         Ok(())
     }
@@ -588,6 +714,27 @@ impl IntoRawFd for UnixStream {
     }
 }
 
+#[unstable(feature = "io_safety", issue = "0")]
+impl AsFd for UnixStream {
+    fn as_fd(&self) -> BorrowedFd {
+        unsafe { BorrowedFd::borrow_raw_fd(self.as_raw_fd()) }
+    }
+}
+
+#[unstable(feature = "io_safety", issue = "0")]
+impl From<UnixStream> for OwnedFd {
+    fn from(stream: UnixStream) -> OwnedFd {
+        unsafe { OwnedFd::from_raw_fd(stream.into_raw_fd()) }
+    }
+}
+
+#[unstable(feature = "io_safety", issue = "0")]
+impl From<OwnedFd> for UnixStream {
+    fn from(owned_fd: OwnedFd) -> UnixStream {
+        unsafe { UnixStream::from_raw_fd(owned_fd.into_raw_fd()) }
+    }
+}
+
 #[stable(feature = "rust1", since = "1.0.0")]
 impl AsRawFd for net::TcpStream {
     fn as_raw_fd(&self) -> RawFd { *self.as_inner().socket().as_inner() }
@@ -646,6 +793,69 @@ impl IntoRawFd for net::UdpSocket {
     }
 }
 
+#[unstable(feature = "io_safety", issue = "0")]
+impl AsFd for net::TcpStream {
+    fn as_fd(&self) -> BorrowedFd {
+        unsafe { BorrowedFd::borrow_raw_fd(self.as_raw_fd()) }
+    }
+}
+
+#[unstable(feature = "io_safety", issue = "0")]
+impl From<net::TcpStream> for OwnedFd {
+    fn from(stream: net::TcpStream) -> OwnedFd {
+        unsafe { OwnedFd::from_raw_fd(stream.into_raw_fd()) }
+    }
+}
+
+#[unstable(feature = "io_safety", issue = "0")]
+impl From<OwnedFd> for net::TcpStream {
+    fn from(owned_fd: OwnedFd) -> net::TcpStream {
+        unsafe { net::TcpStream::from_raw_fd(owned_fd.into_raw_fd()) }
+    }
+}
+
+#[unstable(feature = "io_safety", issue = "0")]
+impl AsFd for net::TcpListener {
+    fn as_fd(&self) -> BorrowedFd {
+        unsafe { BorrowedFd::borrow_raw_fd(self.as_raw_fd()) }
+    }
+}
+
+#[unstable(feature = "io_safety", issue = "0")]
+impl From<net::TcpListener> for OwnedFd {
+    fn from(listener: net::TcpListener) -> OwnedFd {
+        unsafe { OwnedFd::from_raw_fd(listener.into_raw_fd()) }
+    }
+}
+
+#[unstable(feature = "io_safety", issue = "0")]
+impl From<OwnedFd> for net::TcpListener {
+    fn from(owned_fd: OwnedFd) -> net::TcpListener {
+        unsafe { net::TcpListener::from_raw_fd(owned_fd.into_raw_fd()) }
+    }
+}
+
+#[unstable(feature = "io_safety", issue = "0")]
+impl AsFd for net::UdpSocket {
+    fn as_fd(&self) -> BorrowedFd {
+        unsafe { BorrowedFd::borrow_raw_fd(self.as_raw_fd()) }
+    }
+}
+
+#[unstable(feature = "io_safety", issue = "0")]
+impl From<net::UdpSocket> for OwnedFd {
+    fn from(socket: net::UdpSocket) -> OwnedFd {
+        unsafe { OwnedFd::from_raw_fd(socket.into_raw_fd()) }
+    }
+}
+
+#[unstable(feature = "io_safety", issue = "0")]
+impl From<OwnedFd> for net::UdpSocket {
+    fn from(owned_fd: OwnedFd) -> net::UdpSocket {
+        unsafe { net::UdpSocket::from_raw_fd(owned_fd.into_raw_fd()) }
+    }
+}
+
 /// A structure representing a Unix domain socket server.
 ///
 /// # Examples
@@ -878,6 +1088,27 @@ impl IntoRawFd for UnixListener {
     }
 }
 
+#[unstable(feature = "io_safety", issue = "0")]
+impl AsFd for UnixListener {
+    fn as_fd(&self) -> BorrowedFd {
+        unsafe { BorrowedFd::borrow_raw_fd(self.as_raw_fd()) }
+    }
+}
+
+#[unstable(feature = "io_safety", issue = "0")]
+impl From<UnixListener> for OwnedFd {
+    fn from(listener: UnixListener) -> OwnedFd {
+        unsafe { OwnedFd::from_raw_fd(listener.into_raw_fd()) }
+    }
+}
+
+#[unstable(feature = "io_safety", issue = "0")]
+impl From<OwnedFd> for UnixListener {
+    fn from(owned_fd: OwnedFd) -> UnixListener {
+        unsafe { UnixListener::from_raw_fd(owned_fd.into_raw_fd()) }
+    }
+}
+
 #[stable(feature = "unix_socket", since = "1.10.0")]
 impl<'a> IntoIterator for &'a UnixListener {
     type Item = io::Result<UnixStream>;
@@ -1384,6 +1615,143 @@ impl UnixDatagram {
     pub fn shutdown(&self, how: Shutdown) -> io::Result<()> {
         self.0.shutdown(how)
     }
+
+    /// Receives data and ancillary data from the socket.
+    ///
+    /// On success, returns the number of bytes read, whether the data was
+    /// truncated and the address it came from. Control messages are written
+    /// to `ancillary`, which takes ownership of any received file
+    /// descriptors.
+    ///
+    /// This method is only available on Linux and Android.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(unix_socket_ancillary_data, iovec)]
+    /// use std::io::IoSliceMut;
+    /// use std::os::unix::net::{SocketAncillary, UnixDatagram};
+    ///
+    /// let sock = UnixDatagram::bind("/path/to/the/socket").unwrap();
+    /// let mut buf = [0; 64];
+    /// let mut ancillary_buffer = [0; 128];
+    /// let mut ancillary = SocketAncillary::new(&mut ancillary_buffer[..]);
+    /// let (n, truncated, addr) =
+    ///     sock.recv_vectored_with_ancillary_from(&mut [IoSliceMut::new(&mut buf)],
+    ///                                            &mut ancillary)
+    ///         .expect("recv_vectored_with_ancillary_from function failed");
+    /// println!("received {} bytes (truncated: {}) from {:?}", n, truncated, addr);
+    /// ```
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    #[unstable(feature = "unix_socket_ancillary_data", issue = "0")]
+    pub fn recv_vectored_with_ancillary_from(&self,
+                                             bufs: &mut [IoSliceMut],
+                                             ancillary: &mut SocketAncillary)
+                                             -> io::Result<(usize, bool, SocketAddr)> {
+        let (count, truncated, addr) =
+            ancillary::recv_vectored_with_ancillary_from(&self.0, bufs, ancillary)?;
+        Ok((count, truncated, addr?))
+    }
+
+    /// Receives data and ancillary data from the socket's peer.
+    ///
+    /// On success, returns the number of bytes read and whether the data was
+    /// truncated.
+    ///
+    /// This method is only available on Linux and Android.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    #[unstable(feature = "unix_socket_ancillary_data", issue = "0")]
+    pub fn recv_vectored_with_ancillary(&self,
+                                        bufs: &mut [IoSliceMut],
+                                        ancillary: &mut SocketAncillary)
+                                        -> io::Result<(usize, bool)> {
+        let (count, truncated, _) =
+            ancillary::recv_vectored_with_ancillary_from(&self.0, bufs, ancillary)?;
+        Ok((count, truncated))
+    }
+
+    /// Sends data and ancillary data on the socket to the specified address.
+    ///
+    /// On success, returns the number of bytes written.
+    ///
+    /// This method is only available on Linux and Android.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(unix_socket_ancillary_data, iovec, getpid)]
+    /// use std::io::IoSlice;
+    /// use std::os::unix::net::{SocketAncillary, SocketCred, UnixDatagram};
+    ///
+    /// let sock = UnixDatagram::unbound().unwrap();
+    /// let mut creds = SocketCred::new();
+    /// creds.set_pid(std::process::id() as i32);
+    /// let mut ancillary_buffer = [0; 128];
+    /// let mut ancillary = SocketAncillary::new(&mut ancillary_buffer[..]);
+    /// ancillary.add_creds(&[creds]);
+    /// sock.send_vectored_with_ancillary_to(&[IoSlice::new(b"hello")], &mut ancillary,
+    ///                                      "/some/sock")
+    ///     .expect("send_vectored_with_ancillary_to function failed");
+    /// ```
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    #[unstable(feature = "unix_socket_ancillary_data", issue = "0")]
+    pub fn send_vectored_with_ancillary_to<P: AsRef<Path>>(&self,
+                                                           bufs: &[IoSlice],
+                                                           ancillary: &mut SocketAncillary,
+                                                           path: P)
+                                                           -> io::Result<usize> {
+        ancillary::send_vectored_with_ancillary_to(&self.0, Some(path.as_ref()), bufs,
+                                                   ancillary)
+    }
+
+    /// Sends data and ancillary data on the socket to the socket's peer.
+    ///
+    /// On success, returns the number of bytes written.
+    ///
+    /// This method is only available on Linux and Android.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    #[unstable(feature = "unix_socket_ancillary_data", issue = "0")]
+    pub fn send_vectored_with_ancillary(&self,
+                                        bufs: &[IoSlice],
+                                        ancillary: &mut SocketAncillary)
+                                        -> io::Result<usize> {
+        ancillary::send_vectored_with_ancillary_to(&self.0, None, bufs, ancillary)
+    }
+
+    /// Enables or disables the `SO_PASSCRED` option, which makes the kernel
+    /// attach the sender's credentials to every received message as an
+    /// `SCM_CREDENTIALS` control message.
+    ///
+    /// This method is only available on Linux and Android.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(unix_socket_ancillary_data)]
+    /// use std::os::unix::net::UnixDatagram;
+    ///
+    /// let sock = UnixDatagram::unbound().unwrap();
+    /// sock.set_passcred(true).expect("set_passcred function failed");
+    /// ```
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    #[unstable(feature = "unix_socket_ancillary_data", issue = "0")]
+    pub fn set_passcred(&self, passcred: bool) -> io::Result<()> {
+        sys_common::net::setsockopt(&self.0, libc::SOL_SOCKET, libc::SO_PASSCRED,
+                                    passcred as libc::c_int)
+    }
+
+    /// Returns whether the `SO_PASSCRED` option is enabled; see [`set_passcred`].
+    ///
+    /// This method is only available on Linux and Android.
+    ///
+    /// [`set_passcred`]: #method.set_passcred
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    #[unstable(feature = "unix_socket_ancillary_data", issue = "0")]
+    pub fn passcred(&self) -> io::Result<bool> {
+        let passcred: libc::c_int = sys_common::net::getsockopt(&self.0, libc::SOL_SOCKET,
+                                                                libc::SO_PASSCRED)?;
+        Ok(passcred != 0)
+    }
 }
 
 #[stable(feature = "unix_socket", since = "1.10.0")]
@@ -1407,6 +1775,27 @@ impl IntoRawFd for UnixDatagram {
     }
 }
 
+#[unstable(feature = "io_safety", issue = "0")]
+impl AsFd for UnixDatagram {
+    fn as_fd(&self) -> BorrowedFd {
+        unsafe { BorrowedFd::borrow_raw_fd(self.as_raw_fd()) }
+    }
+}
+
+#[unstable(feature = "io_safety", issue = "0")]
+impl From<UnixDatagram> for OwnedFd {
+    fn from(datagram: UnixDatagram) -> OwnedFd {
+        unsafe { OwnedFd::from_raw_fd(datagram.into_raw_fd()) }
+    }
+}
+
+#[unstable(feature = "io_safety", issue = "0")]
+impl From<OwnedFd> for UnixDatagram {
+    fn from(owned_fd: OwnedFd) -> UnixDatagram {
+        unsafe { UnixDatagram::from_raw_fd(owned_fd.into_raw_fd()) }
+    }
+}
+
 #[cfg(all(test, not(target_os = "emscripten")))]
 mod test {
     use thread;
@@ -1454,6 +1843,35 @@ mod test {
         thread.join().unwrap();
     }
 
+    #[test]
+    fn read_write_vectored() {
+        use io::{IoSlice, IoSliceMut};
+
+        let (mut s1, mut s2) = or_panic!(UnixStream::pair());
+
+        // More buffers than `IOV_MAX`; the excess must be ignored rather than
+        // make the call fail.
+        let data = (0..2048).map(|i| i as u8).collect::<Vec<u8>>();
+        let expected = data.clone();
+        let thread = thread::spawn(move || {
+            let bufs = data.chunks(1).map(IoSlice::new).collect::<Vec<_>>();
+            let n = or_panic!(s1.write_vectored(&bufs));
+            assert!(n > 0);
+            or_panic!(s1.write_all(&data[n..]));
+        });
+
+        let mut received = vec![0; expected.len()];
+        let n = {
+            let mut bufs = received.chunks_mut(1).map(IoSliceMut::new).collect::<Vec<_>>();
+            or_panic!(s2.read_vectored(&mut bufs))
+        };
+        assert!(n > 0);
+        or_panic!(s2.read_exact(&mut received[n..]));
+        assert_eq!(received, expected);
+
+        thread.join().unwrap();
+    }
+
     #[test]
     fn pair() {
         let msg1 = b"hello";
@@ -1716,4 +2134,120 @@ mod test {
     fn abstract_namespace_not_allowed() {
         assert!(UnixStream::connect("\0asdf").is_err());
     }
+
+    #[test]
+    fn owned_fd_round_trip() {
+        use os::unix::io::{AsFd, AsRawFd, OwnedFd};
+
+        let (s1, s2) = or_panic!(UnixStream::pair());
+        let raw = s1.as_raw_fd();
+        assert_eq!(s1.as_fd().as_raw_fd(), raw);
+
+        let owned = OwnedFd::from(s1);
+        assert_eq!(owned.as_raw_fd(), raw);
+        let cloned = or_panic!(owned.try_clone());
+        assert!(cloned.as_raw_fd() != raw);
+        drop(owned);
+
+        let s1 = UnixStream::from(cloned);
+        or_panic!((&s1).write_all(b"hello"));
+        let mut buf = [0; 5];
+        or_panic!((&s2).read_exact(&mut buf));
+        assert_eq!(&buf, b"hello");
+    }
+
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    #[test]
+    fn send_vectored_fds_unix_stream() {
+        use io::{IoSlice, IoSliceMut};
+        use os::unix::io::AsRawFd;
+
+        let (s1, s2) = or_panic!(UnixStream::pair());
+        let (p1, p2) = or_panic!(UnixStream::pair());
+
+        let buf1 = [1u8; 8];
+        let mut ancillary1_buffer = [0; 128];
+        let mut ancillary1 = SocketAncillary::new(&mut ancillary1_buffer[..]);
+        assert!(ancillary1.add_fds(&[p1.as_raw_fd()]));
+        let size = or_panic!(s1.send_vectored_with_ancillary(&[IoSlice::new(&buf1)],
+                                                             &mut ancillary1));
+        assert_eq!(size, 8);
+
+        let mut buf2 = [0; 8];
+        let mut ancillary2_buffer = [0; 128];
+        let mut ancillary2 = SocketAncillary::new(&mut ancillary2_buffer[..]);
+        let (size, truncated) =
+            or_panic!(s2.recv_vectored_with_ancillary(&mut [IoSliceMut::new(&mut buf2)],
+                                                      &mut ancillary2));
+        assert_eq!(size, 8);
+        assert!(!truncated);
+        assert_eq!(buf1, buf2);
+        assert!(!ancillary2.truncated());
+
+        let mut fds = ancillary2.take_fds();
+        assert_eq!(fds.len(), 1);
+        assert!(ancillary2.take_fds().is_empty());
+
+        // The received descriptor is a new handle to the same socket as `p1`.
+        let received = UnixStream::from(fds.pop().unwrap());
+        or_panic!((&received).write_all(b"ping"));
+        let mut ping = [0; 4];
+        or_panic!((&p2).read_exact(&mut ping));
+        assert_eq!(&ping, b"ping");
+    }
+
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    #[test]
+    fn send_vectored_creds_unix_datagram() {
+        use io::{IoSlice, IoSliceMut};
+        use libc;
+
+        let (s1, s2) = or_panic!(UnixDatagram::pair());
+        or_panic!(s2.set_passcred(true));
+        assert!(or_panic!(s2.passcred()));
+
+        let mut cred = SocketCred::new();
+        unsafe {
+            cred.set_pid(libc::getpid());
+            cred.set_uid(libc::getuid());
+            cred.set_gid(libc::getgid());
+        }
+
+        let mut ancillary1_buffer = [0; 128];
+        let mut ancillary1 = SocketAncillary::new(&mut ancillary1_buffer[..]);
+        assert!(ancillary1.add_creds(&[cred]));
+        or_panic!(s1.send_vectored_with_ancillary(&[IoSlice::new(b"hello")], &mut ancillary1));
+
+        let mut buf = [0; 5];
+        let mut ancillary2_buffer = [0; 128];
+        let mut ancillary2 = SocketAncillary::new(&mut ancillary2_buffer[..]);
+        let (size, truncated) =
+            or_panic!(s2.recv_vectored_with_ancillary(&mut [IoSliceMut::new(&mut buf)],
+                                                      &mut ancillary2));
+        assert_eq!(size, 5);
+        assert!(!truncated);
+
+        let mut messages = ancillary2.messages();
+        match messages.next() {
+            Some(Ok(AncillaryData::ScmCredentials(mut creds))) => {
+                let received = creds.next().unwrap();
+                assert_eq!(received.get_pid(), cred.get_pid());
+                assert_eq!(received.get_uid(), cred.get_uid());
+                assert_eq!(received.get_gid(), cred.get_gid());
+                assert!(creds.next().is_none());
+            }
+            other => panic!("expected SCM_CREDENTIALS, got {:?}", other),
+        }
+        assert!(messages.next().is_none());
+    }
+
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    #[test]
+    fn ancillary_buffer_too_small() {
+        let mut buffer = [0; 8];
+        let mut ancillary = SocketAncillary::new(&mut buffer[..]);
+        assert!(!ancillary.add_fds(&[0]));
+        assert!(ancillary.is_empty());
+        assert_eq!(ancillary.capacity(), 8);
+    }
 }
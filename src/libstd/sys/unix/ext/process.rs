@@ -13,7 +13,7 @@
 #![stable(feature = "rust1", since = "1.0.0")]
 
 use io;
-use os::unix::io::{FromRawFd, RawFd, AsRawFd, IntoRawFd};
+use os::unix::io::{FromRawFd, RawFd, AsRawFd, IntoRawFd, AsFd, BorrowedFd, OwnedFd};
 use process;
 use sys;
 use sys_common::{AsInnerMut, AsInner, FromInner, IntoInner};
@@ -91,6 +91,50 @@ pub trait CommandExt {
     /// cross-platform `spawn` instead.
     #[stable(feature = "process_exec2", since = "1.9.0")]
     fn exec(&mut self) -> io::Error;
+
+    /// Sets the process group ID of the child process. This translates to a
+    /// `setpgid` call in the child process.
+    ///
+    /// A `pgroup` of 0 makes the child the leader of a new process group with
+    /// an ID equal to its process ID, so that it and every process it spawns
+    /// can later be signalled at once, e.g. with `killpg`. Any other value
+    /// moves the child into that existing process group, which must belong
+    /// to the same session.
+    ///
+    /// Failure in the `setpgid` call will cause the spawn to fail.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(process_group)]
+    ///
+    /// use std::os::unix::process::CommandExt;
+    /// use std::process::Command;
+    ///
+    /// # fn foo() -> std::io::Result<()> {
+    /// let child = Command::new("make")
+    ///     .process_group(0)
+    ///     .spawn()?;
+    /// // The build and all of its jobs are in the process group `child.id()`.
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[unstable(feature = "process_group", issue = "0")]
+    fn process_group(&mut self, pgroup: i32) -> &mut process::Command;
+
+    /// Sets whether the child process starts a new session. This translates
+    /// to a `setsid` call in the child process.
+    ///
+    /// The child becomes the leader of a new session and of a new process
+    /// group in it, and is detached from the controlling terminal of the
+    /// parent. Since a session leader can't change its process group,
+    /// combining this with [`process_group`] makes the spawn fail.
+    ///
+    /// Failure in the `setsid` call will cause the spawn to fail.
+    ///
+    /// [`process_group`]: #tymethod.process_group
+    #[unstable(feature = "process_setsid", issue = "0")]
+    fn setsid(&mut self, setsid: bool) -> &mut process::Command;
 }
 
 #[stable(feature = "rust1", since = "1.0.0")]
@@ -115,6 +159,56 @@ impl CommandExt for process::Command {
     fn exec(&mut self) -> io::Error {
         self.as_inner_mut().exec(sys::process::Stdio::Inherit)
     }
+
+    fn process_group(&mut self, pgroup: i32) -> &mut process::Command {
+        self.as_inner_mut().pgroup(pgroup);
+        self
+    }
+
+    fn setsid(&mut self, setsid: bool) -> &mut process::Command {
+        self.as_inner_mut().setsid(setsid);
+        self
+    }
+}
+
+/// Unix-specific extensions to `std::process::Child`
+#[unstable(feature = "unix_child_signal", issue = "0")]
+pub trait ChildExt {
+    /// Sends the signal `signal` to the child process.
+    ///
+    /// Like [`Child::kill`], this returns an [`InvalidInput`] error if the
+    /// child has already been waited on, since its process ID may have been
+    /// reused by then. On Linux the signal is sent through the child's pidfd
+    /// if it has one, see `os::linux::process::CommandExt::create_pidfd`.
+    ///
+    /// [`Child::kill`]: ../../../process/struct.Child.html#method.kill
+    /// [`InvalidInput`]: ../../../io/enum.ErrorKind.html#variant.InvalidInput
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(unix_child_signal)]
+    ///
+    /// use std::os::unix::process::{ChildExt, ExitStatusExt};
+    /// use std::process::Command;
+    ///
+    /// # fn foo() -> std::io::Result<()> {
+    /// let mut child = Command::new("sleep").arg("100").spawn()?;
+    /// // SIGTERM
+    /// child.signal(15)?;
+    /// assert_eq!(child.wait()?.signal(), Some(15));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[unstable(feature = "unix_child_signal", issue = "0")]
+    fn signal(&mut self, signal: i32) -> io::Result<()>;
+}
+
+#[unstable(feature = "unix_child_signal", issue = "0")]
+impl ChildExt for process::Child {
+    fn signal(&mut self, signal: i32) -> io::Result<()> {
+        self.as_inner_mut().signal(signal)
+    }
 }
 
 /// Unix-specific extensions to `std::process::ExitStatus`
@@ -128,6 +222,20 @@ pub trait ExitStatusExt {
     /// If the process was terminated by a signal, returns that signal.
     #[stable(feature = "rust1", since = "1.0.0")]
     fn signal(&self) -> Option<i32>;
+
+    /// If the process was terminated by a signal, says whether it dumped core.
+    #[unstable(feature = "unix_process_wait_more", issue = "0")]
+    fn core_dumped(&self) -> bool;
+
+    /// If the process was stopped by a signal, returns that signal.
+    ///
+    /// This can only be the case for statuses obtained from `waitpid` with
+    /// the `WUNTRACED` flag, e.g. through [`from_raw`], as `std` itself only
+    /// waits for processes to terminate.
+    ///
+    /// [`from_raw`]: #tymethod.from_raw
+    #[unstable(feature = "unix_process_wait_more", issue = "0")]
+    fn stopped_signal(&self) -> Option<i32>;
 }
 
 #[stable(feature = "rust1", since = "1.0.0")]
@@ -139,6 +247,14 @@ impl ExitStatusExt for process::ExitStatus {
     fn signal(&self) -> Option<i32> {
         self.as_inner().signal()
     }
+
+    fn core_dumped(&self) -> bool {
+        self.as_inner().core_dumped()
+    }
+
+    fn stopped_signal(&self) -> Option<i32> {
+        self.as_inner().stopped_signal()
+    }
 }
 
 #[stable(feature = "process_extensions", since = "1.2.0")]
@@ -192,6 +308,55 @@ impl IntoRawFd for process::ChildStderr {
     }
 }
 
+#[unstable(feature = "io_safety", issue = "0")]
+impl From<OwnedFd> for process::Stdio {
+    fn from(owned_fd: OwnedFd) -> process::Stdio {
+        unsafe { process::Stdio::from_raw_fd(owned_fd.into_raw_fd()) }
+    }
+}
+
+#[unstable(feature = "io_safety", issue = "0")]
+impl AsFd for process::ChildStdin {
+    fn as_fd(&self) -> BorrowedFd {
+        unsafe { BorrowedFd::borrow_raw_fd(self.as_raw_fd()) }
+    }
+}
+
+#[unstable(feature = "io_safety", issue = "0")]
+impl From<process::ChildStdin> for OwnedFd {
+    fn from(stdin: process::ChildStdin) -> OwnedFd {
+        unsafe { OwnedFd::from_raw_fd(stdin.into_raw_fd()) }
+    }
+}
+
+#[unstable(feature = "io_safety", issue = "0")]
+impl AsFd for process::ChildStdout {
+    fn as_fd(&self) -> BorrowedFd {
+        unsafe { BorrowedFd::borrow_raw_fd(self.as_raw_fd()) }
+    }
+}
+
+#[unstable(feature = "io_safety", issue = "0")]
+impl From<process::ChildStdout> for OwnedFd {
+    fn from(stdout: process::ChildStdout) -> OwnedFd {
+        unsafe { OwnedFd::from_raw_fd(stdout.into_raw_fd()) }
+    }
+}
+
+#[unstable(feature = "io_safety", issue = "0")]
+impl AsFd for process::ChildStderr {
+    fn as_fd(&self) -> BorrowedFd {
+        unsafe { BorrowedFd::borrow_raw_fd(self.as_raw_fd()) }
+    }
+}
+
+#[unstable(feature = "io_safety", issue = "0")]
+impl From<process::ChildStderr> for OwnedFd {
+    fn from(stderr: process::ChildStderr) -> OwnedFd {
+        unsafe { OwnedFd::from_raw_fd(stderr.into_raw_fd()) }
+    }
+}
+
 /// Returns the OS-assigned process identifier associated with this process's parent.
 #[unstable(feature = "unix_ppid", issue = "46104")]
 pub fn parent_id() -> u32 {
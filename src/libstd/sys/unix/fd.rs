@@ -11,10 +11,10 @@
 #![unstable(reason = "not public", issue = "0", feature = "fd")]
 
 use cmp;
-use io::{self, Read};
+use io::{self, Read, IoSlice, IoSliceMut};
 use libc::{self, c_int, c_void, ssize_t};
 use mem;
-use sync::atomic::{AtomicBool, Ordering};
+use sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use sys::cvt;
 use sys_common::AsInner;
 
@@ -39,6 +39,47 @@ fn max_len() -> usize {
     }
 }
 
+// The maximum number of buffers `readv` and `writev` accept. Passing more
+// fails with `EINVAL` rather than doing a short read or write, so the buffer
+// count is capped to this instead.
+#[cfg(any(target_os = "android",
+          target_os = "bitrig",
+          target_os = "dragonfly",
+          target_os = "emscripten",
+          target_os = "freebsd",
+          target_os = "ios",
+          target_os = "linux",
+          target_os = "macos",
+          target_os = "netbsd",
+          target_os = "openbsd"))]
+pub fn max_iov() -> usize {
+    static LIMIT: AtomicUsize = AtomicUsize::new(0);
+
+    let mut limit = LIMIT.load(Ordering::Relaxed);
+    if limit == 0 {
+        limit = match unsafe { libc::sysconf(libc::_SC_IOV_MAX) } {
+            n if n > 0 => cmp::min(n as usize, c_int::max_value() as usize),
+            _ => 16,
+        };
+        LIMIT.store(limit, Ordering::Relaxed);
+    }
+    limit
+}
+
+#[cfg(not(any(target_os = "android",
+              target_os = "bitrig",
+              target_os = "dragonfly",
+              target_os = "emscripten",
+              target_os = "freebsd",
+              target_os = "ios",
+              target_os = "linux",
+              target_os = "macos",
+              target_os = "netbsd",
+              target_os = "openbsd")))]
+pub fn max_iov() -> usize {
+    16 // The minimum value required by POSIX (`_XOPEN_IOV_MAX`).
+}
+
 impl FileDesc {
     pub fn new(fd: c_int) -> FileDesc {
         FileDesc { fd: fd }
@@ -62,6 +103,15 @@ impl FileDesc {
         Ok(ret as usize)
     }
 
+    pub fn read_vectored(&self, bufs: &mut [IoSliceMut]) -> io::Result<usize> {
+        let ret = cvt(unsafe {
+            libc::readv(self.fd,
+                        bufs.as_ptr() as *const libc::iovec,
+                        cmp::min(bufs.len(), max_iov()) as c_int)
+        })?;
+        Ok(ret as usize)
+    }
+
     pub fn read_to_end(&self, buf: &mut Vec<u8>) -> io::Result<usize> {
         let mut me = self;
         (&mut me).read_to_end(buf)
@@ -108,6 +158,15 @@ impl FileDesc {
         Ok(ret as usize)
     }
 
+    pub fn write_vectored(&self, bufs: &[IoSlice]) -> io::Result<usize> {
+        let ret = cvt(unsafe {
+            libc::writev(self.fd,
+                         bufs.as_ptr() as *const libc::iovec,
+                         cmp::min(bufs.len(), max_iov()) as c_int)
+        })?;
+        Ok(ret as usize)
+    }
+
     pub fn write_at(&self, buf: &[u8], offset: u64) -> io::Result<usize> {
         #[cfg(target_os = "android")]
         use super::android::cvt_pwrite64;
@@ -12,7 +12,7 @@ use os::unix::prelude::*;
 
 use ffi::{CString, CStr, OsString, OsStr};
 use fmt;
-use io::{self, Error, ErrorKind, SeekFrom};
+use io::{self, Error, ErrorKind, SeekFrom, IoSlice, IoSliceMut};
 use libc::{self, c_int, mode_t};
 use mem;
 use path::{Path, PathBuf};
@@ -41,6 +41,19 @@ use libc::{stat as stat64, fstat as fstat64, lstat as lstat64, off_t as off64_t,
               target_os = "fuchsia")))]
 use libc::{readdir_r as readdir64_r};
 
+#[cfg(not(target_os = "solaris"))]
+use libc::{LOCK_EX, LOCK_NB, LOCK_SH, LOCK_UN};
+// Solaris has no `flock`, so these only exist to give the locking methods
+// on `File` a single definition.
+#[cfg(target_os = "solaris")]
+const LOCK_SH: c_int = 1;
+#[cfg(target_os = "solaris")]
+const LOCK_EX: c_int = 2;
+#[cfg(target_os = "solaris")]
+const LOCK_NB: c_int = 4;
+#[cfg(target_os = "solaris")]
+const LOCK_UN: c_int = 8;
+
 pub struct File(FileDesc);
 
 #[derive(Clone)]
@@ -496,6 +509,10 @@ impl File {
         self.0.read(buf)
     }
 
+    pub fn read_vectored(&self, bufs: &mut [IoSliceMut]) -> io::Result<usize> {
+        self.0.read_vectored(bufs)
+    }
+
     pub fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
         self.0.read_at(buf, offset)
     }
@@ -504,6 +521,10 @@ impl File {
         self.0.write(buf)
     }
 
+    pub fn write_vectored(&self, bufs: &[IoSlice]) -> io::Result<usize> {
+        self.0.write_vectored(bufs)
+    }
+
     pub fn write_at(&self, buf: &[u8], offset: u64) -> io::Result<usize> {
         self.0.write_at(buf, offset)
     }
@@ -536,6 +557,39 @@ impl File {
         cvt_r(|| unsafe { libc::fchmod(self.0.raw(), perm.mode) })?;
         Ok(())
     }
+
+    pub fn lock(&self) -> io::Result<()> {
+        self.flock(LOCK_EX)
+    }
+
+    pub fn lock_shared(&self) -> io::Result<()> {
+        self.flock(LOCK_SH)
+    }
+
+    pub fn try_lock(&self) -> io::Result<()> {
+        self.flock(LOCK_EX | LOCK_NB)
+    }
+
+    pub fn try_lock_shared(&self) -> io::Result<()> {
+        self.flock(LOCK_SH | LOCK_NB)
+    }
+
+    pub fn unlock(&self) -> io::Result<()> {
+        self.flock(LOCK_UN)
+    }
+
+    // A contended `LOCK_NB` request fails with `EWOULDBLOCK`, which is
+    // reported as `ErrorKind::WouldBlock`.
+    #[cfg(not(target_os = "solaris"))]
+    fn flock(&self, operation: c_int) -> io::Result<()> {
+        cvt_r(|| unsafe { libc::flock(self.0.raw(), operation) })?;
+        Ok(())
+    }
+
+    #[cfg(target_os = "solaris")]
+    fn flock(&self, _operation: c_int) -> io::Result<()> {
+        Err(io::Error::new(ErrorKind::Other, "file locking is not supported on this platform"))
+    }
 }
 
 impl DirBuilder {
@@ -761,6 +815,7 @@ pub fn canonicalize(p: &Path) -> io::Result<PathBuf> {
     Ok(PathBuf::from(OsString::from_vec(buf)))
 }
 
+#[cfg(not(target_os = "linux"))]
 pub fn copy(from: &Path, to: &Path) -> io::Result<u64> {
     use fs::{File, set_permissions};
     if !from.is_file() {
@@ -776,3 +831,36 @@ pub fn copy(from: &Path, to: &Path) -> io::Result<u64> {
     set_permissions(to, perm)?;
     Ok(ret)
 }
+
+#[cfg(target_os = "linux")]
+pub fn copy(from: &Path, to: &Path) -> io::Result<u64> {
+    use fs::{File, OpenOptions};
+    use sys::kernel_copy;
+
+    let reader = File::open(from)?;
+    let metadata = reader.metadata()?;
+    if !metadata.is_file() {
+        return Err(Error::new(ErrorKind::InvalidInput,
+                              "the source path is not an existing regular file"))
+    }
+
+    // Create the file with the source's mode right away so its contents are
+    // never accessible with more permissive permissions than the source.
+    let perm = metadata.permissions();
+    let writer = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(perm.mode())
+        .open(to)?;
+    if !writer.metadata()?.is_file() {
+        // Writing to something like `/dev/null`: don't touch its permissions
+        // and don't try to reproduce holes in it.
+        return io::copy(&mut &reader, &mut &writer)
+    }
+    // The mode given to `open` was subject to the umask and is ignored if the
+    // file already existed.
+    writer.set_permissions(perm)?;
+
+    kernel_copy::copy_regular_files(&reader, &writer, metadata.len())
+}
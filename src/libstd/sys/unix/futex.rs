@@ -0,0 +1,111 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+
+//! Thin wrappers around the Linux `futex(2)` system call.
+//!
+//! These are the only blocking primitives used by the lock implementations in
+//! `futex_mutex.rs`, `futex_condvar.rs` and `futex_rwlock.rs`. All futexes
+//! are process-private.
+
+use libc;
+use mem;
+use ptr;
+use sync::atomic::AtomicU32;
+use sync::atomic::Ordering::Relaxed;
+use sys::os::errno;
+use time::Duration;
+
+const FUTEX_WAKE: libc::c_int = 1;
+const FUTEX_WAIT_BITSET: libc::c_int = 9;
+const FUTEX_PRIVATE_FLAG: libc::c_int = 128;
+const FUTEX_BITSET_MATCH_ANY: u32 = !0;
+
+/// Blocks the current thread while `futex` holds `expected`, until woken by
+/// `futex_wake` or `futex_wake_all`, or until `timeout` has elapsed.
+///
+/// Returns immediately if the futex does not hold the expected value. Spurious
+/// wakeups are possible, so callers must re-check their condition.
+///
+/// Returns `false` if the timeout elapsed, and `true` otherwise.
+pub fn futex_wait(futex: &AtomicU32, expected: u32, timeout: Option<Duration>) -> bool {
+    // FUTEX_WAIT_BITSET takes an absolute CLOCK_MONOTONIC deadline, which
+    // lets us retry after EINTR without the wait getting longer. A timeout
+    // too large to represent is treated as no timeout at all.
+    let deadline = timeout.and_then(deadline_after);
+    let deadline_ptr = deadline.as_ref().map_or(ptr::null(), |d| d as *const libc::timespec);
+
+    loop {
+        // No need to wait if the value already changed.
+        if futex.load(Relaxed) != expected {
+            return true
+        }
+
+        let r = unsafe {
+            libc::syscall(libc::SYS_futex,
+                          futex as *const AtomicU32,
+                          FUTEX_WAIT_BITSET | FUTEX_PRIVATE_FLAG,
+                          expected,
+                          deadline_ptr,
+                          ptr::null::<u32>(),
+                          FUTEX_BITSET_MATCH_ANY)
+        };
+
+        if r == -1 {
+            match errno() {
+                libc::EINTR => continue,
+                libc::ETIMEDOUT => return false,
+                _ => {}
+            }
+        }
+        return true
+    }
+}
+
+/// Wakes up one thread that's blocked on `futex_wait` on this futex.
+///
+/// Returns true if this actually woke up such a thread, or false if no thread
+/// was waiting on this futex.
+pub fn futex_wake(futex: &AtomicU32) -> bool {
+    unsafe {
+        libc::syscall(libc::SYS_futex,
+                      futex as *const AtomicU32,
+                      FUTEX_WAKE | FUTEX_PRIVATE_FLAG,
+                      1) > 0
+    }
+}
+
+/// Wakes up all threads that are waiting on `futex_wait` on this futex.
+pub fn futex_wake_all(futex: &AtomicU32) {
+    unsafe {
+        libc::syscall(libc::SYS_futex,
+                      futex as *const AtomicU32,
+                      FUTEX_WAKE | FUTEX_PRIVATE_FLAG,
+                      i32::max_value());
+    }
+}
+
+fn deadline_after(dur: Duration) -> Option<libc::timespec> {
+    let mut now: libc::timespec = unsafe { mem::zeroed() };
+    let r = unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut now) };
+    debug_assert_eq!(r, 0);
+
+    if dur.as_secs() > <libc::time_t>::max_value() as u64 {
+        return None
+    }
+
+    // Nanosecond calculations can't overflow because both values are below 1e9.
+    let nsec = dur.subsec_nanos() + now.tv_nsec as u32;
+    let sec = (dur.as_secs() as libc::time_t)
+        .checked_add((nsec / 1_000_000_000) as libc::time_t)
+        .and_then(|s| s.checked_add(now.tv_sec));
+
+    sec.map(|s| libc::timespec { tv_sec: s, tv_nsec: (nsec % 1_000_000_000) as _ })
+}
@@ -0,0 +1,408 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+
+//! Copying data between file descriptors inside the kernel.
+//!
+//! On Linux `io::copy` and `fs::copy` try to avoid bouncing data through a
+//! userspace buffer when both ends are backed by file descriptors. In order
+//! of preference this uses:
+//!
+//! * `copy_file_range(2)` when both ends are regular files, which lets the
+//!   filesystem share extents or do a server-side copy,
+//! * `sendfile(2)` when the source is a regular file or a block device,
+//! * `splice(2)` when either end is a pipe.
+//!
+//! Each of these is only attempted when the kind of both file descriptors
+//! suggests it can work, and every syscall that fails before having moved
+//! any data with an error indicating that it's unsupported for the given
+//! pair falls back to the next option, ending with the generic read/write
+//! loop of `io::copy`.
+
+use cmp;
+use fs::File;
+use io::{self, Read, Write};
+use libc::{self, c_int, c_long, c_uint};
+use mem;
+use net::TcpStream;
+use os::unix::io::{AsRawFd, RawFd};
+use os::unix::net::UnixStream;
+use process::{ChildStderr, ChildStdin, ChildStdout};
+use ptr;
+use sync::atomic::{AtomicBool, Ordering};
+use sys::cvt;
+
+// `SEEK_DATA` and `SEEK_HOLE` from <linux/fs.h>
+const SEEK_DATA: c_int = 3;
+const SEEK_HOLE: c_int = 4;
+
+// The largest amount of data handed to a single syscall. This stays well
+// below `ssize_t::MAX` on 32-bit platforms.
+const CHUNK: usize = 0x4000_0000;
+
+// Set once `copy_file_range` returned `ENOSYS`, i.e. on kernels before 4.5.
+static NO_COPY_FILE_RANGE: AtomicBool = AtomicBool::new(false);
+
+/// Copies `reader` to `writer`, using the kernel to move the data if both
+/// sides are file descriptors.
+pub fn copy_spec<R, W>(reader: &mut R, writer: &mut W) -> io::Result<u64>
+    where R: Read + ?Sized, W: Write + ?Sized
+{
+    Copier { read: reader, write: writer }.copy()
+}
+
+/// Copies the regular file `reader` of length `len` to the regular file
+/// `writer`, which must be empty, without allocating space for the holes of
+/// the source.
+///
+/// The file offsets of both files are left untouched if the data could be
+/// copied through `copy_file_range`, otherwise both files are read from and
+/// written to at their current offsets.
+pub fn copy_regular_files(reader: &File, writer: &File, len: u64) -> io::Result<u64> {
+    // Files in procfs and sysfs claim to be empty, so don't trust a length of
+    // zero and let the read/write loop find out how much data there is.
+    if len > 0 {
+        let fd = reader.as_raw_fd();
+        // Seeking to data and holes moves the offset of `reader`, which the
+        // read/write loop below would otherwise start from.
+        let start = lseek(fd, 0, libc::SEEK_CUR)?;
+        let copied = copy_data_segments(fd, writer.as_raw_fd(), len);
+        lseek(fd, start, libc::SEEK_SET)?;
+        if let Some(copied) = copied? {
+            return Ok(copied)
+        }
+    }
+    copy_spec(&mut &*reader, &mut &*writer)
+}
+
+// Copies every data segment of `reader` to the same offset in `writer` and
+// extends `writer` to the end of what was copied, which leaves the holes of
+// `reader` as holes. That end is `len`, unless `reader` shrank while it was
+// being copied, and it is returned.
+//
+// Returns `Ok(None)` without having written anything if either seeking to
+// data or `copy_file_range` isn't supported for these files.
+fn copy_data_segments(reader: RawFd, writer: RawFd, len: u64) -> io::Result<Option<u64>> {
+    let mut offset = 0;
+    let mut copied_any = false;
+    'segments: while offset < len {
+        let data = match lseek(reader, offset, SEEK_DATA) {
+            Ok(data) => data,
+            // There is no data past `offset`, only a hole up to the end, which
+            // is before `len` if the source shrank.
+            Err(ref e) if e.raw_os_error() == Some(libc::ENXIO) => {
+                let end = lseek(reader, 0, libc::SEEK_END)?;
+                offset = cmp::min(cmp::max(end, offset), len);
+                break
+            }
+            Err(ref e) if !copied_any && e.raw_os_error() == Some(libc::EINVAL) => {
+                return Ok(None)
+            }
+            Err(e) => return Err(e),
+        };
+        let hole = cmp::min(lseek(reader, data, SEEK_HOLE)?, len);
+
+        let mut off_in = data as i64;
+        let mut off_out = data as i64;
+        while (off_in as u64) < hole {
+            let chunk = cmp::min(hole - off_in as u64, CHUNK as u64) as usize;
+            let ret = unsafe {
+                copy_file_range(reader, &mut off_in, writer, &mut off_out, chunk, 0)
+            };
+            match ret {
+                // The source shrank while we were copying it, and now ends
+                // where we are.
+                Ok(0) => {
+                    offset = off_in as u64;
+                    break 'segments
+                }
+                Ok(_) => copied_any = true,
+                Err(ref e) if !copied_any && is_unsupported(e) => return Ok(None),
+                Err(e) => return Err(e),
+            }
+        }
+        offset = hole;
+    }
+
+    cvt(unsafe { libc::ftruncate64(writer, offset as i64) })?;
+    Ok(Some(offset))
+}
+
+struct Copier<'a, 'b, R: Read + ?Sized + 'a, W: Write + ?Sized + 'b> {
+    read: &'a mut R,
+    write: &'b mut W,
+}
+
+trait SpecCopy {
+    fn copy(self) -> io::Result<u64>;
+}
+
+impl<'a, 'b, R: Read + ?Sized, W: Write + ?Sized> SpecCopy for Copier<'a, 'b, R, W> {
+    default fn copy(self) -> io::Result<u64> {
+        io::generic_copy(self.read, self.write)
+    }
+}
+
+impl<'a, 'b, R: CopyRead + ?Sized, W: CopyWrite + ?Sized> SpecCopy for Copier<'a, 'b, R, W> {
+    fn copy(self) -> io::Result<u64> {
+        let (reader, writer) = (self.read.fd(), self.write.fd());
+        let (reader_kind, writer_kind) = (fd_kind(reader), fd_kind(writer));
+        let mut written = 0;
+
+        if reader_kind == FdKind::File && writer_kind == FdKind::File {
+            match copy_file_range_loop(reader, writer) {
+                CopyResult::Ended(n) => return Ok(written + n),
+                CopyResult::Error(e) => return Err(e),
+                CopyResult::Fallback(n) => written += n,
+            }
+        }
+
+        if reader_kind == FdKind::File || reader_kind == FdKind::Block {
+            match sendfile_splice(SpliceMode::Sendfile, reader, writer) {
+                CopyResult::Ended(n) => return Ok(written + n),
+                CopyResult::Error(e) => return Err(e),
+                CopyResult::Fallback(n) => written += n,
+            }
+        }
+
+        if reader_kind == FdKind::Pipe || writer_kind == FdKind::Pipe {
+            match sendfile_splice(SpliceMode::Splice, reader, writer) {
+                CopyResult::Ended(n) => return Ok(written + n),
+                CopyResult::Error(e) => return Err(e),
+                CopyResult::Fallback(n) => written += n,
+            }
+        }
+
+        io::generic_copy(self.read, self.write).map(|n| written + n)
+    }
+}
+
+/// Readers whose data can be moved by the kernel.
+///
+/// Only types that don't buffer anything in userspace may implement this,
+/// otherwise buffered data would be skipped.
+trait CopyRead: Read {
+    fn fd(&self) -> RawFd;
+}
+
+/// Writers that data can be moved to by the kernel.
+///
+/// Only types that don't buffer anything in userspace may implement this,
+/// otherwise data would be written out of order.
+trait CopyWrite: Write {
+    fn fd(&self) -> RawFd;
+}
+
+macro_rules! copy_impls {
+    ($tr:ident for $($t:ty),*) => {$(
+        impl $tr for $t {
+            fn fd(&self) -> RawFd { self.as_raw_fd() }
+        }
+        impl<'a> $tr for &'a $t {
+            fn fd(&self) -> RawFd { self.as_raw_fd() }
+        }
+    )*}
+}
+
+copy_impls!(CopyRead for File, TcpStream, UnixStream);
+copy_impls!(CopyWrite for File, TcpStream, UnixStream);
+
+impl CopyRead for ChildStdout {
+    fn fd(&self) -> RawFd { self.as_raw_fd() }
+}
+
+impl CopyRead for ChildStderr {
+    fn fd(&self) -> RawFd { self.as_raw_fd() }
+}
+
+impl CopyWrite for ChildStdin {
+    fn fd(&self) -> RawFd { self.as_raw_fd() }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum FdKind {
+    File,
+    Block,
+    Pipe,
+    Other,
+}
+
+fn fd_kind(fd: RawFd) -> FdKind {
+    let mut stat: libc::stat64 = unsafe { mem::zeroed() };
+    if unsafe { libc::fstat64(fd, &mut stat) } == -1 {
+        return FdKind::Other
+    }
+    match stat.st_mode & libc::S_IFMT {
+        libc::S_IFREG => FdKind::File,
+        libc::S_IFBLK => FdKind::Block,
+        libc::S_IFIFO => FdKind::Pipe,
+        _ => FdKind::Other,
+    }
+}
+
+enum CopyResult {
+    /// The source reached its end after this many bytes.
+    Ended(u64),
+    Error(io::Error),
+    /// This many bytes were copied before the method stopped working and the
+    /// next one should be tried.
+    Fallback(u64),
+}
+
+// Whether `err`, returned before any data was moved, means that the syscall
+// can't be used for this pair of file descriptors rather than a real error.
+fn is_unsupported(err: &io::Error) -> bool {
+    match err.raw_os_error() {
+        // EXDEV: cross-filesystem copy on kernels before 5.3
+        // EINVAL: the file or filesystem doesn't support the operation
+        // EBADF: e.g. the destination was opened with O_APPEND
+        // EPERM: e.g. the destination is immutable, or a seccomp filter
+        Some(libc::ENOSYS) | Some(libc::EXDEV) | Some(libc::EINVAL) |
+        Some(libc::EOPNOTSUPP) | Some(libc::EBADF) | Some(libc::EPERM) => true,
+        _ => false,
+    }
+}
+
+fn copy_file_range_loop(reader: RawFd, writer: RawFd) -> CopyResult {
+    let mut written = 0;
+    loop {
+        let ret = unsafe {
+            copy_file_range(reader, ptr::null_mut(), writer, ptr::null_mut(), CHUNK, 0)
+        };
+        match ret {
+            // Files in procfs and sysfs report a size of zero, so the kernel
+            // thinks they're empty. Let the next method check for itself.
+            Ok(0) if written == 0 => return CopyResult::Fallback(0),
+            Ok(0) => return CopyResult::Ended(written),
+            Ok(n) => written += n as u64,
+            Err(ref e) if written == 0 && is_unsupported(e) => {
+                return CopyResult::Fallback(0)
+            }
+            Err(e) => return CopyResult::Error(e),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum SpliceMode {
+    Sendfile,
+    Splice,
+}
+
+fn sendfile_splice(mode: SpliceMode, reader: RawFd, writer: RawFd) -> CopyResult {
+    let mut written = 0;
+    loop {
+        let ret = cvt(unsafe {
+            match mode {
+                SpliceMode::Sendfile => {
+                    libc::sendfile(writer, reader, ptr::null_mut(), CHUNK)
+                }
+                SpliceMode::Splice => {
+                    libc::splice(reader, ptr::null_mut(), writer, ptr::null_mut(), CHUNK, 0)
+                }
+            }
+        });
+        match ret {
+            Ok(0) if written == 0 => return CopyResult::Fallback(0),
+            Ok(0) => return CopyResult::Ended(written),
+            Ok(n) => written += n as u64,
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(ref e) if written == 0 && is_unsupported(e) => {
+                return CopyResult::Fallback(0)
+            }
+            Err(e) => return CopyResult::Error(e),
+        }
+    }
+}
+
+// Wrapper around the raw syscall, glibc only gained a wrapper in 2.27.
+// Retries on `EINTR` and remembers if the kernel doesn't know about it.
+unsafe fn copy_file_range(fd_in: RawFd,
+                          off_in: *mut i64,
+                          fd_out: RawFd,
+                          off_out: *mut i64,
+                          len: usize,
+                          flags: c_uint) -> io::Result<usize> {
+    if NO_COPY_FILE_RANGE.load(Ordering::Relaxed) {
+        return Err(io::Error::from_raw_os_error(libc::ENOSYS))
+    }
+    loop {
+        let ret: c_long = libc::syscall(libc::SYS_copy_file_range,
+                                        fd_in, off_in, fd_out, off_out, len, flags);
+        if ret != -1 {
+            return Ok(ret as usize)
+        }
+        let err = io::Error::last_os_error();
+        match err.raw_os_error() {
+            Some(libc::EINTR) => continue,
+            Some(libc::ENOSYS) => NO_COPY_FILE_RANGE.store(true, Ordering::Relaxed),
+            _ => {}
+        }
+        return Err(err)
+    }
+}
+
+fn lseek(fd: RawFd, offset: u64, whence: c_int) -> io::Result<u64> {
+    let n = cvt(unsafe { libc::lseek64(fd, offset as i64, whence) })?;
+    Ok(n as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use fs::File;
+    use io::{Read, Seek, SeekFrom, Write};
+    use sync::atomic::Ordering;
+    use sys_common::io::test::tmpdir;
+    use super::{copy_regular_files, NO_COPY_FILE_RANGE};
+
+    #[test]
+    fn regular_files_fallback_copies_everything() {
+        let tmp = tmpdir();
+        let in_path = tmp.join("in.txt");
+        let out_path = tmp.join("out.txt");
+        let data = (0..100_000u32).map(|i| i as u8).collect::<Vec<_>>();
+        File::create(&in_path).unwrap().write_all(&data).unwrap();
+
+        // Pretend to be on a kernel without `copy_file_range`, so that the
+        // copy has to go through the fallback after seeking to data.
+        let was_missing = NO_COPY_FILE_RANGE.swap(true, Ordering::Relaxed);
+        let reader = File::open(&in_path).unwrap();
+        let writer = File::create(&out_path).unwrap();
+        let res = copy_regular_files(&reader, &writer, data.len() as u64);
+        NO_COPY_FILE_RANGE.store(was_missing, Ordering::Relaxed);
+
+        assert_eq!(res.unwrap(), data.len() as u64);
+        assert_eq!((&reader).seek(SeekFrom::Current(0)).unwrap(), data.len() as u64);
+        let mut contents = Vec::new();
+        File::open(&out_path).unwrap().read_to_end(&mut contents).unwrap();
+        assert!(contents == data);
+    }
+
+    #[test]
+    fn regular_files_shorter_than_expected() {
+        let tmp = tmpdir();
+        let in_path = tmp.join("in.txt");
+        let out_path = tmp.join("out.txt");
+        let data = (0..100_000u32).map(|i| i as u8).collect::<Vec<_>>();
+        File::create(&in_path).unwrap().write_all(&data).unwrap();
+
+        // A length that is out of date, as if the source had shrunk since it
+        // was measured: only the data that is still there is copied, and the
+        // destination isn't padded up to the old length.
+        let reader = File::open(&in_path).unwrap();
+        let writer = File::create(&out_path).unwrap();
+        let res = copy_regular_files(&reader, &writer, data.len() as u64 + 10_000);
+
+        assert_eq!(res.unwrap(), data.len() as u64);
+        let mut contents = Vec::new();
+        File::open(&out_path).unwrap().read_to_end(&mut contents).unwrap();
+        assert!(contents == data);
+    }
+}
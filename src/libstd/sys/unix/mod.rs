@@ -31,6 +31,10 @@ use libc;
 
 pub use self::rand::hashmap_random_keys;
 pub use libc::strlen;
+#[cfg(target_os = "linux")]
+pub use self::kernel_copy::copy_spec as copy_impl;
+#[cfg(not(target_os = "linux"))]
+pub(crate) use io::generic_copy as copy_impl;
 
 #[macro_use]
 pub mod weak;
@@ -40,13 +44,26 @@ pub mod android;
 #[cfg(feature = "backtrace")]
 pub mod backtrace;
 pub mod cmath;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[path = "futex_condvar.rs"]
+pub mod condvar;
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
 pub mod condvar;
 pub mod env;
 pub mod ext;
 pub mod fast_thread_local;
 pub mod fd;
 pub mod fs;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub mod futex;
+pub mod io;
+#[cfg(target_os = "linux")]
+pub mod kernel_copy;
 pub mod memchr;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[path = "futex_mutex.rs"]
+pub mod mutex;
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
 pub mod mutex;
 #[cfg(not(target_os = "l4re"))]
 pub mod net;
@@ -60,6 +77,10 @@ pub mod path;
 pub mod pipe;
 pub mod process;
 pub mod rand;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[path = "futex_rwlock.rs"]
+pub mod rwlock;
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
 pub mod rwlock;
 pub mod stack_overflow;
 pub mod thread;
@@ -11,6 +11,9 @@
 use cell::UnsafeCell;
 use libc;
 use mem;
+use sys_common::lazy_box::{LazyBox, LazyInit};
+
+pub type MovableMutex = LazyBox<Mutex>;
 
 pub struct Mutex { inner: UnsafeCell<libc::pthread_mutex_t> }
 
@@ -95,6 +98,18 @@ impl Mutex {
     }
 }
 
+impl LazyInit for Mutex {
+    fn init() -> Box<Mutex> {
+        let mut mutex = box Mutex::new();
+        unsafe { mutex.init() };
+        mutex
+    }
+
+    unsafe fn destroy(&self) {
+        Mutex::destroy(self)
+    }
+}
+
 pub struct ReentrantMutex { inner: UnsafeCell<libc::pthread_mutex_t> }
 
 unsafe impl Send for ReentrantMutex {}
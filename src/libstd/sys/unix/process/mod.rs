@@ -10,6 +10,8 @@
 
 pub use self::process_common::{Command, ExitStatus, Stdio, StdioPipes};
 pub use self::process_inner::Process;
+#[cfg(target_os = "linux")]
+pub use self::process_inner::{pidfd_send_signal, pidfd_wait};
 
 mod process_common;
 #[cfg(not(target_os = "fuchsia"))]
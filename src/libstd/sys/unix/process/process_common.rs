@@ -15,7 +15,7 @@ use env;
 use ffi::{OsString, OsStr, CString, CStr};
 use fmt;
 use io;
-use libc::{self, c_int, gid_t, uid_t, c_char};
+use libc::{self, c_int, gid_t, pid_t, uid_t, c_char};
 use ptr;
 use sys::fd::FileDesc;
 use sys::fs::{File, OpenOptions};
@@ -52,6 +52,9 @@ pub struct Command {
     cwd: Option<CString>,
     uid: Option<uid_t>,
     gid: Option<gid_t>,
+    pgroup: Option<pid_t>,
+    setsid: bool,
+    create_pidfd: bool,
     saw_nul: bool,
     closures: Vec<Box<FnMut() -> io::Result<()> + Send + Sync>>,
     stdin: Option<Stdio>,
@@ -101,6 +104,9 @@ impl Command {
             cwd: None,
             uid: None,
             gid: None,
+            pgroup: None,
+            setsid: false,
+            create_pidfd: false,
             saw_nul,
             closures: Vec::new(),
             stdin: None,
@@ -192,6 +198,15 @@ impl Command {
     pub fn gid(&mut self, id: gid_t) {
         self.gid = Some(id);
     }
+    pub fn pgroup(&mut self, pgroup: pid_t) {
+        self.pgroup = Some(pgroup);
+    }
+    pub fn setsid(&mut self, setsid: bool) {
+        self.setsid = setsid;
+    }
+    pub fn create_pidfd(&mut self, create_pidfd: bool) {
+        self.create_pidfd = create_pidfd;
+    }
 
     pub fn saw_nul(&self) -> bool {
         self.saw_nul
@@ -215,6 +230,18 @@ impl Command {
     pub fn get_gid(&self) -> Option<gid_t> {
         self.gid
     }
+    #[allow(dead_code)]
+    pub fn get_pgroup(&self) -> Option<pid_t> {
+        self.pgroup
+    }
+    #[allow(dead_code)]
+    pub fn get_setsid(&self) -> bool {
+        self.setsid
+    }
+    #[allow(dead_code)]
+    pub fn get_create_pidfd(&self) -> bool {
+        self.create_pidfd
+    }
 
     pub fn get_closures(&mut self) -> &mut Vec<Box<FnMut() -> io::Result<()> + Send + Sync>> {
         &mut self.closures
@@ -385,12 +412,26 @@ impl ExitStatus {
     }
 
     pub fn signal(&self) -> Option<i32> {
-        if !self.exited() {
+        if unsafe { libc::WIFSIGNALED(self.0) } {
             Some(unsafe { libc::WTERMSIG(self.0) })
         } else {
             None
         }
     }
+
+    pub fn core_dumped(&self) -> bool {
+        // `WCOREDUMP` isn't part of POSIX, but every Unix we support flags a
+        // core dump with the same bit of the status of a signalled process.
+        unsafe { libc::WIFSIGNALED(self.0) && (self.0 & 0x80) != 0 }
+    }
+
+    pub fn stopped_signal(&self) -> Option<i32> {
+        if unsafe { libc::WIFSTOPPED(self.0) } {
+            Some(unsafe { libc::WSTOPSIG(self.0) })
+        } else {
+            None
+        }
+    }
 }
 
 impl From<c_int> for ExitStatus {
@@ -403,9 +444,16 @@ impl fmt::Display for ExitStatus {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         if let Some(code) = self.code() {
             write!(f, "exit code: {}", code)
+        } else if let Some(signal) = self.signal() {
+            if self.core_dumped() {
+                write!(f, "signal: {} (core dumped)", signal)
+            } else {
+                write!(f, "signal: {}", signal)
+            }
+        } else if let Some(signal) = self.stopped_signal() {
+            write!(f, "stopped (not terminated) by signal: {}", signal)
         } else {
-            let signal = self.signal().unwrap();
-            write!(f, "signal: {}", signal)
+            write!(f, "unrecognised wait status: {} {:#x}", self.0, self.0)
         }
     }
 }
@@ -507,4 +555,30 @@ mod tests {
             t!(cat.wait());
         }
     }
+
+    #[test]
+    fn exit_status_decoding() {
+        // Terminated by SIGSEGV, with and without a core dump.
+        let status = ExitStatus::new(libc::SIGSEGV);
+        assert_eq!(status.code(), None);
+        assert_eq!(status.signal(), Some(libc::SIGSEGV));
+        assert!(!status.core_dumped());
+        assert_eq!(status.to_string(), format!("signal: {}", libc::SIGSEGV));
+
+        let status = ExitStatus::new(libc::SIGSEGV | 0x80);
+        assert_eq!(status.signal(), Some(libc::SIGSEGV));
+        assert!(status.core_dumped());
+
+        // Stopped by SIGSTOP, as reported by `waitpid` with `WUNTRACED`.
+        let status = ExitStatus::new((libc::SIGSTOP << 8) | 0x7f);
+        assert_eq!(status.code(), None);
+        assert_eq!(status.signal(), None);
+        assert!(!status.core_dumped());
+        assert_eq!(status.stopped_signal(), Some(libc::SIGSTOP));
+
+        let status = ExitStatus::new(3 << 8);
+        assert_eq!(status.code(), Some(3));
+        assert_eq!(status.signal(), None);
+        assert_eq!(status.stopped_signal(), None);
+    }
 }
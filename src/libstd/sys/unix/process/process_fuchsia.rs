@@ -27,6 +27,10 @@ impl Command {
             return Err(io::Error::new(io::ErrorKind::InvalidInput,
                                       "nul byte found in provided data"));
         }
+        if self.get_pgroup().is_some() || self.get_setsid() {
+            return Err(io::Error::new(io::ErrorKind::Other,
+                                      "process groups and sessions are not supported on Fuchsia"));
+        }
 
         let (ours, theirs) = self.setup_io(default, needs_stdin)?;
 
@@ -143,6 +147,10 @@ impl Process {
         Ok(())
     }
 
+    pub fn signal(&mut self, _signal: i32) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::Other, "signals are not supported on Fuchsia"))
+    }
+
     pub fn wait(&mut self) -> io::Result<ExitStatus> {
         use default::Default;
         use sys::process::zircon::*;
@@ -12,7 +12,13 @@ use io::{self, Error, ErrorKind};
 use libc::{self, c_int, gid_t, pid_t, uid_t};
 use ptr;
 
+#[cfg(target_os = "linux")]
+use os::linux::process::PidFd;
 use sys::cvt;
+#[cfg(target_os = "linux")]
+use sys::fd::FileDesc;
+#[cfg(target_os = "linux")]
+use sys_common::{AsInner, FromInner};
 use sys::process::process_common::*;
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -58,7 +64,7 @@ impl Command {
             }
         };
 
-        let mut p = Process { pid: pid, status: None };
+        let mut p = Process::new(pid, self.get_create_pidfd());
         drop(output);
         let mut bytes = [0; 8];
 
@@ -160,6 +166,13 @@ impl Command {
             t!(cvt_r(|| libc::dup2(fd, libc::STDERR_FILENO)));
         }
 
+        if self.get_setsid() {
+            t!(cvt(libc::setsid()));
+        }
+        if let Some(pgroup) = self.get_pgroup() {
+            t!(cvt(libc::setpgid(0, pgroup)));
+        }
+
         if cfg!(not(any(target_os = "l4re"))) {
             if let Some(u) = self.get_gid() {
                 t!(cvt(libc::setgid(u as gid_t)));
@@ -231,13 +244,46 @@ impl Command {
 pub struct Process {
     pid: pid_t,
     status: Option<ExitStatus>,
+    // Signals are delivered through this, if present, so that they can't
+    // reach another process that was handed the same pid after ours got
+    // reaped behind our back.
+    #[cfg(target_os = "linux")]
+    pidfd: Option<PidFd>,
 }
 
 impl Process {
+    #[cfg(target_os = "linux")]
+    fn new(pid: pid_t, create_pidfd: bool) -> Process {
+        // The child can't have been reaped yet, so the pidfd is guaranteed to
+        // refer to it. Failing to create one, e.g. on kernels older than 5.3,
+        // isn't fatal: it's reported when the pidfd is asked for.
+        let pidfd = if create_pidfd {
+            pidfd_open(pid).ok().map(PidFd::from_inner)
+        } else {
+            None
+        };
+        Process { pid: pid, status: None, pidfd: pidfd }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn new(pid: pid_t, _create_pidfd: bool) -> Process {
+        Process { pid: pid, status: None }
+    }
+
     pub fn id(&self) -> u32 {
         self.pid as u32
     }
 
+    #[cfg(target_os = "linux")]
+    pub fn pidfd(&self) -> Option<&PidFd> {
+        self.pidfd.as_ref()
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn take_pidfd(&mut self) -> Option<PidFd> {
+        self.pidfd.take()
+    }
+
     pub fn kill(&mut self) -> io::Result<()> {
         // If we've already waited on this process then the pid can be recycled
         // and used for another process, and we probably shouldn't be killing
@@ -246,10 +292,30 @@ impl Process {
             Err(Error::new(ErrorKind::InvalidInput,
                            "invalid argument: can't kill an exited process"))
         } else {
-            cvt(unsafe { libc::kill(self.pid, libc::SIGKILL) }).map(|_| ())
+            self.send_signal(libc::SIGKILL)
+        }
+    }
+
+    pub fn signal(&mut self, signal: c_int) -> io::Result<()> {
+        // Same as in `kill`, the pid may already belong to another process.
+        if self.status.is_some() {
+            Err(Error::new(ErrorKind::InvalidInput,
+                           "invalid argument: can't signal an exited process"))
+        } else {
+            self.send_signal(signal)
         }
     }
 
+    fn send_signal(&self, signal: c_int) -> io::Result<()> {
+        #[cfg(target_os = "linux")]
+        {
+            if let Some(ref pidfd) = self.pidfd {
+                return pidfd_send_signal(pidfd.as_inner(), signal)
+            }
+        }
+        cvt(unsafe { libc::kill(self.pid, signal) }).map(|_| ())
+    }
+
     pub fn wait(&mut self) -> io::Result<ExitStatus> {
         use sys::cvt_r;
         if let Some(status) = self.status {
@@ -277,3 +343,81 @@ impl Process {
         }
     }
 }
+
+// Neither syscall is exposed by the libc crate yet. Both were added after the
+// syscall tables were unified, so their numbers are the same on every
+// architecture except MIPS, where each ABI numbers its syscalls from its own
+// base: 4000 for o32, 5000 for n64 and 6000 for n32.
+#[cfg(target_os = "linux")]
+const SYS_PIDFD_SEND_SIGNAL: libc::c_long = SYSCALL_BASE + 424;
+#[cfg(target_os = "linux")]
+const SYS_PIDFD_OPEN: libc::c_long = SYSCALL_BASE + 434;
+
+#[cfg(all(target_os = "linux", target_arch = "mips"))]
+const SYSCALL_BASE: libc::c_long = 4000;
+#[cfg(all(target_os = "linux", target_arch = "mips64", target_pointer_width = "64"))]
+const SYSCALL_BASE: libc::c_long = 5000;
+#[cfg(all(target_os = "linux", target_arch = "mips64", target_pointer_width = "32"))]
+const SYSCALL_BASE: libc::c_long = 6000;
+#[cfg(all(target_os = "linux", not(any(target_arch = "mips", target_arch = "mips64"))))]
+const SYSCALL_BASE: libc::c_long = 0;
+
+// The `idtype` of `waitid` that waits on a pidfd, added in Linux 5.4.
+#[cfg(target_os = "linux")]
+const P_PIDFD: libc::c_int = 3;
+
+#[cfg(target_os = "linux")]
+fn pidfd_open(pid: pid_t) -> io::Result<FileDesc> {
+    // The returned descriptor always has `FD_CLOEXEC` set.
+    let fd = cvt(unsafe { libc::syscall(SYS_PIDFD_OPEN, pid, 0) })?;
+    Ok(FileDesc::new(fd as c_int))
+}
+
+#[cfg(target_os = "linux")]
+pub fn pidfd_send_signal(pidfd: &FileDesc, signal: c_int) -> io::Result<()> {
+    cvt(unsafe {
+        libc::syscall(SYS_PIDFD_SEND_SIGNAL, pidfd.raw(), signal, ptr::null::<libc::siginfo_t>(), 0)
+    }).map(|_| ())
+}
+
+// Waits for the process to exit without reaping it, which is left to
+// `Process::wait` so that it knows not to signal the process ID anymore.
+#[cfg(target_os = "linux")]
+pub fn pidfd_wait(pidfd: &FileDesc) -> io::Result<ExitStatus> {
+    use mem;
+    use sys::cvt_r;
+
+    // The fields of `siginfo_t` that `waitid` fills in. The union the last
+    // three are in is aligned like a pointer.
+    #[repr(C)]
+    struct SigInfoPrefix {
+        a: [c_int; 3], // si_signo, si_errno, si_code
+        _align: [usize; 0],
+        _b: [c_int; 2], // si_pid, si_uid
+        si_status: c_int,
+    }
+    // MIPS swaps `si_errno` and `si_code`.
+    #[cfg(not(any(target_arch = "mips", target_arch = "mips64")))]
+    const SI_CODE: usize = 2;
+    #[cfg(any(target_arch = "mips", target_arch = "mips64"))]
+    const SI_CODE: usize = 1;
+    const CLD_EXITED: c_int = 1;
+    const CLD_KILLED: c_int = 2;
+    const CLD_DUMPED: c_int = 3;
+
+    let mut info: libc::siginfo_t = unsafe { mem::zeroed() };
+    cvt_r(|| unsafe {
+        libc::syscall(libc::SYS_waitid, P_PIDFD, pidfd.raw(), &mut info as *mut libc::siginfo_t,
+                      libc::WEXITED | libc::WNOWAIT, ptr::null_mut::<libc::rusage>())
+    })?;
+    let info = unsafe { &*(&info as *const libc::siginfo_t as *const SigInfoPrefix) };
+
+    // Rebuild the status that `waitpid` would have returned.
+    let status = match info.a[SI_CODE] {
+        CLD_EXITED => (info.si_status & 0xff) << 8,
+        CLD_KILLED => info.si_status,
+        CLD_DUMPED => info.si_status | 0x80,
+        _ => return Err(Error::new(ErrorKind::Other, "waitid returned an unexpected si_code")),
+    };
+    Ok(ExitStatus::new(status))
+}
@@ -11,6 +11,9 @@
 use libc;
 use cell::UnsafeCell;
 use sync::atomic::{AtomicUsize, Ordering};
+use sys_common::lazy_box::{LazyBox, LazyInit};
+
+pub type MovableRWLock = LazyBox<RWLock>;
 
 pub struct RWLock {
     inner: UnsafeCell<libc::pthread_rwlock_t>,
@@ -140,3 +143,13 @@ impl RWLock {
         }
     }
 }
+
+impl LazyInit for RWLock {
+    fn init() -> Box<RWLock> {
+        box RWLock::new()
+    }
+
+    unsafe fn destroy(&self) {
+        RWLock::destroy(self)
+    }
+}
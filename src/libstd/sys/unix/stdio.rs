@@ -8,7 +8,7 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use io;
+use io::{self, IoSlice};
 use libc;
 use sys::fd::FileDesc;
 
@@ -37,6 +37,13 @@ impl Stdout {
         ret
     }
 
+    pub fn write_vectored(&self, bufs: &[IoSlice]) -> io::Result<usize> {
+        let fd = FileDesc::new(libc::STDOUT_FILENO);
+        let ret = fd.write_vectored(bufs);
+        fd.into_raw();
+        ret
+    }
+
     pub fn flush(&self) -> io::Result<()> {
         Ok(())
     }
@@ -9,8 +9,12 @@
 // except according to those terms.
 
 use sys::mutex::Mutex;
+use sys_common::condvar::NoCheck;
 use time::Duration;
 
+pub type MovableCondvar = Condvar;
+pub type CondvarCheck = NoCheck;
+
 pub struct Condvar { }
 
 impl Condvar {
@@ -27,6 +27,8 @@
 use io;
 use os::raw::c_char;
 
+pub(crate) use io::generic_copy as copy_impl;
+
 // Right now the wasm backend doesn't even have the ability to print to the
 // console by default. Wasm can't import anything from JS! (you have to
 // explicitly provide it).
@@ -44,6 +46,7 @@ pub mod cmath;
 pub mod condvar;
 pub mod env;
 pub mod fs;
+pub mod io;
 pub mod memchr;
 pub mod mutex;
 pub mod net;
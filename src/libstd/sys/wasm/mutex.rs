@@ -10,6 +10,8 @@
 
 use cell::UnsafeCell;
 
+pub type MovableMutex = Mutex;
+
 pub struct Mutex {
     locked: UnsafeCell<bool>,
 }
@@ -10,6 +10,8 @@
 
 use cell::UnsafeCell;
 
+pub type MovableRWLock = RWLock;
+
 pub struct RWLock {
     mode: UnsafeCell<isize>,
 }
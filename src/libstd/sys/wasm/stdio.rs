@@ -8,7 +8,7 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use io;
+use io::{self, IoSlice};
 use sys::{Void, unsupported};
 
 pub struct Stdin(Void);
@@ -47,6 +47,10 @@ impl Stdout {
         Ok(data.len())
     }
 
+    pub fn write_vectored(&self, bufs: &[IoSlice]) -> io::Result<usize> {
+        io::default_write_vectored(|data| self.write(data), bufs)
+    }
+
     pub fn flush(&self) -> io::Result<()> {
         Ok(())
     }
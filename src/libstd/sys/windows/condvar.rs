@@ -12,8 +12,13 @@ use cell::UnsafeCell;
 use sys::c;
 use sys::mutex::{self, Mutex};
 use sys::os;
+use sys_common::condvar::SameMutexCheck;
+use sys_common::lazy_box::{LazyBox, LazyInit};
 use time::Duration;
 
+pub type MovableCondvar = LazyBox<Condvar>;
+pub type CondvarCheck = SameMutexCheck;
+
 pub struct Condvar { inner: UnsafeCell<c::CONDITION_VARIABLE> }
 
 unsafe impl Send for Condvar {}
@@ -63,3 +68,15 @@ impl Condvar {
         // ...
     }
 }
+
+impl LazyInit for Condvar {
+    fn init() -> Box<Condvar> {
+        let mut condvar = box Condvar::new();
+        unsafe { condvar.init() };
+        condvar
+    }
+
+    unsafe fn destroy(&self) {
+        Condvar::destroy(self)
+    }
+}
@@ -12,7 +12,7 @@ use os::windows::prelude::*;
 
 use ffi::OsString;
 use fmt;
-use io::{self, Error, SeekFrom};
+use io::{self, Error, SeekFrom, IoSlice, IoSliceMut};
 use mem;
 use path::{Path, PathBuf};
 use ptr;
@@ -279,6 +279,57 @@ impl File {
 
     pub fn datasync(&self) -> io::Result<()> { self.fsync() }
 
+    pub fn lock(&self) -> io::Result<()> {
+        self.acquire_lock(c::LOCKFILE_EXCLUSIVE_LOCK)
+    }
+
+    pub fn lock_shared(&self) -> io::Result<()> {
+        self.acquire_lock(0)
+    }
+
+    pub fn try_lock(&self) -> io::Result<()> {
+        self.acquire_lock(c::LOCKFILE_EXCLUSIVE_LOCK | c::LOCKFILE_FAIL_IMMEDIATELY)
+    }
+
+    pub fn try_lock_shared(&self) -> io::Result<()> {
+        self.acquire_lock(c::LOCKFILE_FAIL_IMMEDIATELY)
+    }
+
+    pub fn unlock(&self) -> io::Result<()> {
+        cvt(unsafe {
+            c::UnlockFile(self.handle.raw(), 0, 0, c::DWORD::max_value(), c::DWORD::max_value())
+        })?;
+        Ok(())
+    }
+
+    // Locks the whole file. Handles opened with `FILE_FLAG_OVERLAPPED` may
+    // report the lock as pending, in which case we wait for it here. A lock
+    // held elsewhere makes `LOCKFILE_FAIL_IMMEDIATELY` fail with
+    // `ERROR_LOCK_VIOLATION`, which is reported as `WouldBlock`. That mapping
+    // is specific to locking: reads and writes that run into a lock fail with
+    // the same code, but retrying them later is not what the caller wants.
+    fn acquire_lock(&self, flags: c::DWORD) -> io::Result<()> {
+        unsafe {
+            let mut overlapped: c::OVERLAPPED = mem::zeroed();
+            let res = cvt(c::LockFileEx(self.handle.raw(), flags, 0,
+                                        c::DWORD::max_value(), c::DWORD::max_value(),
+                                        &mut overlapped));
+            match res {
+                Ok(_) => Ok(()),
+                Err(ref e) if e.raw_os_error() == Some(c::ERROR_IO_PENDING as i32) => {
+                    self.handle.overlapped_result(&mut overlapped, true).map(|_| ())
+                }
+                Err(e) => {
+                    if e.raw_os_error() == Some(c::ERROR_LOCK_VIOLATION as i32) {
+                        Err(io::Error::new(io::ErrorKind::WouldBlock, e))
+                    } else {
+                        Err(e)
+                    }
+                }
+            }
+        }
+    }
+
     pub fn truncate(&self, size: u64) -> io::Result<()> {
         let mut info = c::FILE_END_OF_FILE_INFO {
             EndOfFile: size as c::LARGE_INTEGER,
@@ -320,6 +371,10 @@ impl File {
         self.handle.read(buf)
     }
 
+    pub fn read_vectored(&self, bufs: &mut [IoSliceMut]) -> io::Result<usize> {
+        io::default_read_vectored(|buf| self.read(buf), bufs)
+    }
+
     pub fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
         self.handle.read_at(buf, offset)
     }
@@ -328,6 +383,10 @@ impl File {
         self.handle.write(buf)
     }
 
+    pub fn write_vectored(&self, bufs: &[IoSlice]) -> io::Result<usize> {
+        io::default_write_vectored(|buf| self.write(buf), bufs)
+    }
+
     pub fn write_at(&self, buf: &[u8], offset: u64) -> io::Result<usize> {
         self.handle.write_at(buf, offset)
     }
@@ -19,6 +19,7 @@ use time::Duration;
 
 pub use libc::strlen;
 pub use self::rand::hashmap_random_keys;
+pub(crate) use io::generic_copy as copy_impl;
 
 #[macro_use] pub mod compat;
 
@@ -35,6 +36,7 @@ pub mod ext;
 pub mod fast_thread_local;
 pub mod fs;
 pub mod handle;
+pub mod io;
 pub mod memchr;
 pub mod mutex;
 pub mod net;
@@ -34,6 +34,9 @@ use mem;
 use sync::atomic::{AtomicUsize, Ordering};
 use sys::c;
 use sys::compat;
+use sys_common::lazy_box::{LazyBox, LazyInit};
+
+pub type MovableMutex = LazyBox<Mutex>;
 
 pub struct Mutex {
     lock: AtomicUsize,
@@ -137,6 +140,18 @@ impl Mutex {
     }
 }
 
+impl LazyInit for Mutex {
+    fn init() -> Box<Mutex> {
+        let mut mutex = box Mutex::new();
+        unsafe { mutex.init() };
+        mutex
+    }
+
+    unsafe fn destroy(&self) {
+        Mutex::destroy(self)
+    }
+}
+
 fn kind() -> Kind {
     static KIND: AtomicUsize = AtomicUsize::new(0);
 
@@ -10,6 +10,9 @@
 
 use cell::UnsafeCell;
 use sys::c;
+use sys_common::lazy_box::{LazyBox, LazyInit};
+
+pub type MovableRWLock = LazyBox<RWLock>;
 
 pub struct RWLock { inner: UnsafeCell<c::SRWLOCK> }
 
@@ -50,3 +53,13 @@ impl RWLock {
         // ...
     }
 }
+
+impl LazyInit for RWLock {
+    fn init() -> Box<RWLock> {
+        box RWLock::new()
+    }
+
+    unsafe fn destroy(&self) {
+        RWLock::destroy(self)
+    }
+}
@@ -13,7 +13,7 @@
 use io::prelude::*;
 
 use cmp;
-use io::{self, Cursor};
+use io::{self, Cursor, IoSlice};
 use ptr;
 use str;
 use sync::Mutex;
@@ -161,6 +161,10 @@ impl Stdout {
         write(c::STD_OUTPUT_HANDLE, data)
     }
 
+    pub fn write_vectored(&self, bufs: &[IoSlice]) -> io::Result<usize> {
+        io::default_write_vectored(|data| self.write(data), bufs)
+    }
+
     pub fn flush(&self) -> io::Result<()> {
         Ok(())
     }
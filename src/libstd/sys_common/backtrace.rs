@@ -44,17 +44,24 @@ pub struct Frame {
 }
 
 /// Max number of frames to print.
-const MAX_NB_FRAMES: usize = 100;
+pub const MAX_NB_FRAMES: usize = 100;
 
 /// Prints the current backtrace.
 pub fn print(w: &mut Write, format: PrintFormat) -> io::Result<()> {
+    // Use a lock to prevent mixed output in multithreading context.
+    lock(|| _print(w, format))
+}
+
+/// Runs `f` while holding the lock that serializes unwinding and symbol
+/// resolution. Some platforms require it, like `SymFromAddr` on Windows.
+pub fn lock<F, R>(f: F) -> R
+    where F: FnOnce() -> R
+{
     static LOCK: Mutex = Mutex::new();
 
-    // Use a lock to prevent mixed output in multithreading context.
-    // Some platforms also requires it, like `SymFromAddr` on Windows.
     unsafe {
         LOCK.lock();
-        let res = _print(w, format);
+        let res = f();
         LOCK.unlock();
         res
     }
@@ -175,7 +182,7 @@ pub fn log_enabled() -> Option<PrintFormat> {
 ///
 /// These output functions should now be used everywhere to ensure consistency.
 /// You may want to also use `output_fileline`.
-fn output(w: &mut Write, idx: usize, frame: Frame,
+pub fn output(w: &mut Write, idx: usize, frame: Frame,
               s: Option<&str>, format: PrintFormat) -> io::Result<()> {
     // Remove the `17: 0x0 - <unknown>` line.
     if format == PrintFormat::Short && frame.exact_position == ptr::null() {
@@ -200,7 +207,7 @@ fn output(w: &mut Write, idx: usize, frame: Frame,
 ///
 /// See also `output`.
 #[allow(dead_code)]
-fn output_fileline(w: &mut Write,
+pub fn output_fileline(w: &mut Write,
                    file: &[u8],
                    line: u32,
                    format: PrintFormat) -> io::Result<()> {
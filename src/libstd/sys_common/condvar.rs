@@ -8,8 +8,9 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use sync::atomic::{AtomicUsize, Ordering};
 use time::Duration;
-use sys_common::mutex::{self, Mutex};
+use sys_common::mutex::{self, Mutex, MovableMutex};
 use sys::condvar as imp;
 
 /// An OS-based condition variable.
@@ -68,3 +69,98 @@ impl Condvar {
     #[inline]
     pub unsafe fn destroy(&self) { self.0.destroy() }
 }
+
+/// An OS-based condition variable that may be moved, for `std::sync::Condvar`.
+///
+/// It is stored like `MovableMutex`, and checks that it is only used with
+/// one mutex where the platform requires that.
+pub struct MovableCondvar {
+    inner: imp::MovableCondvar,
+    check: imp::CondvarCheck,
+}
+
+impl MovableCondvar {
+    /// Creates a new condition variable.
+    pub const fn new() -> MovableCondvar {
+        MovableCondvar {
+            inner: imp::MovableCondvar::new(),
+            check: imp::CondvarCheck::new(),
+        }
+    }
+
+    /// Signals one waiter on this condition variable to wake up.
+    #[inline]
+    pub unsafe fn notify_one(&self) { self.inner.notify_one() }
+
+    /// Awakens all current waiters on this condition variable.
+    #[inline]
+    pub unsafe fn notify_all(&self) { self.inner.notify_all() }
+
+    /// Waits for a signal on the specified mutex.
+    ///
+    /// Behavior is undefined if the mutex is not locked by the current thread.
+    ///
+    /// May panic if used with more than one mutex.
+    #[inline]
+    pub unsafe fn wait(&self, mutex: &MovableMutex) {
+        self.check.verify(mutex);
+        self.inner.wait(mutex.raw())
+    }
+
+    /// Waits for a signal on the specified mutex with a timeout duration
+    /// specified by `dur` (a relative time into the future).
+    ///
+    /// Behavior is undefined if the mutex is not locked by the current thread.
+    ///
+    /// May panic if used with more than one mutex.
+    #[inline]
+    pub unsafe fn wait_timeout(&self, mutex: &MovableMutex, dur: Duration) -> bool {
+        self.check.verify(mutex);
+        self.inner.wait_timeout(mutex.raw(), dur)
+    }
+}
+
+/// Binds a condition variable to the first mutex it is used with, and panics
+/// if it is later used with another one.
+///
+/// Used on platforms where using a condition variable with several mutexes is
+/// undefined behavior, and where the native mutex stays at one address.
+pub struct SameMutexCheck {
+    addr: AtomicUsize,
+}
+
+impl SameMutexCheck {
+    pub const fn new() -> SameMutexCheck {
+        SameMutexCheck { addr: AtomicUsize::new(0) }
+    }
+
+    fn verify(&self, mutex: &MovableMutex) {
+        let addr = mutex.raw() as *const _ as usize;
+        match self.addr.compare_and_swap(0, addr, Ordering::SeqCst) {
+            // If we got out 0, then we have successfully bound the mutex to
+            // this cvar.
+            0 => {}
+
+            // If we get out a value that's the same as `addr`, then someone
+            // already beat us to the punch.
+            n if n == addr => {}
+
+            // Anything else and we're using more than one mutex on this cvar,
+            // which is currently disallowed.
+            _ => panic!("attempted to use a condition variable with two \
+                         mutexes"),
+        }
+    }
+}
+
+/// Accepts any mutex, for platforms whose condition variables work with
+/// several mutexes.
+pub struct NoCheck;
+
+impl NoCheck {
+    pub const fn new() -> NoCheck {
+        NoCheck
+    }
+
+    fn verify(&self, _: &MovableMutex) {}
+}
@@ -0,0 +1,103 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+
+//! A box that allocates its contents the first time they are used.
+//!
+//! Native locks which must not be moved once used live in a `LazyBox`, which
+//! gives them a fixed address without making the constructors of the
+//! `std::sync` types allocate, so those constructors can be `const fn`s.
+
+use marker::PhantomData;
+use ops::{Deref, DerefMut};
+use ptr;
+use sync::atomic::AtomicPtr;
+use sync::atomic::Ordering::{AcqRel, Acquire};
+
+pub struct LazyBox<T: LazyInit> {
+    ptr: AtomicPtr<T>,
+    _phantom: PhantomData<T>,
+}
+
+pub trait LazyInit {
+    /// Creates a new value, already at the address it will be used from.
+    ///
+    /// If several threads race to initialize the same `LazyBox`, the values
+    /// created by all but one of them are passed to `destroy` and dropped
+    /// without ever having been used.
+    fn init() -> Box<Self>;
+
+    /// Releases the resources acquired by `init`, right before the value is
+    /// freed.
+    unsafe fn destroy(&self);
+}
+
+impl<T: LazyInit> LazyBox<T> {
+    #[inline]
+    pub const fn new() -> LazyBox<T> {
+        LazyBox {
+            ptr: AtomicPtr::new(ptr::null_mut()),
+            _phantom: PhantomData,
+        }
+    }
+
+    #[inline]
+    fn get_pointer(&self) -> *mut T {
+        let ptr = self.ptr.load(Acquire);
+        if ptr.is_null() {
+            self.initialize()
+        } else {
+            ptr
+        }
+    }
+
+    #[cold]
+    fn initialize(&self) -> *mut T {
+        let new_ptr = Box::into_raw(T::init());
+        match self.ptr.compare_exchange(ptr::null_mut(), new_ptr, AcqRel, Acquire) {
+            Ok(_) => new_ptr,
+            Err(ptr) => {
+                // Lost the race to another thread.
+                unsafe { free(new_ptr) };
+                ptr
+            }
+        }
+    }
+}
+
+impl<T: LazyInit> Deref for LazyBox<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        unsafe { &*self.get_pointer() }
+    }
+}
+
+impl<T: LazyInit> DerefMut for LazyBox<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.get_pointer() }
+    }
+}
+
+impl<T: LazyInit> Drop for LazyBox<T> {
+    fn drop(&mut self) {
+        let ptr = *self.ptr.get_mut();
+        if !ptr.is_null() {
+            unsafe { free(ptr) };
+        }
+    }
+}
+
+unsafe fn free<T: LazyInit>(ptr: *mut T) {
+    let boxed = Box::from_raw(ptr);
+    T::destroy(&boxed);
+}
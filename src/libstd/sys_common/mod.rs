@@ -33,6 +33,7 @@ pub mod at_exit_imp;
 pub mod backtrace;
 pub mod condvar;
 pub mod io;
+pub mod lazy_box;
 pub mod memchr;
 pub mod mutex;
 pub mod poison;
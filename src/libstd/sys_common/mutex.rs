@@ -62,5 +62,38 @@ impl Mutex {
     pub unsafe fn destroy(&self) { self.0.destroy() }
 }
 
+/// An OS-based mutual exclusion lock that may be moved, for `std::sync::Mutex`.
+///
+/// Each platform chooses how to store it in `sys::mutex::MovableMutex`: inline
+/// where the native mutex may be moved, or in a box that is allocated on first
+/// use otherwise. Either way `new` doesn't allocate, and any resources are
+/// released when it is dropped.
+pub struct MovableMutex(imp::MovableMutex);
+
+unsafe impl Sync for MovableMutex {}
+
+impl MovableMutex {
+    /// Creates a new mutex.
+    pub const fn new() -> MovableMutex { MovableMutex(imp::MovableMutex::new()) }
+
+    pub(super) fn raw(&self) -> &imp::Mutex { &self.0 }
+
+    /// Locks the mutex blocking the current thread until it is available.
+    #[inline]
+    pub unsafe fn lock(&self) { self.raw().lock() }
+
+    /// Attempts to lock the mutex without blocking, returning whether it was
+    /// successfully acquired or not.
+    #[inline]
+    pub unsafe fn try_lock(&self) -> bool { self.raw().try_lock() }
+
+    /// Unlocks the mutex.
+    ///
+    /// Behavior is undefined if the current thread does not actually hold the
+    /// mutex.
+    #[inline]
+    pub unsafe fn unlock(&self) { self.raw().unlock() }
+}
+
 // not meant to be exported to the outside world, just the containing module
 pub fn raw(mutex: &Mutex) -> &imp::Mutex { &mutex.0 }
@@ -80,3 +80,41 @@ impl RWLock {
     #[inline]
     pub unsafe fn destroy(&self) { self.0.destroy() }
 }
+
+/// An OS-based reader-writer lock that may be moved, for `std::sync::RwLock`.
+///
+/// It is stored like `MovableMutex`.
+pub struct MovableRWLock(imp::MovableRWLock);
+
+impl MovableRWLock {
+    /// Creates a new reader-writer lock.
+    pub const fn new() -> MovableRWLock { MovableRWLock(imp::MovableRWLock::new()) }
+
+    /// Acquires shared access to the underlying lock, blocking the current
+    /// thread to do so.
+    #[inline]
+    pub unsafe fn read(&self) { self.0.read() }
+
+    /// Attempts to acquire shared access to this lock, returning whether it
+    /// succeeded or not.
+    #[inline]
+    pub unsafe fn try_read(&self) -> bool { self.0.try_read() }
+
+    /// Acquires write access to the underlying lock, blocking the current
+    /// thread to do so.
+    #[inline]
+    pub unsafe fn write(&self) { self.0.write() }
+
+    /// Attempts to acquire exclusive access to this lock, returning whether it
+    /// succeeded or not.
+    #[inline]
+    pub unsafe fn try_write(&self) -> bool { self.0.try_write() }
+
+    /// Unlocks previously acquired shared access to this lock.
+    #[inline]
+    pub unsafe fn read_unlock(&self) { self.0.read_unlock() }
+
+    /// Unlocks previously acquired exclusive access to this lock.
+    #[inline]
+    pub unsafe fn write_unlock(&self) { self.0.write_unlock() }
+}
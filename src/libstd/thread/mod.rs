@@ -74,6 +74,24 @@
 //! value produced by the child thread, or [`Err`] of the value given to
 //! a call to [`panic!`] if the child panicked.
 //!
+//! ## Scoped threads
+//!
+//! Threads spawned with [`spawn`] may outlive their parent, so the closures
+//! they run can't borrow data from the parent's stack. Threads spawned within
+//! a [`thread::scope`] are all joined before the scope returns, which lets
+//! them borrow anything that outlives the scope:
+//!
+//! ```rust
+//! #![feature(scoped_threads)]
+//! use std::thread;
+//!
+//! let numbers = vec![1, 2, 3];
+//! thread::scope(|s| {
+//!     s.spawn(|| println!("the sum is {}", numbers.iter().sum::<i32>()));
+//!     s.spawn(|| println!("the length is {}", numbers.len()));
+//! });
+//! ```
+//!
 //! ## Configuring threads
 //!
 //! A new thread can be configured before it is spawned via the [`Builder`] type,
@@ -143,6 +161,7 @@
 //! [`Arc`]: ../../std/sync/struct.Arc.html
 //! [`spawn`]: ../../std/thread/fn.spawn.html
 //! [`JoinHandle`]: ../../std/thread/struct.JoinHandle.html
+//! [`thread::scope`]: ../../std/thread/fn.scope.html
 //! [`JoinHandle::thread`]: ../../std/thread/struct.JoinHandle.html#method.thread
 //! [`join`]: ../../std/thread/struct.JoinHandle.html#method.join
 //! [`Result`]: ../../std/result/enum.Result.html
@@ -171,10 +190,11 @@ use cell::UnsafeCell;
 use ffi::{CStr, CString};
 use fmt;
 use io;
+use marker::PhantomData;
 use panic;
 use panicking;
 use str;
-use sync::{Mutex, Condvar, Arc};
+use sync::{Mutex, MutexGuard, Condvar, Arc};
 use sync::atomic::AtomicUsize;
 use sync::atomic::Ordering::SeqCst;
 use sys::thread as imp;
@@ -383,6 +403,82 @@ impl Builder {
     #[stable(feature = "rust1", since = "1.0.0")]
     pub fn spawn<F, T>(self, f: F) -> io::Result<JoinHandle<T>> where
         F: FnOnce() -> T, F: Send + 'static, T: Send + 'static
+    {
+        unsafe { self.spawn_unchecked(f).map(JoinHandle) }
+    }
+
+    /// Spawns a new thread within a scope, taking ownership of the `Builder`,
+    /// and returns an [`io::Result`] to its [`ScopedJoinHandle`].
+    ///
+    /// Unlike [`Builder::spawn`], the closure may borrow data that outlives
+    /// the scope, as the thread is joined before [`thread::scope`] returns.
+    /// See [`Scope::spawn`] for more details.
+    ///
+    /// # Errors
+    ///
+    /// Unlike [`Scope::spawn`], this method yields an [`io::Result`] to
+    /// capture any failure to create the thread at the OS level.
+    ///
+    /// [`io::Result`]: ../../std/io/type.Result.html
+    /// [`ScopedJoinHandle`]: ../../std/thread/struct.ScopedJoinHandle.html
+    /// [`Builder::spawn`]: ../../std/thread/struct.Builder.html#method.spawn
+    /// [`thread::scope`]: ../../std/thread/fn.scope.html
+    /// [`Scope::spawn`]: ../../std/thread/struct.Scope.html#method.spawn
+    ///
+    /// # Panics
+    ///
+    /// Panics if a thread name was set and it contained null bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(scoped_threads)]
+    /// use std::thread;
+    ///
+    /// let mut a = vec![1, 2, 3];
+    /// let mut x = 0;
+    ///
+    /// thread::scope(|s| {
+    ///     thread::Builder::new()
+    ///         .name("first".to_string())
+    ///         .spawn_scoped(s, || {
+    ///             println!("hello from the {:?} scoped thread", thread::current().name());
+    ///             // We can borrow `a` here.
+    ///             println!("{:?}", a);
+    ///         })
+    ///         .unwrap();
+    ///     thread::Builder::new()
+    ///         .name("second".to_string())
+    ///         .spawn_scoped(s, || {
+    ///             // We can even mutably borrow `x` here,
+    ///             // because no other threads are using it.
+    ///             x += a[0] + a[2];
+    ///         })
+    ///         .unwrap();
+    /// });
+    ///
+    /// // After the scope, we can modify and access our variables again:
+    /// a.push(4);
+    /// assert_eq!(x, a.len());
+    /// ```
+    #[unstable(feature = "scoped_threads", issue = "0")]
+    pub fn spawn_scoped<'scope, 'env, F, T>(self, scope: &'scope Scope<'scope, 'env>, f: F)
+                                            -> io::Result<ScopedJoinHandle<'scope, T>> where
+        F: FnOnce() -> T, F: Send + 'scope, T: Send + 'scope
+    {
+        let inner = unsafe { self.spawn_unchecked(f)? };
+        let thread = inner.thread.clone();
+        let inner = Arc::new(Mutex::new(inner));
+        scope.threads.lock().unwrap().push(inner.clone());
+        Ok(ScopedJoinHandle { inner, thread, scope: PhantomData })
+    }
+
+    /// Spawns a new thread which may borrow data of the lifetime `'a`.
+    ///
+    /// This is unsafe because the caller must make sure that the thread is
+    /// joined before `'a` ends.
+    unsafe fn spawn_unchecked<'a, F, T>(self, f: F) -> io::Result<JoinInner<T>> where
+        F: FnOnce() -> T, F: Send + 'a, T: Send + 'a
     {
         let Builder { name, stack_size } = self;
 
@@ -399,25 +495,21 @@ impl Builder {
             if let Some(name) = their_thread.cname() {
                 imp::Thread::set_name(name);
             }
-            unsafe {
-                thread_info::set(imp::guard::current(), their_thread);
-                #[cfg(feature = "backtrace")]
-                let try_result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
-                    ::sys_common::backtrace::__rust_begin_short_backtrace(f)
-                }));
-                #[cfg(not(feature = "backtrace"))]
-                let try_result = panic::catch_unwind(panic::AssertUnwindSafe(f));
-                *their_packet.get() = Some(try_result);
-            }
+            thread_info::set(imp::guard::current(), their_thread);
+            #[cfg(feature = "backtrace")]
+            let try_result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+                ::sys_common::backtrace::__rust_begin_short_backtrace(f)
+            }));
+            #[cfg(not(feature = "backtrace"))]
+            let try_result = panic::catch_unwind(panic::AssertUnwindSafe(f));
+            *their_packet.get() = Some(try_result);
         };
 
-        Ok(JoinHandle(JoinInner {
-            native: unsafe {
-                Some(imp::Thread::new(stack_size, Box::new(main))?)
-            },
+        Ok(JoinInner {
+            native: Some(imp::Thread::new(stack_size, Box::new(main))?),
             thread: my_thread,
             packet: Packet(my_packet),
-        }))
+        })
     }
 }
 
@@ -536,6 +628,93 @@ pub fn spawn<F, T>(f: F) -> JoinHandle<T> where
     Builder::new().spawn(f).unwrap()
 }
 
+/// Creates a scope for spawning scoped threads.
+///
+/// The function passed to `scope` will be provided a [`Scope`] object,
+/// through which scoped threads can be [spawned][`Scope::spawn`].
+///
+/// Unlike non-scoped threads, scoped threads can borrow non-`'static` data,
+/// as the scope guarantees all threads will be joined at the end of the scope.
+///
+/// All threads spawned within the scope that haven't been manually joined
+/// will be automatically joined before this function returns.
+///
+/// # Panics
+///
+/// If any of the automatically joined threads panicked, this function will
+/// panic. If the function passed to `scope` panics, its panic is resumed
+/// once all the threads have been joined.
+///
+/// If you want to handle panics from spawned threads,
+/// [`join`][`ScopedJoinHandle::join`] them before the end of the scope.
+///
+/// The [`Scope`] is shared by all the threads spawned in it, so they can
+/// spawn more threads in it too; those are joined before `scope` returns as
+/// well.
+///
+/// # Examples
+///
+/// ```
+/// #![feature(scoped_threads)]
+/// use std::thread;
+///
+/// let mut a = vec![1, 2, 3];
+/// let mut x = 0;
+///
+/// thread::scope(|s| {
+///     s.spawn(|| {
+///         println!("hello from the first scoped thread");
+///         // We can borrow `a` here.
+///         println!("{:?}", a);
+///     });
+///     s.spawn(|| {
+///         println!("hello from the second scoped thread");
+///         // We can even mutably borrow `x` here,
+///         // because no other threads are using it.
+///         x += a[0] + a[2];
+///     });
+///     println!("hello from the main thread");
+/// });
+///
+/// // After the scope, we can modify and access our variables again:
+/// a.push(4);
+/// assert_eq!(x, a.len());
+/// ```
+///
+/// [`Scope`]: ../../std/thread/struct.Scope.html
+/// [`Scope::spawn`]: ../../std/thread/struct.Scope.html#method.spawn
+/// [`ScopedJoinHandle::join`]: ../../std/thread/struct.ScopedJoinHandle.html#method.join
+#[unstable(feature = "scoped_threads", issue = "0")]
+pub fn scope<'env, F, T>(f: F) -> T where
+    F: for<'scope> FnOnce(&'scope Scope<'scope, 'env>) -> T
+{
+    let scope = Scope {
+        threads: Mutex::new(Vec::new()),
+        scope: PhantomData,
+        env: PhantomData,
+    };
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(|| f(&scope)));
+
+    // Join the threads which haven't been joined through their handle, even if
+    // `f` panicked, as they may still be using data borrowed for `'env`. A
+    // thread may spawn more threads in the scope until it has been joined, so
+    // keep going until there are none left.
+    let mut a_thread_panicked = false;
+    loop {
+        let thread = scope.threads.lock().unwrap_or_else(|e| e.into_inner()).pop();
+        match thread {
+            Some(thread) => a_thread_panicked |= thread.join_scoped(),
+            None => break,
+        }
+    }
+
+    match result {
+        Err(e) => panic::resume_unwind(e),
+        Ok(_) if a_thread_panicked => panic!("a scoped thread panicked"),
+        Ok(result) => result,
+    }
+}
+
 /// Gets a handle to the thread that invokes it.
 ///
 /// # Examples
@@ -1338,10 +1517,166 @@ impl<T> fmt::Debug for JoinHandle<T> {
     }
 }
 
+/// A scope to spawn scoped threads in.
+///
+/// See [`scope`] for details.
+///
+/// [`scope`]: ../../std/thread/fn.scope.html
+#[unstable(feature = "scoped_threads", issue = "0")]
+pub struct Scope<'scope, 'env: 'scope> {
+    /// The threads spawned in this scope, which are joined when it ends unless
+    /// they have been joined through their handle. Behind a lock so that the
+    /// spawned threads can spawn more threads in the scope.
+    threads: Mutex<Vec<Arc<JoinScoped + Send + Sync + 'scope>>>,
+    /// Invariance over `'scope`, so that a borrow of the scope can't be
+    /// shortened to one that ends before its threads are joined.
+    scope: PhantomData<&'scope mut &'scope ()>,
+    /// Invariance over `'env`, so that the lifetime of the data that threads
+    /// can borrow can't be shortened to one that ends within the scope.
+    env: PhantomData<&'env mut &'env ()>,
+}
+
+impl<'scope, 'env> Scope<'scope, 'env> {
+    /// Spawns a new thread within a scope, returning a [`ScopedJoinHandle`]
+    /// for it.
+    ///
+    /// Unlike non-scoped threads, threads spawned with this function may
+    /// borrow non-`'static` data from the outside the scope, including the
+    /// scope itself to spawn more threads in it. See [`scope`] for details.
+    ///
+    /// The join handle provides a [`join`] method that can be used to join
+    /// the spawned thread. If the spawned thread panics, [`join`] will return
+    /// an [`Err`] containing the panic payload.
+    ///
+    /// If the join handle is dropped, the spawned thread will be implicitly
+    /// joined at the end of the scope. In that case, if the spawned thread
+    /// panics, [`scope`] will panic after all threads are joined.
+    ///
+    /// This call will create a thread using default parameters of
+    /// [`Builder`]. If you want to specify the stack size or the name of the
+    /// thread, use [`Builder::spawn_scoped`] instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the OS fails to create a thread; use
+    /// [`Builder::spawn_scoped`] to recover from such errors.
+    ///
+    /// [`ScopedJoinHandle`]: ../../std/thread/struct.ScopedJoinHandle.html
+    /// [`join`]: ../../std/thread/struct.ScopedJoinHandle.html#method.join
+    /// [`Err`]: ../../std/result/enum.Result.html#variant.Err
+    /// [`scope`]: ../../std/thread/fn.scope.html
+    /// [`Builder`]: ../../std/thread/struct.Builder.html
+    /// [`Builder::spawn_scoped`]: ../../std/thread/struct.Builder.html#method.spawn_scoped
+    #[unstable(feature = "scoped_threads", issue = "0")]
+    pub fn spawn<F, T>(&'scope self, f: F) -> ScopedJoinHandle<'scope, T> where
+        F: FnOnce() -> T, F: Send + 'scope, T: Send + 'scope
+    {
+        Builder::new().spawn_scoped(self, f).expect("failed to spawn thread")
+    }
+}
+
+#[unstable(feature = "scoped_threads", issue = "0")]
+impl<'scope, 'env> fmt::Debug for Scope<'scope, 'env> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Scope")
+            .field("num_threads", &self.threads.lock().unwrap_or_else(|e| e.into_inner()).len())
+            .finish()
+    }
+}
+
+/// An owned permission to join on a scoped thread (block on its termination).
+///
+/// See [`Scope::spawn`] for details.
+///
+/// [`Scope::spawn`]: ../../std/thread/struct.Scope.html#method.spawn
+#[unstable(feature = "scoped_threads", issue = "0")]
+pub struct ScopedJoinHandle<'scope, T> {
+    /// Shared with the scope, which joins the thread if this handle doesn't.
+    inner: Arc<Mutex<JoinInner<T>>>,
+    thread: Thread,
+    scope: PhantomData<&'scope ()>,
+}
+
+impl<'scope, T> ScopedJoinHandle<'scope, T> {
+    /// Extracts a handle to the underlying thread.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(scoped_threads)]
+    /// use std::thread;
+    ///
+    /// thread::scope(|s| {
+    ///     let t = s.spawn(|| {
+    ///         println!("hello");
+    ///     });
+    ///     println!("thread id: {:?}", t.thread().id());
+    /// });
+    /// ```
+    #[unstable(feature = "scoped_threads", issue = "0")]
+    pub fn thread(&self) -> &Thread {
+        &self.thread
+    }
+
+    /// Waits for the associated thread to finish.
+    ///
+    /// If the associated thread panics, [`Err`] is returned with the panic
+    /// payload, and the panic isn't propagated by [`scope`].
+    ///
+    /// [`Err`]: ../../std/result/enum.Result.html#variant.Err
+    /// [`scope`]: ../../std/thread/fn.scope.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(scoped_threads)]
+    /// use std::thread;
+    ///
+    /// thread::scope(|s| {
+    ///     let t = s.spawn(|| {
+    ///         panic!("oh no");
+    ///     });
+    ///     assert!(t.join().is_err());
+    /// });
+    /// ```
+    #[unstable(feature = "scoped_threads", issue = "0")]
+    pub fn join(self) -> Result<T> {
+        lock_join_inner(&self.inner).join()
+    }
+}
+
+#[unstable(feature = "scoped_threads", issue = "0")]
+impl<'scope, T> fmt::Debug for ScopedJoinHandle<'scope, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("ScopedJoinHandle { .. }")
+    }
+}
+
+// The lock is only held while joining, which doesn't run any user code, so a
+// poisoned lock is still consistent.
+fn lock_join_inner<T>(inner: &Mutex<JoinInner<T>>) -> MutexGuard<JoinInner<T>> {
+    inner.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// A scoped thread, type-erased so that the scope can join it at its end.
+trait JoinScoped {
+    /// Joins the thread unless it has been joined through its handle, and
+    /// returns whether it panicked.
+    fn join_scoped(&self) -> bool;
+}
+
+impl<T> JoinScoped for Mutex<JoinInner<T>> {
+    fn join_scoped(&self) -> bool {
+        let mut inner = lock_join_inner(self);
+        inner.native.is_some() && inner.join().is_err()
+    }
+}
+
 fn _assert_sync_and_send() {
     fn _assert_both<T: Send + Sync>() {}
     _assert_both::<JoinHandle<()>>();
     _assert_both::<Thread>();
+    _assert_both::<Scope<'static, 'static>>();
 }
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -1595,6 +1930,76 @@ mod tests {
         assert!(thread::current().id() != spawned_id);
     }
 
+    #[test]
+    fn test_scoped_borrow() {
+        let mut data = vec![1, 2, 3];
+        let mut sum: i32 = 0;
+        thread::scope(|s| {
+            s.spawn(|| sum = data.iter().sum());
+            s.spawn(|| assert_eq!(data.len(), 3));
+        });
+        data.push(4);
+        assert_eq!(sum, 6);
+    }
+
+    #[test]
+    fn test_scoped_join() {
+        let data = [1, 2, 3];
+        let total = thread::scope(|s| {
+            let handles: Vec<_> = data.iter().map(|x| s.spawn(move || x * 2)).collect();
+            handles.into_iter().map(|h| h.join().unwrap()).sum::<i32>()
+        });
+        assert_eq!(total, 12);
+    }
+
+    #[test]
+    fn test_scoped_builder() {
+        thread::scope(|s| {
+            Builder::new().name("scoped".to_string()).spawn_scoped(s, || {
+                assert_eq!(thread::current().name(), Some("scoped"));
+            }).unwrap();
+        });
+    }
+
+    #[test]
+    fn test_scoped_joined_panic() {
+        let caught = thread::scope(|s| s.spawn(|| panic!()).join().is_err());
+        assert!(caught);
+    }
+
+    #[test]
+    fn test_scoped_nested_spawn() {
+        let data = vec![1, 2, 3];
+        let mut total = 0;
+        thread::scope(|s| {
+            let data = &data;
+            let total = &mut total;
+            s.spawn(move || {
+                s.spawn(move || *total = data.iter().sum());
+                s.spawn(move || assert_eq!(data.len(), 3));
+            });
+        });
+        assert_eq!(total, 6);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_scoped_nested_unjoined_panic() {
+        thread::scope(|s| {
+            s.spawn(move || {
+                s.spawn(|| panic!());
+            });
+        });
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_scoped_unjoined_panic() {
+        thread::scope(|s| {
+            s.spawn(|| panic!());
+        });
+    }
+
     // NOTE: the corresponding test for stderr is in run-pass/thread-stderr, due
     // to the test harness apparently interfering with stderr configuration.
 }
@@ -1003,8 +1003,10 @@ impl<'a, 'b> Folder for InvocationCollector<'a, 'b> {
                 self.cx.current_expansion.directory_ownership = orig_directory_ownership;
                 result
             }
-            // Ensure that test functions are accessible from the test harness.
-            ast::ItemKind::Fn(..) if self.cx.ecfg.should_test => {
+            // Ensure that test functions and items are accessible from the test harness.
+            ast::ItemKind::Fn(..) |
+            ast::ItemKind::Const(..) |
+            ast::ItemKind::Static(..) if self.cx.ecfg.should_test => {
                 if item.attrs.iter().any(|attr| is_test_or_bench(attr)) {
                     item = item.map(|mut item| { item.vis = ast::Visibility::Public; item });
                 }
@@ -435,6 +435,15 @@ declare_features! (
 
     // Resolve absolute paths as paths from other crates
     (active, extern_absolute_paths, "1.24.0", Some(44660)),
+
+    // Allows `#[test_case(...)]` to run a test function once per set of arguments
+    (active, test_case, "1.24.0", None),
+
+    // Allows `#[test_setup]` and `#[test_teardown]` functions around a module's tests
+    (active, test_fixtures, "1.24.0", None),
+
+    // Allows `#![test_runner]` and `#[test_item]` for custom test frameworks
+    (active, custom_test_frameworks, "1.24.0", None),
 );
 
 declare_features! (
@@ -939,6 +948,31 @@ pub const BUILTIN_ATTRIBUTES: &'static [(&'static str, AttributeType, AttributeG
                                  "allow_fail attribute is currently unstable",
                                  cfg_fn!(allow_fail))),
 
+    ("test_case", Normal, Gated(Stability::Unstable,
+                                "test_case",
+                                "parameterized tests are currently unstable",
+                                cfg_fn!(test_case))),
+
+    ("test_setup", Normal, Gated(Stability::Unstable,
+                                 "test_fixtures",
+                                 "test fixtures are currently unstable",
+                                 cfg_fn!(test_fixtures))),
+
+    ("test_teardown", Normal, Gated(Stability::Unstable,
+                                    "test_fixtures",
+                                    "test fixtures are currently unstable",
+                                    cfg_fn!(test_fixtures))),
+
+    ("test_runner", CrateLevel, Gated(Stability::Unstable,
+                                      "custom_test_frameworks",
+                                      "custom test runners are currently unstable",
+                                      cfg_fn!(custom_test_frameworks))),
+
+    ("test_item", Normal, Gated(Stability::Unstable,
+                                "custom_test_frameworks",
+                                "custom test items are currently unstable",
+                                cfg_fn!(custom_test_frameworks))),
+
     ("rustc_std_internal_symbol", Whitelisted, Gated(Stability::Unstable,
                                      "rustc_attrs",
                                      "this is an internal attribute that will \
@@ -1331,6 +1365,12 @@ impl<'a> Visitor<'a> for PostExpansionVisitor<'a> {
             return
         }
 
+        // The arguments of a test case or test runner are expressions and paths
+        // rather than meta items.
+        if attr.check_name("test_case") || attr.check_name("test_runner") {
+            return
+        }
+
         let meta = panictry!(attr.parse_meta(self.context.parse_sess));
         if contains_novel_literal(&meta) {
             gate_feature_post!(&self, attr_literals, attr.span,
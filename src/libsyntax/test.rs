@@ -35,12 +35,14 @@ use fold::Folder;
 use util::move_map::MoveMap;
 use fold;
 use parse::{token, ParseSess};
+use parse::parser::PathStyle;
 use print::pprust;
 use ast::{self, Ident};
 use ptr::P;
 use symbol::{self, Symbol, keywords};
 use util::small_vector::SmallVector;
 
+#[derive(Clone, Copy)]
 enum ShouldPanic {
     No,
     Yes(Option<Symbol>),
@@ -49,10 +51,21 @@ enum ShouldPanic {
 struct Test {
     span: Span,
     path: Vec<Ident> ,
+    name: String,
     bench: bool,
     ignore: bool,
     should_panic: ShouldPanic,
     allow_fail: bool,
+    // paths to the `#[test_setup]` and `#[test_teardown]` functions of the test's module
+    setup: Option<Vec<Ident>>,
+    teardown: Option<Vec<Ident>>,
+}
+
+// The `#[test_setup]` and `#[test_teardown]` functions declared in a module.
+#[derive(Clone, Default)]
+struct Fixtures {
+    setup: Option<Ident>,
+    teardown: Option<Ident>,
 }
 
 struct TestCtxt<'a> {
@@ -60,7 +73,11 @@ struct TestCtxt<'a> {
     path: Vec<Ident>,
     ext_cx: ExtCtxt<'a>,
     testfns: Vec<Test>,
+    // paths to the `#[test_item]`s of the crate
+    test_items: Vec<Vec<Ident>>,
     reexport_test_harness_main: Option<Symbol>,
+    // the function named by `#![test_runner(...)]`
+    test_runner: Option<ast::Path>,
     is_libtest: bool,
     ctxt: SyntaxContext,
 
@@ -83,19 +100,138 @@ pub fn modify_for_testing(sess: &ParseSess,
         attr::first_attr_value_str_by_name(&krate.attrs,
                                            "reexport_test_harness_main");
 
+    // Likewise for #![test_runner(path::to::runner)], which replaces the
+    // default console test runner.
+    let test_runner = attr::find_by_name(&krate.attrs, "test_runner").cloned();
+
     if should_test {
-        generate_test_harness(sess, resolver, reexport_test_harness_main, krate, span_diagnostic)
+        let test_runner = test_runner.and_then(|attr| {
+            parse_test_runner(sess, span_diagnostic, &attr)
+        });
+        generate_test_harness(sess, resolver, reexport_test_harness_main, test_runner, krate,
+                              span_diagnostic)
     } else {
         krate
     }
 }
 
+fn parse_test_runner(sess: &ParseSess, sd: &errors::Handler, attr: &ast::Attribute)
+                     -> Option<ast::Path> {
+    match attr.parse_list(sess, |parser| parser.parse_path(PathStyle::Mod)) {
+        Ok(mut paths) => {
+            if paths.len() == 1 {
+                paths.pop()
+            } else {
+                sd.span_err(attr.span, "expected a single path to the test runner, \
+                                        e.g. `#![test_runner(my_framework::run_tests)]`");
+                None
+            }
+        }
+        Err(mut e) => {
+            e.emit();
+            None
+        }
+    }
+}
+
 struct TestHarnessGenerator<'a> {
     cx: TestCtxt<'a>,
     tests: Vec<Ident>,
 
     // submodule name, gensym'd identifier for re-exports
     tested_submods: Vec<(Ident, Ident)>,
+
+    // the module currently being folded and its fixtures
+    mod_id: ast::NodeId,
+    fixtures: Fixtures,
+}
+
+impl<'a> TestHarnessGenerator<'a> {
+    // Paths to the fixtures of the current module, for a test whose path is `self.cx.path`.
+    fn fixture_paths(&self) -> (Option<Vec<Ident>>, Option<Vec<Ident>>) {
+        let mod_path = &self.cx.path[..self.cx.path.len() - 1];
+        let in_mod = |ident: Ident| {
+            let mut path = mod_path.to_vec();
+            path.push(ident);
+            path
+        };
+        (self.fixtures.setup.map(&in_mod), self.fixtures.teardown.map(&in_mod))
+    }
+
+    // Expands a `#[test_case(...)]` function into one test per case. Each case gets a
+    // wrapper function next to the parameterized one that calls it with the case's
+    // arguments; the wrappers are returned so that they can be added to the module.
+    fn mk_test_cases(&mut self, i: &ast::Item) -> Vec<P<ast::Item>> {
+        let inputs = match i.node {
+            ast::ItemKind::Fn(ref decl, _, _, _, _, _) => decl.inputs.len(),
+            _ => return Vec::new(),
+        };
+        let mod_path = self.cx.path[..self.cx.path.len() - 1].to_vec();
+        let fn_name = path_name_i(&self.cx.path);
+        let (setup, teardown) = self.fixture_paths();
+        let ignore = is_ignored(i);
+        let should_panic = should_panic(i, &self.cx);
+        let allow_fail = is_allowed_fail(i);
+        let sess = self.cx.ext_cx.parse_sess;
+
+        let mut wrappers = Vec::new();
+        let cases = i.attrs.iter().filter(|attr| attr.check_name("test_case"));
+        for (idx, attr) in cases.enumerate() {
+            let args = match attr.parse_list(sess, |parser| parser.parse_expr()) {
+                Ok(args) => args,
+                Err(mut e) => {
+                    e.emit();
+                    continue;
+                }
+            };
+            if args.len() != inputs {
+                self.cx.span_diagnostic.span_err(
+                    attr.span,
+                    &format!("this test case passes {} argument{}, but `{}` takes {}",
+                             args.len(),
+                             if args.len() == 1 { "" } else { "s" },
+                             i.ident,
+                             inputs));
+                continue;
+            }
+
+            // fn __test_case_$fn_$idx() { $fn($args) }
+            let sp = ignored_span(&self.cx, attr.span);
+            let wrapper_ident = Ident::with_empty_ctxt(
+                Symbol::gensym(&format!("__test_case_{}_{}", i.ident, idx)));
+            let wrapper = {
+                let ecx = &self.cx.ext_cx;
+                let call = ecx.expr_call_ident(sp, i.ident, args);
+                ecx.item_fn(sp,
+                            wrapper_ident,
+                            vec![],
+                            ecx.ty(sp, ast::TyKind::Tup(vec![])),
+                            ecx.block(sp, vec![ecx.stmt_expr(call)]))
+            };
+            // public, like the test functions themselves, so that it can be re-exported
+            let wrapper = wrapper.map(|mut item| {
+                item.vis = ast::Visibility::Public;
+                item
+            });
+            wrappers.push(expand_synthesized_item(&mut self.cx, self.mod_id, wrapper));
+
+            let mut path = mod_path.clone();
+            path.push(wrapper_ident);
+            self.cx.testfns.push(Test {
+                span: i.span,
+                path,
+                name: format!("{}::case_{}", fn_name, idx + 1),
+                bench: false,
+                ignore,
+                should_panic,
+                allow_fail,
+                setup: setup.clone(),
+                teardown: teardown.clone(),
+            });
+            self.tests.push(wrapper_ident);
+        }
+        wrappers
+    }
 }
 
 impl<'a> fold::Folder for TestHarnessGenerator<'a> {
@@ -119,6 +255,7 @@ impl<'a> fold::Folder for TestHarnessGenerator<'a> {
         }
         debug!("current path: {}", path_name_i(&self.cx.path));
 
+        let mut test_cases = Vec::new();
         if is_test_fn(&self.cx, &i) || is_bench_fn(&self.cx, &i) {
             match i.node {
                 ast::ItemKind::Fn(_, ast::Unsafety::Unsafe, _, _, _, _) => {
@@ -127,18 +264,34 @@ impl<'a> fold::Folder for TestHarnessGenerator<'a> {
                 }
                 _ => {
                     debug!("this is a test function");
+                    let bench = is_bench_fn(&self.cx, &i);
+                    let (setup, teardown) = if bench {
+                        (None, None)
+                    } else {
+                        self.fixture_paths()
+                    };
                     let test = Test {
                         span: i.span,
                         path: self.cx.path.clone(),
-                        bench: is_bench_fn(&self.cx, &i),
+                        name: path_name_i(&self.cx.path),
+                        bench,
                         ignore: is_ignored(&i),
                         should_panic: should_panic(&i, &self.cx),
                         allow_fail: is_allowed_fail(&i),
+                        setup,
+                        teardown,
                     };
                     self.cx.testfns.push(test);
                     self.tests.push(i.ident);
                 }
             }
+        } else if is_parameterized_test_fn(&self.cx, &i) {
+            debug!("this is a parameterized test function");
+            test_cases = self.mk_test_cases(&i);
+        } else if is_test_item(&self.cx, &i) {
+            debug!("this is a test item");
+            self.cx.test_items.push(self.cx.path.clone());
+            self.tests.push(i.ident);
         }
 
         let mut item = i.into_inner();
@@ -147,9 +300,19 @@ impl<'a> fold::Folder for TestHarnessGenerator<'a> {
         if let ast::ItemKind::Mod(module) = item.node {
             let tests = mem::replace(&mut self.tests, Vec::new());
             let tested_submods = mem::replace(&mut self.tested_submods, Vec::new());
+            let fixtures = mem::replace(&mut self.fixtures, find_fixtures(&self.cx, &module));
+            let mod_id = mem::replace(&mut self.mod_id, item.id);
             let mut mod_folded = fold::noop_fold_mod(module, self);
-            let tests = mem::replace(&mut self.tests, tests);
+            let mut tests = mem::replace(&mut self.tests, tests);
             let tested_submods = mem::replace(&mut self.tested_submods, tested_submods);
+            let mod_fixtures = mem::replace(&mut self.fixtures, fixtures);
+            self.mod_id = mod_id;
+
+            // The fixtures are called through the same re-exports as the tests.
+            if !tests.is_empty() {
+                tests.extend(mod_fixtures.setup);
+                tests.extend(mod_fixtures.teardown);
+            }
 
             if !tests.is_empty() || !tested_submods.is_empty() {
                 let (it, sym) = mk_reexport_mod(&mut self.cx, item.id, tests, tested_submods);
@@ -167,7 +330,9 @@ impl<'a> fold::Folder for TestHarnessGenerator<'a> {
         if ident.name != keywords::Invalid.name() {
             self.cx.path.pop();
         }
-        SmallVector::one(P(item))
+        let mut items = SmallVector::one(P(item));
+        items.extend(test_cases);
+        items
     }
 
     fn fold_mac(&mut self, mac: ast::Mac) -> ast::Mac { mac }
@@ -246,9 +411,7 @@ fn mk_reexport_mod(cx: &mut TestCtxt,
     };
 
     let sym = Ident::with_empty_ctxt(Symbol::gensym("__test_reexports"));
-    let parent = if parent == ast::DUMMY_NODE_ID { ast::CRATE_NODE_ID } else { parent };
-    cx.ext_cx.current_expansion.mark = cx.ext_cx.resolver.get_module_scope(parent);
-    let it = cx.ext_cx.monotonic_expander().fold_item(P(ast::Item {
+    let it = expand_synthesized_item(cx, parent, P(ast::Item {
         ident: sym,
         attrs: Vec::new(),
         id: ast::DUMMY_NODE_ID,
@@ -256,14 +419,24 @@ fn mk_reexport_mod(cx: &mut TestCtxt,
         vis: ast::Visibility::Public,
         span: DUMMY_SP,
         tokens: None,
-    })).pop().unwrap();
+    }));
 
     (it, sym)
 }
 
+// Assigns node ids to an item synthesized by the harness and registers it with the
+// resolver, as if it had been expanded from a macro invoked in the module `parent`.
+fn expand_synthesized_item(cx: &mut TestCtxt, parent: ast::NodeId, item: P<ast::Item>)
+                           -> P<ast::Item> {
+    let parent = if parent == ast::DUMMY_NODE_ID { ast::CRATE_NODE_ID } else { parent };
+    cx.ext_cx.current_expansion.mark = cx.ext_cx.resolver.get_module_scope(parent);
+    cx.ext_cx.monotonic_expander().fold_item(item).pop().unwrap()
+}
+
 fn generate_test_harness(sess: &ParseSess,
                          resolver: &mut Resolver,
                          reexport_test_harness_main: Option<Symbol>,
+                         test_runner: Option<ast::Path>,
                          krate: ast::Crate,
                          sd: &errors::Handler) -> ast::Crate {
     // Remove the entry points
@@ -277,7 +450,9 @@ fn generate_test_harness(sess: &ParseSess,
         ext_cx: ExtCtxt::new(sess, ExpansionConfig::default("test".to_string()), resolver),
         path: Vec::new(),
         testfns: Vec::new(),
+        test_items: Vec::new(),
         reexport_test_harness_main,
+        test_runner,
         // NB: doesn't consider the value of `--crate-name` passed on the command line.
         is_libtest: attr::find_crate_name(&krate.attrs).map(|s| s == "test").unwrap_or(false),
         toplevel_reexport: None,
@@ -298,6 +473,8 @@ fn generate_test_harness(sess: &ParseSess,
         cx,
         tests: Vec::new(),
         tested_submods: Vec::new(),
+        mod_id: ast::CRATE_NODE_ID,
+        fixtures: Fixtures::default(),
     }.fold_crate(krate)
 }
 
@@ -315,28 +492,30 @@ enum HasTestSignature {
     NotEvenAFunction,
 }
 
-fn is_test_fn(cx: &TestCtxt, i: &ast::Item) -> bool {
-    let has_test_attr = attr::contains_name(&i.attrs, "test");
-
-    fn has_test_signature(i: &ast::Item) -> HasTestSignature {
-        match i.node {
-          ast::ItemKind::Fn(ref decl, _, _, _, ref generics, _) => {
-            let no_output = match decl.output {
-                ast::FunctionRetTy::Default(..) => true,
-                ast::FunctionRetTy::Ty(ref t) if t.node == ast::TyKind::Tup(vec![]) => true,
-                _ => false
-            };
-            if decl.inputs.is_empty()
-                   && no_output
-                   && !generics.is_parameterized() {
-                Yes
-            } else {
-                No
-            }
-          }
-          _ => NotEvenAFunction,
+fn has_test_signature(i: &ast::Item) -> HasTestSignature {
+    match i.node {
+      ast::ItemKind::Fn(ref decl, _, _, _, ref generics, _) => {
+        let no_output = match decl.output {
+            ast::FunctionRetTy::Default(..) => true,
+            ast::FunctionRetTy::Ty(ref t) if t.node == ast::TyKind::Tup(vec![]) => true,
+            _ => false
+        };
+        if decl.inputs.is_empty()
+               && no_output
+               && !generics.is_parameterized() {
+            Yes
+        } else {
+            No
         }
+      }
+      _ => NotEvenAFunction,
     }
+}
+
+fn is_test_fn(cx: &TestCtxt, i: &ast::Item) -> bool {
+    // `#[test]` is allowed alongside `#[test_case]`, see `is_parameterized_test_fn`
+    let has_test_attr = attr::contains_name(&i.attrs, "test") &&
+        !attr::contains_name(&i.attrs, "test_case");
 
     if has_test_attr {
         let diag = cx.span_diagnostic;
@@ -382,6 +561,95 @@ fn is_bench_fn(cx: &TestCtxt, i: &ast::Item) -> bool {
     has_bench_attr && has_test_signature(i)
 }
 
+fn is_parameterized_test_fn(cx: &TestCtxt, i: &ast::Item) -> bool {
+    if !attr::contains_name(&i.attrs, "test_case") {
+        return false;
+    }
+
+    let diag = cx.span_diagnostic;
+    match i.node {
+        ast::ItemKind::Fn(_, ast::Unsafety::Unsafe, _, _, _, _) => {
+            panic!(diag.span_fatal(i.span, "unsafe functions cannot be used for tests"));
+        }
+        ast::ItemKind::Fn(ref decl, _, _, _, ref generics, _) => {
+            let no_output = match decl.output {
+                ast::FunctionRetTy::Default(..) => true,
+                ast::FunctionRetTy::Ty(ref t) if t.node == ast::TyKind::Tup(vec![]) => true,
+                _ => false
+            };
+            if no_output && !generics.is_parameterized() {
+                true
+            } else {
+                diag.span_err(i.span, "functions used as parameterized tests must not be \
+                                       generic and must return `()`");
+                false
+            }
+        }
+        _ => {
+            diag.span_err(i.span, "only functions may be used as parameterized tests");
+            false
+        }
+    }
+}
+
+// Finds the `#[test_setup]` and `#[test_teardown]` functions among the items of a module.
+fn find_fixtures(cx: &TestCtxt, module: &ast::Mod) -> Fixtures {
+    let mut fixtures = Fixtures::default();
+    for item in &module.items {
+        if let Some(setup) = check_fixture_fn(cx, item, "test_setup", fixtures.setup) {
+            fixtures.setup = Some(setup);
+        }
+        if let Some(teardown) = check_fixture_fn(cx, item, "test_teardown", fixtures.teardown) {
+            fixtures.teardown = Some(teardown);
+        }
+    }
+    fixtures
+}
+
+fn check_fixture_fn(cx: &TestCtxt, i: &ast::Item, attr_name: &str, previous: Option<Ident>)
+                    -> Option<Ident> {
+    if !attr::contains_name(&i.attrs, attr_name) {
+        return None;
+    }
+
+    let diag = cx.span_diagnostic;
+    match has_test_signature(i) {
+        Yes => {}
+        No => {
+            diag.span_err(i.span, &format!("functions used as `#[{}]` must have signature \
+                                            fn() -> ()", attr_name));
+            return None;
+        }
+        NotEvenAFunction => {
+            diag.span_err(i.span, &format!("only functions may be used as `#[{}]`", attr_name));
+            return None;
+        }
+    }
+    if previous.is_some() {
+        diag.span_err(i.span, &format!("a module may have at most one `#[{}]` function",
+                                       attr_name));
+        return None;
+    }
+    Some(i.ident)
+}
+
+// Items registered with `#[test_item]`, typically by a procedural macro implementing
+// a custom test attribute, are passed to the test runner along with the `#[test]`s.
+fn is_test_item(cx: &TestCtxt, i: &ast::Item) -> bool {
+    if !attr::contains_name(&i.attrs, "test_item") {
+        return false;
+    }
+
+    match i.node {
+        ast::ItemKind::Const(..) | ast::ItemKind::Static(..) => true,
+        _ => {
+            cx.span_diagnostic.span_err(i.span, "only constants and statics may be used \
+                                                 as `#[test_item]`s");
+            false
+        }
+    }
+}
+
 fn is_ignored(i: &ast::Item) -> bool {
     i.attrs.iter().any(|attr| attr.check_name("ignore"))
 }
@@ -440,7 +708,12 @@ We're going to be building a module that looks more or less like:
 mod __test {
   extern crate test (name = "test", vers = "...");
   fn main() {
-    test::test_main_static(&::os::args()[], tests, test::Options::new())
+    test::test_main_static(&[&TESTS[0], &TESTS[1], ..., &::path::to::test_item])
+  }
+
+  // one for each test in a module with a #[test_setup] or #[test_teardown] function
+  fn __test_fixtures_N() {
+    test::run_with_fixtures(::path::to::setup, ::path::to::test, test::no_fixture)
   }
 
   static tests : &'static [test::TestDescAndFn] = &[
@@ -448,6 +721,11 @@ mod __test {
   ];
 }
 
+With `#![test_runner(path::to::runner)]`, main calls `::path::to::runner`
+instead of `test::test_main_static`. If the crate then has no #[test] or
+#[bench] functions, neither the test crate nor TESTS are needed, which lets
+`#![no_std]` crates use their own test harness.
+
 */
 
 fn mk_std(cx: &TestCtxt) -> P<ast::Item> {
@@ -478,22 +756,39 @@ fn mk_main(cx: &mut TestCtxt) -> P<ast::Item> {
     // Writing this out by hand with 'ignored_span':
     //        pub fn main() {
     //            #![main]
-    //            use std::slice::AsSlice;
-    //            test::test_main_static(::std::os::args().as_slice(), TESTS, test::Options::new());
+    //            test::test_main_static(&[&TESTS[0], ..., &::path::to::test_item]);
     //        }
 
     let sp = ignored_span(cx, DUMMY_SP);
     let ecx = &cx.ext_cx;
 
-    // test::test_main_static
-    let test_main_path =
-        ecx.path(sp, vec![Ident::from_str("test"), Ident::from_str("test_main_static")]);
+    // test::test_main_static, or the path given to #![test_runner], which is
+    // relative to the crate root
+    let test_main_path = match cx.test_runner {
+        Some(ref path) => {
+            let idents = path.segments.iter()
+                                      .map(|segment| segment.identifier)
+                                      .filter(|ident| ident.name != keywords::CrateRoot.name())
+                                      .collect();
+            ecx.path_global(sp, idents)
+        }
+        None => ecx.path(sp, vec![Ident::from_str("test"), Ident::from_str("test_main_static")]),
+    };
+
+    // &[&TESTS[0], ..., &::path::to::test_item]
+    let test_refs = (0..cx.testfns.len()).map(|idx| {
+        let tests_ident_expr = ecx.expr_ident(sp, Ident::from_str("TESTS"));
+        let index = ast::ExprKind::Index(tests_ident_expr, ecx.expr_usize(sp, idx));
+        ecx.expr_addr_of(sp, ecx.expr(sp, index))
+    }).chain(cx.test_items.iter().map(|path| {
+        ecx.expr_addr_of(sp, ecx.expr_path(ecx.path_global(sp, visible_path(cx, path))))
+    })).collect();
+    let tests_expr = ecx.expr_addr_of(sp, ecx.expr_vec(sp, test_refs));
 
     // test::test_main_static(...)
     let test_main_path_expr = ecx.expr_path(test_main_path);
-    let tests_ident_expr = ecx.expr_ident(sp, Ident::from_str("TESTS"));
     let call_test_main = ecx.expr_call(sp, test_main_path_expr,
-                                       vec![tests_ident_expr]);
+                                       vec![tests_expr]);
     let call_test_main = ecx.stmt_expr(call_test_main);
     // #![main]
     let main_meta = ecx.meta_word(sp, Symbol::intern("main"));
@@ -517,19 +812,28 @@ fn mk_main(cx: &mut TestCtxt) -> P<ast::Item> {
 }
 
 fn mk_test_module(cx: &mut TestCtxt) -> (P<ast::Item>, Option<P<ast::Item>>) {
-    // Link to test crate
-    let import = mk_std(cx);
+    let mut items = vec![];
+
+    // Link to test crate, which a custom test runner may do without
+    let has_testfns = !cx.testfns.is_empty();
+    if has_testfns || cx.test_runner.is_none() {
+        items.push(mk_std(cx));
+    }
 
     // A constant vector of test descriptors.
-    let tests = mk_tests(cx);
+    if has_testfns {
+        items.push(mk_tests(cx));
+    }
 
     // The synthesized main function which will call the console test runner
     // with our list of tests
-    let mainfn = mk_main(cx);
+    items.push(mk_main(cx));
+
+    items.extend(mk_fixture_wrappers(cx));
 
     let testmod = ast::Mod {
         inner: DUMMY_SP,
-        items: vec![import, mainfn, tests],
+        items,
     };
     let item_ = ast::ItemKind::Mod(testmod);
     let mod_ident = Ident::with_empty_ctxt(Symbol::gensym("__test"));
@@ -586,6 +890,54 @@ fn path_name_i(idents: &[Ident]) -> String {
     idents.iter().map(|i| i.to_string()).collect::<Vec<String>>().join("::")
 }
 
+// Path through the top-level re-export module to a test or fixture at `path`.
+fn visible_path(cx: &TestCtxt, path: &[Ident]) -> Vec<Ident> {
+    let mut visible_path = match cx.toplevel_reexport {
+        Some(id) => vec![id],
+        None => {
+            let diag = cx.span_diagnostic;
+            diag.bug("expected to find top-level re-export name, but found None");
+        }
+    };
+    visible_path.extend(path.iter().cloned());
+    visible_path
+}
+
+fn fixture_wrapper_ident(idx: usize) -> Ident {
+    Ident::from_str(&format!("__test_fixtures_{}", idx))
+}
+
+fn mk_fixture_wrappers(cx: &TestCtxt) -> Vec<P<ast::Item>> {
+    let ecx = &cx.ext_cx;
+    cx.testfns.iter().enumerate().filter(|&(_, test)| {
+        test.setup.is_some() || test.teardown.is_some()
+    }).map(|(idx, test)| {
+        let sp = ignored_span(cx, test.span);
+        let fixture_expr = |path: &Option<Vec<Ident>>| match *path {
+            Some(ref path) => ecx.expr_path(ecx.path_global(sp, visible_path(cx, path))),
+            None => ecx.expr_path(ecx.path(sp, vec![ecx.ident_of("self"),
+                                                    ecx.ident_of("test"),
+                                                    ecx.ident_of("no_fixture")])),
+        };
+        let test_expr = ecx.expr_path(ecx.path_global(sp, visible_path(cx, &test.path)));
+
+        // self::test::run_with_fixtures($setup, $test, $teardown)
+        let run_path = ecx.path(sp, vec![ecx.ident_of("self"),
+                                         ecx.ident_of("test"),
+                                         ecx.ident_of("run_with_fixtures")]);
+        let call = ecx.expr_call(sp,
+                                 ecx.expr_path(run_path),
+                                 vec![fixture_expr(&test.setup),
+                                      test_expr,
+                                      fixture_expr(&test.teardown)]);
+        ecx.item_fn(sp,
+                    fixture_wrapper_ident(idx),
+                    vec![],
+                    ecx.ty(sp, ast::TyKind::Tup(vec![])),
+                    ecx.block(sp, vec![ecx.stmt_expr(call)]))
+    }).collect()
+}
+
 fn mk_tests(cx: &TestCtxt) -> P<ast::Item> {
     // The vector of test_descs for this crate
     let test_descs = mk_test_descs(cx);
@@ -619,8 +971,8 @@ fn mk_test_descs(cx: &TestCtxt) -> P<ast::Expr> {
         node: ast::ExprKind::AddrOf(ast::Mutability::Immutable,
             P(ast::Expr {
                 id: ast::DUMMY_NODE_ID,
-                node: ast::ExprKind::Array(cx.testfns.iter().map(|test| {
-                    mk_test_desc_and_fn_rec(cx, test)
+                node: ast::ExprKind::Array(cx.testfns.iter().enumerate().map(|(idx, test)| {
+                    mk_test_desc_and_fn_rec(cx, test, idx)
                 }).collect()),
                 span: DUMMY_SP,
                 attrs: ast::ThinVec::new(),
@@ -630,7 +982,7 @@ fn mk_test_descs(cx: &TestCtxt) -> P<ast::Expr> {
     })
 }
 
-fn mk_test_desc_and_fn_rec(cx: &TestCtxt, test: &Test) -> P<ast::Expr> {
+fn mk_test_desc_and_fn_rec(cx: &TestCtxt, test: &Test, idx: usize) -> P<ast::Expr> {
     // FIXME #15962: should be using quote_expr, but that stringifies
     // __test_reexports, causing it to be reinterned, losing the
     // gensym information.
@@ -648,11 +1000,10 @@ fn mk_test_desc_and_fn_rec(cx: &TestCtxt, test: &Test) -> P<ast::Expr> {
     // creates $name: $expr
     let field = |name, expr| ecx.field_imm(span, ecx.ident_of(name), expr);
 
-    debug!("encoding {}", path_name_i(&path[..]));
+    debug!("encoding {}", test.name);
 
-    // path to the #[test] function: "foo::bar::baz"
-    let path_string = path_name_i(&path[..]);
-    let name_expr = ecx.expr_str(span, Symbol::intern(&path_string));
+    // path to the #[test] function: "foo::bar::baz", plus "::case_N" for test cases
+    let name_expr = ecx.expr_str(span, Symbol::intern(&test.name));
 
     // self::test::StaticTestName($name_expr)
     let name_expr = ecx.expr_call(span,
@@ -688,16 +1039,12 @@ fn mk_test_desc_and_fn_rec(cx: &TestCtxt, test: &Test) -> P<ast::Expr> {
              field("allow_fail", allow_fail_expr)]);
 
 
-    let mut visible_path = match cx.toplevel_reexport {
-        Some(id) => vec![id],
-        None => {
-            let diag = cx.span_diagnostic;
-            diag.bug("expected to find top-level re-export name, but found None");
-        }
+    let fn_expr = if test.setup.is_some() || test.teardown.is_some() {
+        // the wrapper from `mk_fixture_wrappers`, a sibling of the test list
+        ecx.expr_ident(span, fixture_wrapper_ident(idx))
+    } else {
+        ecx.expr_path(ecx.path_global(span, visible_path(cx, &path)))
     };
-    visible_path.extend(path);
-
-    let fn_expr = ecx.expr_path(ecx.path_global(span, visible_path));
 
     let variant_name = if test.bench { "StaticBenchFn" } else { "StaticTestFn" };
     // self::test::$variant_name($fn_expr)
@@ -54,13 +54,13 @@ use self::TestEvent::*;
 use self::NamePadding::*;
 use self::OutputLocation::*;
 
-use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
 use std::any::Any;
 use std::cmp;
 use std::collections::BTreeMap;
 use std::env;
 use std::fmt;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::prelude::*;
 use std::io;
 use std::iter::repeat;
@@ -78,7 +78,7 @@ pub mod test {
     pub use {Bencher, TestName, TestResult, TestDesc, TestDescAndFn, TestOpts, TrFailed,
              TrFailedMsg, TrIgnored, TrOk, Metric, MetricMap, StaticTestFn, StaticTestName,
              DynTestName, DynTestFn, run_test, test_main, test_main_static, filter_tests,
-             parse_opts, StaticBenchFn, ShouldPanic, Options};
+             parse_opts, StaticBenchFn, ShouldPanic, Options, run_with_fixtures, no_fixture};
 }
 
 pub mod stats;
@@ -185,7 +185,9 @@ impl fmt::Debug for TestFn {
 pub struct Bencher {
     mode: BenchMode,
     summary: Option<stats::Summary>,
+    samples: Vec<f64>,
     pub bytes: u64,
+    pub elements: u64,
 }
 
 #[derive(Clone, PartialEq, Eq)]
@@ -287,30 +289,46 @@ pub fn test_main(args: &[String], tests: Vec<TestDescAndFn>, options: Options) {
 // a Vec<TestDescAndFn> is used in order to effect ownership-transfer
 // semantics into parallel test runners, which in turn requires a Vec<>
 // rather than a &[].
+#[cfg(stage0)]
 pub fn test_main_static(tests: &[TestDescAndFn]) {
     let args = env::args().collect::<Vec<_>>();
-    let owned_tests = tests.iter()
-                           .map(|t| {
-                               match t.testfn {
-                                   StaticTestFn(f) => {
-                                       TestDescAndFn {
-                                           testfn: StaticTestFn(f),
-                                           desc: t.desc.clone(),
-                                       }
-                                   }
-                                   StaticBenchFn(f) => {
-                                       TestDescAndFn {
-                                           testfn: StaticBenchFn(f),
-                                           desc: t.desc.clone(),
-                                       }
-                                   }
-                                   _ => panic!("non-static tests passed to test::test_main_static"),
-                               }
-                           })
-                           .collect();
+    let owned_tests = tests.iter().map(make_owned_test).collect();
     test_main(&args, owned_tests, Options::new())
 }
 
+/// The default test runner of the test harness, see `test_main`. This is what the
+/// generated `main` calls with the `#[test]` and `#[bench]` functions and the
+/// `#[test_item]`s of a crate, unless a different runner is chosen with the
+/// `#![test_runner]` crate attribute.
+///
+/// This will panic (intentionally) when fed any dynamic tests, see `make_owned_test`.
+#[cfg(not(stage0))]
+pub fn test_main_static(tests: &[&TestDescAndFn]) {
+    let args = env::args().collect::<Vec<_>>();
+    let owned_tests = tests.iter().map(|t| make_owned_test(*t)).collect();
+    test_main(&args, owned_tests, Options::new())
+}
+
+// Copies a static test out of the test harness' static test list. Dynamic tests
+// cannot be copied, which is why they cannot be passed to `test_main_static`.
+fn make_owned_test(test: &TestDescAndFn) -> TestDescAndFn {
+    match test.testfn {
+        StaticTestFn(f) => {
+            TestDescAndFn {
+                testfn: StaticTestFn(f),
+                desc: test.desc.clone(),
+            }
+        }
+        StaticBenchFn(f) => {
+            TestDescAndFn {
+                testfn: StaticBenchFn(f),
+                desc: test.desc.clone(),
+            }
+        }
+        _ => panic!("non-static tests passed to test::test_main_static"),
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
 pub enum ColorConfig {
     AutoColor,
@@ -332,6 +350,8 @@ pub struct TestOpts {
     pub quiet: bool,
     pub test_threads: Option<usize>,
     pub skip: Vec<String>,
+    pub save_baseline: Option<String>,
+    pub baseline: Option<String>,
     pub options: Options,
 }
 
@@ -351,6 +371,8 @@ impl TestOpts {
             quiet: false,
             test_threads: None,
             skip: vec![],
+            save_baseline: None,
+            baseline: None,
             options: Options::new(),
         }
     }
@@ -376,6 +398,10 @@ fn optgroups() -> getopts::Options {
                                be used multiple times)","FILTER")
         .optflag("q", "quiet", "Display one character per test instead of one line")
         .optflag("", "exact", "Exactly match filters rather than by substring")
+        .optopt("", "save-baseline", "Save the samples of this benchmark run under NAME \
+                                      for later comparison", "NAME")
+        .optopt("", "baseline", "Compare benchmark results against the samples saved \
+                                 under NAME", "NAME")
         .optopt("", "color", "Configure coloring of output:
             auto   = colorize if stdout is a tty and tests are run on serially (default);
             always = always colorize output;
@@ -411,7 +437,22 @@ Test Attributes:
     #[ignore]      - When applied to a function which is already attributed as a
                      test, then the test runner will ignore these tests during
                      normal test runs. Running with --ignored will run these
-                     tests."#,
+                     tests.
+    #[test_case(args..)] - Runs the function it is applied to once with each
+                     set of arguments given by a #[test_case] attribute, as
+                     tests named after the function followed by ::case_N.
+    #[test_setup]  - Indicates a function that is run before each test in the
+                     module it is declared in. Takes no arguments.
+    #[test_teardown] - Indicates a function that is run after each test in the
+                     module it is declared in, even if the test failed.
+
+Benchmark baselines:
+
+    --save-baseline NAME stores the samples of every benchmark that is run in
+    NAME.baseline and --baseline NAME compares a run against them, reporting the
+    change of the median and flagging changes that are statistically significant.
+    Baselines live in the directory named by RUST_BENCH_BASELINE_DIR, or in
+    target/bench-baselines if it is not set."#,
              usage = options.usage(&message));
 }
 
@@ -468,6 +509,15 @@ pub fn parse_opts(args: &[String]) -> Option<OptRes> {
             None,
     };
 
+    let save_baseline = match parse_baseline_name(&matches, "save-baseline") {
+        Ok(name) => name,
+        Err(e) => return Some(Err(e)),
+    };
+    let baseline = match parse_baseline_name(&matches, "baseline") {
+        Ok(name) => name,
+        Err(e) => return Some(Err(e)),
+    };
+
     let color = match matches.opt_str("color").as_ref().map(|s| &**s) {
         Some("auto") | None => AutoColor,
         Some("always") => AlwaysColor,
@@ -493,16 +543,38 @@ pub fn parse_opts(args: &[String]) -> Option<OptRes> {
         quiet,
         test_threads,
         skip: matches.opt_strs("skip"),
+        save_baseline,
+        baseline,
         options: Options::new(),
     };
 
     Some(Ok(test_opts))
 }
 
+// Baseline names end up as file names, so keep them to a conservative set of characters.
+fn parse_baseline_name(matches: &getopts::Matches, opt: &str) -> Result<Option<String>, String> {
+    match matches.opt_str(opt) {
+        Some(name) => {
+            let valid = !name.is_empty() && name.chars().all(|c| {
+                c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.'
+            }) && !name.starts_with('.');
+            if valid {
+                Ok(Some(name))
+            } else {
+                Err(format!("argument for --{} must be a non-empty name made of ASCII \
+                             letters, digits, `-`, `_` and `.` (was {})", opt, name))
+            }
+        }
+        None => Ok(None),
+    }
+}
+
 #[derive(Clone, PartialEq)]
 pub struct BenchSamples {
     ns_iter_summ: stats::Summary,
+    ns_iter_samples: Vec<f64>,
     mb_s: usize,
+    elems_s: usize,
 }
 
 #[derive(Clone, PartialEq)]
@@ -538,6 +610,12 @@ struct ConsoleTestState<T> {
     failures: Vec<(TestDesc, Vec<u8>)>,
     not_failures: Vec<(TestDesc, Vec<u8>)>,
     max_name_len: usize, // number of columns to fill when aligning names
+    baseline: Option<Baseline>, // compared against, from `--baseline`
+    new_baseline: Option<Baseline>, // recorded by `--save-baseline`
+    bench_regressed: usize,
+    bench_improved: usize,
+    bench_unchanged: usize,
+    bench_not_in_baseline: usize,
     options: Options,
 }
 
@@ -551,6 +629,16 @@ impl<T: Write> ConsoleTestState<T> {
             None => Raw(io::stdout()),
             Some(t) => Pretty(t),
         };
+        let baseline = match opts.baseline {
+            Some(ref name) if opts.bench_benchmarks => Some(Baseline::load(name)?),
+            _ => None,
+        };
+        let new_baseline = match opts.save_baseline {
+            // Start from the existing samples so that a filtered run only replaces the
+            // benchmarks it actually measured.
+            Some(ref name) if opts.bench_benchmarks => Some(Baseline::load_or_empty(name)?),
+            _ => None,
+        };
 
         Ok(ConsoleTestState {
             out,
@@ -568,6 +656,12 @@ impl<T: Write> ConsoleTestState<T> {
             failures: Vec::new(),
             not_failures: Vec::new(),
             max_name_len: 0,
+            baseline,
+            new_baseline,
+            bench_regressed: 0,
+            bench_improved: 0,
+            bench_unchanged: 0,
+            bench_not_in_baseline: 0,
             options: opts.options,
         })
     }
@@ -661,7 +755,7 @@ impl<T: Write> ConsoleTestState<T> {
         }
     }
 
-    pub fn write_result(&mut self, result: &TestResult) -> io::Result<()> {
+    pub fn write_result(&mut self, test: &TestDesc, result: &TestResult) -> io::Result<()> {
         match *result {
             TrOk => self.write_ok(),
             TrFailed | TrFailedMsg(_) => self.write_failed(),
@@ -669,7 +763,45 @@ impl<T: Write> ConsoleTestState<T> {
             TrAllowedFail => self.write_allowed_fail(),
             TrBench(ref bs) => {
                 self.write_bench()?;
-                self.write_plain(&format!(": {}\n", fmt_bench_samples(bs)))
+                self.write_plain(&format!(": {}", fmt_bench_samples(bs)))?;
+                self.write_bench_comparison(test, bs)?;
+                self.write_plain("\n")
+            }
+        }
+    }
+
+    fn write_bench_comparison(&mut self, test: &TestDesc, bs: &BenchSamples) -> io::Result<()> {
+        let comparison = match self.baseline {
+            None => return Ok(()),
+            Some(ref baseline) => {
+                match baseline.samples.get(test.name.as_slice()) {
+                    Some(old) if !old.is_empty() && !bs.ns_iter_samples.is_empty() => {
+                        Some(compare_bench_samples(old, &bs.ns_iter_samples))
+                    }
+                    _ => None,
+                }
+            }
+        };
+
+        match comparison {
+            None => {
+                self.bench_not_in_baseline += 1;
+                self.write_plain(" (not in baseline)")
+            }
+            Some(c) => {
+                self.write_plain(&format!(" ({:+.2}%, p = {:.3}", c.change_pct, c.p_value))?;
+                if c.is_regression() {
+                    self.bench_regressed += 1;
+                    self.write_plain(", ")?;
+                    self.write_pretty("regressed", term::color::RED)?;
+                } else if c.is_improvement() {
+                    self.bench_improved += 1;
+                    self.write_plain(", ")?;
+                    self.write_pretty("improved", term::color::GREEN)?;
+                } else {
+                    self.bench_unchanged += 1;
+                }
+                self.write_plain(")")
             }
         }
     }
@@ -769,6 +901,27 @@ impl<T: Write> ConsoleTestState<T> {
             self.write_failures()?;
         }
 
+        let comparison_summary = self.baseline.as_ref().map(|baseline| {
+            format!("\nbenchmarks compared to baseline `{}`: {} regressed; {} improved; \
+                     {} unchanged; {} not in baseline\n",
+                    baseline.name,
+                    self.bench_regressed,
+                    self.bench_improved,
+                    self.bench_unchanged,
+                    self.bench_not_in_baseline)
+        });
+        if let Some(s) = comparison_summary {
+            self.write_plain(&s)?;
+        }
+        let saved_note = self.new_baseline.as_ref().map(|baseline| {
+            format!("\nsaved benchmark baseline `{}` to {}\n",
+                    baseline.name,
+                    Baseline::path(&baseline.name).display())
+        });
+        if let Some(s) = saved_note {
+            self.write_plain(&s)?;
+        }
+
         self.write_plain("\ntest result: ")?;
         if success {
             // There's no parallelism at this point so it's safe to use color
@@ -837,9 +990,142 @@ pub fn fmt_bench_samples(bs: &BenchSamples) -> String {
     if bs.mb_s != 0 {
         output.write_fmt(format_args!(" = {} MB/s", bs.mb_s)).unwrap();
     }
+    if bs.elems_s != 0 {
+        output.write_fmt(format_args!(" = {} elem/s", fmt_thousands_sep(bs.elems_s, ',')))
+              .unwrap();
+    }
     output
 }
 
+// Changes of the median smaller than this are reported but never flagged, however
+// significant: with enough samples even scheduler noise becomes "significant".
+const BENCH_NOISE_THRESHOLD_PCT: f64 = 2.0;
+const BENCH_SIGNIFICANCE_LEVEL: f64 = 0.05;
+
+/// The difference between the samples of a benchmark and those recorded for it in a
+/// baseline.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct BenchComparison {
+    /// Change of the median time per iteration in percent; positive means slower.
+    pub change_pct: f64,
+    /// Two-sided p-value of a Mann-Whitney U test on the two sample-sets.
+    pub p_value: f64,
+}
+
+impl BenchComparison {
+    fn is_significant(&self) -> bool {
+        self.p_value < BENCH_SIGNIFICANCE_LEVEL &&
+            self.change_pct.abs() >= BENCH_NOISE_THRESHOLD_PCT
+    }
+
+    pub fn is_regression(&self) -> bool {
+        self.is_significant() && self.change_pct > 0.0
+    }
+
+    pub fn is_improvement(&self) -> bool {
+        self.is_significant() && self.change_pct < 0.0
+    }
+}
+
+/// Compares the ns/iter samples of a benchmark run against `old`, those of an earlier run.
+pub fn compare_bench_samples(old: &[f64], new: &[f64]) -> BenchComparison {
+    use stats::Stats;
+
+    let old_median = old.median();
+    let change_pct = if old_median > 0.0 {
+        (new.median() - old_median) / old_median * 100.0
+    } else {
+        0.0
+    };
+    BenchComparison {
+        change_pct,
+        p_value: stats::mann_whitney_u(old, new).p_value,
+    }
+}
+
+// The samples of each benchmark of a run, stored under a name with `--save-baseline` so
+// that later runs can be compared against them with `--baseline`.
+//
+// The file format is line-based: each line holds a benchmark name, a tab and the
+// space-separated ns/iter samples of that benchmark.
+struct Baseline {
+    name: String,
+    samples: BTreeMap<String, Vec<f64>>,
+}
+
+impl Baseline {
+    fn path(name: &str) -> PathBuf {
+        let dir = match env::var_os("RUST_BENCH_BASELINE_DIR") {
+            Some(dir) => PathBuf::from(dir),
+            None => PathBuf::from("target").join("bench-baselines"),
+        };
+        dir.join(format!("{}.baseline", name))
+    }
+
+    fn load(name: &str) -> io::Result<Baseline> {
+        let path = Baseline::path(name);
+        let mut contents = String::new();
+        File::open(&path).and_then(|mut f| f.read_to_string(&mut contents)).map_err(|e| {
+            io::Error::new(e.kind(), format!("failed to read benchmark baseline `{}` from {}: {}",
+                                             name, path.display(), e))
+        })?;
+        let samples = Baseline::parse(&contents).map_err(|msg| {
+            io::Error::new(io::ErrorKind::InvalidData,
+                           format!("malformed benchmark baseline {}: {}", path.display(), msg))
+        })?;
+        Ok(Baseline {
+            name: name.to_owned(),
+            samples,
+        })
+    }
+
+    fn load_or_empty(name: &str) -> io::Result<Baseline> {
+        match Baseline::load(name) {
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => {
+                Ok(Baseline {
+                    name: name.to_owned(),
+                    samples: BTreeMap::new(),
+                })
+            }
+            res => res,
+        }
+    }
+
+    fn parse(contents: &str) -> Result<BTreeMap<String, Vec<f64>>, String> {
+        let mut samples = BTreeMap::new();
+        for (i, line) in contents.lines().enumerate() {
+            if line.is_empty() {
+                continue;
+            }
+            let mut parts = line.rsplitn(2, '\t');
+            let values = parts.next().unwrap();
+            let name = match parts.next() {
+                Some(name) => name,
+                None => return Err(format!("line {}: expected a name and samples", i + 1)),
+            };
+            let values = values.split(' ')
+                               .map(|v| v.parse::<f64>())
+                               .collect::<Result<Vec<_>, _>>()
+                               .map_err(|e| format!("line {}: {}", i + 1, e))?;
+            samples.insert(name.to_owned(), values);
+        }
+        Ok(samples)
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let path = Baseline::path(&self.name);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut out = String::new();
+        for (name, samples) in &self.samples {
+            let samples = samples.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+            out.push_str(&format!("{}\t{}\n", name, samples.join(" ")));
+        }
+        File::create(&path)?.write_all(out.as_bytes())
+    }
+}
+
 // List the tests to console, and optionally to logfile. Filters are honored.
 pub fn list_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Result<()> {
     let mut st = ConsoleTestState::new(opts, None::<io::Stdout>)?;
@@ -891,7 +1177,7 @@ pub fn run_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Resu
             TeTimeout(ref test) => st.write_timeout(test),
             TeResult(test, result, stdout) => {
                 st.write_log_result(&test, &result)?;
-                st.write_result(&result)?;
+                st.write_result(&test, &result)?;
                 match result {
                     TrOk => {
                         st.passed += 1;
@@ -903,6 +1189,12 @@ pub fn run_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Resu
                         st.metrics.insert_metric(test.name.as_slice(),
                                                  bs.ns_iter_summ.median,
                                                  bs.ns_iter_summ.max - bs.ns_iter_summ.min);
+                        if let Some(ref mut baseline) = st.new_baseline {
+                            if !bs.ns_iter_samples.is_empty() {
+                                baseline.samples.insert(test.name.to_string(),
+                                                        bs.ns_iter_samples);
+                            }
+                        }
                         st.measured += 1
                     }
                     TrFailed => {
@@ -935,6 +1227,9 @@ pub fn run_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Resu
         st.max_name_len = n.len();
     }
     run_tests(opts, tests, |x| callback(&x, &mut st))?;
+    if let Some(ref baseline) = st.new_baseline {
+        baseline.save()?;
+    }
     return st.write_run_finish();
 }
 
@@ -967,6 +1262,12 @@ fn should_sort_failures_before_printing_them() {
         filtered_out: 0,
         measured: 0,
         max_name_len: 10,
+        baseline: None,
+        new_baseline: None,
+        bench_regressed: 0,
+        bench_improved: 0,
+        bench_unchanged: 0,
+        bench_not_in_baseline: 0,
         metrics: MetricMap::new(),
         failures: vec![(test_b, Vec::new()), (test_a, Vec::new())],
         options: Options::new(),
@@ -1460,6 +1761,27 @@ pub fn run_test(opts: &TestOpts,
     }
 }
 
+/// Runs `testfn` between `setup` and `teardown`, the `#[test_setup]` and `#[test_teardown]`
+/// functions of the module the test is declared in. The test harness passes `no_fixture` for
+/// whichever of the two the module lacks.
+///
+/// `teardown` runs even if the setup or the test itself panics. It runs after the panic has
+/// been caught rather than while unwinding, so a panicking teardown fails the test instead of
+/// aborting the process; if both panic, the test's panic is the one propagated.
+pub fn run_with_fixtures(setup: fn(), testfn: fn(), teardown: fn()) {
+    let result = catch_unwind(|| {
+        setup();
+        testfn();
+    });
+    let torn_down = catch_unwind(teardown);
+    if let Err(payload) = result.and(torn_down) {
+        resume_unwind(payload);
+    }
+}
+
+/// Stands in for a missing `#[test_setup]` or `#[test_teardown]` function.
+pub fn no_fixture() {}
+
 /// Fixed frame used to clean the backtrace with `RUST_BACKTRACE=1`.
 #[inline(never)]
 fn __rust_begin_short_backtrace<F: FnOnce()>(f: F) {
@@ -1559,7 +1881,9 @@ impl Bencher {
             return;
         }
 
-        self.summary = Some(iter(&mut inner));
+        let (summary, samples) = iter_samples(&mut inner);
+        self.summary = Some(summary);
+        self.samples = samples;
     }
 
     pub fn bench<F>(&mut self, mut f: F) -> Option<stats::Summary>
@@ -1587,6 +1911,13 @@ fn ns_iter_inner<T, F>(inner: &mut F, k: u64) -> u64
 
 pub fn iter<T, F>(inner: &mut F) -> stats::Summary
     where F: FnMut() -> T
+{
+    iter_samples(inner).0
+}
+
+// Like `iter`, but also returns the samples the summary was computed from.
+fn iter_samples<T, F>(inner: &mut F) -> (stats::Summary, Vec<f64>)
+    where F: FnMut() -> T
 {
     // Initial bench run to get ballpark figure.
     let ns_single = ns_iter_inner(inner, 1);
@@ -1629,13 +1960,13 @@ pub fn iter<T, F>(inner: &mut F) -> stats::Summary
         // stable median.
         if loop_run > Duration::from_millis(100) && summ.median_abs_dev_pct < 1.0 &&
            summ.median - summ5.median < summ5.median_abs_dev {
-            return summ5;
+            return (summ5, samples.to_vec());
         }
 
         total_run = total_run + loop_run;
         // Longest we ever run for is 3s.
         if total_run > Duration::from_secs(3) {
-            return summ5;
+            return (summ5, samples.to_vec());
         }
 
         // If we overflow here just return the results so far. We check a
@@ -1645,7 +1976,7 @@ pub fn iter<T, F>(inner: &mut F) -> stats::Summary
         n = match n.checked_mul(10) {
             Some(_) => n * 2,
             None => {
-                return summ5;
+                return (summ5, samples.to_vec());
             }
         };
     }
@@ -1662,17 +1993,22 @@ pub mod bench {
         let mut bs = Bencher {
             mode: BenchMode::Auto,
             summary: None,
+            samples: Vec::new(),
             bytes: 0,
+            elements: 0,
         };
 
         return match bs.bench(f) {
             Some(ns_iter_summ) => {
                 let ns_iter = cmp::max(ns_iter_summ.median as u64, 1);
                 let mb_s = bs.bytes * 1000 / ns_iter;
+                let elems_s = bs.elements as f64 * 1_000_000_000.0 / ns_iter as f64;
 
                 BenchSamples {
                     ns_iter_summ,
+                    ns_iter_samples: bs.samples,
                     mb_s: mb_s as usize,
+                    elems_s: elems_s as usize,
                 }
             }
             None => {
@@ -1681,7 +2017,9 @@ pub mod bench {
                 let samples: &mut [f64] = &mut [0.0_f64; 1];
                 BenchSamples {
                     ns_iter_summ: stats::Summary::new(samples),
+                    ns_iter_samples: Vec::new(),
                     mb_s: 0,
+                    elems_s: 0,
                 }
             }
         };
@@ -1693,7 +2031,9 @@ pub mod bench {
         let mut bs = Bencher {
             mode: BenchMode::Single,
             summary: None,
+            samples: Vec::new(),
             bytes: 0,
+            elements: 0,
         };
         bs.bench(f);
     }
@@ -1705,8 +2045,13 @@ mod tests {
                TestDescAndFn, TestOpts, run_test, MetricMap, StaticTestName, DynTestName,
                DynTestFn, ShouldPanic};
     use std::sync::mpsc::channel;
+    use std::collections::BTreeMap;
+    use std::env;
+    use std::fs::{self, File};
+    use std::io::Read;
+    use std::time::{SystemTime, UNIX_EPOCH};
     use bench;
-    use Bencher;
+    use {Baseline, Bencher, compare_bench_samples};
 
     #[test]
     pub fn do_not_run_ignored_tests() {
@@ -1836,6 +2181,25 @@ mod tests {
         assert!((opts.run_ignored));
     }
 
+    #[test]
+    fn parse_baseline_flags() {
+        let args = vec!["progname".to_string(), "--bench".to_string(),
+                        "--baseline".to_string(), "main".to_string(),
+                        "--save-baseline".to_string(), "topic-1.2_x".to_string()];
+        let opts = match parse_opts(&args) {
+            Some(Ok(o)) => o,
+            _ => panic!("Malformed arg in parse_baseline_flags"),
+        };
+        assert_eq!(opts.baseline, Some("main".to_string()));
+        assert_eq!(opts.save_baseline, Some("topic-1.2_x".to_string()));
+
+        let args = vec!["progname".to_string(), "--baseline".to_string(), "../etc".to_string()];
+        match parse_opts(&args) {
+            Some(Err(_)) => {}
+            _ => panic!("baseline names with path separators must be rejected"),
+        }
+    }
+
     #[test]
     pub fn filter_for_ignored_option() {
         // When we run ignored tests the test filter should filter out all the
@@ -2013,6 +2377,55 @@ mod tests {
         m2.insert_metric("in-both-want-upwards-and-improved", 2000.0, -10.0);
     }
 
+    #[test]
+    pub fn test_baseline_round_trip() {
+        let mut samples = BTreeMap::new();
+        samples.insert("a::b".to_string(), vec![1.5, 2.0, 1000.25]);
+        samples.insert("src/lib.rs - foo (line 3)".to_string(), vec![7.0]);
+
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().subsec_nanos();
+        let dir = env::temp_dir().join(format!("rust-bench-baseline-{}", nanos));
+        env::set_var("RUST_BENCH_BASELINE_DIR", &dir);
+
+        assert_eq!(Baseline::load_or_empty("round-trip").unwrap().samples, BTreeMap::new());
+        Baseline { name: "round-trip".to_string(), samples: samples.clone() }.save().unwrap();
+        assert_eq!(Baseline::path("round-trip"), dir.join("round-trip.baseline"));
+        assert_eq!(Baseline::load("round-trip").unwrap().samples, samples);
+
+        let mut contents = String::new();
+        File::open(dir.join("round-trip.baseline")).unwrap()
+            .read_to_string(&mut contents).unwrap();
+        assert_eq!(Baseline::parse(&contents).unwrap(), samples);
+
+        env::remove_var("RUST_BENCH_BASELINE_DIR");
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(Baseline::parse("no samples here\n").is_err());
+        assert!(Baseline::parse("a\t1.0 abc\n").is_err());
+    }
+
+    #[test]
+    pub fn test_compare_bench_samples() {
+        let old = (0..50).map(|i| 1000.0 + (i % 10) as f64).collect::<Vec<_>>();
+
+        let same = compare_bench_samples(&old, &old);
+        assert!(!same.is_regression() && !same.is_improvement());
+
+        let slower = old.iter().map(|x| x * 1.2).collect::<Vec<_>>();
+        let cmp = compare_bench_samples(&old, &slower);
+        assert!(cmp.change_pct > 19.0 && cmp.change_pct < 21.0);
+        assert!(cmp.is_regression());
+
+        let faster = old.iter().map(|x| x * 0.8).collect::<Vec<_>>();
+        assert!(compare_bench_samples(&old, &faster).is_improvement());
+
+        // Significant, but too small to be anything but noise.
+        let barely = old.iter().map(|x| x + 10.0).collect::<Vec<_>>();
+        let cmp = compare_bench_samples(&old, &barely);
+        assert!(cmp.p_value < 0.05);
+        assert!(!cmp.is_regression());
+    }
+
     #[test]
     pub fn test_bench_once_no_iter() {
         fn f(_: &mut Bencher) {}
@@ -0,0 +1,27 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: --test
+
+#![feature(test_case)]
+
+#[test_case(1)] //~ ERROR this test case passes 1 argument, but `add` takes 2
+#[test_case(1, 2)]
+fn add(a: u32, b: u32) {
+    assert!(a + b > 0);
+}
+
+#[test_case(1)]
+fn returns(x: u32) -> u32 { //~ ERROR functions used as parameterized tests must not be generic
+    x
+}
+
+#[test_case(1)]
+struct NotAFunction; //~ ERROR only functions may be used as parameterized tests
@@ -0,0 +1,28 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: --test
+
+#![feature(test_fixtures)]
+
+#[test_setup]
+fn setup(x: u32) {} //~ ERROR functions used as `#[test_setup]` must have signature fn() -> ()
+
+#[test_teardown]
+fn teardown() {}
+
+#[test_teardown]
+fn teardown_again() {} //~ ERROR a module may have at most one `#[test_teardown]` function
+
+#[test_setup]
+static SETUP: u32 = 0; //~ ERROR only functions may be used as `#[test_setup]`
+
+#[test]
+fn test() {}
@@ -0,0 +1,20 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+
+// compile-flags: --test
+
+#![feature(custom_test_frameworks)]
+#![test_runner(runner)]
+
+fn runner(_: &[&u32]) {}
+
+#[test_item]
+fn not_an_item() -> u32 { 0 } //~ ERROR only constants and statics may be used as `#[test_item]`s
@@ -0,0 +1,34 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![crate_name = "foo"]
+#![feature(optin_builtin_traits)]
+
+use std::fmt;
+
+/// A documented struct.
+pub struct Foo {
+    pub bar: u32,
+}
+
+pub trait Describe {
+    fn describe(&self) -> String;
+}
+
+impl<T: fmt::Debug> Describe for T {
+    fn describe(&self) -> String {
+        format!("{:?}", self)
+    }
+}
+
+pub trait Marker {}
+
+#[allow(auto_impl)]
+impl Marker for .. {}
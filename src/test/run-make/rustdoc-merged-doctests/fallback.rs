@@ -0,0 +1,22 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// The second doctest doesn't compile, so neither of them can be run from the shared binary.
+
+/// ```
+/// assert!(std::env::args().nth(1).is_none());
+/// assert_eq!(fallback::answer(), 42);
+/// ```
+pub fn answer() -> u32 { 42 }
+
+/// ```
+/// let x: u32 = "not a number";
+/// ```
+pub fn broken() {}
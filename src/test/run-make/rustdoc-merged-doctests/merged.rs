@@ -0,0 +1,33 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![doc(test(attr(deny(warnings))))]
+
+/// ```
+/// assert!(std::env::args().nth(1).is_some());
+/// assert_eq!(merged::answer(), 42);
+/// ```
+pub fn answer() -> u32 { 42 }
+
+/// ```
+/// use merged::Unit;
+///
+/// assert!(std::env::args().nth(1).is_some());
+/// assert_eq!(Unit, Unit);
+/// ```
+#[derive(Debug, PartialEq)]
+pub struct Unit;
+
+/// ```
+/// fn main() {
+///     assert!(std::env::args().nth(1).is_some());
+/// }
+/// ```
+pub fn with_main() {}
@@ -0,0 +1,25 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+
+extern crate foo;
+
+use foo::Counter;
+
+fn main() {
+    let sum = foo::add(1, 2);
+    let mut counter = Counter(sum);
+    counter.bump();
+    other(&mut counter);
+}
+
+fn other(counter: &mut Counter) {
+    counter.bump();
+}
@@ -13,9 +13,9 @@
 // This test may not always fail, but it can be flaky if the race it used to
 // expose is still present.
 
-#![feature(mpsc_select)]
+#![feature(mpmc_channel, mpmc_select)]
 
-use std::sync::mpsc::{channel, Sender, Receiver};
+use std::sync::mpmc::{channel, Sender, Receiver};
 use std::thread;
 
 fn helper(rx: Receiver<Sender<()>>) {
@@ -33,8 +33,8 @@ fn main() {
         let (tx2, rx2) = channel();
         tx.send(tx2).unwrap();
         select! {
-            _ = rx2.recv() => (),
-            _ = rcv.recv() => ()
+            recv(rx2) -> _ => (),
+            recv(rcv) -> _ => (),
         }
     }
     drop(tx);
@@ -0,0 +1,80 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+
+// ignore-android FIXME #17520
+// ignore-emscripten spawning processes is not supported
+// ignore-openbsd no support for libbacktrace without filename
+// compile-flags:-g
+
+#![feature(backtrace)]
+
+use std::backtrace::{Backtrace, BacktraceStatus};
+use std::env;
+use std::process::{Command, Stdio};
+use std::str;
+
+#[inline(never)]
+fn foo() -> Backtrace {
+    Backtrace::capture()
+}
+
+fn template(me: &str) -> Command {
+    let mut m = Command::new(me);
+    m.arg("child")
+     .env_remove("RUST_BACKTRACE")
+     .env_remove("RUST_LIB_BACKTRACE")
+     .stdout(Stdio::piped())
+     .stderr(Stdio::piped());
+    return m;
+}
+
+fn run(mut cmd: Command) -> String {
+    let out = cmd.spawn().unwrap().wait_with_output().unwrap();
+    assert!(out.status.success());
+    str::from_utf8(&out.stdout).unwrap().to_string()
+}
+
+fn runtest(me: &str) {
+    // Nothing is captured unless asked for.
+    let s = run(template(me));
+    assert!(s.contains("disabled backtrace"), "bad output: {}", s);
+
+    // RUST_BACKTRACE enables library backtraces too...
+    let mut cmd = template(me);
+    cmd.env("RUST_BACKTRACE", "1");
+    let s = run(cmd);
+    assert!(s.contains("stack backtrace") && s.contains("foo"), "bad output2: {}", s);
+
+    // ... unless RUST_LIB_BACKTRACE turns them off ...
+    let mut cmd = template(me);
+    cmd.env("RUST_BACKTRACE", "1").env("RUST_LIB_BACKTRACE", "0");
+    let s = run(cmd);
+    assert!(s.contains("disabled backtrace"), "bad output3: {}", s);
+
+    // ... and RUST_LIB_BACKTRACE alone is enough to turn them on.
+    let mut cmd = template(me);
+    cmd.env("RUST_LIB_BACKTRACE", "1");
+    let s = run(cmd);
+    assert!(s.contains("stack backtrace") && s.contains("foo"), "bad output4: {}", s);
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() >= 2 && args[1] == "child" {
+        let backtrace = foo();
+        println!("{}", backtrace);
+    } else {
+        // Forced captures ignore the environment entirely.
+        let backtrace = Backtrace::force_capture();
+        assert_eq!(backtrace.status(), BacktraceStatus::Captured);
+        runtest(&args[0]);
+    }
+}
@@ -0,0 +1,44 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// ignore-wasm32-bare compiled with panic=abort by default
+// compile-flags: --test
+
+#![feature(test_case)]
+
+#[test_case(1, 1, 2)]
+#[test_case(2, 3, 5)]
+#[test_case(u32::max_value() - 1, 1, u32::max_value())]
+fn add(a: u32, b: u32, sum: u32) {
+    assert_eq!(a + b, sum);
+}
+
+#[test_case(0)]
+#[should_panic(expected = "attempt to divide by zero")]
+fn divide_by(divisor: u32) {
+    let _ = 1 / divisor;
+}
+
+#[test_case(1)]
+#[ignore]
+fn ignored(x: u32) {
+    assert_eq!(x, 0);
+}
+
+mod nested {
+    const BIG: u64 = 1 << 40;
+
+    #[test]
+    #[test_case(BIG, "1099511627776")]
+    #[test_case(0, "0")]
+    fn formats(n: u64, s: &str) {
+        assert_eq!(n.to_string(), s);
+    }
+}
@@ -0,0 +1,92 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// ignore-wasm32-bare compiled with panic=abort by default
+// ignore-emscripten no threads support, so the thread-local state is shared by all tests
+// compile-flags: --test
+
+#![feature(test_fixtures)]
+
+use std::cell::Cell;
+
+thread_local!(static STATE: Cell<u32> = Cell::new(0));
+
+#[test_setup]
+fn setup() {
+    STATE.with(|s| {
+        assert_eq!(s.get(), 0);
+        s.set(1);
+    })
+}
+
+#[test_teardown]
+fn teardown() {
+    STATE.with(|s| assert_eq!(s.get(), 2))
+}
+
+fn run() {
+    STATE.with(|s| {
+        assert_eq!(s.get(), 1);
+        s.set(2);
+    })
+}
+
+#[test]
+fn first() {
+    run();
+}
+
+#[test]
+fn second() {
+    run();
+}
+
+#[test]
+#[should_panic(expected = "torn down anyway")]
+fn panicking() {
+    run();
+    panic!("torn down anyway");
+}
+
+mod without_fixtures {
+    #[test]
+    fn not_set_up() {
+        super::STATE.with(|s| assert_eq!(s.get(), 0))
+    }
+
+    mod teardown_only {
+        #[test_teardown]
+        fn teardown() {
+            ::STATE.with(|s| assert_eq!(s.get(), 3))
+        }
+
+        #[test]
+        fn torn_down() {
+            ::STATE.with(|s| s.set(3))
+        }
+    }
+}
+
+mod panicking_teardown {
+    #[test_teardown]
+    fn teardown() {
+        panic!("teardown failed")
+    }
+
+    #[test]
+    #[should_panic(expected = "teardown failed")]
+    fn passing() {}
+
+    #[test]
+    #[should_panic(expected = "test failed")]
+    fn panicking() {
+        panic!("test failed");
+    }
+}
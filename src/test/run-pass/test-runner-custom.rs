@@ -0,0 +1,46 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+
+// compile-flags: --test
+
+#![feature(custom_test_frameworks)]
+#![test_runner(runner)]
+
+pub struct Check {
+    pub name: &'static str,
+    pub run: fn() -> bool,
+}
+
+fn runner(checks: &[&Check]) {
+    let names: Vec<_> = checks.iter().map(|check| check.name).collect();
+    assert_eq!(names, ["top", "nested"]);
+    for check in checks {
+        assert!((check.run)(), "check `{}` failed", check.name);
+    }
+}
+
+fn always() -> bool {
+    true
+}
+
+#[test_item]
+const TOP: Check = Check { name: "top", run: always };
+
+mod nested {
+    use Check;
+
+    fn two() -> bool {
+        1 + 1 == 2
+    }
+
+    #[test_item]
+    static NESTED: Check = Check { name: "nested", run: two };
+}
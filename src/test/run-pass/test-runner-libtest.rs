@@ -0,0 +1,33 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+
+// ignore-wasm32-bare compiled with panic=abort by default
+// compile-flags: --test
+
+#![feature(custom_test_frameworks, test)]
+#![test_runner(counting_runner)]
+
+extern crate test;
+
+use test::TestDescAndFn;
+
+fn counting_runner(tests: &[&TestDescAndFn]) {
+    assert_eq!(tests.len(), 2);
+    test::test_main_static(tests);
+}
+
+#[test]
+fn first() {}
+
+mod second {
+    #[test]
+    fn second() {}
+}
@@ -0,0 +1,19 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// References are left out of signatures, so `&[u8]` and `[u8]` are the same.
+
+const QUERY = '&[u8] -> Result<&str, _>';
+
+const EXPECTED = {
+    'others': [
+        { 'path': 'std::str', 'name': 'from_utf8' },
+    ],
+};
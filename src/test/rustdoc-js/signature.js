@@ -0,0 +1,21 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Methods taking `self` match the type they're defined on, and single letters stand
+// for any generic argument. Functions with no inputs beyond the query's come first.
+
+const QUERY = 'Vec<T> -> usize';
+
+const EXPECTED = {
+    'others': [
+        { 'path': 'std::vec', 'name': 'len' },
+        { 'path': 'std::vec', 'name': 'capacity' },
+    ],
+};
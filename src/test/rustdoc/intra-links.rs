@@ -0,0 +1,50 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+
+// @has intra_links/index.html
+// @has - '//a/@href' '../intra_links/struct.ThisType.html'
+// @has - '//a/@href' '../intra_links/struct.ThisType.html#method.this_method'
+// @has - '//a/@href' '../intra_links/enum.ThisEnum.html'
+// @has - '//a/@href' '../intra_links/enum.ThisEnum.html#variant.ThisVariant'
+// @has - '//a/@href' '../intra_links/trait.ThisTrait.html'
+// @has - '//a/@href' '../intra_links/trait.ThisTrait.html#tymethod.this_associated_method'
+// @has - '//a/@href' '../intra_links/fn.this_function.html'
+// @has - '//a/@href' '../intra_links/constant.THIS_CONST.html'
+// @has - '//a/@href' '../intra_links/nested/struct.Nested.html'
+//! Links to [`ThisType`], [`ThisType::this_method`], [the enum](ThisEnum),
+//! [`ThisEnum::ThisVariant`], [`ThisTrait`], [`ThisTrait::this_associated_method`],
+//! [`this_function()`], [the constant](value@THIS_CONST) and [`nested::Nested`].
+
+pub struct ThisType;
+
+impl ThisType {
+    pub fn this_method() {}
+}
+
+pub enum ThisEnum { ThisVariant, }
+
+pub trait ThisTrait {
+    fn this_associated_method();
+}
+
+pub fn this_function() {}
+
+pub const THIS_CONST: usize = 5;
+
+pub mod nested {
+    // @has intra_links/nested/struct.Nested.html
+    // @has - '//a/@href' '../../intra_links/struct.ThisType.html'
+    // @has - '//a/@href' '../../intra_links/nested/fn.sibling.html'
+    /// Links to [`super::ThisType`] and to [`sibling`], from within `nested`.
+    pub struct Nested;
+
+    pub fn sibling() {}
+}
@@ -0,0 +1,35 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![crate_name = "foo"]
+
+//! Docs for the crate.
+
+// Each crate's items go in a search index file of their own, which is only loaded when a
+// search needs them, while `search-index.js` lists the crates.
+
+// @has search-index.js 'searchCrates["foo"] = "Docs for the crate.";'
+// @has - 'initSearch(searchCrates);'
+// @!has - 'parse_bytes'
+// @has search-index/foo.js 'registerSearchIndex("foo", '
+// @has - '"parse_bytes"'
+
+// Signatures are written out with the generic arguments of each type, for `->` queries.
+// @has - '[["slice",["u8"]]],["option",[["vec",["u8"]]]]'
+pub fn parse_bytes(bytes: &[u8]) -> Option<Vec<u8>> {
+    Some(bytes.to_vec())
+}
+
+// Generic parameters are written out with their bounds.
+// @has - '"duplicate"'
+// @has - '[[["t",[],["clone"]]],["tuple",[["t",[],["clone"]],["t",[],["clone"]]]]]'
+pub fn duplicate<T: Clone>(x: T) -> (T, T) {
+    (x.clone(), x)
+}
@@ -0,0 +1,40 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+
+#![crate_name = "foo"]
+
+// @has src/foo/source-links.rs.html
+
+// Definitions link to their documentation.
+// @has - '//a[@href="../../foo/struct.Bar.html"]' 'Bar'
+// @has - '//a[@href="../../foo/struct.Bar.html#structfield.value"]' 'value'
+// @has - '//a[@href="../../foo/struct.Bar.html#method.get"]' 'get'
+// @has - '//a[@href="../../foo/fn.double.html"]' 'double'
+pub struct Bar {
+    pub value: u32,
+}
+
+impl Bar {
+    pub fn get(&self) -> u32 {
+        // @has - '//a[@href="../../src/foo/source-links.rs.html#22"]' 'value'
+        self.value
+    }
+}
+
+// Uses of local items and variables link to their definitions in the source.
+// @has - '//a[@href="../../src/foo/source-links.rs.html#21"]' 'Bar'
+// @has - '//a[@href="../../src/foo/source-links.rs.html#26"]' 'get'
+// @has - '//a[@href="../../src/foo/source-links.rs.html#38"]' 'bar'
+// Items from other crates link to their documentation.
+// @has - '//a/@href' 'string/struct.String.html'
+pub fn double(bar: Bar) -> String {
+    (bar.get() * 2).to_string()
+}
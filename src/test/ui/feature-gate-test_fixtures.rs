@@ -0,0 +1,22 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: --test
+
+// check that #[test_setup] and #[test_teardown] are feature-gated
+
+#[test_setup] //~ ERROR test fixtures are currently unstable
+fn setup() {}
+
+#[test_teardown] //~ ERROR test fixtures are currently unstable
+fn teardown() {}
+
+#[test]
+fn test() {}
@@ -497,6 +497,8 @@ pub fn test_opts(config: &Config) -> test::TestOpts {
         test_threads: None,
         skip: vec![],
         list: false,
+        save_baseline: None,
+        baseline: None,
         options: test::Options::new(),
     }
 }
@@ -0,0 +1,169 @@
+// Copyright 2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Runs the queries in `src/test/rustdoc-js` against the search index of some
+// generated documentation, using the search code from its `main.js`.
+//
+// Usage: node tester.js <doc dir> <test dir>
+//
+// Each test file declares a `QUERY` string and an `EXPECTED` object with the
+// results expected in any of the `others`, `in_args` and `returned` tabs. Each
+// expected result must be found in its tab, after the one before it, with the
+// same values for the fields it gives.
+
+const fs = require('fs');
+const path = require('path');
+
+// The functions and variables of `main.js` that searching needs. They're
+// cut out of the file, as the rest of it expects to run in a browser.
+const FUNCTIONS = ['decodeType', 'addCrateIndex', 'execQuery', 'validateResult',
+                   'parseQuery', 'itemTypeFromName'];
+const VARIABLES = ['itemTypes', 'TY_PRIMITIVE', 'levenshtein', 'MAX_LEV_DISTANCE',
+                   'MAX_RESULTS'];
+
+// Returns the position of the quote that closes the string opening at `start`.
+function skipString(content, start) {
+    var i = start + 1;
+    for (; content.charAt(i) !== content.charAt(start); ++i) {
+        if (content.charAt(i) === '\\') {
+            ++i;
+        }
+    }
+    return i;
+}
+
+// Returns the position just past the brackets that open at `start`, skipping
+// over strings and comments.
+function skipBrackets(content, start) {
+    var depth = 0;
+    for (var i = start; i < content.length; ++i) {
+        var c = content.charAt(i);
+        if (c === '"' || c === "'") {
+            i = skipString(content, i);
+        } else if (c === '/' && content.charAt(i + 1) === '/') {
+            i = content.indexOf('\n', i);
+        } else if (c === '{' || c === '(' || c === '[') {
+            depth += 1;
+        } else if (c === '}' || c === ')' || c === ']') {
+            depth -= 1;
+            if (depth === 0) {
+                return i + 1;
+            }
+        }
+    }
+    throw new Error('unbalanced brackets from position ' + start);
+}
+
+function extractFunction(content, name) {
+    var start = content.indexOf('function ' + name + '(');
+    if (start === -1) {
+        throw new Error('function `' + name + '` not found');
+    }
+    return content.substring(start, skipBrackets(content, content.indexOf('{', start)));
+}
+
+// Returns `var name = ...;`, whose value may span several lines.
+function extractVariable(content, name) {
+    var match = new RegExp('(var|const) ' + name + ' = ').exec(content);
+    if (match === null) {
+        throw new Error('variable `' + name + '` not found');
+    }
+    var end = match.index + match[0].length;
+    while (content.charAt(end) !== ';' && content.charAt(end) !== ',') {
+        var c = content.charAt(end);
+        if (c === '"' || c === "'") {
+            end = skipString(content, end) + 1;
+        } else if (c === '{' || c === '(' || c === '[') {
+            end = skipBrackets(content, end);
+        } else {
+            end += 1;
+        }
+    }
+    return 'var ' + name + ' = ' + content.substring(match.index + match[0].length, end) + ';';
+}
+
+// Evaluates the search code of `mainJs` and returns the functions used to fill
+// in the index and run queries.
+function loadSearch(mainJs) {
+    var code = VARIABLES.map(function(name) { return extractVariable(mainJs, name); })
+                        .concat(FUNCTIONS.map(function(name) {
+                            return extractFunction(mainJs, name);
+                        }))
+                        .join('\n');
+    return new Function('window', '"use strict";\n' +
+                        'var index = [], searchIndex = [];\n' +
+                        code + '\n' +
+                        'return {addCrateIndex: addCrateIndex, execQuery: execQuery,\n' +
+                        '        parseQuery: parseQuery, index: index};')({});
+}
+
+// Adds the search index of every crate documented in `docDir` to `search`.
+function loadIndex(search, docDir) {
+    var crates = fs.readFileSync(path.join(docDir, 'search-index.js'), 'utf8');
+    var re = /searchCrates\["([^"]+)"\]/g;
+    var match;
+    while ((match = re.exec(crates)) !== null) {
+        var shard = fs.readFileSync(path.join(docDir, 'search-index', match[1] + '.js'),
+                                    'utf8');
+        new Function('registerSearchIndex', shard)(search.addCrateIndex);
+    }
+}
+
+// Checks that `expected` are found in `results` in the same order, and returns
+// a description of the first one that isn't.
+function checkResults(expected, results) {
+    var pos = 0;
+    for (var i = 0; i < expected.length; ++i) {
+        var found = false;
+        for (; pos < results.length && !found; ++pos) {
+            found = Object.keys(expected[i]).every(function(key) {
+                return results[pos][key] === expected[i][key];
+            });
+        }
+        if (!found) {
+            return JSON.stringify(expected[i]);
+        }
+    }
+    return null;
+}
+
+function main(argv) {
+    if (argv.length !== 4) {
+        console.error('Usage: node tester.js <doc dir> <test dir>');
+        return 1;
+    }
+    var docDir = argv[2], testDir = argv[3];
+    var search = loadSearch(fs.readFileSync(path.join(docDir, 'main.js'), 'utf8'));
+    loadIndex(search, docDir);
+
+    var failed = 0;
+    fs.readdirSync(testDir).filter(function(file) {
+        return file.endsWith('.js');
+    }).sort().forEach(function(file) {
+        var test = fs.readFileSync(path.join(testDir, file), 'utf8');
+        var QUERY, EXPECTED;
+        eval(extractVariable(test, 'QUERY') + extractVariable(test, 'EXPECTED'));
+        var results = search.execQuery(search.parseQuery(QUERY), 20000, search.index);
+        var errors = Object.keys(EXPECTED).map(function(tab) {
+            var missing = checkResults(EXPECTED[tab], results[tab] || []);
+            return missing === null ? null : tab + ': ' + missing + ' not found';
+        }).filter(function(error) { return error !== null; });
+        if (errors.length === 0) {
+            console.log('Checking "' + file + '" ... OK');
+        } else {
+            console.log('Checking "' + file + '" ... FAILED');
+            errors.forEach(function(error) { console.log('    ' + error); });
+            failed += 1;
+        }
+    });
+    return failed > 0 ? 1 : 0;
+}
+
+process.exit(main(process.argv));
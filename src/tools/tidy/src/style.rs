@@ -162,24 +162,16 @@ pub fn check(path: &Path, bad: &mut bool) {
                 err(LLVM_UNREACHABLE_INFO);
             }
         }
-        if !licenseck(file, &contents) {
+        if !licenseck(&contents) {
             tidy_error!(bad, "{}: incorrect license", file.display());
         }
     })
 }
 
-fn licenseck(file: &Path, contents: &str) -> bool {
+fn licenseck(contents: &str) -> bool {
     if contents.contains("ignore-license") {
         return true
     }
-    let exceptions = [
-        "libstd/sync/mpsc/mpsc_queue.rs",
-        "libstd/sync/mpsc/spsc_queue.rs",
-    ];
-    if exceptions.iter().any(|f| file.ends_with(f)) {
-        return true
-    }
-
     // Skip the BOM if it's there
     let bom = "\u{feff}";
     let contents = if contents.starts_with(bom) {&contents[3..]} else {contents};